@@ -0,0 +1,8 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE invoice_items DROP COLUMN tier_min_quantity;
+ALTER TABLE invoice_items DROP COLUMN price_tier_id;
+
+ALTER TABLE order_items DROP COLUMN tier_min_quantity;
+ALTER TABLE order_items DROP COLUMN price_tier_id;
+
+DROP TABLE IF EXISTS product_price_tiers;
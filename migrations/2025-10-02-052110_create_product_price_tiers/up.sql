@@ -0,0 +1,21 @@
+-- Your SQL goes here
+CREATE TABLE product_price_tiers (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    uuid TEXT NOT NULL UNIQUE,
+    product_id INTEGER NOT NULL,
+    min_quantity DOUBLE NOT NULL CHECK (min_quantity > 0),
+    price DOUBLE NOT NULL CHECK (price >= 0),
+    FOREIGN KEY (product_id) REFERENCES products(id) ON DELETE CASCADE
+);
+
+-- Product Price Tiers Indexes
+CREATE INDEX idx_product_price_tiers_product_id ON product_price_tiers(product_id);
+CREATE UNIQUE INDEX idx_product_price_tiers_product_min_quantity ON product_price_tiers(product_id, min_quantity);
+
+-- Snapshot of the tier applied when the line was priced. The tier id is kept
+-- without a foreign key so that deleting a tier does not rewrite order history.
+ALTER TABLE order_items ADD COLUMN price_tier_id INTEGER;
+ALTER TABLE order_items ADD COLUMN tier_min_quantity DOUBLE;
+
+ALTER TABLE invoice_items ADD COLUMN price_tier_id INTEGER;
+ALTER TABLE invoice_items ADD COLUMN tier_min_quantity DOUBLE;
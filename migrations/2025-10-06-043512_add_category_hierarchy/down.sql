@@ -0,0 +1,10 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS idx_categories_display_order;
+DROP INDEX IF EXISTS idx_categories_parent_id;
+DROP INDEX IF EXISTS idx_categories_slug;
+
+ALTER TABLE categories DROP COLUMN is_active;
+ALTER TABLE categories DROP COLUMN image;
+ALTER TABLE categories DROP COLUMN display_order;
+ALTER TABLE categories DROP COLUMN slug;
+ALTER TABLE categories DROP COLUMN parent_id;
@@ -0,0 +1,18 @@
+-- Your SQL goes here
+ALTER TABLE categories ADD COLUMN parent_id INTEGER REFERENCES categories(id) ON DELETE SET NULL;
+ALTER TABLE categories ADD COLUMN slug TEXT NOT NULL DEFAULT '';
+ALTER TABLE categories ADD COLUMN display_order INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE categories ADD COLUMN image TEXT;
+ALTER TABLE categories ADD COLUMN is_active BOOLEAN NOT NULL DEFAULT 1;
+
+-- Backfill slugs and ordering for existing categories
+UPDATE categories SET slug = lower(replace(trim(name), ' ', '-'));
+UPDATE categories SET slug = 'category-' || id WHERE slug = '';
+UPDATE categories SET slug = slug || '-' || id
+WHERE slug IN (SELECT slug FROM categories GROUP BY slug HAVING COUNT(*) > 1);
+UPDATE categories SET display_order = id;
+
+-- Categories Indexes
+CREATE UNIQUE INDEX idx_categories_slug ON categories(slug);
+CREATE INDEX idx_categories_parent_id ON categories(parent_id);
+CREATE INDEX idx_categories_display_order ON categories(display_order);
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS product_attributes;
+DROP TABLE IF EXISTS product_tags;
+DROP TABLE IF EXISTS tags;
@@ -0,0 +1,34 @@
+-- Your SQL goes here
+CREATE TABLE tags (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    uuid TEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL,
+    slug TEXT NOT NULL UNIQUE
+);
+
+CREATE TABLE product_tags (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    product_id INTEGER NOT NULL,
+    tag_id INTEGER NOT NULL,
+    FOREIGN KEY (product_id) REFERENCES products(id) ON DELETE CASCADE,
+    FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
+);
+
+CREATE TABLE product_attributes (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    product_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    value TEXT NOT NULL,
+    FOREIGN KEY (product_id) REFERENCES products(id) ON DELETE CASCADE
+);
+
+-- Tags Indexes
+CREATE UNIQUE INDEX idx_tags_slug ON tags(slug);
+
+-- Product Tags Indexes
+CREATE UNIQUE INDEX idx_product_tags_product_tag ON product_tags(product_id, tag_id);
+CREATE INDEX idx_product_tags_tag_id ON product_tags(tag_id);
+
+-- Product Attributes Indexes
+CREATE UNIQUE INDEX idx_product_attributes_product_name ON product_attributes(product_id, name);
+CREATE INDEX idx_product_attributes_name_value ON product_attributes(name, value);
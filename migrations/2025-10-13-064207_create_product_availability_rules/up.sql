@@ -0,0 +1,15 @@
+-- Your SQL goes here
+-- start_day and end_day are MM-DD so a rule repeats every year, a range whose
+-- end comes before its start wraps over the new year (e.g. 11-15 to 02-28).
+-- weekday_mask has bit 0 for Monday through bit 6 for Sunday.
+CREATE TABLE product_availability_rules (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    uuid TEXT NOT NULL UNIQUE,
+    product_id INTEGER NOT NULL,
+    start_day TEXT,
+    end_day TEXT,
+    weekday_mask INTEGER NOT NULL DEFAULT 127 CHECK (weekday_mask > 0 AND weekday_mask <= 127),
+    FOREIGN KEY (product_id) REFERENCES products(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_product_availability_rules_product_id ON product_availability_rules(product_id);
@@ -0,0 +1,19 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER IF EXISTS update_product_ratings_updated_at;
+
+CREATE TRIGGER update_product_ratings_updated_at
+AFTER UPDATE ON product_ratings
+FOR EACH ROW
+BEGIN
+  UPDATE product_ratings
+  SET updated_at = CURRENT_TIMESTAMP
+  WHERE id = OLD.id;
+END;
+
+DROP INDEX IF EXISTS idx_product_ratings_moderation_status;
+DROP INDEX IF EXISTS idx_product_ratings_product_moderation;
+
+ALTER TABLE product_ratings DROP COLUMN replied_at;
+ALTER TABLE product_ratings DROP COLUMN admin_reply;
+ALTER TABLE product_ratings DROP COLUMN moderation_status;
+ALTER TABLE product_ratings DROP COLUMN verified_purchase;
@@ -0,0 +1,34 @@
+-- Your SQL goes here
+ALTER TABLE product_ratings ADD COLUMN verified_purchase BOOLEAN NOT NULL DEFAULT 0;
+ALTER TABLE product_ratings ADD COLUMN moderation_status TEXT NOT NULL DEFAULT 'Pending';
+ALTER TABLE product_ratings ADD COLUMN admin_reply TEXT;
+ALTER TABLE product_ratings ADD COLUMN replied_at TIMESTAMP;
+
+-- ratings given before moderation existed stay visible
+UPDATE product_ratings SET moderation_status = 'Approved';
+
+UPDATE product_ratings
+SET verified_purchase = 1
+WHERE EXISTS (
+    SELECT 1
+    FROM order_items
+    INNER JOIN orders ON orders.id = order_items.order_id
+    WHERE orders.user_id = product_ratings.user_id
+      AND order_items.product_id = product_ratings.product_id
+      AND orders.status = 'Fulfilled'
+);
+
+CREATE INDEX idx_product_ratings_product_moderation ON product_ratings(product_id, moderation_status);
+CREATE INDEX idx_product_ratings_moderation_status ON product_ratings(moderation_status);
+
+-- moderating or replying should not make a review look edited
+DROP TRIGGER IF EXISTS update_product_ratings_updated_at;
+
+CREATE TRIGGER update_product_ratings_updated_at
+AFTER UPDATE OF rating, review ON product_ratings
+FOR EACH ROW
+BEGIN
+  UPDATE product_ratings
+  SET updated_at = CURRENT_TIMESTAMP
+  WHERE id = OLD.id;
+END;
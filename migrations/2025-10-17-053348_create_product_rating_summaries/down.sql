@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER IF EXISTS update_rating_in_summary;
+DROP TRIGGER IF EXISTS remove_approved_rating_from_summary;
+DROP TRIGGER IF EXISTS add_approved_rating_to_summary;
+DROP TABLE IF EXISTS product_rating_summaries;
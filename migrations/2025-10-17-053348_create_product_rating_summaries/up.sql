@@ -0,0 +1,121 @@
+-- Your SQL goes here
+-- One row per product holding the aggregate of its approved ratings. The
+-- triggers below keep it current so listings never have to scan product_ratings.
+CREATE TABLE product_rating_summaries (
+    product_id INTEGER NOT NULL PRIMARY KEY,
+    rating_count INTEGER NOT NULL DEFAULT 0,
+    rating_total DOUBLE NOT NULL DEFAULT 0,
+    average_rating DOUBLE NOT NULL DEFAULT 0,
+    one_star INTEGER NOT NULL DEFAULT 0,
+    two_star INTEGER NOT NULL DEFAULT 0,
+    three_star INTEGER NOT NULL DEFAULT 0,
+    four_star INTEGER NOT NULL DEFAULT 0,
+    five_star INTEGER NOT NULL DEFAULT 0,
+    FOREIGN KEY (product_id) REFERENCES products(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_product_rating_summaries_average ON product_rating_summaries(average_rating, rating_count);
+
+INSERT INTO product_rating_summaries (
+    product_id, rating_count, rating_total, average_rating,
+    one_star, two_star, three_star, four_star, five_star
+)
+SELECT
+    product_id,
+    COUNT(*),
+    SUM(rating),
+    AVG(rating),
+    SUM(CAST(ROUND(rating) AS INTEGER) = 1),
+    SUM(CAST(ROUND(rating) AS INTEGER) = 2),
+    SUM(CAST(ROUND(rating) AS INTEGER) = 3),
+    SUM(CAST(ROUND(rating) AS INTEGER) = 4),
+    SUM(CAST(ROUND(rating) AS INTEGER) = 5)
+FROM product_ratings
+WHERE moderation_status = 'Approved'
+GROUP BY product_id;
+
+CREATE TRIGGER add_approved_rating_to_summary
+AFTER INSERT ON product_ratings
+FOR EACH ROW
+WHEN NEW.moderation_status = 'Approved'
+BEGIN
+  INSERT INTO product_rating_summaries (
+      product_id, rating_count, rating_total, average_rating,
+      one_star, two_star, three_star, four_star, five_star
+  )
+  SELECT
+      NEW.product_id, 1, NEW.rating, NEW.rating,
+      CAST(ROUND(NEW.rating) AS INTEGER) = 1,
+      CAST(ROUND(NEW.rating) AS INTEGER) = 2,
+      CAST(ROUND(NEW.rating) AS INTEGER) = 3,
+      CAST(ROUND(NEW.rating) AS INTEGER) = 4,
+      CAST(ROUND(NEW.rating) AS INTEGER) = 5
+  WHERE true
+  ON CONFLICT (product_id) DO UPDATE SET
+      rating_count = rating_count + 1,
+      rating_total = rating_total + NEW.rating,
+      average_rating = (rating_total + NEW.rating) / (rating_count + 1),
+      one_star = one_star + (CAST(ROUND(NEW.rating) AS INTEGER) = 1),
+      two_star = two_star + (CAST(ROUND(NEW.rating) AS INTEGER) = 2),
+      three_star = three_star + (CAST(ROUND(NEW.rating) AS INTEGER) = 3),
+      four_star = four_star + (CAST(ROUND(NEW.rating) AS INTEGER) = 4),
+      five_star = five_star + (CAST(ROUND(NEW.rating) AS INTEGER) = 5);
+END;
+
+CREATE TRIGGER remove_approved_rating_from_summary
+AFTER DELETE ON product_ratings
+FOR EACH ROW
+WHEN OLD.moderation_status = 'Approved'
+BEGIN
+  UPDATE product_rating_summaries SET
+      rating_count = rating_count - 1,
+      rating_total = rating_total - OLD.rating,
+      average_rating = CASE WHEN rating_count > 1
+          THEN (rating_total - OLD.rating) / (rating_count - 1) ELSE 0 END,
+      one_star = one_star - (CAST(ROUND(OLD.rating) AS INTEGER) = 1),
+      two_star = two_star - (CAST(ROUND(OLD.rating) AS INTEGER) = 2),
+      three_star = three_star - (CAST(ROUND(OLD.rating) AS INTEGER) = 3),
+      four_star = four_star - (CAST(ROUND(OLD.rating) AS INTEGER) = 4),
+      five_star = five_star - (CAST(ROUND(OLD.rating) AS INTEGER) = 5)
+  WHERE product_id = OLD.product_id;
+END;
+
+-- an update is handled as taking the old rating out and putting the new one in
+CREATE TRIGGER update_rating_in_summary
+AFTER UPDATE OF rating, moderation_status, product_id ON product_ratings
+FOR EACH ROW
+BEGIN
+  UPDATE product_rating_summaries SET
+      rating_count = rating_count - 1,
+      rating_total = rating_total - OLD.rating,
+      average_rating = CASE WHEN rating_count > 1
+          THEN (rating_total - OLD.rating) / (rating_count - 1) ELSE 0 END,
+      one_star = one_star - (CAST(ROUND(OLD.rating) AS INTEGER) = 1),
+      two_star = two_star - (CAST(ROUND(OLD.rating) AS INTEGER) = 2),
+      three_star = three_star - (CAST(ROUND(OLD.rating) AS INTEGER) = 3),
+      four_star = four_star - (CAST(ROUND(OLD.rating) AS INTEGER) = 4),
+      five_star = five_star - (CAST(ROUND(OLD.rating) AS INTEGER) = 5)
+  WHERE product_id = OLD.product_id AND OLD.moderation_status = 'Approved';
+
+  INSERT INTO product_rating_summaries (
+      product_id, rating_count, rating_total, average_rating,
+      one_star, two_star, three_star, four_star, five_star
+  )
+  SELECT
+      NEW.product_id, 1, NEW.rating, NEW.rating,
+      CAST(ROUND(NEW.rating) AS INTEGER) = 1,
+      CAST(ROUND(NEW.rating) AS INTEGER) = 2,
+      CAST(ROUND(NEW.rating) AS INTEGER) = 3,
+      CAST(ROUND(NEW.rating) AS INTEGER) = 4,
+      CAST(ROUND(NEW.rating) AS INTEGER) = 5
+  WHERE NEW.moderation_status = 'Approved'
+  ON CONFLICT (product_id) DO UPDATE SET
+      rating_count = rating_count + 1,
+      rating_total = rating_total + NEW.rating,
+      average_rating = (rating_total + NEW.rating) / (rating_count + 1),
+      one_star = one_star + (CAST(ROUND(NEW.rating) AS INTEGER) = 1),
+      two_star = two_star + (CAST(ROUND(NEW.rating) AS INTEGER) = 2),
+      three_star = three_star + (CAST(ROUND(NEW.rating) AS INTEGER) = 3),
+      four_star = four_star + (CAST(ROUND(NEW.rating) AS INTEGER) = 4),
+      five_star = five_star + (CAST(ROUND(NEW.rating) AS INTEGER) = 5);
+END;
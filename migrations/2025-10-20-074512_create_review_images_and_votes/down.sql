@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER IF EXISTS remove_helpful_vote;
+DROP TRIGGER IF EXISTS add_helpful_vote;
+DROP INDEX IF EXISTS idx_product_ratings_product_helpful;
+ALTER TABLE product_ratings DROP COLUMN helpful_count;
+DROP TABLE IF EXISTS product_rating_votes;
+DROP TABLE IF EXISTS product_rating_images;
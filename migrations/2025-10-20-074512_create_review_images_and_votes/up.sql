@@ -0,0 +1,46 @@
+-- Your SQL goes here
+CREATE TABLE product_rating_images (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    uuid TEXT NOT NULL UNIQUE,
+    rating_id INTEGER NOT NULL,
+    image_name TEXT NOT NULL,
+    FOREIGN KEY (rating_id) REFERENCES product_ratings(id) ON DELETE CASCADE
+);
+
+CREATE TABLE product_rating_votes (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    rating_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (rating_id) REFERENCES product_ratings(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+ALTER TABLE product_ratings ADD COLUMN helpful_count INTEGER NOT NULL DEFAULT 0;
+
+-- Product Rating Images Indexes
+CREATE INDEX idx_product_rating_images_rating_id ON product_rating_images(rating_id);
+
+-- Product Rating Votes Indexes
+CREATE UNIQUE INDEX idx_product_rating_votes_rating_user ON product_rating_votes(rating_id, user_id);
+CREATE INDEX idx_product_rating_votes_user_id ON product_rating_votes(user_id);
+
+CREATE INDEX idx_product_ratings_product_helpful ON product_ratings(product_id, helpful_count);
+
+CREATE TRIGGER add_helpful_vote
+AFTER INSERT ON product_rating_votes
+FOR EACH ROW
+BEGIN
+  UPDATE product_ratings
+  SET helpful_count = helpful_count + 1
+  WHERE id = NEW.rating_id;
+END;
+
+CREATE TRIGGER remove_helpful_vote
+AFTER DELETE ON product_rating_votes
+FOR EACH ROW
+BEGIN
+  UPDATE product_ratings
+  SET helpful_count = helpful_count - 1
+  WHERE id = OLD.rating_id;
+END;
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER IF EXISTS update_user_devices_updated_at;
+DROP TABLE IF EXISTS user_devices;
+DROP TABLE IF EXISTS wishlists;
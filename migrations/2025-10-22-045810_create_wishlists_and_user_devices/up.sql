@@ -0,0 +1,37 @@
+-- Your SQL goes here
+CREATE TABLE wishlists (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    uuid TEXT NOT NULL UNIQUE,
+    user_id INTEGER NOT NULL,
+    product_id INTEGER NOT NULL,
+    notify BOOLEAN NOT NULL DEFAULT 0,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (product_id) REFERENCES products(id) ON DELETE CASCADE
+);
+
+-- Customer devices, admin devices stay in admin_devices so order alerts
+-- never reach customers
+CREATE TABLE user_devices (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    uuid TEXT NOT NULL UNIQUE,
+    user_id INTEGER NOT NULL UNIQUE,
+    fcm_token TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Wishlists Indexes
+CREATE UNIQUE INDEX idx_wishlists_user_product ON wishlists(user_id, product_id);
+CREATE INDEX idx_wishlists_product_notify ON wishlists(product_id, notify);
+
+-- User Devices Indexes
+CREATE UNIQUE INDEX idx_user_devices_user_id ON user_devices(user_id);
+
+CREATE TRIGGER update_user_devices_updated_at
+AFTER UPDATE ON user_devices
+FOR EACH ROW
+BEGIN
+  UPDATE user_devices SET updated_at = CURRENT_TIMESTAMP WHERE id = OLD.id;
+END;
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+-- idx_carts_user_product is part of the init schema and stays
+ALTER TABLE carts DROP COLUMN rate;
+ALTER TABLE products DROP COLUMN is_archived;
@@ -0,0 +1,23 @@
+-- Your SQL goes here
+ALTER TABLE products ADD COLUMN is_archived BOOLEAN NOT NULL DEFAULT 0;
+
+-- unit price the customer saw when the item was put in the cart
+ALTER TABLE carts ADD COLUMN rate DOUBLE NOT NULL DEFAULT 0;
+
+UPDATE carts
+SET rate = (SELECT price FROM products WHERE products.id = carts.product_id);
+
+-- fold duplicate lines for the same product into the oldest one, databases
+-- created before the init schema gained its indexes may still have them
+UPDATE carts
+SET quantity = (
+  SELECT SUM(c.quantity)
+  FROM carts c
+  WHERE c.user_id = carts.user_id AND c.product_id = carts.product_id
+)
+WHERE id IN (SELECT MIN(id) FROM carts GROUP BY user_id, product_id);
+
+DELETE FROM carts
+WHERE id NOT IN (SELECT MIN(id) FROM carts GROUP BY user_id, product_id);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_carts_user_product ON carts(user_id, product_id);
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS idx_guest_carts_expires_on;
+DROP INDEX IF EXISTS idx_guest_carts_token_product;
+DROP TABLE IF EXISTS guest_carts;
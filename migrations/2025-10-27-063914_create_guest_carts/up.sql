@@ -0,0 +1,19 @@
+-- Your SQL goes here
+-- Carts of customers who haven't signed in yet, keyed by a token the app
+-- generates and keeps until the customer logs in or registers
+CREATE TABLE guest_carts (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    uuid TEXT NOT NULL UNIQUE,
+    token TEXT NOT NULL,
+    product_id INTEGER NOT NULL,
+    quantity DOUBLE NOT NULL,
+    sku TEXT NOT NULL,
+    rate DOUBLE NOT NULL,
+    created_on TEXT NOT NULL,
+    expires_on TIMESTAMP NOT NULL,
+    FOREIGN KEY (product_id) REFERENCES products(id) ON DELETE CASCADE
+);
+
+-- Guest Carts Indexes
+CREATE UNIQUE INDEX idx_guest_carts_token_product ON guest_carts(token, product_id);
+CREATE INDEX idx_guest_carts_expires_on ON guest_carts(expires_on);
@@ -0,0 +1,10 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER IF EXISTS update_notification_preferences_updated_at;
+DROP INDEX IF EXISTS idx_cart_reminders_user_sent;
+DROP INDEX IF EXISTS idx_carts_updated_at;
+DROP TABLE IF EXISTS cart_reminders;
+DROP TABLE IF EXISTS notification_preferences;
+
+DROP TRIGGER IF EXISTS update_carts_updated_at;
+DROP TRIGGER IF EXISTS set_carts_updated_at_on_insert;
+ALTER TABLE carts DROP COLUMN updated_at;
@@ -0,0 +1,48 @@
+-- Your SQL goes here
+-- created_on comes from the app, so keep our own record of when a cart
+-- line last changed to tell how long it has been left alone
+ALTER TABLE carts ADD COLUMN updated_at TIMESTAMP;
+
+UPDATE carts SET updated_at = CURRENT_TIMESTAMP;
+
+CREATE TRIGGER set_carts_updated_at_on_insert
+AFTER INSERT ON carts
+FOR EACH ROW
+BEGIN
+  UPDATE carts SET updated_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER update_carts_updated_at
+AFTER UPDATE OF quantity ON carts
+FOR EACH ROW
+BEGIN
+  UPDATE carts SET updated_at = CURRENT_TIMESTAMP WHERE id = OLD.id;
+END;
+
+-- Customers without a row get every notification
+CREATE TABLE notification_preferences (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL UNIQUE,
+    cart_reminders BOOLEAN NOT NULL DEFAULT 1,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE TABLE cart_reminders (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    item_count INTEGER NOT NULL,
+    sent_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Indexes
+CREATE INDEX idx_carts_updated_at ON carts(updated_at);
+CREATE INDEX idx_cart_reminders_user_sent ON cart_reminders(user_id, sent_at);
+
+CREATE TRIGGER update_notification_preferences_updated_at
+AFTER UPDATE ON notification_preferences
+FOR EACH ROW
+BEGIN
+  UPDATE notification_preferences SET updated_at = CURRENT_TIMESTAMP WHERE id = OLD.id;
+END;
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER IF EXISTS update_subscriptions_updated_at;
+DROP TABLE IF EXISTS subscription_orders;
+DROP TABLE IF EXISTS subscription_items;
+DROP TABLE IF EXISTS subscriptions;
@@ -0,0 +1,54 @@
+-- Your SQL goes here
+CREATE TABLE subscriptions (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    uuid TEXT NOT NULL UNIQUE,
+    user_id INTEGER NOT NULL,
+    frequency TEXT NOT NULL,
+    delivery_slot TEXT NOT NULL,
+    delivery_location TEXT NOT NULL,
+    payment_method TEXT NOT NULL DEFAULT 'Cash',
+    status TEXT NOT NULL DEFAULT 'Active',
+    -- YYYY-MM-DD of the next delivery, the order for it is placed ahead of time
+    next_delivery_on TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE TABLE subscription_items (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    uuid TEXT NOT NULL UNIQUE,
+    subscription_id INTEGER NOT NULL,
+    product_id INTEGER NOT NULL,
+    quantity DOUBLE NOT NULL,
+    FOREIGN KEY (subscription_id) REFERENCES subscriptions(id) ON DELETE CASCADE,
+    FOREIGN KEY (product_id) REFERENCES products(id) ON DELETE CASCADE
+);
+
+-- One row per delivery an order was placed for, so a cycle is never ordered twice
+CREATE TABLE subscription_orders (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    subscription_id INTEGER NOT NULL,
+    order_id INTEGER NOT NULL,
+    delivery_on TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (subscription_id) REFERENCES subscriptions(id) ON DELETE CASCADE,
+    FOREIGN KEY (order_id) REFERENCES orders(id) ON DELETE CASCADE
+);
+
+-- Subscriptions Indexes
+CREATE INDEX idx_subscriptions_user_id ON subscriptions(user_id);
+CREATE INDEX idx_subscriptions_status_next_delivery ON subscriptions(status, next_delivery_on);
+
+-- Subscription Items Indexes
+CREATE UNIQUE INDEX idx_subscription_items_subscription_product ON subscription_items(subscription_id, product_id);
+
+-- Subscription Orders Indexes
+CREATE UNIQUE INDEX idx_subscription_orders_subscription_delivery ON subscription_orders(subscription_id, delivery_on);
+
+CREATE TRIGGER update_subscriptions_updated_at
+AFTER UPDATE ON subscriptions
+FOR EACH ROW
+BEGIN
+  UPDATE subscriptions SET updated_at = CURRENT_TIMESTAMP WHERE id = OLD.id;
+END;
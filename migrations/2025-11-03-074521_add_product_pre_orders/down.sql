@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS idx_order_items_product_awaiting_stock;
+ALTER TABLE order_items DROP COLUMN awaiting_stock;
+ALTER TABLE products DROP COLUMN preorder_reserved;
+ALTER TABLE products DROP COLUMN preorder_capacity;
+ALTER TABLE products DROP COLUMN preorder_available_on;
+ALTER TABLE products DROP COLUMN is_preorder;
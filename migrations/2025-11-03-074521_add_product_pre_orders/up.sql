@@ -0,0 +1,12 @@
+-- Your SQL goes here
+ALTER TABLE products ADD COLUMN is_preorder BOOLEAN NOT NULL DEFAULT 0;
+-- YYYY-MM-DD the harvest is expected in
+ALTER TABLE products ADD COLUMN preorder_available_on TEXT;
+-- how much may be pre-ordered in total, and how much of it is taken
+ALTER TABLE products ADD COLUMN preorder_capacity DOUBLE NOT NULL DEFAULT 0;
+ALTER TABLE products ADD COLUMN preorder_reserved DOUBLE NOT NULL DEFAULT 0;
+
+-- pre-ordered items stay reserved until the stock for them is received
+ALTER TABLE order_items ADD COLUMN awaiting_stock BOOLEAN NOT NULL DEFAULT 0;
+
+CREATE INDEX idx_order_items_product_awaiting_stock ON order_items(product_id, awaiting_stock);
@@ -0,0 +1,18 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS refresh_tokens;
+
+CREATE TABLE refresh_tokens (
+  id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+  uuid TEXT NOT NULL UNIQUE,
+  token TEXT NOT NULL,
+  user_id INTEGER NOT NULL,
+  expires_on TEXT NOT NULL,
+
+  FOREIGN KEY(user_id) REFERENCES users(id) ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+CREATE UNIQUE INDEX idx_refresh_tokens_uuid ON refresh_tokens(uuid);
+CREATE UNIQUE INDEX idx_refresh_tokens_token ON refresh_tokens(token);
+CREATE INDEX idx_refresh_tokens_user_id ON refresh_tokens(user_id);
+CREATE INDEX idx_refresh_tokens_expires_on ON refresh_tokens(expires_on);
+CREATE INDEX idx_refresh_tokens_user_expires ON refresh_tokens(user_id, expires_on);
@@ -0,0 +1,35 @@
+-- Your SQL goes here
+-- Refresh tokens are now stored hashed and grouped per sign-in session, the
+-- plaintext tokens issued so far can't be carried over so everyone signs in again
+DROP TABLE IF EXISTS refresh_tokens;
+
+CREATE TABLE refresh_tokens (
+  id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+  uuid TEXT NOT NULL UNIQUE,
+  token_hash TEXT NOT NULL,
+  family_id TEXT NOT NULL,
+  user_id INTEGER NOT NULL,
+  expires_on TEXT NOT NULL,
+  created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  rotated_at TIMESTAMP,
+  revoked_at TIMESTAMP,
+
+  FOREIGN KEY(user_id) REFERENCES users(id) ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+-- Refresh Tokens Indexes
+
+-- UUID Index (for API lookups by UUID)
+CREATE UNIQUE INDEX idx_refresh_tokens_uuid ON refresh_tokens(uuid);
+
+-- Token Hash Index (CRITICAL - for token validation/lookup)
+CREATE UNIQUE INDEX idx_refresh_tokens_token_hash ON refresh_tokens(token_hash);
+
+-- Family Index (for revoking every token of a session)
+CREATE INDEX idx_refresh_tokens_family_id ON refresh_tokens(family_id);
+
+-- Foreign Key Index (for JOINs with users table)
+CREATE INDEX idx_refresh_tokens_user_id ON refresh_tokens(user_id);
+
+-- Expiration Index (for cleanup operations)
+CREATE INDEX idx_refresh_tokens_expires_on ON refresh_tokens(expires_on);
@@ -0,0 +1,16 @@
+-- Your SQL goes here
+-- Access tokens signed out before they expire, rows are useless once
+-- expires_at has passed and get cleared out as new ones come in
+CREATE TABLE revoked_access_tokens (
+  id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+  jti TEXT NOT NULL UNIQUE,
+  user_id INTEGER NOT NULL,
+  expires_at TIMESTAMP NOT NULL,
+  created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+  FOREIGN KEY(user_id) REFERENCES users(id) ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+-- Revoked Access Tokens Indexes
+CREATE UNIQUE INDEX idx_revoked_access_tokens_jti ON revoked_access_tokens(jti);
+CREATE INDEX idx_revoked_access_tokens_expires_at ON revoked_access_tokens(expires_at);
@@ -0,0 +1,27 @@
+-- Your SQL goes here
+-- One row per sign-in, uuid is the family_id shared by the refresh tokens
+-- issued for it and the sid claim of its access tokens
+CREATE TABLE user_sessions (
+  id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+  uuid TEXT NOT NULL UNIQUE,
+  user_id INTEGER NOT NULL,
+  device_name TEXT,
+  ip_address TEXT,
+  user_agent TEXT,
+  expires_on TEXT NOT NULL,
+  created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  last_used_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  revoked_at TIMESTAMP,
+
+  FOREIGN KEY(user_id) REFERENCES users(id) ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+-- Sessions started before this table existed, device details are unknown
+INSERT INTO user_sessions (uuid, user_id, expires_on, created_at, last_used_at, revoked_at)
+SELECT family_id, user_id, MAX(expires_on), MIN(created_at), MAX(created_at), MAX(revoked_at)
+FROM refresh_tokens
+GROUP BY family_id, user_id;
+
+-- User Sessions Indexes
+CREATE UNIQUE INDEX idx_user_sessions_uuid ON user_sessions(uuid);
+CREATE INDEX idx_user_sessions_user_id ON user_sessions(user_id);
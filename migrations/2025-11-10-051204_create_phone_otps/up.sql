@@ -0,0 +1,15 @@
+-- Your SQL goes here
+-- Sign-in codes sent by SMS, keyed by phone number so numbers that aren't
+-- registered yet can sign up with them
+CREATE TABLE phone_otps (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    phone_number TEXT NOT NULL,
+    otp_code TEXT NOT NULL,
+    expires_at TEXT NOT NULL, -- Store ISO 8601 timestamp (UTC)
+    is_used BOOLEAN NOT NULL DEFAULT 0,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))
+);
+
+CREATE INDEX idx_phone_otps_phone_expires ON phone_otps(phone_number, expires_at);
+CREATE INDEX idx_phone_otps_expires_at ON phone_otps(expires_at);
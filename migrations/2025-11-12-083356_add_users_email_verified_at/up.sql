@@ -0,0 +1,4 @@
+-- Your SQL goes here
+-- Set once the customer follows the link emailed on sign up, existing
+-- accounts start unverified and can ask for a new link
+ALTER TABLE users ADD COLUMN email_verified_at TIMESTAMP;
@@ -0,0 +1,43 @@
+-- This file should undo anything in `up.sql`
+CREATE TABLE password_reset_otps (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    otp_code TEXT NOT NULL,
+    expires_at TEXT NOT NULL, -- Store ISO 8601 timestamp (UTC)
+    is_used BOOLEAN NOT NULL DEFAULT 0,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))
+);
+
+CREATE INDEX idx_password_reset_otps_user_id ON password_reset_otps(user_id);
+CREATE INDEX idx_password_reset_otps_otp_code ON password_reset_otps(otp_code);
+CREATE INDEX idx_password_reset_otps_expires_at ON password_reset_otps(expires_at);
+CREATE INDEX idx_password_reset_otps_is_used ON password_reset_otps(is_used);
+CREATE INDEX idx_password_reset_otps_created_at ON password_reset_otps(created_at);
+CREATE INDEX idx_password_reset_otps_user_expires ON password_reset_otps(user_id, expires_at);
+CREATE INDEX idx_password_reset_otps_otp_used ON password_reset_otps(otp_code, is_used);
+
+CREATE TABLE phone_otps (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    phone_number TEXT NOT NULL,
+    otp_code TEXT NOT NULL,
+    expires_at TEXT NOT NULL, -- Store ISO 8601 timestamp (UTC)
+    is_used BOOLEAN NOT NULL DEFAULT 0,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))
+);
+
+CREATE INDEX idx_phone_otps_phone_expires ON phone_otps(phone_number, expires_at);
+CREATE INDEX idx_phone_otps_expires_at ON phone_otps(expires_at);
+
+INSERT INTO password_reset_otps (user_id, otp_code, expires_at, is_used, attempts, created_at)
+SELECT user_id, otp_code, expires_at, is_used, attempts, created_at
+FROM otps
+WHERE purpose = 'PasswordReset' AND user_id IS NOT NULL;
+
+INSERT INTO phone_otps (phone_number, otp_code, expires_at, is_used, attempts, created_at)
+SELECT recipient, otp_code, expires_at, is_used, attempts, created_at
+FROM otps
+WHERE purpose = 'PhoneLogin';
+
+DROP TABLE otps;
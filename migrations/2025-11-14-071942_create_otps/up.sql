@@ -0,0 +1,34 @@
+-- Your SQL goes here
+-- One table for every one-time code, whatever it is for and however it is
+-- sent. `recipient` is the email address or local phone number the code went
+-- to, `user_id` is empty for numbers that aren't registered yet.
+CREATE TABLE otps (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    purpose TEXT NOT NULL,
+    channel TEXT NOT NULL,
+    recipient TEXT NOT NULL,
+    user_id INTEGER REFERENCES users(id) ON DELETE CASCADE,
+    otp_code TEXT NOT NULL,
+    expires_at TEXT NOT NULL, -- Store ISO 8601 timestamp (UTC)
+    is_used BOOLEAN NOT NULL DEFAULT 0,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))
+);
+
+INSERT INTO otps (purpose, channel, recipient, user_id, otp_code, expires_at, is_used, attempts, created_at)
+SELECT 'PasswordReset', 'Email', users.email, password_reset_otps.user_id, password_reset_otps.otp_code,
+       password_reset_otps.expires_at, password_reset_otps.is_used, password_reset_otps.attempts,
+       password_reset_otps.created_at
+FROM password_reset_otps
+INNER JOIN users ON users.id = password_reset_otps.user_id;
+
+INSERT INTO otps (purpose, channel, recipient, otp_code, expires_at, is_used, attempts, created_at)
+SELECT 'PhoneLogin', 'Sms', phone_number, otp_code, expires_at, is_used, attempts, created_at
+FROM phone_otps;
+
+DROP TABLE password_reset_otps;
+DROP TABLE phone_otps;
+
+CREATE INDEX idx_otps_purpose_recipient ON otps(purpose, recipient, expires_at);
+CREATE INDEX idx_otps_user_id ON otps(user_id);
+CREATE INDEX idx_otps_expires_at ON otps(expires_at);
@@ -0,0 +1,13 @@
+-- Your SQL goes here
+-- Failed sign-in, password reset and OTP attempts, keyed by action and by the
+-- IP address or account they came from. Only used when RATE_LIMIT_BACKEND is
+-- sqlite, so every process behind the load balancer sees the same counts.
+CREATE TABLE auth_rate_limits (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    limit_key TEXT NOT NULL UNIQUE,
+    failures INTEGER NOT NULL DEFAULT 0,
+    last_failure_at TIMESTAMP NOT NULL,
+    locked_until TIMESTAMP
+);
+
+CREATE INDEX idx_auth_rate_limits_last_failure_at ON auth_rate_limits(last_failure_at);
@@ -0,0 +1,32 @@
+-- Your SQL goes here
+-- TOTP (RFC 6238) enrolment of a staff account. The row is created when setup
+-- starts and only counts once `enabled_at` is set by confirming a code.
+CREATE TABLE user_two_factors (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL UNIQUE REFERENCES users(id) ON DELETE CASCADE,
+    secret TEXT NOT NULL, -- base32, as shown to the authenticator app
+    enabled_at TIMESTAMP,
+    last_used_step BIGINT, -- time step of the last accepted code, a code can't be replayed
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- One-time codes for signing in without the authenticator, stored as SHA-256
+CREATE TABLE two_factor_recovery_codes (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    code_hash TEXT NOT NULL,
+    used_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_two_factor_recovery_codes_user_id ON two_factor_recovery_codes(user_id);
+
+-- Settings admins can change at runtime
+CREATE TABLE app_settings (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    setting_key TEXT NOT NULL UNIQUE,
+    setting_value TEXT NOT NULL,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO app_settings (setting_key, setting_value) VALUES ('require_admin_two_factor', 'false');
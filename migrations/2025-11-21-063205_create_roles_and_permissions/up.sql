@@ -0,0 +1,56 @@
+-- Your SQL goes here
+-- Roles bundle named permissions. `users.user_type` holds the role name, so a
+-- role can't be renamed and is only deleted once nobody has it.
+CREATE TABLE roles (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+    description TEXT,
+    is_system BOOLEAN NOT NULL DEFAULT 0, -- Admin and Customer, can't be edited or deleted
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE permissions (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE, -- e.g. orders.update_status
+    description TEXT NOT NULL
+);
+
+CREATE TABLE role_permissions (
+    role_id INTEGER NOT NULL REFERENCES roles(id) ON DELETE CASCADE,
+    permission_id INTEGER NOT NULL REFERENCES permissions(id) ON DELETE CASCADE,
+    PRIMARY KEY (role_id, permission_id)
+);
+
+INSERT INTO permissions (name, description) VALUES
+    ('dashboard.access', 'Read the reference lists used by the admin dashboard'),
+    ('categories.manage', 'Create, edit and delete categories'),
+    ('products.edit', 'Create, edit and delete products with their prices, attributes, availability and pre-orders'),
+    ('tags.manage', 'Create and delete tags'),
+    ('ratings.moderate', 'Moderate and reply to product ratings'),
+    ('users.manage', 'View, edit and delete users, their sessions, lockouts and roles'),
+    ('roles.manage', 'Create, edit and delete roles'),
+    ('orders.view', 'View every order and get notified about new ones'),
+    ('orders.update_status', 'Change the status of any order'),
+    ('orders.update_delivery', 'Change the delivery status of any order'),
+    ('shipments.view', 'View unassigned shipments and the ones assigned to them'),
+    ('shipments.manage', 'View every shipment and assign them to delivery staff'),
+    ('payments.view', 'View every payment'),
+    ('invoices.view', 'View and download every invoice'),
+    ('customers.assist', 'Act on customers'' carts, orders, invoices and subscriptions'),
+    ('settings.manage', 'Change the security settings');
+
+INSERT INTO roles (name, description, is_system) VALUES
+    ('Admin', 'Full access to everything', 1),
+    ('Customer', 'Shops on the store', 1),
+    ('Staff', 'Helps customers with their orders', 0),
+    ('Delivery', 'Delivers shipments', 0),
+    ('Inventory', 'Looks after the catalogue and stock', 0),
+    ('Accountant', 'Looks after payments and invoices', 0);
+
+INSERT INTO role_permissions (role_id, permission_id)
+SELECT roles.id, permissions.id FROM roles, permissions
+WHERE roles.name = 'Admin'
+   OR (roles.name = 'Staff' AND permissions.name IN ('dashboard.access', 'customers.assist', 'invoices.view'))
+   OR (roles.name = 'Delivery' AND permissions.name IN ('dashboard.access', 'shipments.view', 'orders.update_delivery'))
+   OR (roles.name = 'Inventory' AND permissions.name IN ('dashboard.access', 'categories.manage', 'products.edit', 'tags.manage'))
+   OR (roles.name = 'Accountant' AND permissions.name IN ('dashboard.access', 'orders.view', 'payments.view', 'invoices.view'));
@@ -0,0 +1,20 @@
+-- Your SQL goes here
+-- Every mutating admin request, and the status changes staff make elsewhere.
+-- The actor is kept as a uuid so the entry outlives the user.
+CREATE TABLE audit_logs (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    actor_user_id TEXT,
+    action TEXT NOT NULL, -- method and route, e.g. PATCH /admin/orders/{order_id}/order-status/update
+    entity_type TEXT NOT NULL,
+    entity_id TEXT,
+    changes TEXT, -- JSON of the changed columns, {"column": {"before": .., "after": ..}}
+    status_code INTEGER NOT NULL,
+    ip_address TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_audit_logs_created_at ON audit_logs(created_at);
+CREATE INDEX idx_audit_logs_actor_user_id ON audit_logs(actor_user_id);
+CREATE INDEX idx_audit_logs_entity ON audit_logs(entity_type, entity_id);
+
+INSERT INTO permissions (name, description) VALUES ('audit.view', 'View the audit log');
@@ -0,0 +1,7 @@
+-- Your SQL goes here
+INSERT INTO permissions (name, description) VALUES ('payments.refund', 'Refund and complete payments');
+
+INSERT INTO role_permissions (role_id, permission_id)
+SELECT roles.id, permissions.id FROM roles, permissions
+WHERE roles.name IN ('Admin', 'Accountant')
+  AND permissions.name = 'payments.refund';
@@ -15,7 +15,7 @@ impl DeliveryStatus {
         }
     }
 
-    pub fn from_str(string_value: &String) -> Result<Self, &str> {
+    pub fn from_str(string_value: &str) -> Result<Self, &str> {
         let normalized = string_value.trim().to_lowercase();
         match normalized.as_str(){
             "pending" => Ok(DeliveryStatus::Pending),
@@ -1,8 +1,14 @@
 pub mod delivery_status;
 pub mod email;
+pub mod moderation_status;
 pub mod order_status;
+pub mod otp_channel;
+pub mod otp_purpose;
 pub mod payment_method;
 pub mod payment_status;
+pub mod permission;
 pub mod phone_number;
 pub mod product_sku;
 pub mod shipment_status;
+pub mod subscription_frequency;
+pub mod subscription_status;
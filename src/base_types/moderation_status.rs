@@ -0,0 +1,40 @@
+use std::str::FromStr;
+
+pub enum ModerationStatus {
+    Pending,
+    Approved,
+    Hidden,
+}
+
+impl ModerationStatus {
+    pub fn value(&self) -> &str {
+        match *self {
+            ModerationStatus::Pending => "Pending",
+            ModerationStatus::Approved => "Approved",
+            ModerationStatus::Hidden => "Hidden",
+        }
+    }
+
+    pub fn all() -> Vec<Self> {
+        vec![
+            ModerationStatus::Pending,
+            ModerationStatus::Approved,
+            ModerationStatus::Hidden,
+        ]
+    }
+}
+
+impl FromStr for ModerationStatus {
+    type Err = &'static str;
+
+    fn from_str(string_value: &str) -> Result<Self, Self::Err> {
+        match string_value.trim().to_lowercase().as_str() {
+            "pending" => Ok(ModerationStatus::Pending),
+            "approved" => Ok(ModerationStatus::Approved),
+            "hidden" => Ok(ModerationStatus::Hidden),
+            _ => {
+                Err("Invalid moderation status. Valid values are: 'Pending', 'Approved', 'Hidden'")
+            }
+        }
+    }
+}
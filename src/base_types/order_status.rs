@@ -1,5 +1,6 @@
 pub enum OrderStatus {
     PaymentPending,
+    PreOrder,
     Pending,
     Processed,
     AwaitingDelivery,
@@ -11,6 +12,7 @@ impl OrderStatus {
     pub fn value(&self) -> &str {
         match *self {
             OrderStatus::PaymentPending => "Payment Pending",
+            OrderStatus::PreOrder => "Pre-Order",
             OrderStatus::Pending => "Pending",
             OrderStatus::Processed => "Processed",
             OrderStatus::AwaitingDelivery => "Awaiting Delivery",
@@ -19,23 +21,25 @@ impl OrderStatus {
         }
     }
 
-    pub fn from_str(string_value: &String) -> Result<Self, &str> {
+    pub fn from_str(string_value: &str) -> Result<Self, &str> {
         let normalized = string_value.trim().to_lowercase();
 
         match normalized.as_str() {
             "payment pending" => Ok(OrderStatus::PaymentPending),
+            "pre-order" => Ok(OrderStatus::PreOrder),
             "pending" => Ok(OrderStatus::Pending),
             "processed" => Ok(OrderStatus::Processed),
             "awaiting delivery" => Ok(OrderStatus::AwaitingDelivery),
             "fulfilled" => Ok(OrderStatus::Fulfilled),
             "cancelled" => Ok(OrderStatus::Cancelled),
-            _ => Err("Invalid order status. Valid values are: 'Payment Pending', 'Pending', 'Processed', 'Awaiting Delivery', 'Fulfilled', 'Cancelled'"),
+            _ => Err("Invalid order status. Valid values are: 'Payment Pending', 'Pre-Order', 'Pending', 'Processed', 'Awaiting Delivery', 'Fulfilled', 'Cancelled'"),
         }
     }
 
     pub fn all() -> Vec<Self> {
         vec![
             OrderStatus::PaymentPending,
+            OrderStatus::PreOrder,
             OrderStatus::Pending,
             OrderStatus::Processed,
             OrderStatus::AwaitingDelivery,
@@ -0,0 +1,13 @@
+pub enum OtpChannel {
+    Email,
+    Sms,
+}
+
+impl OtpChannel {
+    pub fn value(&self) -> &str {
+        match *self {
+            OtpChannel::Email => "Email",
+            OtpChannel::Sms => "Sms",
+        }
+    }
+}
@@ -0,0 +1,46 @@
+/// What a one-time code was issued for. A code only ever verifies for the
+/// purpose it was created with.
+pub enum OtpPurpose {
+    PasswordReset,
+    EmailVerification,
+    PhoneLogin,
+    OrderConfirmation,
+    AdminStepUp,
+    PhoneChange,
+}
+
+impl OtpPurpose {
+    pub fn value(&self) -> &str {
+        match *self {
+            OtpPurpose::PasswordReset => "PasswordReset",
+            OtpPurpose::EmailVerification => "EmailVerification",
+            OtpPurpose::PhoneLogin => "PhoneLogin",
+            OtpPurpose::OrderConfirmation => "OrderConfirmation",
+            OtpPurpose::AdminStepUp => "AdminStepUp",
+            OtpPurpose::PhoneChange => "PhoneChange",
+        }
+    }
+
+    pub fn expiry_minutes(&self) -> i32 {
+        match *self {
+            OtpPurpose::PasswordReset => 10,
+            OtpPurpose::EmailVerification => 30,
+            OtpPurpose::PhoneLogin => 5,
+            OtpPurpose::OrderConfirmation => 5,
+            OtpPurpose::AdminStepUp => 3,
+            OtpPurpose::PhoneChange => 10,
+        }
+    }
+
+    /// Wrong codes allowed before the code is burnt and a new one is needed.
+    pub fn max_attempts(&self) -> i32 {
+        match *self {
+            OtpPurpose::PasswordReset => 3,
+            OtpPurpose::EmailVerification => 5,
+            OtpPurpose::PhoneLogin => 3,
+            OtpPurpose::OrderConfirmation => 3,
+            OtpPurpose::AdminStepUp => 2,
+            OtpPurpose::PhoneChange => 3,
+        }
+    }
+}
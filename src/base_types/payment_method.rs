@@ -20,7 +20,7 @@ impl PaymentMethod {
         }
     }
 
-    pub fn from_str(string_value: &String) -> Result<Self, &str> {
+    pub fn from_str(string_value: &str) -> Result<Self, &str> {
         let normalized = string_value.trim().to_lowercase();
 
         match normalized.as_str() {
@@ -0,0 +1,47 @@
+/// Permissions a role can be granted, the names match the `permissions` table.
+#[derive(Clone, Copy)]
+pub enum Permission {
+    DashboardAccess,
+    CategoriesManage,
+    ProductsEdit,
+    TagsManage,
+    RatingsModerate,
+    UsersManage,
+    RolesManage,
+    OrdersView,
+    OrdersUpdateStatus,
+    OrdersUpdateDelivery,
+    ShipmentsView,
+    ShipmentsManage,
+    PaymentsView,
+    PaymentsRefund,
+    InvoicesView,
+    CustomersAssist,
+    SettingsManage,
+    AuditView,
+}
+
+impl Permission {
+    pub fn value(&self) -> &str {
+        match *self {
+            Permission::DashboardAccess => "dashboard.access",
+            Permission::CategoriesManage => "categories.manage",
+            Permission::ProductsEdit => "products.edit",
+            Permission::TagsManage => "tags.manage",
+            Permission::RatingsModerate => "ratings.moderate",
+            Permission::UsersManage => "users.manage",
+            Permission::RolesManage => "roles.manage",
+            Permission::OrdersView => "orders.view",
+            Permission::OrdersUpdateStatus => "orders.update_status",
+            Permission::OrdersUpdateDelivery => "orders.update_delivery",
+            Permission::ShipmentsView => "shipments.view",
+            Permission::ShipmentsManage => "shipments.manage",
+            Permission::PaymentsView => "payments.view",
+            Permission::PaymentsRefund => "payments.refund",
+            Permission::InvoicesView => "invoices.view",
+            Permission::CustomersAssist => "customers.assist",
+            Permission::SettingsManage => "settings.manage",
+            Permission::AuditView => "audit.view",
+        }
+    }
+}
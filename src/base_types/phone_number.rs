@@ -20,4 +20,17 @@ impl PhoneNumber {
     pub fn get_number(&self) -> String {
         self.phone_number.to_owned()
     }
+
+    /// The 10 digit number without the country code.
+    pub fn get_local_number(&self) -> String {
+        let number = self.phone_number.trim_start_matches('+');
+        number.strip_prefix("977").unwrap_or(number).to_owned()
+    }
+
+    /// Every form the number may have been stored in, with or without the
+    /// country code.
+    pub fn get_variants(&self) -> Vec<String> {
+        let local = self.get_local_number();
+        vec![format!("+977{}", local), format!("977{}", local), local]
+    }
 }
@@ -21,21 +21,6 @@ impl ShipmentStatus {
         }
     }
 
-    pub fn from_str(string_value: &String) -> Result<Self, &str> {
-        let normalized = string_value.trim().to_lowercase();
-
-        match normalized.as_str() {
-            "pending" => Ok(ShipmentStatus::Pending),
-            "processed" => Ok(ShipmentStatus::Processed),
-            "reached delivery facility" => Ok(ShipmentStatus::ReachedDeliveryFacility),
-            "awaiting delivery" => Ok(ShipmentStatus::AwaitingDelivery),
-            "out for delivery" => Ok(ShipmentStatus::OutForDelivery),
-            "fulfilled" => Ok(ShipmentStatus::Fulfilled),
-            "cancelled" => Ok(ShipmentStatus::Cancelled),
-            _ => Err("Invalid shipment status. Valid values are: 'Pending', 'Processed', 'Reached Delivery Facility', 'Awaiting Delivery', 'Out For Delivery', 'Fulfilled', 'Cancelled'"),
-        }
-    }
-
     pub fn all() -> Vec<Self> {
         vec![
             ShipmentStatus::Pending,
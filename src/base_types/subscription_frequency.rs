@@ -0,0 +1,56 @@
+use std::str::FromStr;
+
+use chrono::{Months, NaiveDate};
+
+pub enum SubscriptionFrequency {
+    Daily,
+    Weekly,
+    Fortnightly,
+    Monthly,
+}
+
+impl SubscriptionFrequency {
+    pub fn value(&self) -> &str {
+        match *self {
+            SubscriptionFrequency::Daily => "Daily",
+            SubscriptionFrequency::Weekly => "Weekly",
+            SubscriptionFrequency::Fortnightly => "Fortnightly",
+            SubscriptionFrequency::Monthly => "Monthly",
+        }
+    }
+
+    /// The delivery that follows one on `date`.
+    pub fn next_after(&self, date: NaiveDate) -> NaiveDate {
+        match *self {
+            SubscriptionFrequency::Daily => date + chrono::Duration::days(1),
+            SubscriptionFrequency::Weekly => date + chrono::Duration::weeks(1),
+            SubscriptionFrequency::Fortnightly => date + chrono::Duration::weeks(2),
+            SubscriptionFrequency::Monthly => date
+                .checked_add_months(Months::new(1))
+                .unwrap_or(date + chrono::Duration::days(30)),
+        }
+    }
+
+    pub fn all() -> Vec<Self> {
+        vec![
+            SubscriptionFrequency::Daily,
+            SubscriptionFrequency::Weekly,
+            SubscriptionFrequency::Fortnightly,
+            SubscriptionFrequency::Monthly,
+        ]
+    }
+}
+
+impl FromStr for SubscriptionFrequency {
+    type Err = &'static str;
+
+    fn from_str(string_value: &str) -> Result<Self, Self::Err> {
+        match string_value.trim().to_lowercase().as_str() {
+            "daily" => Ok(SubscriptionFrequency::Daily),
+            "weekly" => Ok(SubscriptionFrequency::Weekly),
+            "fortnightly" => Ok(SubscriptionFrequency::Fortnightly),
+            "monthly" => Ok(SubscriptionFrequency::Monthly),
+            _ => Err("Invalid subscription frequency. Valid values are: 'Daily', 'Weekly', 'Fortnightly', 'Monthly'"),
+        }
+    }
+}
@@ -0,0 +1,15 @@
+pub enum SubscriptionStatus {
+    Active,
+    Paused,
+    Cancelled,
+}
+
+impl SubscriptionStatus {
+    pub fn value(&self) -> &str {
+        match *self {
+            SubscriptionStatus::Active => "Active",
+            SubscriptionStatus::Paused => "Paused",
+            SubscriptionStatus::Cancelled => "Cancelled",
+        }
+    }
+}
@@ -4,26 +4,29 @@ pub struct ApplicationConfiguration {
     pub server_port: u16,
     pub database_url: String,
     pub jwt_secret: String,
-    pub jwt_expires_in: String,
     pub jwt_maxage: i32,
     pub refresh_token_secret: String,
     pub refresh_token_maxage: i32,
     pub product_thumbnail_path: String,
     pub product_extraimages_path: String,
+    pub category_image_path: String,
     pub esewa_merchant_id: String,
     pub esewa_merchant_secret: String,
     pub esewa_payment_verification_url: String,
     pub khalti_pidx_url: String,
-    pub khalti_payment_verification_url: String,
-    pub khalti_test_secret_key: String,
-    pub khalti_test_public_key: String,
     pub khalti_live_secret_key: String,
-    pub khalti_live_public_key: String,
     pub khalti_payment_confirm_callback_url: String,
     pub khalti_payment_confirm_callback_webiste_url: String,
     pub khalti_payment_confirm_lookup_url: String,
     pub firebase_service_account_key_path: String,
-    pub firebase_project_id: String,
+    pub abandoned_cart_idle_hours: i64,
+    pub abandoned_cart_cooldown_hours: i64,
+    pub abandoned_cart_check_interval_minutes: u64,
+    pub subscription_order_lead_days: i64,
+    pub subscription_check_interval_minutes: u64,
+    pub email_verification_url: String,
+    pub email_verification_expiry_hours: i64,
+    pub otp_cleanup_interval_minutes: u64,
 }
 
 impl ApplicationConfiguration {
@@ -32,7 +35,6 @@ impl ApplicationConfiguration {
         let server_port = std::env::var("SERVER_PORT").expect("SERVER_PORT must be set");
         let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
         let jwt_secret = std::env::var("JWT_SECRET").expect("JWT_SECRET must be set");
-        let jwt_expires_in = std::env::var("JWT_EXPIRED_IN").expect("JWT_EXPIRED_IN must be set");
         let jwt_maxage = std::env::var("JWT_MAXAGE").expect("JWT_MAXAGE must be set");
         let refresh_token_secret =
             std::env::var("REFRESH_TOKEN_SECRET").expect("REFRESH_TOKEN_SECRET must be set");
@@ -42,6 +44,8 @@ impl ApplicationConfiguration {
             std::env::var("PRODUCT_THUMBNAIL_URL").expect("PRODUCT_THUMBNAIL_URL must be set");
         let product_extraimages_path =
             std::env::var("PRODUCT_EXTRA_IMAGE_URL").expect("PRODUCT_EXTRA_IMAGE_URL must be set");
+        let category_image_path =
+            std::env::var("CATEGORY_IMAGE_URL").expect("CATEGORY_IMAGE_URL must be set");
         let esewa_merchant_id =
             std::env::var("ESEWA_MERCHANT_ID").expect("ESEWA_MERCHANT_ID must be set");
         let esewa_merchant_secret =
@@ -50,16 +54,8 @@ impl ApplicationConfiguration {
             .expect("ESEWA_PAYMENT_VERIFICATION_URL must be set");
         let khalti_pidx_url =
             std::env::var("KHALTI_PIDX_URL").expect("KHALTI_PIDX_URL must be set");
-        let khalti_payment_verification_url = std::env::var("KHALTI_PAYMENT_VERIFICATION_URL")
-            .expect("KHALTI_PAYMENT_VERIFICATION_URL must be set");
-        let khalti_test_secret_key =
-            std::env::var("KHALTI_TEST_SECRET_KEY").expect("KHALTI_TEST_SECRET_KEY must be set");
-        let khalti_test_public_key =
-            std::env::var("KHALTI_TEST_PUBLIC_KEY").expect("KHALTI_TEST_PUBLIC_KEY must be set");
         let khalti_live_secret_key =
             std::env::var("KHALTI_LIVE_SECRET_KEY").expect("KHALTI_LIVE_SECRET_KEY must be set");
-        let khalti_live_public_key =
-            std::env::var("KHALTI_LIVE_PUBLIC_KEY").expect("KHALTI_LIVE_PUBLIC_KEY must be set");
         let khalti_payment_confirm_callback_url =
             std::env::var("KHALTI_PAYMENT_CONFIRM_CALLBACK_URL")
                 .expect("KHALTI_PAYMENT_CONFIRM_CALLBACK_URL must be set");
@@ -70,34 +66,59 @@ impl ApplicationConfiguration {
             .expect("KHALTI_PAYMENT_CONFIRM_LOOKUP_URL must be set");
         let firebase_service_account_key_path = std::env::var("FIREBASE_SERVICE_ACCOUNT_KEY_PATH")
             .expect("FIREBASE_SERVICE_ACCOUNT_KEY_PATH must be set in .env file or environment");
-        let firebase_project_id = std::env::var("FIREBASE_PROJECT_ID")
-            .expect("FIREBASE_PROJECT_ID must be set in .env file or environment");
+        let abandoned_cart_idle_hours = std::env::var("ABANDONED_CART_IDLE_HOURS")
+            .expect("ABANDONED_CART_IDLE_HOURS must be set");
+        let abandoned_cart_cooldown_hours = std::env::var("ABANDONED_CART_COOLDOWN_HOURS")
+            .expect("ABANDONED_CART_COOLDOWN_HOURS must be set");
+        let abandoned_cart_check_interval_minutes =
+            std::env::var("ABANDONED_CART_CHECK_INTERVAL_MINUTES")
+                .expect("ABANDONED_CART_CHECK_INTERVAL_MINUTES must be set");
+        let subscription_order_lead_days = std::env::var("SUBSCRIPTION_ORDER_LEAD_DAYS")
+            .expect("SUBSCRIPTION_ORDER_LEAD_DAYS must be set");
+        let subscription_check_interval_minutes =
+            std::env::var("SUBSCRIPTION_CHECK_INTERVAL_MINUTES")
+                .expect("SUBSCRIPTION_CHECK_INTERVAL_MINUTES must be set");
+        let email_verification_url =
+            std::env::var("EMAIL_VERIFICATION_URL").expect("EMAIL_VERIFICATION_URL must be set");
+        let email_verification_expiry_hours = std::env::var("EMAIL_VERIFICATION_EXPIRY_HOURS")
+            .expect("EMAIL_VERIFICATION_EXPIRY_HOURS must be set");
+        let otp_cleanup_interval_minutes = std::env::var("OTP_CLEANUP_INTERVAL_MINUTES")
+            .expect("OTP_CLEANUP_INTERVAL_MINUTES must be set");
 
         Self {
             server_address,
             server_port: server_port.parse::<u16>().unwrap(),
             database_url,
             jwt_secret,
-            jwt_expires_in,
             jwt_maxage: jwt_maxage.parse::<i32>().unwrap(),
             refresh_token_secret,
             refresh_token_maxage: refresh_token_maxage.parse::<i32>().unwrap(),
             product_thumbnail_path,
             product_extraimages_path,
+            category_image_path,
             esewa_merchant_id,
             esewa_merchant_secret,
             esewa_payment_verification_url,
             khalti_pidx_url,
-            khalti_payment_verification_url,
-            khalti_test_secret_key,
-            khalti_test_public_key,
             khalti_live_secret_key,
-            khalti_live_public_key,
             khalti_payment_confirm_callback_url,
             khalti_payment_confirm_callback_webiste_url,
             khalti_payment_confirm_lookup_url,
             firebase_service_account_key_path,
-            firebase_project_id,
+            abandoned_cart_idle_hours: abandoned_cart_idle_hours.parse::<i64>().unwrap(),
+            abandoned_cart_cooldown_hours: abandoned_cart_cooldown_hours.parse::<i64>().unwrap(),
+            abandoned_cart_check_interval_minutes: abandoned_cart_check_interval_minutes
+                .parse::<u64>()
+                .unwrap(),
+            subscription_order_lead_days: subscription_order_lead_days.parse::<i64>().unwrap(),
+            subscription_check_interval_minutes: subscription_check_interval_minutes
+                .parse::<u64>()
+                .unwrap(),
+            email_verification_url,
+            email_verification_expiry_hours: email_verification_expiry_hours
+                .parse::<i64>()
+                .unwrap(),
+            otp_cleanup_interval_minutes: otp_cleanup_interval_minutes.parse::<u64>().unwrap(),
         }
     }
 }
@@ -158,10 +179,7 @@ impl EmailConfiguration {
 pub struct CompanyConfiguration {
     pub company_name: String,
     pub company_address: String,
-    pub company_email: String,
-    pub company_telephone: String,
     pub tax_rate: f64,
-    pub invoice_storage_base_dir: String,
 }
 
 impl Default for CompanyConfiguration {
@@ -169,10 +187,7 @@ impl Default for CompanyConfiguration {
         Self {
             company_name: "Haatbazaar".to_string(),
             company_address: "".to_string(),
-            company_email: "info@haatbazaar.com.np".to_string(),
-            company_telephone: "0234-56789".to_string(),
             tax_rate: 0.13,
-            invoice_storage_base_dir: "invoices/".to_string(),
         }
     }
 }
@@ -181,19 +196,78 @@ impl CompanyConfiguration {
     pub fn init() -> Self {
         let company_name = std::env::var("COMPANY_NAME").expect("COMPANY_NAME must be set");
         let address = std::env::var("COMPANY_ADDRESS").expect("COMPANY_ADDRESS must be set");
-        let email = std::env::var("COMPANY_EMAIL").expect("COMPANY_EMAIL must be set");
-        let telephone = std::env::var("COMPANY_TELEPHONE").expect("COMPANY_TELEPHONE must be set");
         let tax_rate = std::env::var("TAX_RATE").expect("TAX_RATE must be set");
-        let invoice_storage_base_dir = std::env::var("INVOICE_STORAGE_BASE_DIR")
-            .expect("INVOICE_STORAGE_BASE_DIR must be set");
 
         Self {
             company_name,
             company_address: address,
-            company_email: email,
-            company_telephone: telephone,
             tax_rate: tax_rate.parse::<f64>().unwrap(),
-            invoice_storage_base_dir,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SmsConfiguration {
+    pub provider: String,
+    pub api_url: String,
+    pub api_token: String,
+    pub sender_id: String,
+    pub log_path: String,
+}
+
+impl SmsConfiguration {
+    pub fn init() -> Self {
+        let provider = std::env::var("SMS_PROVIDER").expect("SMS_PROVIDER must be set");
+        let api_url = std::env::var("SMS_API_URL").expect("SMS_API_URL must be set");
+        let api_token = std::env::var("SMS_API_TOKEN").expect("SMS_API_TOKEN must be set");
+        let sender_id = std::env::var("SMS_SENDER_ID").expect("SMS_SENDER_ID must be set");
+        let log_path = std::env::var("SMS_LOG_PATH").expect("SMS_LOG_PATH must be set");
+
+        Self {
+            provider,
+            api_url,
+            api_token,
+            sender_id,
+            log_path,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RateLimitConfiguration {
+    pub backend: String,
+    pub window_minutes: i64,
+    pub account_max_failures: i32,
+    pub ip_max_failures: i32,
+    pub base_delay_seconds: i64,
+    pub max_delay_seconds: i64,
+    pub lockout_minutes: i64,
+}
+
+impl RateLimitConfiguration {
+    pub fn init() -> Self {
+        let backend = std::env::var("RATE_LIMIT_BACKEND").expect("RATE_LIMIT_BACKEND must be set");
+        let window_minutes = std::env::var("RATE_LIMIT_WINDOW_MINUTES")
+            .expect("RATE_LIMIT_WINDOW_MINUTES must be set");
+        let account_max_failures = std::env::var("RATE_LIMIT_ACCOUNT_MAX_FAILURES")
+            .expect("RATE_LIMIT_ACCOUNT_MAX_FAILURES must be set");
+        let ip_max_failures = std::env::var("RATE_LIMIT_IP_MAX_FAILURES")
+            .expect("RATE_LIMIT_IP_MAX_FAILURES must be set");
+        let base_delay_seconds = std::env::var("RATE_LIMIT_BASE_DELAY_SECONDS")
+            .expect("RATE_LIMIT_BASE_DELAY_SECONDS must be set");
+        let max_delay_seconds = std::env::var("RATE_LIMIT_MAX_DELAY_SECONDS")
+            .expect("RATE_LIMIT_MAX_DELAY_SECONDS must be set");
+        let lockout_minutes = std::env::var("RATE_LIMIT_LOCKOUT_MINUTES")
+            .expect("RATE_LIMIT_LOCKOUT_MINUTES must be set");
+
+        Self {
+            backend,
+            window_minutes: window_minutes.parse::<i64>().unwrap(),
+            account_max_failures: account_max_failures.parse::<i32>().unwrap(),
+            ip_max_failures: ip_max_failures.parse::<i32>().unwrap(),
+            base_delay_seconds: base_delay_seconds.parse::<i64>().unwrap(),
+            max_delay_seconds: max_delay_seconds.parse::<i64>().unwrap(),
+            lockout_minutes: lockout_minutes.parse::<i64>().unwrap(),
         }
     }
 }
@@ -0,0 +1,31 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditLog {
+    // uuid of the user who made the request
+    pub user_id: Option<String>,
+    pub action: String,
+    pub entity_type: String,
+    pub entity_id: Option<String>,
+    pub changes: Option<serde_json::Value>,
+    pub status_code: i32,
+    pub ip_address: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+// dates are YYYY-MM-DD and inclusive, action matches any part of
+// "METHOD /route", pages start at 1
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditLogParams {
+    pub user_id: Option<String>,
+    pub entity_type: Option<String>,
+    pub entity_id: Option<String>,
+    pub action: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+}
@@ -6,6 +6,12 @@ use super::user::User;
 pub struct LoginCredentials {
     pub username: String,
     pub password: String,
+    // cart built before signing in, merged into the customer's cart
+    #[serde(rename = "guestToken")]
+    pub guest_token: Option<String>,
+    // shown in the list of signed in devices, e.g. "Pixel 7"
+    #[serde(rename = "deviceName")]
+    pub device_name: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -14,12 +20,13 @@ pub struct LoginResponse {
     pub access_token: String,
     pub refresh_token: String,
     pub user: User,
+    // the admin endpoints stay closed until two-factor authentication is set up
+    pub two_factor_setup_required: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RefreshCredentials {
-    pub access_token: String,
     pub refresh_token: String,
 }
 
@@ -31,16 +38,12 @@ pub struct RefreshTokenResponse {
 }
 
 #[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct PasswordChangePayload {
-    pub user_id: String,
-    pub current_password: String,
-    pub new_password: String,
-    pub confirm_password: String,
+pub struct PasswordResetRequest {
+    pub email: String,
 }
 
 #[derive(Deserialize)]
-pub struct PasswordResetRequest {
+pub struct VerificationEmailRequest {
     pub email: String,
 }
 
@@ -72,3 +75,42 @@ pub struct ResetPasswordRequest {
     pub otp_code: String,
     pub new_password: String,
 }
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhoneOtpRequest {
+    pub phone_number: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhoneOtpResponse {
+    pub message: String,
+    pub expires_in_minutes: i32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhoneLoginRequest {
+    pub phone_number: String,
+    pub otp_code: String,
+    pub device_name: Option<String>,
+    pub guest_token: Option<String>,
+    // only needed when the number isn't registered yet
+    pub registration: Option<PhoneRegistration>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhoneRegistration {
+    pub first_name: String,
+    pub last_name: String,
+    pub email: String,
+    pub location: Option<String>,
+    pub nearest_landmark: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct EmailVerificationQuery {
+    pub token: String,
+}
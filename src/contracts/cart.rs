@@ -1,7 +1,6 @@
-use diesel::Queryable;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Queryable)]
+#[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Cart {
     #[serde(rename = "id")]
@@ -15,6 +14,45 @@ pub struct Cart {
     pub created_on: String,
     pub product_stock: f64,
     pub product_unit_change: f64,
+    pub discount: f64,
+    pub line_total: f64,
+    pub warnings: Vec<CartWarning>,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum CartWarningKind {
+    PriceChanged,
+    InsufficientStock,
+    InvalidQuantity,
+    Unavailable,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CartWarning {
+    pub kind: CartWarningKind,
+    pub message: String,
+}
+
+// totals only cover the lines that can currently be ordered
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CartSummary {
+    pub subtotal: f64,
+    pub discount: f64,
+    pub delivery_charge: f64,
+    pub total: f64,
+}
+
+// what happened to one line of a past order when it was copied into the cart
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReorderItem {
+    pub product_id: String,
+    pub product_name: String,
+    pub ordered_quantity: f64,
+    pub added_quantity: f64,
+    pub warnings: Vec<CartWarning>,
 }
 
 #[derive(Deserialize)]
@@ -1,9 +1,59 @@
+use actix_multipart::form::{tempfile::TempFile, MultipartForm};
 use diesel::{deserialize::Queryable, Selectable};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
 #[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct CategoryCreate {
     pub name: String,
+    pub slug: Option<String>,
+    pub parent_id: Option<String>,
+    pub display_order: Option<i32>,
+    pub is_active: Option<bool>,
+}
+
+// Missing fields keep their current value. parentId tells apart being left out
+// (Option::None) from an explicit null (Some(None)) which moves the category
+// to the top level.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryUpdate {
+    pub name: String,
+    pub slug: Option<String>,
+    #[serde(default, deserialize_with = "present")]
+    pub parent_id: Option<Option<String>>,
+    pub display_order: Option<i32>,
+    pub is_active: Option<bool>,
+}
+
+fn present<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Option<String>>, D::Error> {
+    Option::<String>::deserialize(d).map(Some)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryDetail {
+    #[serde(rename = "id")]
+    pub uuid: String,
+    pub name: String,
+    pub slug: String,
+    pub parent_id: Option<String>,
+    pub display_order: i32,
+    pub image: Option<String>,
+    pub is_active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub children: Option<Vec<CategoryDetail>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryDeleteParams {
+    pub reassign_to: Option<String>,
+}
+
+#[derive(MultipartForm)]
+pub struct CategoryImageUpload {
+    pub image: TempFile,
 }
 
 #[derive(Debug, Serialize, Deserialize, Queryable, Selectable)]
@@ -18,8 +68,7 @@ impl Category {
     pub fn new<T: Into<String>>(uuid: T, name: T) -> Self {
         Self {
             uuid: uuid.into(),
-            name: name.into()
+            name: name.into(),
         }
-        
     }
 }
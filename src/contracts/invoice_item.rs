@@ -12,14 +12,13 @@ pub struct InvoiceItem {
     pub discount_percent: f64,
     pub discount_amount: f64,
     pub total: f64,
+    pub tier_min_quantity: Option<f64>,
 }
 
 #[derive(Deserialize)]
 pub struct NewInvoiceItem {
     pub product_id: String,
-    pub invoice_id: Option<String>,
     pub quantity: f64,
-    pub unit_price: f64,
     pub discount_percent: Option<f64>,
     pub discount_amount: Option<f64>,
 }
@@ -64,29 +64,30 @@ pub struct KhaltiPaymentPayload {
 }
 
 impl KhaltiPaymentPayload {
+    /// Payment request for an order, the prices are in rupees.
     pub fn create(
         return_url: &str,
         website_url: &str,
-        amount: f64, // in rupees
         purchase_order_id: String,
-        purchase_order_name: String,
         customer_info: UserInfo,
-        amount_breakdown: Option<Vec<AmountBreakdown>>,
-        product_details: Option<Vec<ProductDetail>>,
-        merchant_username: String,
-        merchant_extra: String,
+        total_price: f64,
+        delivery_charge: f64,
+        product_details: Vec<ProductDetail>,
     ) -> Self {
         Self {
             return_url: return_url.to_string(),
             website_url: website_url.to_string(),
-            amount: (amount * 100.0) as i64,
+            amount: (total_price * 100.0) as i64,
             purchase_order_id,
-            purchase_order_name,
+            purchase_order_name: format!("{}'s Order", customer_info.name),
             customer_info,
-            amount_breakdown,
-            product_details,
-            merchant_username,
-            merchant_extra,
+            amount_breakdown: Some(vec![
+                AmountBreakdown::new("Delivery Charge".into(), delivery_charge),
+                AmountBreakdown::new("Product Charge".into(), total_price - delivery_charge),
+            ]),
+            product_details: Some(product_details),
+            merchant_username: "Himal Poudel".into(),
+            merchant_extra: String::new(),
         }
     }
 }
@@ -94,16 +95,7 @@ impl KhaltiPaymentPayload {
 #[derive(Debug, Deserialize)]
 pub struct KhaltiPaymentCallback {
     pub pidx: String,
-    #[serde(rename = "txnId")]
-    pub txn_id: Option<String>,
-    pub tidx: Option<String>,
-    pub amount: f64,
-    pub total_amount: f64,
-    pub status: String,
-    pub mobile: Option<String>,
     pub purchase_order_id: String,
-    pub purchase_order_name: Option<String>,
-    pub transaction_id: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
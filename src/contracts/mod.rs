@@ -1,20 +1,33 @@
 use actix_web::HttpResponse;
 
 pub mod admin_device;
+pub mod audit_log;
 pub mod auth;
 pub mod cart;
 pub mod category;
 pub mod invoice;
 pub mod invoice_item;
 pub mod khalti_payment;
+pub mod notification_preference;
 pub mod order;
 pub mod order_item;
 pub mod payment;
 pub mod product;
 pub mod product_image;
+pub mod role;
+pub mod session;
+pub mod setting;
 pub mod shipment;
+pub mod subscription;
 pub mod user;
 pub mod product_rating;
+pub mod product_price_tier;
+pub mod product_attribute;
+pub mod product_availability_rule;
+pub mod tag;
+pub mod two_factor;
+pub mod user_device;
+pub mod wishlist;
 
 pub struct ResponseWrapper {
     pub success: bool,
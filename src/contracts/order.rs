@@ -110,6 +110,7 @@ pub struct OrderItemResponse {
     pub price: f64,
     pub discount: f64,
     pub amount: f64,
+    pub tier_min_quantity: Option<f64>,
 }
 
 #[derive(Serialize, Queryable)]
@@ -173,10 +174,8 @@ pub struct UserOrderResponse {
 #[serde(rename_all = "camelCase")]
 pub struct OrderCreate {
     pub created_on: String,
-    pub fulfilled_on: Option<String>,
     pub delivery_charge: f64,
     pub delivery_location: String,
-    pub delivery_status: String,
     pub total_price: f64,
     pub user_id: String,
     pub order_items: Vec<NewOrderItem>,
@@ -187,7 +186,6 @@ pub struct OrderCreate {
 #[serde(rename_all = "camelCase")]
 pub struct PaymentDetails {
     pub payment_method: String,
-    pub status: String,
 }
 
 #[derive(Deserialize)]
@@ -217,7 +215,6 @@ pub struct CartCheckout {
     pub cart_ids: Vec<String>,
     pub user_id: String,
     pub payment_method: String,
-    pub payment_status: String,
 }
 
 #[derive(Deserialize)]
@@ -227,14 +224,6 @@ pub struct DateFilterParams {
     pub final_date: Option<String>,
 }
 
-#[derive(Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct OrderCreatedPayload {
-    pub order_id: String,
-    pub customer_name: String,
-    pub total_amount: f64,
-}
-
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CheckOrderDuplicateRequest {
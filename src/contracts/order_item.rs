@@ -1,8 +1,6 @@
 use diesel::Queryable;
 use serde::{Deserialize, Serialize};
 
-use super::product::Product;
-
 #[derive(Serialize, Queryable)]
 #[serde(rename_all = "camelCase")]
 pub struct OrderItems {
@@ -14,18 +12,7 @@ pub struct OrderItems {
     pub price: f64,
     pub amount: f64,
     pub discount: f64,
-}
-
-#[derive(Serialize)]
-#[serde(rename_all = "camelCase")]
-pub struct OrderItemResponse {
-    #[serde(rename = "id")]
-    pub uuid: String,
-    pub product: Product,
-    pub order_id: String,
-    pub quantity: f64,
-    pub price: f64,
-    pub discount: f64,
+    pub tier_min_quantity: Option<f64>,
 }
 
 #[derive(Deserialize)]
@@ -33,7 +20,6 @@ pub struct OrderItemResponse {
 pub struct NewOrderItem {
     pub quantity: f64,
     pub product_id: String,
-    pub order_id: Option<String>,
     pub price: f64,
     pub discount: f64,
 }
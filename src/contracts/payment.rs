@@ -64,12 +64,10 @@ pub struct EsewaCallbackResponse {
 
 #[derive(Deserialize, Debug)]
 pub struct KhaltiPaymentLookupResponse {
-    pub pidx: String,
     pub total_amount: f64,
     pub status: String,
     pub transaction_id: Option<String>,
     pub fee: f64,
-    pub refunded: bool,
 }
 
 #[derive(Deserialize, Debug)]
@@ -77,9 +75,3 @@ pub struct KhaltiPaymentLookupResponse {
 pub struct KhaltiPidxPayload {
     pub order_id: String,
 }
-
-#[derive(Deserialize, Debug)]
-pub struct KhaltiPaymentConfirmPayload {
-    pub pidx: String,
-    pub order_id: String,
-}
@@ -1,10 +1,14 @@
 use actix_multipart::form::{tempfile::TempFile, text::Text, MultipartForm};
-use diesel::deserialize::Queryable;
 use serde::{Deserialize, Serialize};
 
-use crate::contracts::category::Category;
+use crate::contracts::{
+    category::Category,
+    product_attribute::{AttributeFacet, ProductAttribute},
+    product_rating::RatingSummary,
+    tag::{Tag, TagFacet},
+};
 
-#[derive(Serialize, Deserialize, Queryable)]
+#[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Product {
     #[serde(rename = "id")]
@@ -18,20 +22,38 @@ pub struct Product {
     pub unit_change: f64,
     pub stock: f64,
     pub category: Category,
+    #[serde(default)]
+    pub tags: Vec<Tag>,
+    #[serde(default)]
+    pub attributes: Vec<ProductAttribute>,
+    pub available: bool,
+    #[serde(default)]
+    pub available_from: Option<String>,
+    #[serde(default)]
+    pub pre_order: Option<PreOrder>,
+    #[serde(skip_deserializing)]
+    pub rating: RatingSummary,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreOrder {
+    pub expected_on: Option<String>,
+    pub capacity: f64,
+    pub remaining: f64,
 }
 
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct ProductCreateB {
-    pub name: String,
-    pub description: String,
-    pub image: String,
-    pub price: f64,
-    pub previous_price: f64,
-    pub unit: String,
-    pub unit_change: f64,
-    pub stock: f64,
-    pub category_id: String,
+pub struct PreOrderSettings {
+    // YYYY-MM-DD the stock is expected in
+    pub expected_on: String,
+    pub capacity: f64,
+}
+
+#[derive(Deserialize)]
+pub struct StockReceipt {
+    pub quantity: f64,
 }
 
 #[derive(Deserialize)]
@@ -66,4 +88,20 @@ pub struct ProductCreate {
 #[serde(rename_all = "camelCase")]
 pub struct CategoryFilterParams {
     pub category_id: Option<String>,
+    pub include_subcategories: Option<bool>,
+    // comma separated tag slugs, a product must carry all of them
+    pub tags: Option<String>,
+    // comma separated name:value pairs, e.g. origin:Jhapa,grade:A
+    pub attributes: Option<String>,
+    // leave out products that can't be bought today instead of marking them
+    pub available_only: Option<bool>,
+    // "rating" lists the best rated products first
+    pub sort: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProductFacets {
+    pub tags: Vec<TagFacet>,
+    pub attributes: Vec<AttributeFacet>,
 }
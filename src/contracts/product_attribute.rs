@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProductAttribute {
+    pub name: String,
+    pub value: String,
+}
+
+// Replaces every attribute on the product with the given ones
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProductAttributesUpdate {
+    pub attributes: Vec<ProductAttribute>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttributeFacet {
+    pub name: String,
+    pub value: String,
+    pub count: i64,
+}
@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProductAvailabilityRule {
+    #[serde(rename = "id")]
+    pub uuid: String,
+    pub start_day: Option<String>,
+    pub end_day: Option<String>,
+    pub weekdays: Vec<String>,
+}
+
+// startDay and endDay are MM-DD and must be given together, leaving both out
+// means every day of the year. Missing weekdays means every weekday.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewProductAvailabilityRule {
+    pub start_day: Option<String>,
+    pub end_day: Option<String>,
+    pub weekdays: Option<Vec<String>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProductAvailability {
+    pub available: bool,
+    pub available_from: Option<String>,
+    pub rules: Vec<ProductAvailabilityRule>,
+}
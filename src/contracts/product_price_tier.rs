@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProductPriceTier {
+    #[serde(rename = "id")]
+    pub uuid: String,
+    pub min_quantity: f64,
+    pub price: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewProductPriceTier {
+    pub min_quantity: f64,
+    pub price: f64,
+}
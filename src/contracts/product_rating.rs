@@ -1,7 +1,8 @@
+use actix_multipart::form::{tempfile::TempFile, MultipartForm};
 use diesel::prelude::Queryable;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Queryable)]
+#[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ProductRating {
     #[serde(rename = "id")]
@@ -12,6 +13,39 @@ pub struct ProductRating {
     pub review: Option<String>,
     pub created_at: chrono::NaiveDateTime,
     pub updated_at: Option<chrono::NaiveDateTime>,
+    pub verified_purchase: bool,
+    pub moderation_status: String,
+    pub admin_reply: Option<String>,
+    pub replied_at: Option<chrono::NaiveDateTime>,
+    pub helpful_count: i32,
+    pub images: Vec<RatingImage>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RatingImage {
+    #[serde(rename = "id")]
+    pub uuid: String,
+    pub image_name: String,
+}
+
+#[derive(Serialize, Queryable)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminProductRating {
+    #[serde(rename = "id")]
+    pub uuid: String,
+    pub product_id: String,
+    pub product_name: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub rating: f64,
+    pub review: Option<String>,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: Option<chrono::NaiveDateTime>,
+    pub verified_purchase: bool,
+    pub moderation_status: String,
+    pub admin_reply: Option<String>,
+    pub replied_at: Option<chrono::NaiveDateTime>,
 }
 
 #[derive(Deserialize)]
@@ -21,3 +55,41 @@ pub struct NewProductRating {
     pub rating: f64,
     pub review: Option<String>,
 }
+
+#[derive(Deserialize)]
+pub struct RatingModerationParams {
+    pub status: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct RatingModerationUpdate {
+    pub status: String,
+}
+
+#[derive(Deserialize)]
+pub struct RatingReply {
+    pub reply: String,
+}
+
+#[derive(Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RatingSummary {
+    pub average: f64,
+    pub count: i32,
+    // number of ratings per star, one star first
+    pub histogram: [i32; 5],
+}
+
+// sort is "recent" (default) or "helpful", pages start at 1
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RatingListParams {
+    pub sort: Option<String>,
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+}
+
+#[derive(MultipartForm)]
+pub struct RatingImageUpload {
+    pub images: Vec<TempFile>,
+}
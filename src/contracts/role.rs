@@ -0,0 +1,41 @@
+use diesel::Queryable;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Queryable)]
+#[serde(rename_all = "camelCase")]
+pub struct Permission {
+    pub name: String,
+    pub description: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Role {
+    pub name: String,
+    pub description: Option<String>,
+    // Admin and Customer can't be changed
+    pub is_system: bool,
+    pub permissions: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoleCreate {
+    pub name: String,
+    pub description: Option<String>,
+    pub permissions: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoleUpdate {
+    pub description: Option<String>,
+    // replaces every permission the role had
+    pub permissions: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserRoleUpdate {
+    pub role: String,
+}
@@ -0,0 +1,16 @@
+use serde::Serialize;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Session {
+    #[serde(rename = "id")]
+    pub uuid: String,
+    pub device_name: Option<String>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: chrono::NaiveDateTime,
+    pub last_used_at: chrono::NaiveDateTime,
+    pub expires_on: String,
+    // the session the request was made from
+    pub current: bool,
+}
@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionItemCreate {
+    pub product_id: String,
+    pub quantity: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionCreate {
+    pub frequency: String,
+    // e.g. "07:00-09:00", shown to the delivery staff as is
+    pub delivery_slot: String,
+    // falls back to the customer's saved location
+    pub delivery_location: Option<String>,
+    // falls back to cash on delivery
+    pub payment_method: Option<String>,
+    // YYYY-MM-DD of the first delivery
+    pub start_on: String,
+    pub items: Vec<SubscriptionItemCreate>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionItem {
+    #[serde(rename = "id")]
+    pub uuid: String,
+    pub product_id: String,
+    pub product_name: String,
+    pub quantity: f64,
+    pub rate: f64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Subscription {
+    #[serde(rename = "id")]
+    pub uuid: String,
+    pub frequency: String,
+    pub delivery_slot: String,
+    pub delivery_location: String,
+    pub payment_method: String,
+    pub status: String,
+    pub next_delivery_on: String,
+    pub items: Vec<SubscriptionItem>,
+    pub created_at: chrono::NaiveDateTime,
+}
@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tag {
+    #[serde(rename = "id")]
+    pub uuid: String,
+    pub name: String,
+    pub slug: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagCreate {
+    pub name: String,
+    pub slug: Option<String>,
+}
+
+// Replaces every tag on the product with the given ones
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProductTagsUpdate {
+    pub tag_ids: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagFacet {
+    pub slug: String,
+    pub name: String,
+    pub count: i64,
+}
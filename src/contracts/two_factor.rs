@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TwoFactorStatus {
+    pub enabled: bool,
+    // the account can't use the admin endpoints until it's enabled
+    pub required: bool,
+    pub recovery_codes_remaining: i64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TwoFactorSetup {
+    // for typing into the app when the QR code can't be scanned
+    pub secret: String,
+    pub otpauth_uri: String,
+    pub qr_code_svg: String,
+}
+
+#[derive(Deserialize)]
+pub struct TwoFactorCode {
+    pub code: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveryCodes {
+    pub recovery_codes: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TwoFactorChallenge {
+    pub message: String,
+    pub two_factor_required: bool,
+    pub challenge_token: String,
+    pub expires_in_minutes: i64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TwoFactorLoginRequest {
+    pub challenge_token: String,
+    // a code from the authenticator app or a recovery code
+    pub code: String,
+}
@@ -29,6 +29,20 @@ pub struct UserCreate {
     pub password: String,
     pub location: Option<String>,
     pub nearest_landmark: Option<String>,
+    // cart built before registering, merged into the new account's cart
+    pub guest_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserUpdate {
+    pub first_name: String,
+    pub last_name: String,
+    pub phone_number: String,
+    pub location: Option<String>,
+    pub nearest_landmark: Option<String>,
+    // code sent to the new number, only needed when the number changes
+    pub otp_code: Option<String>,
 }
 
 #[derive(QueryableByName, Serialize)]
@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+use super::product::Product;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WishlistCreate {
+    pub product_id: String,
+    // ask to be told when the product is back in stock or its price drops
+    pub notify: Option<bool>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Wishlist {
+    #[serde(rename = "id")]
+    pub uuid: String,
+    pub product: Product,
+    pub notify: bool,
+    pub created_at: chrono::NaiveDateTime,
+}
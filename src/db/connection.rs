@@ -1,5 +1,5 @@
+use diesel::r2d2::PooledConnection;
 use diesel::r2d2::{ConnectionManager, Pool};
-use diesel::r2d2::{PoolError, PooledConnection};
 use diesel::sqlite::SqliteConnection;
 
 pub type SqliteConnectionPool = Pool<ConnectionManager<SqliteConnection>>;
@@ -19,9 +19,3 @@ pub fn establish_connection(database_url: &String) -> SqliteConnectionPool {
 pub fn get_conn(pool: &SqliteConnectionPool) -> PooledSqliteConnection {
     pool.get().unwrap()
 }
-
-pub fn get_db_connection_from_pool(
-    pool: &SqliteConnectionPool,
-) -> Result<PooledSqliteConnection, PoolError> {
-    pool.get()
-}
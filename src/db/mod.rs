@@ -1 +1,3 @@
 pub mod connection;
+#[cfg(test)]
+pub mod test_db;
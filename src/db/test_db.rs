@@ -0,0 +1,110 @@
+use std::{fs, path::Path};
+
+use diesel::{connection::SimpleConnection, prelude::*};
+use uuid::Uuid;
+
+use crate::models::{order::Order, product::Product, user::User};
+
+/// Opens an in-memory database with every migration applied, in the order
+/// diesel would run them.
+pub fn connection() -> SqliteConnection {
+    let mut conn = SqliteConnection::establish(":memory:").expect("in-memory database");
+    conn.batch_execute("PRAGMA foreign_keys = ON")
+        .expect("foreign keys on");
+
+    let migrations = Path::new(env!("CARGO_MANIFEST_DIR")).join("migrations");
+    let mut dirs: Vec<_> = fs::read_dir(migrations)
+        .expect("migrations directory")
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_dir())
+        .collect();
+    dirs.sort();
+
+    for dir in dirs {
+        let up = fs::read_to_string(dir.join("up.sql")).expect("up.sql");
+        // the initial schema was dumped from a live database, sqlite makes
+        // its own sequence table and refuses to have it created
+        let up = up.replace("CREATE TABLE sqlite_sequence(name,seq);", "");
+        conn.batch_execute(&up)
+            .unwrap_or_else(|e| panic!("{}: {}", dir.display(), e));
+    }
+
+    conn
+}
+
+/// Adds a product to a fresh category and returns it.
+pub fn product(conn: &mut SqliteConnection, price: f64, stock: f64, unit_change: f64) -> Product {
+    use crate::schema::{categories, products};
+
+    let category_uuid = Uuid::new_v4().to_string();
+    let category_id: i32 = diesel::insert_into(categories::table)
+        .values((
+            categories::uuid.eq(&category_uuid),
+            categories::name.eq(&category_uuid),
+            categories::slug.eq(&category_uuid),
+        ))
+        .returning(categories::id)
+        .get_result(conn)
+        .expect("category");
+
+    diesel::insert_into(products::table)
+        .values((
+            products::uuid.eq(Uuid::new_v4().to_string()),
+            products::name.eq("Carrot"),
+            products::description.eq(""),
+            products::image.eq(""),
+            products::price.eq(price),
+            products::previous_price.eq(price),
+            products::unit.eq("kg"),
+            products::unit_change.eq(unit_change),
+            products::stock.eq(stock),
+            products::category_id.eq(category_id),
+        ))
+        .returning(Product::as_returning())
+        .get_result(conn)
+        .expect("product")
+}
+
+/// Adds a user with the given role and returns it.
+pub fn user(conn: &mut SqliteConnection, user_type: &str) -> User {
+    use crate::schema::users;
+
+    let uuid = Uuid::new_v4().to_string();
+    diesel::insert_into(users::table)
+        .values((
+            users::email.eq(format!("{}@example.com", uuid)),
+            users::uuid.eq(uuid),
+            users::first_name.eq("Test"),
+            users::last_name.eq("User"),
+            users::phone_number.eq("9800000000"),
+            users::password.eq(""),
+            users::user_type.eq(user_type),
+        ))
+        .returning(User::as_returning())
+        .get_result(conn)
+        .expect("user")
+}
+
+/// Adds an empty order for `user` and returns it.
+pub fn order(conn: &mut SqliteConnection, user: &User) -> Order {
+    use crate::schema::orders;
+
+    diesel::insert_into(orders::table)
+        .values((
+            orders::uuid.eq(Uuid::new_v4().to_string()),
+            orders::created_on.eq("2025-11-01 10:00:00"),
+            orders::fulfilled_on.eq(""),
+            orders::delivery_charge.eq(0.0),
+            orders::delivery_location.eq("Birtamod"),
+            orders::delivery_status.eq("Pending"),
+            orders::total_price.eq(0.0),
+            orders::user_id.eq(user.get_id()),
+            orders::quantity.eq(0.0),
+            orders::status.eq("PaymentPending"),
+            orders::discount.eq(0.0),
+            orders::amount.eq(0.0),
+        ))
+        .returning(Order::as_returning())
+        .get_result(conn)
+        .expect("order")
+}
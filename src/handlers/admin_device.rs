@@ -2,9 +2,11 @@ use actix_web::{http::StatusCode, post, web, HttpResponse, Responder};
 use diesel::prelude::*;
 
 use crate::{
+    base_types::permission::Permission,
     contracts::admin_device::AdminDevice,
     db::connection::{get_conn, SqliteConnectionPool},
     models::{admin_device::NewAdminDevice, user::User},
+    services::permission_service,
 };
 
 #[post("/register-fcm-token")]
@@ -36,10 +38,23 @@ pub async fn register_fcm_token(
             ),
     };
 
-    if !user.is_admin() {
-        return HttpResponse::Forbidden()
-            .status(StatusCode::FORBIDDEN)
-            .json(serde_json::json!({"message": "Request user is not an amdin"}));
+    // the device gets a notification for every new order
+    match permission_service::role_has_permission(
+        conn,
+        user.get_user_type(),
+        Permission::OrdersView,
+    ) {
+        Ok(true) => {}
+        Ok(false) => {
+            return HttpResponse::Forbidden()
+                .status(StatusCode::FORBIDDEN)
+                .json(serde_json::json!({"message": "User is not allowed to view orders"}))
+        }
+        Err(_) => return HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(
+                serde_json::json!({"message": "Ops! something went wrong. Please try again later"}),
+            ),
     }
 
     let admin_dev = NewAdminDevice::new(&user, token.fcm_token.clone());
@@ -0,0 +1,114 @@
+use actix_web::{get, http::StatusCode, web, HttpResponse, Responder};
+use chrono::{Days, NaiveDate};
+use diesel::{prelude::*, sqlite::Sqlite};
+
+use crate::{
+    contracts::audit_log::{AuditLog, AuditLogParams},
+    db::connection::{get_conn, SqliteConnectionPool},
+    models::audit_log::AuditLog as AuditLogModel,
+    schema::audit_logs,
+};
+
+fn audit_log_response(log: &AuditLogModel) -> AuditLog {
+    AuditLog {
+        user_id: log.get_actor_user_id().map(str::to_owned),
+        action: log.get_action().to_owned(),
+        entity_type: log.get_entity_type().to_owned(),
+        entity_id: log.get_entity_id().map(str::to_owned),
+        changes: log.get_changes().and_then(|c| serde_json::from_str(c).ok()),
+        status_code: log.get_status_code(),
+        ip_address: log.get_ip_address().map(str::to_owned),
+        created_at: log.get_created_at(),
+    }
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate, HttpResponse> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
+        HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": format!("Invalid date {}, use YYYY-MM-DD", value)}))
+    })
+}
+
+// boxed queries can't be cloned, so it is built once for the page and once
+// for the total
+fn filtered<'a>(
+    params: &'a AuditLogParams,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+) -> audit_logs::BoxedQuery<'a, Sqlite> {
+    let mut query = audit_logs::table.into_boxed();
+
+    if let Some(user_id) = &params.user_id {
+        query = query.filter(audit_logs::actor_user_id.eq(user_id));
+    }
+    if let Some(entity_type) = &params.entity_type {
+        query = query.filter(audit_logs::entity_type.eq(entity_type));
+    }
+    if let Some(entity_id) = &params.entity_id {
+        query = query.filter(audit_logs::entity_id.eq(entity_id));
+    }
+    if let Some(action) = &params.action {
+        query = query.filter(audit_logs::action.like(format!("%{}%", action)));
+    }
+    if let Some(from) = from {
+        query = query.filter(audit_logs::created_at.ge(from.and_hms_opt(0, 0, 0).unwrap()));
+    }
+    if let Some(to) = to.and_then(|d| d.checked_add_days(Days::new(1))) {
+        query = query.filter(audit_logs::created_at.lt(to.and_hms_opt(0, 0, 0).unwrap()));
+    }
+
+    query
+}
+
+#[get("")]
+pub async fn get_all(
+    params: web::Query<AuditLogParams>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    let page = params.page.unwrap_or(1).max(1);
+    let per_page = params.per_page.unwrap_or(50).clamp(1, 200);
+
+    let from = match params.from.as_deref().map(parse_date).transpose() {
+        Ok(d) => d,
+        Err(res) => return res,
+    };
+    let to = match params.to.as_deref().map(parse_date).transpose() {
+        Ok(d) => d,
+        Err(res) => return res,
+    };
+
+    let conn = &mut get_conn(&pool);
+
+    let total = match filtered(&params, from, to).count().get_result::<i64>(conn) {
+        Ok(t) => t,
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! something went wrong"}))
+        }
+    };
+
+    match filtered(&params, from, to)
+        .order((audit_logs::created_at.desc(), audit_logs::id.desc()))
+        .limit(per_page)
+        .offset((page - 1) * per_page)
+        .select(AuditLogModel::as_select())
+        .load(conn)
+    {
+        Ok(l) => {
+            let l: Vec<AuditLog> = l.iter().map(audit_log_response).collect();
+            HttpResponse::Ok()
+                .status(StatusCode::OK)
+                .json(serde_json::json!({
+                    "auditLogs": l,
+                    "page": page,
+                    "perPage": per_page,
+                    "total": total,
+                }))
+        }
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
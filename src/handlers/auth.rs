@@ -1,19 +1,44 @@
 use std::sync::Arc;
 
-use actix_web::{delete, put, HttpRequest};
+use actix_web::{
+    delete, get,
+    http::header::{RETRY_AFTER, USER_AGENT},
+    HttpRequest,
+};
 use actix_web::{http::StatusCode, post, web, HttpResponse, Responder};
-use chrono::{NaiveDateTime, Utc};
+use chrono::Utc;
 use diesel::prelude::*;
+use log::error;
+use uuid::Uuid;
 
 use crate::base_types::email::Email;
+use crate::base_types::otp_channel::OtpChannel;
+use crate::base_types::otp_purpose::OtpPurpose;
+use crate::base_types::phone_number::PhoneNumber;
 use crate::contracts::auth::{
-    OtpResponse, PasswordChangePayload, PasswordResetRequest, RefreshCredentials,
-    RefreshTokenResponse, ResetPasswordRequest, VerifyOtpResponse,
+    EmailVerificationQuery, OtpResponse, PasswordResetRequest, PhoneLoginRequest, PhoneOtpRequest,
+    PhoneOtpResponse, PhoneRegistration, RefreshCredentials, RefreshTokenResponse,
+    ResetPasswordRequest, VerificationEmailRequest, VerifyOtpResponse,
 };
-use crate::models::refresh_token::{NewRefreshToken, RefreshToken};
+use crate::contracts::two_factor::{TwoFactorChallenge, TwoFactorLoginRequest};
+use crate::handlers::product_rating::find_user;
+use crate::handlers::user::is_valid_location;
+use crate::middlewares::user_info::UserInfo;
+use crate::models::user::NewUser;
+use crate::models::user_session::NewUserSession;
+use crate::services::access_token_service;
+use crate::services::cart_service;
 use crate::services::email_service::EmailService;
+use crate::services::email_verification_service;
 use crate::services::opt_service::{OtpError, OtpService};
-use crate::utils::jwt_helper::{create_refresh_token, get_expiration, verify_jwt_with_validation};
+use crate::services::rate_limit_service::{self, RateLimitAction, RateLimiter, Throttled};
+use crate::services::refresh_token_service::{self, RefreshError};
+use crate::services::sms_service::SmsService;
+use crate::services::two_factor_service::{self, TwoFactorError};
+use crate::utils::jwt_helper::{
+    create_refresh_token, create_two_factor_challenge_token, get_expiration,
+    verify_email_verification_token, verify_jwt, verify_two_factor_challenge_token,
+};
 use crate::utils::password_helper::hash_password;
 use crate::{
     config::ApplicationConfiguration,
@@ -26,16 +51,22 @@ use crate::{
     utils::{jwt_helper::create_jwt_token, password_helper::verify_password_hash},
 };
 
+// time allowed between the password and the two-factor code
+const TWO_FACTOR_CHALLENGE_MINUTES: i64 = 5;
+
 #[post("/login")]
 pub async fn login(
+    req: HttpRequest,
     creds: web::Json<LoginCredentials>,
     pool: web::Data<SqliteConnectionPool>,
     app_config: web::Data<ApplicationConfiguration>,
+    rate_limiter: web::Data<RateLimiter>,
+    email_service: web::Data<Arc<dyn EmailService>>,
 ) -> impl Responder {
-    use crate::schema::refresh_tokens::dsl::*;
     use crate::schema::users::dsl::*;
 
     let conn = &mut get_conn(&pool);
+    let ip = client_ip(&req);
 
     // Step 1: Get user by email or phone
     let login_user = match users
@@ -50,6 +81,13 @@ pub async fn login(
     {
         Ok(Some(user)) => user,
         Ok(None) => {
+            // unknown usernames are throttled too, guessing them costs the same
+            if let Err(t) = rate_limiter.check(&RateLimitAction::Login, &ip, Some(&creds.username))
+            {
+                return throttled_response(&t);
+            }
+            rate_limiter.record_failure(&RateLimitAction::Login, &ip, Some(&creds.username));
+
             return HttpResponse::Unauthorized()
                 .status(StatusCode::UNAUTHORIZED)
                 .json(serde_json::json!({ "message": "Invalid Username or Password" }));
@@ -61,19 +99,248 @@ pub async fn login(
         }
     };
 
-    // Step 2: Verify password
+    // Step 2: Make sure the address and the account aren't locked out
+    if let Err(t) = rate_limiter.check(&RateLimitAction::Login, &ip, Some(login_user.get_uuid())) {
+        return throttled_response(&t);
+    }
+
+    // Step 3: Verify password
     if !verify_password_hash(login_user.get_password(), &creds.password) {
+        count_failure(
+            &rate_limiter,
+            &email_service,
+            RateLimitAction::Login,
+            &ip,
+            &login_user,
+        );
         return HttpResponse::Unauthorized()
             .status(StatusCode::UNAUTHORIZED)
             .json(serde_json::json!({ "message": "Invalid Username or Password" }));
     }
+    rate_limiter.record_success(&RateLimitAction::Login, login_user.get_uuid());
+
+    // Step 4: Accounts with two-factor authentication finish in a second step
+    if let Some(res) = two_factor_challenge(
+        conn,
+        &app_config,
+        &login_user,
+        creds.device_name.clone(),
+        creds.guest_token.clone(),
+    ) {
+        return res;
+    }
+
+    // Step 5: Issue the tokens for a new session
+    sign_in(
+        &req,
+        conn,
+        &app_config,
+        &login_user,
+        creds.device_name.clone(),
+        creds.guest_token.as_ref(),
+    )
+    .await
+}
+
+pub fn client_ip(req: &HttpRequest) -> String {
+    req.connection_info()
+        .realip_remote_addr()
+        .unwrap_or("unknown")
+        .to_owned()
+}
+
+pub fn throttled_response(throttled: &Throttled) -> HttpResponse {
+    let message = if throttled.locked {
+        "Too many failed attempts. Please try again later."
+    } else {
+        "Please wait before trying again."
+    };
+
+    HttpResponse::TooManyRequests()
+        .status(StatusCode::TOO_MANY_REQUESTS)
+        .insert_header((RETRY_AFTER, throttled.retry_after_seconds.to_string()))
+        .json(serde_json::json!({
+            "message": message,
+            "retryAfterSeconds": throttled.retry_after_seconds
+        }))
+}
+
+// counts a failure against `user`, who is emailed when it locks the account
+pub fn count_failure(
+    rate_limiter: &RateLimiter,
+    email_service: &Arc<dyn EmailService>,
+    action: RateLimitAction,
+    ip: &str,
+    user: &UserModel,
+) {
+    if rate_limiter.record_failure(&action, ip, Some(user.get_uuid())) {
+        rate_limit_service::spawn_lockout_alert(
+            email_service.clone(),
+            &action,
+            user.get_email().to_owned(),
+            user.get_fullname(),
+            rate_limiter.get_lockout_minutes(),
+        );
+    }
+}
+
+// answers with a challenge instead of tokens when `user` has two-factor
+// authentication, None when the sign in can go ahead
+fn two_factor_challenge(
+    conn: &mut SqliteConnection,
+    app_config: &ApplicationConfiguration,
+    user: &UserModel,
+    device_name: Option<String>,
+    guest_token: Option<String>,
+) -> Option<HttpResponse> {
+    match two_factor_service::is_enabled(conn, user) {
+        Ok(false) => None,
+        Ok(true) => match create_two_factor_challenge_token(
+            user.get_uuid().to_owned(),
+            device_name,
+            guest_token,
+            TWO_FACTOR_CHALLENGE_MINUTES,
+            &app_config.jwt_secret,
+        ) {
+            Ok(token) => Some(
+                HttpResponse::Ok()
+                    .status(StatusCode::OK)
+                    .json(TwoFactorChallenge {
+                        message: "Enter the code from your authenticator app".to_string(),
+                        two_factor_required: true,
+                        challenge_token: token,
+                        expires_in_minutes: TWO_FACTOR_CHALLENGE_MINUTES,
+                    }),
+            ),
+            Err(_) => Some(
+                HttpResponse::InternalServerError()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .json(serde_json::json!({ "message": "Oops! Could not start two-factor sign in" })),
+            ),
+        },
+        Err(_) => Some(
+            HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({ "message": "Ops! something went wrong" })),
+        ),
+    }
+}
+
+/// Second step of signing in to an account with two-factor authentication.
+#[post("/login/two-factor")]
+pub async fn login_two_factor(
+    req: HttpRequest,
+    payload: web::Json<TwoFactorLoginRequest>,
+    pool: web::Data<SqliteConnectionPool>,
+    app_config: web::Data<ApplicationConfiguration>,
+    rate_limiter: web::Data<RateLimiter>,
+    email_service: web::Data<Arc<dyn EmailService>>,
+) -> impl Responder {
+    use crate::schema::users;
+
+    let claims = match verify_two_factor_challenge_token(
+        &payload.challenge_token,
+        app_config.jwt_secret.as_bytes(),
+    ) {
+        Ok(c) => c,
+        Err(_) => {
+            return HttpResponse::Unauthorized()
+                .status(StatusCode::UNAUTHORIZED)
+                .json(serde_json::json!({"message": "Sign in has expired, please sign in again"}))
+        }
+    };
+
+    let conn = &mut get_conn(&pool);
+
+    let user = match users::table
+        .filter(users::uuid.eq(&claims.sub))
+        .select(UserModel::as_select())
+        .first(conn)
+        .optional()
+    {
+        Ok(Some(u)) => u,
+        Ok(None) => {
+            return HttpResponse::Unauthorized()
+                .status(StatusCode::UNAUTHORIZED)
+                .json(serde_json::json!({"message": "Sign in has expired, please sign in again"}))
+        }
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! something went wrong"}))
+        }
+    };
+
+    let ip = client_ip(&req);
+    if let Err(t) = rate_limiter.check(&RateLimitAction::OtpVerify, &ip, Some(user.get_uuid())) {
+        return throttled_response(&t);
+    }
 
-    // Step 3: Create access token
+    match two_factor_service::verify(conn, &user, &payload.code) {
+        Ok(()) => rate_limiter.record_success(&RateLimitAction::OtpVerify, user.get_uuid()),
+        Err(TwoFactorError::InvalidCode) => {
+            count_failure(
+                &rate_limiter,
+                &email_service,
+                RateLimitAction::OtpVerify,
+                &ip,
+                &user,
+            );
+            return HttpResponse::BadRequest()
+                .status(StatusCode::BAD_REQUEST)
+                .json(serde_json::json!({"message": "Invalid authentication code"}));
+        }
+        // switched off since the password step
+        Err(TwoFactorError::NotSetUp) => {
+            return HttpResponse::Unauthorized()
+                .status(StatusCode::UNAUTHORIZED)
+                .json(serde_json::json!({"message": "Sign in has expired, please sign in again"}))
+        }
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! something went wrong"}))
+        }
+    }
+
+    sign_in(
+        &req,
+        conn,
+        &app_config,
+        &user,
+        claims.device_name.clone(),
+        claims.guest_token.as_ref(),
+    )
+    .await
+}
+
+/// Starts a new session for `login_user` on the requesting device and answers
+/// with its tokens. Shared by every way of signing in.
+async fn sign_in(
+    req: &HttpRequest,
+    conn: &mut SqliteConnection,
+    app_config: &ApplicationConfiguration,
+    login_user: &UserModel,
+    device_name: Option<String>,
+    guest_token: Option<&String>,
+) -> HttpResponse {
+    // Step 1: Create access token for a new session
+    let two_factor_setup_required = match two_factor_service::setup_required(conn, login_user) {
+        Ok(r) => r,
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({ "message": "Ops! something went wrong" }));
+        }
+    };
+    let session_id = Uuid::new_v4().to_string();
     let tok = create_jwt_token(
         login_user.get_uuid().to_owned(),
         login_user.get_user_type().to_owned(),
         app_config.jwt_maxage,
         app_config.jwt_secret.to_owned(),
+        &session_id,
+        two_factor_setup_required,
     )
     .await;
 
@@ -86,12 +353,13 @@ pub async fn login(
         }
     };
 
-    // Step 4: Create refresh token
+    // Step 2: Create refresh token
     let expiration = get_expiration((app_config.refresh_token_maxage * 24 * 60) as i64); // 7 days
     let ref_tok = create_refresh_token(
         login_user.get_uuid().to_string(),
         &app_config.refresh_token_secret,
         expiration.0,
+        &session_id,
     )
     .await;
 
@@ -104,50 +372,46 @@ pub async fn login(
         }
     };
 
-    // Step 5: Insert or update refresh token
-    let existing_token = refresh_tokens
-        .filter(user_id.eq(login_user.get_id()))
-        .first::<RefreshToken>(conn)
-        .optional();
-
-    match existing_token {
-        Ok(Some(_)) => {
-            // Update existing token
-            if diesel::update(refresh_tokens.filter(user_id.eq(login_user.get_id())))
-                .set((token.eq(&rt), expires_on.eq(expiration.1)))
-                .execute(conn)
-                .is_err()
-            {
-                return HttpResponse::InternalServerError()
-                    .status(StatusCode::INTERNAL_SERVER_ERROR)
-                    .json(
-                        serde_json::json!({ "message": "Oops! Failed to update refresh token" }),
-                    );
-            }
-        }
-        Ok(None) => {
-            // Insert new token
-            let rt_new = NewRefreshToken::new(&login_user, rt.clone(), &expiration.1);
-            if diesel::insert_into(refresh_tokens)
-                .values(&rt_new)
-                .execute(conn)
-                .is_err()
-            {
-                return HttpResponse::InternalServerError()
-                    .status(StatusCode::INTERNAL_SERVER_ERROR)
-                    .json(
-                        serde_json::json!({ "message": "Oops! Failed to insert refresh token" }),
-                    );
-            }
-        }
-        Err(_) => {
-            return HttpResponse::InternalServerError()
-                .status(StatusCode::INTERNAL_SERVER_ERROR)
-                .json(serde_json::json!({ "message": "Oops! Could not check refresh token" }));
+    // Step 3: Store the refresh token as a new session, other devices keep theirs
+    let session = NewUserSession::new(
+        login_user,
+        &session_id,
+        device_name,
+        req.connection_info()
+            .realip_remote_addr()
+            .map(|s| s.to_owned()),
+        req.headers()
+            .get(USER_AGENT)
+            .and_then(|h| h.to_str().ok())
+            .map(|s| s.to_owned()),
+        &expiration.1,
+    );
+    if let Err(e) = refresh_token_service::start_session(
+        conn,
+        login_user,
+        &session,
+        &session_id,
+        &rt,
+        &expiration.1,
+    ) {
+        return HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({ "message": format!("Oops! Failed to store refresh token: {}", e) }));
+    }
+
+    // Step 4: Move the cart built before signing in, a failed merge shouldn't
+    // keep the customer from logging in
+    if let Some(guest_token) = guest_token {
+        if let Err(e) = cart_service::merge_guest_cart(conn, guest_token, login_user) {
+            eprintln!(
+                "Failed to merge guest cart into user {}: {:?}",
+                login_user.get_uuid(),
+                e
+            );
         }
     }
 
-    // Step 6: Build and return response
+    // Step 5: Build and return response
     let login_response = LoginResponse {
         access_token: tk,
         refresh_token: rt,
@@ -161,6 +425,7 @@ pub async fn login(
             location: login_user.get_location().map(|s| s.to_owned()),
             nearest_landmark: login_user.get_nearest_landmark().map(|s| s.to_owned()),
         },
+        two_factor_setup_required,
     };
 
     HttpResponse::Ok()
@@ -174,218 +439,117 @@ pub async fn refresh_token(
     pool: web::Data<SqliteConnectionPool>,
     app_config: web::Data<ApplicationConfiguration>,
 ) -> impl Responder {
-    use crate::schema::refresh_tokens::dsl::*;
-    use crate::schema::users::dsl::*;
-    use crate::schema::{refresh_tokens, users};
+    use crate::schema::users;
 
     let conn = &mut get_conn(&pool);
 
-    let decoded = verify_jwt_with_validation(
-        tokens.access_token.as_ref(),
-        app_config.jwt_secret.as_bytes(),
-        false,
+    let claims = match verify_jwt(
+        tokens.refresh_token.as_ref(),
+        app_config.refresh_token_secret.as_bytes(),
     )
-    .await;
-
-    match decoded {
-        Ok(claims) => {
-            let usr_id = claims.sub;
-            //find the refresh token and check for validity and issue a new access token.
-            let user = match users
-                .filter(users::uuid.eq(&usr_id))
-                .select(UserModel::as_select())
-                .first::<UserModel>(conn)
-                .optional()
-            {
-                Ok(us) => match us {
-                    Some(u) => u,
-                    None => {
-                        return HttpResponse::Unauthorized()
-                            .status(StatusCode::UNAUTHORIZED)
-                            .json(serde_json::json!({"message": "Invalid access token provided. User not found"}))
-                    }
-                },
-                Err(e) => {
-                    return HttpResponse::InternalServerError()
-                        .status(StatusCode::INTERNAL_SERVER_ERROR)
-                        .json(
-                            serde_json::json!({"message": format!("Internal server error: {}", e)}),
-                        )
-                }
-            };
-
-            let ref_tok = match refresh_tokens
-                .filter(refresh_tokens::user_id.eq(user.get_id()))
-                .select(RefreshToken::as_select())
-                .first::<RefreshToken>(conn)
-                .optional()
-            {
-                Ok(rt) => match rt {
-                    Some(t) => t,
-                    None => {
-                        return HttpResponse::Unauthorized()
-                            .status(StatusCode::UNAUTHORIZED)
-                            .json(serde_json::json!({"message": "Invalid access token provided. Refresh token not found"}))
-                    }
-                },
-                Err(e) => {
-                    return HttpResponse::InternalServerError()
-                        .status(StatusCode::INTERNAL_SERVER_ERROR)
-                        .json(
-                            serde_json::json!({"message": format!("Internal server error: {}", e)}),
-                        )
-                }
-            };
-
-            if ref_tok.get_token() != tokens.refresh_token {
-                return HttpResponse::Unauthorized()
-                    .status(StatusCode::UNAUTHORIZED)
-                    .json(serde_json::json!({"message": "Invalid refresh token provided. refresh token do not match"}));
-            }
-
-            let ref_tok_expiry =
-                NaiveDateTime::parse_from_str(ref_tok.get_expires_on(), "%Y-%m-%d %H:%M:%S");
-
-            if ref_tok_expiry.is_err() {
-                return HttpResponse::Unauthorized()
-                    .status(StatusCode::UNAUTHORIZED)
-                    .json(serde_json::json!({"message": "Invalid refresh token provided, refresh token expiration parsing failed"}));
-            }
-
-            let now = Utc::now().naive_utc();
-
-            if ref_tok_expiry.unwrap() < now {
-                return HttpResponse::Unauthorized()
-                    .status(StatusCode::UNAUTHORIZED)
-                    .json(serde_json::json!({"message": "Invalid refresh token provided: refresh token expired"}));
-            }
-
-            let access_token = create_jwt_token(
-                user.get_uuid().to_owned(),
-                user.get_user_type().to_owned(),
-                app_config.jwt_maxage,
-                app_config.jwt_secret.to_owned(),
-            )
-            .await;
-
-            if let Err(e) = access_token {
-                return HttpResponse::InternalServerError()
-                    .status(StatusCode::INTERNAL_SERVER_ERROR)
-                    .json(serde_json::json!({"message": format!("Internal server error: {}", e)}));
-            }
-
-            let expiration = get_expiration((app_config.refresh_token_maxage * 24 * 60) as i64); // 7 days
-
-            let refresh_tok = create_refresh_token(
-                user.get_uuid().to_owned(),
-                &app_config.refresh_token_secret,
-                expiration.0,
-            )
-            .await;
-
-            if let Err(e) = &refresh_tok {
-                return HttpResponse::InternalServerError()
-                    .status(StatusCode::INTERNAL_SERVER_ERROR)
-                    .json(
-                        serde_json::json!({ "message": format!("Internal server error: {}", e) }),
-                    );
-            }
-
-            // Safe to unwrap now, because we handled the error case
-            let refresh_tok = refresh_tok.unwrap();
-
-            match diesel::update(&ref_tok)
-                .set((
-                    refresh_tokens::expires_on.eq(expiration.1),
-                    refresh_tokens::token.eq(&refresh_tok),
-                ))
-                .execute(conn)
-            {
-                Ok(_) => HttpResponse::Ok()
-                    .status(StatusCode::OK)
-                    .json(RefreshTokenResponse {
-                        access_token: access_token.unwrap(),
-                        refresh_token: refresh_tok,
-                    }),
-                Err(e) => HttpResponse::InternalServerError()
-                    .status(StatusCode::INTERNAL_SERVER_ERROR)
-                    .json(
-                        serde_json::json!({ "message": format!("Internal server error: {}", e) }),
-                    ),
-            }
+    .await
+    {
+        Ok(c) => c,
+        Err(e) => {
+            return HttpResponse::Unauthorized()
+                .status(StatusCode::UNAUTHORIZED)
+                .json(serde_json::json!({ "message": format!("Invalid refresh token: {}", e)}))
         }
-        Err(e) => HttpResponse::Unauthorized()
-            .status(StatusCode::UNAUTHORIZED)
-            .json(serde_json::json!({ "message": format!("Invalid access token: {}", e)})),
-    }
-}
-
-#[put("/change-password")]
-pub async fn change_password(
-    payload: web::Json<PasswordChangePayload>,
-    pool: web::Data<SqliteConnectionPool>,
-) -> impl Responder {
-    if payload.new_password != payload.confirm_password {
-        return HttpResponse::BadRequest()
-            .status(StatusCode::BAD_REQUEST)
-            .json(serde_json::json!({"message": "New password do not match confirm password"}));
-    }
+    };
 
-    use crate::schema::users::dsl::*;
+    let user = match users::table
+        .filter(users::uuid.eq(&claims.sub))
+        .select(UserModel::as_select())
+        .first::<UserModel>(conn)
+        .optional()
+    {
+        Ok(Some(u)) => u,
+        Ok(None) => return HttpResponse::Unauthorized()
+            .status(StatusCode::UNAUTHORIZED)
+            .json(
+                serde_json::json!({"message": "Invalid refresh token provided. User not found"}),
+            ),
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": format!("Internal server error: {}", e)}))
+        }
+    };
 
-    let conn = &mut get_conn(&pool);
+    let two_factor_setup_required = match two_factor_service::setup_required(conn, &user) {
+        Ok(r) => r,
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": format!("Internal server error: {}", e)}))
+        }
+    };
 
-    let user: UserModel =
-        match users
-            .filter(uuid.eq(&payload.user_id))
-            .select(UserModel::as_select())
-            .first(conn)
-            .optional()
-        {
-            Ok(Some(u)) => u,
-            Ok(None) => return HttpResponse::BadRequest()
-                .status(StatusCode::BAD_REQUEST)
-                .json(
-                    serde_json::json!({"message": "Invalid user id provided, Couldnot find user"}),
-                ),
-            Err(e) => return HttpResponse::InternalServerError()
+    let access_token = match create_jwt_token(
+        user.get_uuid().to_owned(),
+        user.get_user_type().to_owned(),
+        app_config.jwt_maxage,
+        app_config.jwt_secret.to_owned(),
+        &claims.sid,
+        two_factor_setup_required,
+    )
+    .await
+    {
+        Ok(t) => t,
+        Err(e) => {
+            return HttpResponse::InternalServerError()
                 .status(StatusCode::INTERNAL_SERVER_ERROR)
-                .json(
-                    serde_json::json!({ "message": format!("Ops! something went wrong: {}", e)}),
-                ),
-        };
+                .json(serde_json::json!({"message": format!("Internal server error: {}", e)}))
+        }
+    };
 
-    if !verify_password_hash(user.get_password(), &payload.current_password) {
-        return HttpResponse::Unauthorized()
-            .status(StatusCode::UNAUTHORIZED)
-            .json(serde_json::json!({ "message": "Invalid Username or Password" }));
-    }
+    let expiration = get_expiration((app_config.refresh_token_maxage * 24 * 60) as i64); // 7 days
 
-    let hashed_password = match hash_password(&payload.new_password) {
-        Ok(hp) => hp,
+    let refresh_tok = match create_refresh_token(
+        user.get_uuid().to_owned(),
+        &app_config.refresh_token_secret,
+        expiration.0,
+        &claims.sid,
+    )
+    .await
+    {
+        Ok(t) => t,
         Err(e) => {
             return HttpResponse::InternalServerError()
                 .status(StatusCode::INTERNAL_SERVER_ERROR)
-                .json(serde_json::json!({ "message": format!("Ops! something went wrong: {}", e)}))
+                .json(serde_json::json!({ "message": format!("Internal server error: {}", e) }))
         }
     };
 
-    match diesel::update(&user)
-        .set(password.eq(hashed_password))
-        .execute(conn)
-    {
-        Ok(_) => HttpResponse::Ok().finish(),
-        Err(e) => HttpResponse::InternalServerError()
+    match refresh_token_service::rotate(
+        conn,
+        &user,
+        &tokens.refresh_token,
+        &refresh_tok,
+        &expiration.1,
+    ) {
+        Ok(()) => HttpResponse::Ok()
+            .status(StatusCode::OK)
+            .json(RefreshTokenResponse {
+                access_token,
+                refresh_token: refresh_tok,
+            }),
+        Err(RefreshError::Database(e)) => HttpResponse::InternalServerError()
             .status(StatusCode::INTERNAL_SERVER_ERROR)
-            .json(serde_json::json!({ "message": format!("Ops! something went wrong: {}", e)})),
+            .json(serde_json::json!({ "message": format!("Internal server error: {}", e) })),
+        Err(e) => HttpResponse::Unauthorized()
+            .status(StatusCode::UNAUTHORIZED)
+            .json(serde_json::json!({ "message": e.to_string() })),
     }
 }
 
 #[post("/password-reset/request")]
 pub async fn reset_password_request(
+    req: HttpRequest,
     payload: web::Json<PasswordResetRequest>,
     pool: web::Data<SqliteConnectionPool>,
     email_service: web::Data<Arc<dyn EmailService>>,
+    rate_limiter: web::Data<RateLimiter>,
 ) -> impl Responder {
     if let Err(e) = Email::from_str(&payload.email) {
         return HttpResponse::BadRequest()
@@ -405,6 +569,20 @@ pub async fn reset_password_request(
     {
         Ok(Some(u)) => u,
         Ok(None) => {
+            let ip = client_ip(&req);
+            if let Err(t) = rate_limiter.check(
+                &RateLimitAction::PasswordResetRequest,
+                &ip,
+                Some(&payload.email),
+            ) {
+                return throttled_response(&t);
+            }
+            rate_limiter.record_failure(
+                &RateLimitAction::PasswordResetRequest,
+                &ip,
+                Some(&payload.email),
+            );
+
             return HttpResponse::BadRequest()
                 .status(StatusCode::BAD_REQUEST)
                 .json(serde_json::json!({"message": "The provided email does not exist in our records"}));
@@ -416,11 +594,35 @@ pub async fn reset_password_request(
         }
     };
 
+    // every request counts, they all send an email
+    let ip = client_ip(&req);
+    if let Err(t) = rate_limiter.check(
+        &RateLimitAction::PasswordResetRequest,
+        &ip,
+        Some(user.get_uuid()),
+    ) {
+        return throttled_response(&t);
+    }
+    count_failure(
+        &rate_limiter,
+        &email_service,
+        RateLimitAction::PasswordResetRequest,
+        &ip,
+        &user,
+    );
+
+    // the code would go to an address nobody has confirmed owning
+    if !user.is_email_verified() {
+        return HttpResponse::Forbidden()
+            .status(StatusCode::FORBIDDEN)
+            .json(serde_json::json!({"message": "Email address is not verified. Please verify your email first, a new verification link can be requested from /auth/verify-email/request"}));
+    }
+
     let user_fullname = user.get_fullname().clone();
     let email_service = email_service.into_inner();
 
     let otp_service = OtpService::new(&pool);
-    match otp_service.has_valid_otp(user.get_id()) {
+    match otp_service.has_valid_otp(&OtpPurpose::PasswordReset, user.get_email()) {
         Ok(true) => {
             return HttpResponse::TooManyRequests()
                 .json("An OTP has already been sent. Please wait before requesting a new one.");
@@ -431,20 +633,24 @@ pub async fn reset_password_request(
         _ => {} // Continue
     }
 
-    let otp_expiry_minutes = 2;
+    let otp_expiry_minutes = OtpPurpose::PasswordReset.expiry_minutes();
     tokio::spawn(async move {
-        let otp_record =
-            match otp_service.create_password_reset_otp(user.get_id(), otp_expiry_minutes * 60) {
-                Ok(otp) => otp,
-                Err(e) => {
-                    println!(
-                        "Failed to create OTP for user {}: {:?}",
-                        user.get_email(),
-                        e
-                    );
-                    return;
-                }
-            };
+        let otp_record = match otp_service.create_otp(
+            &OtpPurpose::PasswordReset,
+            &OtpChannel::Email,
+            user.get_email(),
+            Some(user.get_id()),
+        ) {
+            Ok(otp) => otp,
+            Err(e) => {
+                println!(
+                    "Failed to create OTP for user {}: {:?}",
+                    user.get_email(),
+                    e
+                );
+                return;
+            }
+        };
 
         let email_content = format!(
             r#"
@@ -477,8 +683,11 @@ pub async fn reset_password_request(
 
 #[post("/password-reset/verify-otp")]
 pub async fn verify_otp(
+    req: HttpRequest,
     verify_req: web::Json<VerifyOtpRequest>,
     pool: web::Data<SqliteConnectionPool>,
+    rate_limiter: web::Data<RateLimiter>,
+    email_service: web::Data<Arc<dyn EmailService>>,
 ) -> impl Responder {
     if let Err(e) = Email::from_str(&verify_req.email) {
         return HttpResponse::BadRequest()
@@ -508,19 +717,49 @@ pub async fn verify_otp(
         }
     };
 
-    match otp_service.verify_otp(user.get_id(), &verify_req.otp_code) {
-        Ok(true) => HttpResponse::Ok().json(VerifyOtpResponse {
-            message: "OTP verified successfully. You can now reset your password.".to_string(),
-            is_valid: true,
-        }),
-        Ok(false) => HttpResponse::BadRequest().json(VerifyOtpResponse {
-            message: "Invalid OTP. Please try again.".to_string(),
-            is_valid: false,
-        }),
-        Err(OtpError::AttemptsExceeded) => HttpResponse::BadRequest().json(VerifyOtpResponse {
-            message: "Too many attempts. Please request a new OTP.".to_string(),
-            is_valid: false,
-        }),
+    let ip = client_ip(&req);
+    if let Err(t) = rate_limiter.check(&RateLimitAction::OtpVerify, &ip, Some(user.get_uuid())) {
+        return throttled_response(&t);
+    }
+
+    match otp_service.verify_otp(
+        &OtpPurpose::PasswordReset,
+        user.get_email(),
+        &verify_req.otp_code,
+    ) {
+        Ok(true) => {
+            rate_limiter.record_success(&RateLimitAction::OtpVerify, user.get_uuid());
+            HttpResponse::Ok().json(VerifyOtpResponse {
+                message: "OTP verified successfully. You can now reset your password.".to_string(),
+                is_valid: true,
+            })
+        }
+        Ok(false) => {
+            count_failure(
+                &rate_limiter,
+                &email_service,
+                RateLimitAction::OtpVerify,
+                &ip,
+                &user,
+            );
+            HttpResponse::BadRequest().json(VerifyOtpResponse {
+                message: "Invalid OTP. Please try again.".to_string(),
+                is_valid: false,
+            })
+        }
+        Err(OtpError::AttemptsExceeded) => {
+            count_failure(
+                &rate_limiter,
+                &email_service,
+                RateLimitAction::OtpVerify,
+                &ip,
+                &user,
+            );
+            HttpResponse::BadRequest().json(VerifyOtpResponse {
+                message: "Too many attempts. Please request a new OTP.".to_string(),
+                is_valid: false,
+            })
+        }
         Err(_) => HttpResponse::BadRequest()
             .json(serde_json::json!({"message": "OTP verification failed"})),
     }
@@ -528,8 +767,11 @@ pub async fn verify_otp(
 
 #[post("/password-reset/new-password")]
 pub async fn reset_password(
+    http_req: HttpRequest,
     pool: web::Data<SqliteConnectionPool>,
     req: web::Json<ResetPasswordRequest>,
+    rate_limiter: web::Data<RateLimiter>,
+    email_service: web::Data<Arc<dyn EmailService>>,
 ) -> impl Responder {
     if req.new_password.len() < 8 {
         return HttpResponse::BadRequest().json("Password must be at least 8 characters long");
@@ -551,17 +793,27 @@ pub async fn reset_password(
         }
     };
 
-    match otp_service.verify_otp(user.get_id(), &req.otp_code) {
-        Ok(true) => {
-            match otp_service.mark_otp_as_used(user.get_id(), &req.otp_code) {
-                Ok(_) => (),
-                Err(_) => {
-                    return HttpResponse::InternalServerError()
-                        .status(StatusCode::INTERNAL_SERVER_ERROR)
-                        .json(serde_json::json!({"message": "Ops! something went wrong while updating password"}))
-                }
-            };
+    let ip = client_ip(&http_req);
+    if let Err(t) = rate_limiter.check(&RateLimitAction::OtpVerify, &ip, Some(user.get_uuid())) {
+        return throttled_response(&t);
+    }
 
+    let verified =
+        otp_service.consume_otp(&OtpPurpose::PasswordReset, user.get_email(), &req.otp_code);
+    match verified {
+        Ok(false) | Err(OtpError::AttemptsExceeded) => count_failure(
+            &rate_limiter,
+            &email_service,
+            RateLimitAction::OtpVerify,
+            &ip,
+            &user,
+        ),
+        Ok(true) => rate_limiter.record_success(&RateLimitAction::OtpVerify, user.get_uuid()),
+        Err(_) => {}
+    }
+
+    match verified {
+        Ok(true) => {
             let new_password_hash = match hash_password(&req.new_password) {
                 Ok(hash) => hash,
                 Err(_) => {
@@ -589,25 +841,537 @@ pub async fn reset_password(
             .json(serde_json::json!({"message": "Invalid otp provided"})),
         Err(OtpError::AttemptsExceeded) => HttpResponse::BadRequest()
             .json(serde_json::json!({"message": "Too many attempts. Please request a new OTP."})),
-        Err(_) => HttpResponse::InternalServerError()
+        Err(e) => {
+            error!("Otp verification failed: {}", e);
+            HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Otp verification failed"}))
+        }
+    }
+}
+
+#[post("/phone/request-otp")]
+pub async fn request_phone_otp(
+    req: HttpRequest,
+    payload: web::Json<PhoneOtpRequest>,
+    pool: web::Data<SqliteConnectionPool>,
+    sms_service: web::Data<Arc<dyn SmsService>>,
+    rate_limiter: web::Data<RateLimiter>,
+) -> impl Responder {
+    let phone = match PhoneNumber::from_str(payload.phone_number.to_owned()) {
+        Ok(p) => p,
+        Err(e) => {
+            return HttpResponse::BadRequest()
+                .status(StatusCode::BAD_REQUEST)
+                .json(serde_json::json!({"message": e}));
+        }
+    };
+    let local_number = phone.get_local_number();
+
+    // every request counts, they all may send an SMS
+    let ip = client_ip(&req);
+    if let Err(t) = rate_limiter.check(&RateLimitAction::PhoneOtpRequest, &ip, Some(&local_number))
+    {
+        return throttled_response(&t);
+    }
+    rate_limiter.record_failure(&RateLimitAction::PhoneOtpRequest, &ip, Some(&local_number));
+
+    let otp_service = OtpService::new(&pool);
+    match otp_service.has_valid_otp(&OtpPurpose::PhoneLogin, &local_number) {
+        Ok(true) => {
+            return HttpResponse::TooManyRequests()
+                .json("An OTP has already been sent. Please wait before requesting a new one.");
+        }
+        Err(_) => {
+            return HttpResponse::InternalServerError().json("Database error");
+        }
+        _ => {} // Continue
+    }
+
+    let otp_expiry_minutes = OtpPurpose::PhoneLogin.expiry_minutes();
+    let sms_service = sms_service.into_inner();
+    tokio::spawn(async move {
+        let otp_record = match otp_service.create_otp(
+            &OtpPurpose::PhoneLogin,
+            &OtpChannel::Sms,
+            &local_number,
+            None,
+        ) {
+            Ok(otp) => otp,
+            Err(e) => {
+                error!("Failed to create OTP for phone {}: {:?}", local_number, e);
+                return;
+            }
+        };
+
+        let message = format!(
+            "{} is your Haatbazar sign-in code. It expires in {} minutes.",
+            otp_record.otp_code, otp_expiry_minutes
+        );
+        if let Err(e) = sms_service.send_sms(&local_number, &message).await {
+            error!("Failed to send OTP SMS to {}: {:?}", local_number, e);
+        }
+    });
+
+    HttpResponse::Ok().json(PhoneOtpResponse {
+        message: "OTP sent to your phone".to_string(),
+        expires_in_minutes: otp_expiry_minutes,
+    })
+}
+
+/// Signs in with the code sent by SMS. A number that isn't registered yet is
+/// signed up on the way when the registration details come along.
+#[post("/phone/verify")]
+pub async fn verify_phone_otp(
+    req: HttpRequest,
+    payload: web::Json<PhoneLoginRequest>,
+    pool: web::Data<SqliteConnectionPool>,
+    app_config: web::Data<ApplicationConfiguration>,
+    email_service: web::Data<Arc<dyn EmailService>>,
+    rate_limiter: web::Data<RateLimiter>,
+) -> impl Responder {
+    use crate::schema::users;
+
+    let phone = match PhoneNumber::from_str(payload.phone_number.to_owned()) {
+        Ok(p) => p,
+        Err(e) => {
+            return HttpResponse::BadRequest()
+                .status(StatusCode::BAD_REQUEST)
+                .json(serde_json::json!({"message": e}));
+        }
+    };
+
+    if payload.otp_code.len() != 6 {
+        return HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": "Invalid otp"}));
+    }
+
+    let conn = &mut get_conn(&pool);
+
+    let existing_user = match users::table
+        .filter(users::phone_number.eq_any(phone.get_variants()))
+        .select(UserModel::as_select())
+        .first(conn)
+        .optional()
+    {
+        Ok(u) => u,
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! something went wrong"}));
+        }
+    };
+
+    // checked before the code so it isn't used up by a sign up that fails
+    if existing_user.is_none() {
+        match &payload.registration {
+            Some(registration) => {
+                if let Err(res) = check_registration(conn, registration) {
+                    return res;
+                }
+            }
+            None => {
+                return HttpResponse::BadRequest()
+                    .status(StatusCode::BAD_REQUEST)
+                    .json(serde_json::json!({"message": "Phone number is not registered, registration details are required"}));
+            }
+        }
+    }
+
+    // registered numbers count against the account, new ones against the number
+    let ip = client_ip(&req);
+    let account = existing_user
+        .as_ref()
+        .map(|u| u.get_uuid().to_owned())
+        .unwrap_or_else(|| phone.get_local_number());
+    if let Err(t) = rate_limiter.check(&RateLimitAction::OtpVerify, &ip, Some(&account)) {
+        return throttled_response(&t);
+    }
+
+    let otp_service = OtpService::new(pool.get_ref());
+    let verified = otp_service.consume_otp(
+        &OtpPurpose::PhoneLogin,
+        &phone.get_local_number(),
+        &payload.otp_code,
+    );
+    if matches!(verified, Ok(false) | Err(OtpError::AttemptsExceeded)) {
+        match &existing_user {
+            Some(u) => count_failure(
+                &rate_limiter,
+                &email_service,
+                RateLimitAction::OtpVerify,
+                &ip,
+                u,
+            ),
+            None => {
+                rate_limiter.record_failure(&RateLimitAction::OtpVerify, &ip, Some(&account));
+            }
+        }
+    }
+
+    match verified {
+        Ok(true) => rate_limiter.record_success(&RateLimitAction::OtpVerify, &account),
+        Ok(false) => {
+            return HttpResponse::BadRequest()
+                .status(StatusCode::BAD_REQUEST)
+                .json(serde_json::json!({"message": "Invalid otp provided"}));
+        }
+        Err(OtpError::AttemptsExceeded) => {
+            return HttpResponse::BadRequest().json(
+                serde_json::json!({"message": "Too many attempts. Please request a new OTP."}),
+            );
+        }
+        Err(e) => {
+            error!("Otp verification failed: {}", e);
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Otp verification failed"}));
+        }
+    }
+
+    let (login_user, is_new_user) = match (existing_user, &payload.registration) {
+        (Some(u), _) => (u, false),
+        (None, Some(registration)) => match register_phone_user(conn, phone, registration) {
+            Ok(u) => (u, true),
+            Err(res) => return res,
+        },
+        (None, None) => unreachable!("registration is checked before the otp"),
+    };
+
+    if let Some(res) = two_factor_challenge(
+        conn,
+        &app_config,
+        &login_user,
+        payload.device_name.clone(),
+        payload.guest_token.clone(),
+    ) {
+        return res;
+    }
+
+    let response = sign_in(
+        &req,
+        conn,
+        &app_config,
+        &login_user,
+        payload.device_name.clone(),
+        payload.guest_token.as_ref(),
+    )
+    .await;
+
+    if is_new_user {
+        email_verification_service::spawn_verification_email(
+            email_service.get_ref().clone(),
+            app_config.get_ref().clone(),
+            login_user,
+        );
+    }
+
+    response
+}
+
+fn check_registration(
+    conn: &mut SqliteConnection,
+    registration: &PhoneRegistration,
+) -> Result<(), HttpResponse> {
+    use crate::schema::users;
+
+    if let Err(e) = Email::from_str(&registration.email) {
+        return Err(HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": e})));
+    }
+
+    if !is_valid_location(&registration.location) {
+        return Err(HttpResponse::BadRequest().json(serde_json::json!({
+            "message": "Invalid location format. Expected: 'zip code, street, city, state, country'"
+        })));
+    }
+
+    match users::table
+        .filter(users::email.eq(&registration.email))
+        .count()
+        .get_result::<i64>(conn)
+    {
+        Ok(0) => Ok(()),
+        Ok(_) => Err(HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": "Email or Phone number already used"}))),
+        Err(_) => Err(HttpResponse::InternalServerError()
             .status(StatusCode::INTERNAL_SERVER_ERROR)
-            .json(serde_json::json!({"message": "Otp verification failed"})),
+            .json(serde_json::json!({"message": "Ops! something went wrong"}))),
     }
 }
 
-#[delete("/logout")]
-pub async fn logout(req: HttpRequest) -> impl Responder {
-    if let Some(auth_header) = req.headers().get("Authorization") {
-        if let Ok(auth_str) = auth_header.to_str() {
-            if let Some(_token) = auth_str.strip_prefix("Bearer ") {
-                return HttpResponse::Ok()
-                    .status(StatusCode::OK)
-                    .json(serde_json::json!({"message": "Logged out successfully"}));
+fn register_phone_user(
+    conn: &mut SqliteConnection,
+    phone: PhoneNumber,
+    registration: &PhoneRegistration,
+) -> Result<UserModel, HttpResponse> {
+    use crate::schema::users;
+
+    // signed up without a password, a random one keeps password login closed
+    // until the customer sets their own through a reset
+    let new_user = match NewUser::new(
+        registration.first_name.to_owned(),
+        registration.last_name.to_owned(),
+        phone,
+        registration.email.to_owned(),
+        Uuid::new_v4().to_string(),
+        registration.location.to_owned(),
+        registration.nearest_landmark.to_owned(),
+    ) {
+        Ok(u) => u,
+        Err(_) => return Err(HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(
+                serde_json::json!({"message": "Something went wrong while creating a new user"}),
+            )),
+    };
+
+    diesel::insert_into(users::table)
+        .values(&new_user)
+        .get_result::<UserModel>(conn)
+        .map_err(|_| {
+            HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Something went wrong while creating a new user"}))
+        })
+}
+
+/// Opened from the link in the verification email.
+#[get("/verify-email")]
+pub async fn verify_email(
+    query: web::Query<EmailVerificationQuery>,
+    pool: web::Data<SqliteConnectionPool>,
+    app_config: web::Data<ApplicationConfiguration>,
+) -> impl Responder {
+    use crate::schema::users;
+
+    let claims =
+        match verify_email_verification_token(&query.token, app_config.jwt_secret.as_bytes()) {
+            Ok(c) => c,
+            Err(_) => {
+                return HttpResponse::BadRequest()
+                    .status(StatusCode::BAD_REQUEST)
+                    .json(serde_json::json!({"message": "Invalid or expired verification link"}))
             }
+        };
+
+    let conn = &mut get_conn(&pool);
+
+    let user = match users::table
+        .filter(users::uuid.eq(&claims.sub))
+        .select(UserModel::as_select())
+        .first(conn)
+        .optional()
+    {
+        Ok(Some(u)) => u,
+        Ok(None) => {
+            return HttpResponse::NotFound()
+                .status(StatusCode::NOT_FOUND)
+                .json(serde_json::json!({"message": "User not found"}))
+        }
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! something went wrong"}))
+        }
+    };
+
+    // the link was sent to an address the account no longer uses
+    if user.get_email() != claims.email {
+        return HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": "Invalid or expired verification link"}));
+    }
+
+    if user.is_email_verified() {
+        return HttpResponse::Ok()
+            .status(StatusCode::OK)
+            .json(serde_json::json!({"message": "Email address is already verified"}));
+    }
+
+    match diesel::update(&user)
+        .set(users::email_verified_at.eq(Utc::now().naive_utc()))
+        .execute(conn)
+    {
+        Ok(_) => HttpResponse::Ok()
+            .status(StatusCode::OK)
+            .json(serde_json::json!({"message": "Email address verified successfully"})),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+#[post("/verify-email/resend")]
+pub async fn resend_verification_email(
+    user_info: UserInfo,
+    pool: web::Data<SqliteConnectionPool>,
+    app_config: web::Data<ApplicationConfiguration>,
+    email_service: web::Data<Arc<dyn EmailService>>,
+) -> impl Responder {
+    let conn = &mut get_conn(&pool);
+
+    let user = match find_user(conn, &user_info) {
+        Ok(u) => u,
+        Err(res) => return res,
+    };
+
+    if user.is_email_verified() {
+        return HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": "Email address is already verified"}));
+    }
+
+    match email_verification_service::send_verification_email(
+        email_service.get_ref().as_ref(),
+        &app_config,
+        &user,
+    )
+    .await
+    {
+        Ok(_) => HttpResponse::Ok().status(StatusCode::OK).json(
+            serde_json::json!({"message": "Verification email sent. Please check your inbox."}),
+        ),
+        Err(e) => {
+            println!(
+                "Failed to send verification email to {}: {:?}",
+                user.get_email(),
+                e
+            );
+            HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! could not send the verification email"}))
         }
     }
+}
 
-    HttpResponse::BadRequest()
-        .status(StatusCode::BAD_REQUEST)
-        .json(serde_json::json!({"message": "Invalid or missing token"}))
+/// Sends a verification link to an address without signing in, so accounts
+/// made before email verification existed can still get to password reset.
+/// The reply is the same whether or not the address has an account.
+#[post("/verify-email/request")]
+pub async fn request_verification_email(
+    req: HttpRequest,
+    payload: web::Json<VerificationEmailRequest>,
+    pool: web::Data<SqliteConnectionPool>,
+    app_config: web::Data<ApplicationConfiguration>,
+    email_service: web::Data<Arc<dyn EmailService>>,
+    rate_limiter: web::Data<RateLimiter>,
+) -> impl Responder {
+    use crate::schema::users;
+
+    if let Err(e) = Email::from_str(&payload.email) {
+        return HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": e}));
+    }
+
+    // every request counts, they all may send an email
+    let ip = client_ip(&req);
+    if let Err(t) = rate_limiter.check(
+        &RateLimitAction::VerificationEmailRequest,
+        &ip,
+        Some(&payload.email),
+    ) {
+        return throttled_response(&t);
+    }
+    rate_limiter.record_failure(
+        &RateLimitAction::VerificationEmailRequest,
+        &ip,
+        Some(&payload.email),
+    );
+
+    let conn = &mut get_conn(&pool);
+
+    let user = match users::table
+        .filter(users::email.eq(&payload.email))
+        .select(UserModel::as_select())
+        .first(conn)
+        .optional()
+    {
+        Ok(u) => u,
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! something went wrong"}))
+        }
+    };
+
+    if let Some(user) = user.filter(|u| !u.is_email_verified()) {
+        let email_service = email_service.into_inner();
+        let app_config = app_config.into_inner();
+        tokio::spawn(async move {
+            if let Err(e) = email_verification_service::send_verification_email(
+                email_service.as_ref().as_ref(),
+                &app_config,
+                &user,
+            )
+            .await
+            {
+                println!(
+                    "Failed to send verification email to {}: {:?}",
+                    user.get_email(),
+                    e
+                );
+            }
+        });
+    }
+
+    HttpResponse::Ok().status(StatusCode::OK).json(serde_json::json!({
+        "message": "If the address belongs to an unverified account, a verification link has been sent"
+    }))
+}
+
+#[delete("/logout")]
+pub async fn logout(user_info: UserInfo, pool: web::Data<SqliteConnectionPool>) -> impl Responder {
+    let conn = &mut get_conn(&pool);
+
+    let user = match find_user(conn, &user_info) {
+        Ok(u) => u,
+        Err(res) => return res,
+    };
+
+    let result = conn.transaction(|conn| {
+        refresh_token_service::revoke_family(conn, &user_info.session_id)?;
+        access_token_service::revoke(conn, &user, &user_info.token_id, user_info.token_expires_at)
+    });
+
+    match result {
+        Ok(_) => HttpResponse::Ok()
+            .status(StatusCode::OK)
+            .json(serde_json::json!({"message": "Logged out successfully"})),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+#[delete("/logout-all")]
+pub async fn logout_all(
+    user_info: UserInfo,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    let conn = &mut get_conn(&pool);
+
+    let user = match find_user(conn, &user_info) {
+        Ok(u) => u,
+        Err(res) => return res,
+    };
+
+    // access tokens of the other devices stop working with their sessions
+    let result = conn.transaction(|conn| {
+        refresh_token_service::revoke_all(conn, &user)?;
+        access_token_service::revoke(conn, &user, &user_info.token_id, user_info.token_expires_at)
+    });
+
+    match result {
+        Ok(_) => HttpResponse::Ok()
+            .status(StatusCode::OK)
+            .json(serde_json::json!({"message": "Logged out of all devices"})),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
 }
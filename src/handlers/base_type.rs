@@ -1,8 +1,9 @@
 use actix_web::{get, HttpResponse, Responder};
 
 use crate::base_types::{
-    delivery_status::DeliveryStatus, order_status::OrderStatus, payment_method::PaymentMethod,
-    product_sku::ProductSKU, shipment_status::ShipmentStatus,
+    delivery_status::DeliveryStatus, moderation_status::ModerationStatus,
+    order_status::OrderStatus, payment_method::PaymentMethod, product_sku::ProductSKU,
+    shipment_status::ShipmentStatus, subscription_frequency::SubscriptionFrequency,
 };
 
 #[get("/order-status")]
@@ -53,3 +54,23 @@ pub async fn get_product_sku() -> impl Responder {
 
     HttpResponse::Ok().json(units)
 }
+
+#[get("/moderation-status")]
+pub async fn get_moderation_status() -> impl Responder {
+    let statuses: Vec<String> = ModerationStatus::all()
+        .iter()
+        .map(|status| status.value().to_string())
+        .collect();
+
+    HttpResponse::Ok().json(statuses)
+}
+
+#[get("/subscription-frequencies")]
+pub async fn get_subscription_frequencies() -> impl Responder {
+    let frequencies: Vec<String> = SubscriptionFrequency::all()
+        .iter()
+        .map(|frequency| frequency.value().to_string())
+        .collect();
+
+    HttpResponse::Ok().json(frequencies)
+}
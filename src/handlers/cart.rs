@@ -6,11 +6,20 @@ use crate::contracts::cart::{Cart, NewCart, UpdateCartQuantity};
 use crate::models::cart::{Cart as CartModel, NewCartItem};
 use crate::{
     db::connection::{get_conn, SqliteConnectionPool},
+    handlers::order::DELIVERY_CHARGE,
+    middlewares::user_info::UserInfo,
     models::{product::Product as ProductModel, user::User as UserModel},
+    policies::ownership::{self, Resource},
+    services::{
+        availability_service,
+        cart_service::{self, CartLine},
+        pricing_service,
+    },
 };
 
 #[get("/{cust_id}")]
 pub async fn get(
+    user_info: UserInfo,
     cust_id: web::Path<(String,)>,
     pool: web::Data<SqliteConnectionPool>,
 ) -> impl Responder {
@@ -29,15 +38,20 @@ pub async fn get(
     //check if customer exists or not
     //maybe not needed
 
-    use crate::schema::carts::dsl::*;
     use crate::schema::products::dsl::*;
+    use crate::schema::users;
     use crate::schema::users::dsl::*;
-    use crate::schema::{carts, products, users};
+
+    let conn = &mut get_conn(&pool);
+
+    if let Err(res) = ownership::authorize(conn, &user_info, Resource::User(&cust_id.to_string())) {
+        return res;
+    }
 
     let customer: UserModel = match users
         .filter(users::uuid.eq(&cust_id.to_string()))
         .select(UserModel::as_select())
-        .first(&mut get_conn(&pool))
+        .first(conn)
         .optional()
     {
         Ok(Some(c)) => c,
@@ -53,31 +67,12 @@ pub async fn get(
         }
     };
 
-    let carts_vec: Vec<Cart> = match carts
-        .inner_join(users)
+    let cart_rows: Vec<(CartModel, ProductModel)> = match CartModel::belonging_to(&customer)
         .inner_join(products)
-        .filter(carts::user_id.eq(&customer.get_id()))
-        .select((
-            carts::uuid,
-            products::uuid,
-            products::name,
-            carts::quantity,
-            products::price,
-            carts::sku,
-            products::image,
-            carts::created_on,
-            products::stock,
-            products::unit_change,
-        ))
-        .load::<Cart>(&mut get_conn(&pool))
-        .optional()
+        .select((CartModel::as_select(), ProductModel::as_select()))
+        .load(conn)
     {
-        Ok(Some(c)) => c,
-        Ok(None) => {
-            return HttpResponse::NotFound()
-                .status(StatusCode::NOT_FOUND)
-                .json(serde_json::json!({"message": "User not found"}))
-        }
+        Ok(c) => c,
         Err(_) => {
             return HttpResponse::InternalServerError()
                 .status(StatusCode::INTERNAL_SERVER_ERROR)
@@ -85,11 +80,29 @@ pub async fn get(
         }
     };
 
-    HttpResponse::Ok().json(serde_json::json!({"carts": carts_vec}))
+    // every line is repriced and checked against the product as it is today
+    let today = availability_service::today();
+    let mut lines: Vec<CartLine<CartModel>> = Vec::with_capacity(cart_rows.len());
+    for (cart, product) in cart_rows {
+        match cart_service::validate_line(conn, cart, product, today) {
+            Ok(line) => lines.push(line),
+            Err(_) => {
+                return HttpResponse::InternalServerError()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .json(serde_json::json!({"message": "Ops! something went wrong"}))
+            }
+        }
+    }
+
+    let carts_vec: Vec<Cart> = lines.iter().map(CartLine::as_response).collect();
+    let summary = cart_service::summarize(&lines, DELIVERY_CHARGE);
+
+    HttpResponse::Ok().json(serde_json::json!({"carts": carts_vec, "summary": summary}))
 }
 
 #[post("/{cust_id}")]
 pub async fn create(
+    user_info: UserInfo,
     cust_id: web::Path<(String,)>,
     cart_item: web::Json<NewCart>,
     pool: web::Data<SqliteConnectionPool>,
@@ -119,10 +132,14 @@ pub async fn create(
     use crate::schema::carts::dsl::*;
     use crate::schema::products::dsl::*;
     use crate::schema::users::dsl::*;
-    use crate::schema::{products, users};
+    use crate::schema::{carts, products, users};
 
     let conn = &mut get_conn(&pool);
 
+    if let Err(res) = ownership::authorize(conn, &user_info, Resource::User(&cust_id.to_string())) {
+        return res;
+    }
+
     let customer: UserModel = match users
         .filter(users::uuid.eq(&cust_id.to_string()))
         .select(UserModel::as_select())
@@ -161,34 +178,84 @@ pub async fn create(
         }
     };
 
+    match availability_service::availability_for_product(
+        conn,
+        &product,
+        availability_service::today(),
+    ) {
+        Ok(a) if a.available => {}
+        Ok(a) => {
+            return HttpResponse::BadRequest()
+                .status(StatusCode::BAD_REQUEST)
+                .json(serde_json::json!({"message": a.unavailable_message(&product)}))
+        }
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! something went wrong"}))
+        }
+    }
+
+    // adding a product that is already in the cart tops up the existing line
+    let existing_quantity: f64 = match carts
+        .filter(carts::user_id.eq(customer.get_id()))
+        .filter(carts::product_id.eq(product.get_id()))
+        .select(carts::quantity)
+        .first(conn)
+        .optional()
+    {
+        Ok(q) => q.unwrap_or(0.0),
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! something went wrong"}))
+        }
+    };
+    let merged_quantity = existing_quantity + cart_item.quantity;
+
+    if merged_quantity > product.get_stock() {
+        return HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": "Requested quantity is greater than stock"}));
+    }
+
+    let applied_price = match pricing_service::price_for_quantity(conn, &product, merged_quantity) {
+        Ok(p) => p,
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! something went wrong"}))
+        }
+    };
+
     let cart: NewCartItem = NewCartItem::new(
         &product,
         &customer,
-        cart_item.quantity,
+        merged_quantity,
         cart_item.created_on.to_owned(),
+        applied_price.unit_price,
     );
 
     match diesel::insert_into(carts)
         .values(&cart)
-        .get_result::<CartModel>(conn)
+        .on_conflict((carts::user_id, carts::product_id))
+        .do_update()
+        .set((
+            carts::quantity.eq(merged_quantity),
+            carts::rate.eq(applied_price.unit_price),
+        ))
+        .returning(CartModel::as_returning())
+        .get_result(conn)
     {
-        Ok(c) => {
-            let cart_vm: Cart = Cart {
-                uuid: c.get_uuid().to_owned(),
-                product_id: product.get_uuid().to_owned(),
-                quantity: c.get_quantity(),
-                rate: product.get_price(),
-                sku: c.get_sku().to_owned(),
-                image: product.get_image().to_owned(),
-                created_on: c.get_created_on().to_owned(),
-                product_name: product.get_name().to_owned(),
-                product_stock: product.get_stock(),
-                product_unit_change: product.get_unit_change(),
-            };
-            HttpResponse::Ok()
+        Ok(c) => match cart_service::validate_line(conn, c, product, availability_service::today())
+        {
+            Ok(line) => HttpResponse::Ok()
                 .status(StatusCode::OK)
-                .json(serde_json::json!({"cart": cart_vm}))
-        }
+                .json(serde_json::json!({"cart": line.as_response()})),
+            Err(_) => HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! something went wrong"})),
+        },
         Err(_) => HttpResponse::InternalServerError()
             .status(StatusCode::INTERNAL_SERVER_ERROR)
             .json(serde_json::json!({"message": "Ops! something went wrong"})),
@@ -197,6 +264,7 @@ pub async fn create(
 
 #[patch("/{cart_id}/update-quantity")]
 pub async fn update_quantity(
+    user_info: UserInfo,
     cart_id: web::Path<(String,)>,
     pool: web::Data<SqliteConnectionPool>,
     quantity_vm: web::Json<UpdateCartQuantity>,
@@ -226,6 +294,11 @@ pub async fn update_quantity(
 
     let conn = &mut get_conn(&pool);
 
+    if let Err(res) = ownership::authorize(conn, &user_info, Resource::Cart(&cart_uuid.to_string()))
+    {
+        return res;
+    }
+
     let cart: CartModel = match carts
         .filter(carts::uuid.eq(&cart_uuid.to_string()))
         .select(CartModel::as_select())
@@ -265,6 +338,24 @@ pub async fn update_quantity(
         }
     };
 
+    match availability_service::availability_for_product(
+        conn,
+        &product,
+        availability_service::today(),
+    ) {
+        Ok(a) if a.available => {}
+        Ok(a) => {
+            return HttpResponse::BadRequest()
+                .status(StatusCode::BAD_REQUEST)
+                .json(serde_json::json!({"message": a.unavailable_message(&product)}))
+        }
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! something went wrong"}))
+        }
+    }
+
     // validate the quantity against the stock of the product
     if quantity_vm.new_quantity > product.get_stock() {
         return HttpResponse::BadRequest()
@@ -272,25 +363,34 @@ pub async fn update_quantity(
             .json(serde_json::json!({"message": "Requested quantity is greater than stock"}));
     }
 
+    let applied_price =
+        match pricing_service::price_for_quantity(conn, &product, quantity_vm.new_quantity) {
+            Ok(p) => p,
+            Err(_) => {
+                return HttpResponse::InternalServerError()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .json(serde_json::json!({"message": "Ops! something went wrong"}))
+            }
+        };
+
+    // changing the quantity means the customer has seen the current price
     match diesel::update(&cart)
-        .set(carts::quantity.eq(quantity_vm.new_quantity))
-        .get_result::<CartModel>(conn)
+        .set((
+            carts::quantity.eq(quantity_vm.new_quantity),
+            carts::rate.eq(applied_price.unit_price),
+        ))
+        .returning(CartModel::as_returning())
+        .get_result(conn)
     {
-        Ok(c) => {
-            let cart_vm: Cart = Cart {
-                uuid: c.get_uuid().to_owned(),
-                product_id: product.get_uuid().to_owned(),
-                quantity: c.get_quantity(),
-                rate: product.get_price(),
-                sku: c.get_sku().to_owned(),
-                image: product.get_image().to_owned(),
-                created_on: c.get_created_on().to_owned(),
-                product_name: product.get_name().to_owned(),
-                product_stock: product.get_stock(),
-                product_unit_change: product.get_unit_change(),
-            };
-            HttpResponse::Ok().status(StatusCode::OK).json(cart_vm)
-        }
+        Ok(c) => match cart_service::validate_line(conn, c, product, availability_service::today())
+        {
+            Ok(line) => HttpResponse::Ok()
+                .status(StatusCode::OK)
+                .json(line.as_response()),
+            Err(_) => HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! something went wrong"})),
+        },
         Err(_) => HttpResponse::InternalServerError()
             .status(StatusCode::INTERNAL_SERVER_ERROR)
             .json(serde_json::json!({"message": "Ops! something went wrong"})),
@@ -299,6 +399,7 @@ pub async fn update_quantity(
 
 #[delete("/{cart_id}")]
 pub async fn delete_cart(
+    user_info: UserInfo,
     cart_id: web::Path<(String,)>,
     pool: web::Data<SqliteConnectionPool>,
 ) -> impl Responder {
@@ -319,6 +420,10 @@ pub async fn delete_cart(
 
     let conn = &mut get_conn(&pool);
 
+    if let Err(res) = ownership::authorize(conn, &user_info, Resource::Cart(&cart_id.to_string())) {
+        return res;
+    }
+
     let cart: CartModel = match carts
         .filter(carts::uuid.eq(&cart_id.to_string()))
         .select(CartModel::as_select())
@@ -350,6 +455,7 @@ pub async fn delete_cart(
 
 #[delete("/delete-carts/{cust_id}")]
 pub async fn delete_customer_cart(
+    user_info: UserInfo,
     cust_id: web::Path<(String,)>,
     pool: web::Data<SqliteConnectionPool>,
 ) -> impl Responder {
@@ -374,6 +480,10 @@ pub async fn delete_customer_cart(
 
     let conn = &mut get_conn(&pool);
 
+    if let Err(res) = ownership::authorize(conn, &user_info, Resource::User(&cust_id.to_string())) {
+        return res;
+    }
+
     let customer: UserModel = match users
         .filter(users::uuid.eq(&cust_id.to_string()))
         .select(UserModel::as_select())
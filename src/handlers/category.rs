@@ -1,12 +1,101 @@
+use std::{collections::HashMap, env, path::Path};
+
+use actix_multipart::form::MultipartForm;
 use actix_web::{delete, get, http::StatusCode, post, put, web, HttpResponse, Responder};
 use diesel::prelude::*;
+use uuid::Uuid;
 
 use crate::{
-    contracts::category::{Category, CategoryCreate},
+    config::ApplicationConfiguration,
+    contracts::category::{
+        CategoryCreate, CategoryDeleteParams, CategoryDetail, CategoryImageUpload, CategoryUpdate,
+    },
     db::connection::{get_conn, SqliteConnectionPool},
-    models::category::NewCategory,
+    models::category::{Category as CategoryModel, NewCategory},
+    utils::slug,
 };
 
+fn category_detail(category: &CategoryModel, parent_uuid: Option<&str>) -> CategoryDetail {
+    CategoryDetail {
+        uuid: category.get_uuid().to_owned(),
+        name: category.get_name().to_owned(),
+        slug: category.get_slug().to_owned(),
+        parent_id: parent_uuid.map(|p| p.to_owned()),
+        display_order: category.get_display_order(),
+        image: category.get_image().map(|i| i.to_owned()),
+        is_active: category.is_active(),
+        children: None,
+    }
+}
+
+fn find_category(conn: &mut SqliteConnection, uid: &str) -> Result<CategoryModel, HttpResponse> {
+    use crate::schema::categories;
+
+    match categories::table
+        .filter(categories::uuid.eq(uid))
+        .select(CategoryModel::as_select())
+        .first(conn)
+        .optional()
+    {
+        Ok(Some(c)) => Ok(c),
+        Ok(None) => Err(HttpResponse::NotFound()
+            .status(StatusCode::NOT_FOUND)
+            .json(serde_json::json!({"message": "Category not found"}))),
+        Err(_) => Err(HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"}))),
+    }
+}
+
+fn parent_uuid_of(
+    conn: &mut SqliteConnection,
+    category: &CategoryModel,
+) -> QueryResult<Option<String>> {
+    use crate::schema::categories;
+
+    match category.get_parent_id() {
+        Some(pid) => categories::table
+            .find(pid)
+            .select(categories::uuid)
+            .first::<String>(conn)
+            .optional(),
+        None => Ok(None),
+    }
+}
+
+// Appends -2, -3, ... until the slug is not taken by another category
+fn unique_slug(
+    conn: &mut SqliteConnection,
+    requested: &str,
+    except_id: Option<i32>,
+) -> QueryResult<String> {
+    use crate::schema::categories;
+
+    let base = match slug::slugify(requested) {
+        s if s.is_empty() => format!("category-{}", &Uuid::new_v4().simple().to_string()[..8]),
+        s => s,
+    };
+
+    let mut candidate = base.clone();
+    let mut suffix = 2;
+    loop {
+        let mut query = categories::table
+            .filter(categories::slug.eq(&candidate))
+            .into_boxed();
+        if let Some(eid) = except_id {
+            query = query.filter(categories::id.ne(eid));
+        }
+
+        let taken: i64 = query.count().get_result(conn)?;
+        if taken == 0 {
+            return Ok(candidate);
+        }
+
+        candidate = format!("{}-{}", base, suffix);
+        suffix += 1;
+    }
+}
+
 #[post("")]
 pub async fn create(
     category: web::Json<CategoryCreate>,
@@ -24,27 +113,58 @@ pub async fn create(
         .first::<String>(conn)
         .optional()
     {
-        Ok(cat) => match cat {
-            Some(c) => HttpResponse::Conflict().json(
+        Ok(Some(c)) => {
+            return HttpResponse::Conflict().json(
                 serde_json::json!({"status": "fail","message": format!("Category with name {} aready exists", c)}),
-            ),
-            None => {
-                let category: NewCategory = NewCategory::new(category.name.to_owned());
-                match diesel::insert_into(categories)
-                    .values(&category)
-                    .execute(conn) {
-                        Ok(_) => HttpResponse::Ok().json(category),
-                        Err(_) => HttpResponse::InternalServerError().finish()
-                    }
-            }
+            )
+        }
+        Ok(None) => (),
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .body(format!("Ops! something went wrong: {}", e))
+        }
+    }
+
+    let parent: Option<CategoryModel> = match &category.parent_id {
+        Some(pid) => match find_category(conn, pid) {
+            Ok(p) => Some(p),
+            Err(e) => return e,
         },
-        Err(e) => 
-             HttpResponse::InternalServerError()
+        None => None,
+    };
+
+    let new_slug = match unique_slug(
+        conn,
+        category.slug.as_deref().unwrap_or(&category.name),
+        None,
+    ) {
+        Ok(s) => s,
+        Err(e) => {
+            return HttpResponse::InternalServerError()
                 .body(format!("Ops! something went wrong: {}", e))
-        
+        }
+    };
+
+    let new_category: NewCategory = NewCategory::new(
+        category.name.to_owned(),
+        new_slug,
+        parent.as_ref(),
+        category.display_order.unwrap_or(0),
+        category.is_active.unwrap_or(true),
+    );
+
+    match diesel::insert_into(categories)
+        .values(&new_category)
+        .get_result::<CategoryModel>(conn)
+    {
+        Ok(c) => {
+            HttpResponse::Ok().json(category_detail(&c, parent.as_ref().map(|p| p.get_uuid())))
+        }
+        Err(_) => HttpResponse::InternalServerError().finish(),
     }
 }
 
+//public listing, only active categories in display order
 #[get("")]
 pub async fn get(pool: web::Data<SqliteConnectionPool>) -> impl Responder {
     use crate::schema::categories::dsl::*;
@@ -53,51 +173,188 @@ pub async fn get(pool: web::Data<SqliteConnectionPool>) -> impl Responder {
     let conn = &mut get_conn(&pool);
 
     let categories_vec = categories
-        .select((uuid, name))
-        .load::<Category>(conn);
+        .order((display_order.asc(), name.asc()))
+        .select(CategoryModel::as_select())
+        .load::<CategoryModel>(conn);
 
     match categories_vec {
-        Ok(cat_v) => HttpResponse::Ok().json(serde_json::json!({"categories": cat_v})),
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({"message": e.to_string()}))
-        
+        Ok(cat_v) => {
+            let uuids: HashMap<i32, &str> =
+                cat_v.iter().map(|c| (c.get_id(), c.get_uuid())).collect();
+
+            let details: Vec<CategoryDetail> = cat_v
+                .iter()
+                .filter(|c| c.is_active())
+                .map(|c| category_detail(c, c.get_parent_id().and_then(|p| uuids.get(&p).copied())))
+                .collect();
+
+            HttpResponse::Ok().json(serde_json::json!({"categories": details}))
+        }
+        Err(e) => {
+            HttpResponse::InternalServerError().json(serde_json::json!({"message": e.to_string()}))
+        }
     }
 }
 
+//nested view of the active categories, e.g. Vegetables > Leafy greens
+#[get("/tree")]
+pub async fn get_tree(pool: web::Data<SqliteConnectionPool>) -> impl Responder {
+    use crate::schema::categories::dsl::*;
+
+    let conn = &mut get_conn(&pool);
+
+    let cat_v: Vec<CategoryModel> = match categories
+        .filter(is_active.eq(true))
+        .order((display_order.asc(), name.asc()))
+        .select(CategoryModel::as_select())
+        .load(conn)
+    {
+        Ok(c) => c,
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .json(serde_json::json!({"message": e.to_string()}))
+        }
+    };
+
+    // subcategories of an inactive category are never reached, so they are
+    // hidden along with it
+    fn build(parent: Option<&CategoryModel>, all: &[CategoryModel]) -> Vec<CategoryDetail> {
+        all.iter()
+            .filter(|c| c.get_parent_id() == parent.map(|p| p.get_id()))
+            .map(|c| {
+                let mut detail = category_detail(c, parent.map(|p| p.get_uuid()));
+                detail.children = Some(build(Some(c), all));
+                detail
+            })
+            .collect()
+    }
+
+    let tree = build(None, &cat_v);
+
+    HttpResponse::Ok().json(serde_json::json!({"categories": tree}))
+}
+
+#[get("/slug/{slug}")]
+pub async fn get_category_by_slug(
+    category_slug: web::Path<(String,)>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    use crate::schema::categories::dsl::*;
+
+    let category_slug: String = category_slug.into_inner().0;
+
+    let conn = &mut get_conn(&pool);
+
+    let category: CategoryModel = match categories
+        .filter(slug.eq(&category_slug))
+        .filter(is_active.eq(true))
+        .select(CategoryModel::as_select())
+        .first(conn)
+        .optional()
+    {
+        Ok(Some(c)) => c,
+        Ok(None) => {
+            return HttpResponse::NotFound()
+                .status(StatusCode::NOT_FOUND)
+                .json(serde_json::json!({"message":"Category not found"}))
+        }
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(format!("Ops! something went wrong: {}", e))
+        }
+    };
+
+    category_with_children(conn, &category)
+}
+
 #[get("/{catgory_id}")]
 pub async fn get_category(
     category_id: web::Path<(String,)>,
     pool: web::Data<SqliteConnectionPool>,
 ) -> impl Responder {
-    use crate::schema::categories::dsl::*;
-
     let uid: String = category_id.into_inner().0; //uid = uuid
 
     //create a connection to db
     let conn = &mut get_conn(&pool);
 
+    match find_category(conn, &uid) {
+        Ok(c) if c.is_active() => category_with_children(conn, &c),
+        Ok(_) => HttpResponse::NotFound()
+            .status(StatusCode::NOT_FOUND)
+            .json(serde_json::json!({"message":"Category not found"})),
+        Err(e) => e,
+    }
+}
+
+fn category_with_children(conn: &mut SqliteConnection, category: &CategoryModel) -> HttpResponse {
+    use crate::schema::categories::dsl::*;
+
+    let parent_uuid = match parent_uuid_of(conn, category) {
+        Ok(p) => p,
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(format!("Ops! something went wrong: {}", e))
+        }
+    };
+
     match categories
-        .filter(uuid.eq(uid))
-        .select((uuid, name))
-        .first::<Category>(conn)
-        .optional()
+        .filter(parent_id.eq(category.get_id()))
+        .filter(is_active.eq(true))
+        .order((display_order.asc(), name.asc()))
+        .select(CategoryModel::as_select())
+        .load::<CategoryModel>(conn)
     {
-        Ok(cat) => match cat {
-            Some(c) => HttpResponse::Ok().status(StatusCode::OK).json(c),
-            None => HttpResponse::NotFound()
-                .status(StatusCode::NOT_FOUND)
-                .json(serde_json::json!({"message":"Category not found"})),
-        },
+        Ok(children) => {
+            let mut detail = category_detail(category, parent_uuid.as_deref());
+            detail.children = Some(
+                children
+                    .iter()
+                    .map(|c| category_detail(c, Some(category.get_uuid())))
+                    .collect(),
+            );
+            HttpResponse::Ok().status(StatusCode::OK).json(detail)
+        }
         Err(e) => HttpResponse::InternalServerError()
-                .status(StatusCode::INTERNAL_SERVER_ERROR)
-                .body(format!("Ops! something went wrong: {}", e))
-        
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .body(format!("Ops! something went wrong: {}", e)),
+    }
+}
+
+//admin listing, includes inactive categories
+#[get("")]
+pub async fn get_all(pool: web::Data<SqliteConnectionPool>) -> impl Responder {
+    use crate::schema::categories::dsl::*;
+
+    let conn = &mut get_conn(&pool);
+
+    match categories
+        .order((display_order.asc(), name.asc()))
+        .select(CategoryModel::as_select())
+        .load::<CategoryModel>(conn)
+    {
+        Ok(cat_v) => {
+            let uuids: HashMap<i32, &str> =
+                cat_v.iter().map(|c| (c.get_id(), c.get_uuid())).collect();
+
+            let details: Vec<CategoryDetail> = cat_v
+                .iter()
+                .map(|c| category_detail(c, c.get_parent_id().and_then(|p| uuids.get(&p).copied())))
+                .collect();
+
+            HttpResponse::Ok().json(serde_json::json!({"categories": details}))
+        }
+        Err(e) => {
+            HttpResponse::InternalServerError().json(serde_json::json!({"message": e.to_string()}))
+        }
     }
 }
 
 #[put("/{category_id}")]
 pub async fn edit(
     category_id: web::Path<(String,)>,
-    category_update: web::Json<CategoryCreate>,
+    category_update: web::Json<CategoryUpdate>,
     pool: web::Data<SqliteConnectionPool>,
 ) -> impl Responder {
     use crate::schema::categories::dsl::*;
@@ -107,63 +364,246 @@ pub async fn edit(
     //create a connection to db
     let conn = &mut get_conn(&pool);
 
-    match diesel::update(categories)
-        .filter(uuid.eq(&uid))
-        .set(name.eq(category_update.name.to_owned()))
-        .execute(conn) 
-        //all this match can be shortened if used get_result() but how?
-    {
-        Ok(urc) if urc > 0 => {
-            //urc = updated row count
-            let category = Category {
-                uuid: uid,
-                name: category_update.name.to_owned(),
-            };
-            HttpResponse::Ok()
-                .status(StatusCode::OK)
-                .json(category)
+    let category: CategoryModel = match find_category(conn, &uid) {
+        Ok(c) => c,
+        Err(e) => return e,
+    };
+
+    let parent: Option<CategoryModel> = match &category_update.parent_id {
+        Some(Some(pid)) => match find_category(conn, pid) {
+            Ok(p) => Some(p),
+            Err(e) => return e,
+        },
+        Some(None) => None,
+        //parentId left out keeps the category where it is
+        None => match category.get_parent_id() {
+            Some(pid) => match categories
+                .find(pid)
+                .select(CategoryModel::as_select())
+                .first(conn)
+            {
+                Ok(p) => Some(p),
+                Err(_) => {
+                    return HttpResponse::InternalServerError()
+                        .status(StatusCode::INTERNAL_SERVER_ERROR)
+                        .json(serde_json::json!({"message": "Ops! something went wrong"}))
+                }
+            },
+            None => None,
+        },
+    };
+
+    //a category cannot be moved under itself or one of its own subcategories
+    if let Some(p) = &parent {
+        match CategoryModel::descendant_ids(conn, category.get_id()) {
+            Ok(ids) if ids.contains(&p.get_id()) => {
+                return HttpResponse::BadRequest()
+                    .status(StatusCode::BAD_REQUEST)
+                    .json(serde_json::json!({"message": "Category cannot be moved under itself or its subcategory"}))
+            }
+            Ok(_) => (),
+            Err(e) => {
+                return HttpResponse::InternalServerError()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .json(serde_json::json!({"message": format!("Ops! something went wrong: {}", e)}))
+            }
         }
-        Ok(_) => 
-            //match branch if the value is <= 0
-            HttpResponse::NotFound()
-                .status(StatusCode::NOT_FOUND)
-                .finish(),
-        
-        Err(e) => 
-             HttpResponse::InternalServerError()
+    }
+
+    //keep the existing slug on rename so old links keep working
+    let new_slug =
+        match &category_update.slug {
+            Some(s) => match unique_slug(conn, s, Some(category.get_id())) {
+                Ok(s) => s,
+                Err(e) => return HttpResponse::InternalServerError()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .json(
+                        serde_json::json!({"message": format!("Ops! something went wrong: {}", e)}),
+                    ),
+            },
+            None => category.get_slug().to_owned(),
+        };
+
+    match diesel::update(&category)
+        .set((
+            name.eq(category_update.name.to_owned()),
+            slug.eq(new_slug),
+            parent_id.eq(parent.as_ref().map(|p| p.get_id())),
+            display_order.eq(category_update
+                .display_order
+                .unwrap_or(category.get_display_order())),
+            is_active.eq(category_update.is_active.unwrap_or(category.is_active())),
+        ))
+        .get_result::<CategoryModel>(conn)
+    {
+        Ok(c) => HttpResponse::Ok()
+            .status(StatusCode::OK)
+            .json(category_detail(&c, parent.as_ref().map(|p| p.get_uuid()))),
+        Err(e) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": format!("Ops! something went wrong: {}", e)})),
+    }
+}
+
+#[post("/{category_id}/image")]
+pub async fn upload_image(
+    category_id: web::Path<(String,)>,
+    MultipartForm(form): MultipartForm<CategoryImageUpload>,
+    app_config: web::Data<ApplicationConfiguration>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    use crate::schema::categories::dsl::*;
+
+    let uid: String = category_id.into_inner().0;
+
+    let conn = &mut get_conn(&pool);
+
+    let category: CategoryModel = match find_category(conn, &uid) {
+        Ok(c) => c,
+        Err(e) => return e,
+    };
+
+    //replace the existing file in place, otherwise create a new one
+    let path = match category.get_image() {
+        Some(existing) => existing.to_owned(),
+        None => format!(
+            "{}category_{}.png",
+            app_config.category_image_path,
+            Uuid::new_v4().to_string().replace("-", "")
+        ),
+    };
+
+    let cwd = &env::current_dir().expect("Failed to get current working directory");
+    let full_path = Path::new(cwd).join(path.trim_start_matches('/'));
+
+    if let Err(err) = form.image.file.persist(&full_path) {
+        eprintln!("Failed to persist image: {:?}", err);
+        return HttpResponse::InternalServerError().json(serde_json::json!({
+            "message": "Could not save image"
+        }));
+    }
+
+    let parent_uuid = match parent_uuid_of(conn, &category) {
+        Ok(p) => p,
+        Err(e) => {
+            return HttpResponse::InternalServerError()
                 .status(StatusCode::INTERNAL_SERVER_ERROR)
                 .json(serde_json::json!({"message": format!("Ops! something went wrong: {}", e)}))
-        
+        }
+    };
+
+    match diesel::update(&category)
+        .set(image.eq(&path))
+        .get_result::<CategoryModel>(conn)
+    {
+        Ok(c) => HttpResponse::Ok()
+            .status(StatusCode::OK)
+            .json(category_detail(&c, parent_uuid.as_deref())),
+        Err(e) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": format!("Ops! something went wrong: {}", e)})),
     }
 }
 
 #[delete("/{category_id}")]
 pub async fn delete(
     category_id: web::Path<(String,)>,
+    params: web::Query<CategoryDeleteParams>,
     pool: web::Data<SqliteConnectionPool>,
 ) -> impl Responder {
-    use crate::schema::categories::dsl::*;
-    
+    use crate::schema::{categories, products};
+
     let uid: String = category_id.into_inner().0;
 
     //create a connection to db
     let conn = &mut get_conn(&pool);
 
-    match diesel::delete(categories.filter(uuid.eq(uid)))
-        .execute(conn)
-    {
+    let category: CategoryModel = match find_category(conn, &uid) {
+        Ok(c) => c,
+        Err(e) => return e,
+    };
+
+    let usage: QueryResult<(i64, i64)> = products::table
+        .filter(products::category_id.eq(category.get_id()))
+        .count()
+        .get_result::<i64>(conn)
+        .and_then(|product_count| {
+            categories::table
+                .filter(categories::parent_id.eq(category.get_id()))
+                .count()
+                .get_result::<i64>(conn)
+                .map(|child_count| (product_count, child_count))
+        });
+
+    let (product_count, child_count) = match usage {
+        Ok(u) => u,
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": format!("Ops! something went wrong: {}", e)}))
+        }
+    };
+
+    //products and subcategories are moved to the target before deleting
+    let reassign_target: Option<CategoryModel> = match &params.reassign_to {
+        Some(target_uid) => {
+            let target = match find_category(conn, target_uid) {
+                Ok(t) => t,
+                Err(e) => return e,
+            };
+
+            match CategoryModel::descendant_ids(conn, category.get_id()) {
+                Ok(ids) if ids.contains(&target.get_id()) => {
+                    return HttpResponse::BadRequest()
+                        .status(StatusCode::BAD_REQUEST)
+                        .json(serde_json::json!({"message": "Cannot reassign to the category being deleted or its subcategory"}))
+                }
+                Ok(_) => Some(target),
+                Err(e) => {
+                    return HttpResponse::InternalServerError()
+                        .status(StatusCode::INTERNAL_SERVER_ERROR)
+                        .json(serde_json::json!({"message": format!("Ops! something went wrong: {}", e)}))
+                }
+            }
+        }
+        None => None,
+    };
+
+    if (product_count > 0 || child_count > 0) && reassign_target.is_none() {
+        return HttpResponse::Conflict()
+            .status(StatusCode::CONFLICT)
+            .json(serde_json::json!({
+                "message": "Category is still in use. Provide reassignTo to move its products and subcategories",
+                "productCount": product_count,
+                "subcategoryCount": child_count,
+            }));
+    }
+
+    match conn.transaction::<_, diesel::result::Error, _>(|con| {
+        if let Some(target) = &reassign_target {
+            diesel::update(products::table.filter(products::category_id.eq(category.get_id())))
+                .set(products::category_id.eq(target.get_id()))
+                .execute(con)?;
+
+            diesel::update(categories::table.filter(categories::parent_id.eq(category.get_id())))
+                .set(categories::parent_id.eq(target.get_id()))
+                .execute(con)?;
+        }
+
+        diesel::delete(&category).execute(con)
+    }) {
         //drc = deleted_row_count
         //execute() function returns the number of row affected
         Ok(drc) if drc > 0 => HttpResponse::Ok().status(StatusCode::OK).finish(),
 
-        //if the drc <= 0 then no row is affected meaning deletetion not successfull. 
+        //if the drc <= 0 then no row is affected meaning deletetion not successfull.
         //Why? because the resource is not found with that uuid
-        Ok(_) => HttpResponse::NotFound().status(StatusCode::NOT_FOUND).finish(),
-        
-        Err(e) => 
-            HttpResponse::InternalServerError()
-                .status(StatusCode::INTERNAL_SERVER_ERROR)
-                .json(serde_json::json!({"message": format!("Ops! something went wrong: {}", e)}))
-        
+        Ok(_) => HttpResponse::NotFound()
+            .status(StatusCode::NOT_FOUND)
+            .finish(),
+
+        Err(e) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": format!("Ops! something went wrong: {}", e)})),
     }
 }
@@ -0,0 +1,320 @@
+use actix_web::{delete, get, http::StatusCode, patch, post, web, HttpResponse, Responder};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    contracts::cart::{Cart, NewCart, UpdateCartQuantity},
+    db::connection::{get_conn, SqliteConnectionPool},
+    handlers::{order::DELIVERY_CHARGE, product::find_product},
+    models::{
+        guest_cart::{GuestCart as GuestCartModel, NewGuestCart},
+        product::Product as ProductModel,
+    },
+    services::{
+        availability_service,
+        cart_service::{self, CartLine},
+        pricing_service,
+    },
+};
+
+// the token is generated by the app, a uuid keeps it unguessable
+fn validate_token(token: &str) -> Result<String, HttpResponse> {
+    match Uuid::parse_str(token) {
+        Ok(t) => Ok(t.to_string()),
+        Err(_) => Err(HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": "Invalid guest cart token"}))),
+    }
+}
+
+fn find_guest_cart(
+    conn: &mut SqliteConnection,
+    guest_token: &str,
+    item_id: &str,
+) -> Result<(GuestCartModel, ProductModel), HttpResponse> {
+    use crate::schema::{guest_carts, products};
+
+    match guest_carts::table
+        .inner_join(products::table)
+        .filter(guest_carts::token.eq(guest_token))
+        .filter(guest_carts::uuid.eq(item_id))
+        .filter(guest_carts::expires_on.gt(chrono::Utc::now().naive_utc()))
+        .select((GuestCartModel::as_select(), ProductModel::as_select()))
+        .first(conn)
+        .optional()
+    {
+        Ok(Some(row)) => Ok(row),
+        Ok(None) => Err(HttpResponse::NotFound()
+            .status(StatusCode::NOT_FOUND)
+            .json(serde_json::json!({"message": "Cart not found"}))),
+        Err(_) => Err(HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"}))),
+    }
+}
+
+// every change to a guest cart keeps the whole cart alive a while longer
+fn touch(conn: &mut SqliteConnection, guest_token: &str) -> QueryResult<usize> {
+    use crate::schema::guest_carts;
+
+    diesel::update(guest_carts::table.filter(guest_carts::token.eq(guest_token)))
+        .set(guest_carts::expires_on.eq(cart_service::guest_cart_expiry()))
+        .execute(conn)
+}
+
+#[get("/{token}")]
+pub async fn get(
+    token: web::Path<(String,)>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    use crate::schema::{guest_carts, products};
+
+    let guest_token = match validate_token(&token.into_inner().0) {
+        Ok(t) => t,
+        Err(res) => return res,
+    };
+
+    let conn = &mut get_conn(&pool);
+
+    let cart_rows: Vec<(GuestCartModel, ProductModel)> = match guest_carts::table
+        .inner_join(products::table)
+        .filter(guest_carts::token.eq(&guest_token))
+        .filter(guest_carts::expires_on.gt(chrono::Utc::now().naive_utc()))
+        .order(guest_carts::id.asc())
+        .select((GuestCartModel::as_select(), ProductModel::as_select()))
+        .load(conn)
+    {
+        Ok(c) => c,
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! something went wrong"}))
+        }
+    };
+
+    let today = availability_service::today();
+    let mut lines: Vec<CartLine<GuestCartModel>> = Vec::with_capacity(cart_rows.len());
+    for (cart, product) in cart_rows {
+        match cart_service::validate_line(conn, cart, product, today) {
+            Ok(line) => lines.push(line),
+            Err(_) => {
+                return HttpResponse::InternalServerError()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .json(serde_json::json!({"message": "Ops! something went wrong"}))
+            }
+        }
+    }
+
+    let carts_vec: Vec<Cart> = lines.iter().map(CartLine::as_response).collect();
+    let summary = cart_service::summarize(&lines, DELIVERY_CHARGE);
+
+    HttpResponse::Ok().json(serde_json::json!({"carts": carts_vec, "summary": summary}))
+}
+
+#[post("/{token}")]
+pub async fn create(
+    token: web::Path<(String,)>,
+    cart_item: web::Json<NewCart>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    use crate::schema::guest_carts;
+
+    if cart_item.quantity <= 0.25 {
+        return HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": "Quantity must be greater than 0.25 sku"}));
+    }
+
+    let guest_token = match validate_token(&token.into_inner().0) {
+        Ok(t) => t,
+        Err(res) => return res,
+    };
+
+    let conn = &mut get_conn(&pool);
+
+    // nothing else cleans up abandoned guest carts
+    if cart_service::purge_expired_guest_carts(conn).is_err() {
+        return HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"}));
+    }
+
+    let product = match find_product(conn, &cart_item.product_id) {
+        Ok(p) => p,
+        Err(res) => return res,
+    };
+
+    let today = availability_service::today();
+    match availability_service::availability_for_product(conn, &product, today) {
+        Ok(a) if a.available => {}
+        Ok(a) => {
+            return HttpResponse::BadRequest()
+                .status(StatusCode::BAD_REQUEST)
+                .json(serde_json::json!({"message": a.unavailable_message(&product)}))
+        }
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! something went wrong"}))
+        }
+    }
+
+    let existing_quantity: f64 = match guest_carts::table
+        .filter(guest_carts::token.eq(&guest_token))
+        .filter(guest_carts::product_id.eq(product.get_id()))
+        .select(guest_carts::quantity)
+        .first(conn)
+        .optional()
+    {
+        Ok(q) => q.unwrap_or(0.0),
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! something went wrong"}))
+        }
+    };
+    let merged_quantity = existing_quantity + cart_item.quantity;
+
+    if merged_quantity > product.get_stock() {
+        return HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": "Requested quantity is greater than stock"}));
+    }
+
+    let applied_price = match pricing_service::price_for_quantity(conn, &product, merged_quantity) {
+        Ok(p) => p,
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! something went wrong"}))
+        }
+    };
+
+    let new_cart = NewGuestCart::new(
+        &guest_token,
+        &product,
+        merged_quantity,
+        cart_item.created_on.to_owned(),
+        applied_price.unit_price,
+        cart_service::guest_cart_expiry(),
+    );
+
+    let result = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        let cart = diesel::insert_into(guest_carts::table)
+            .values(&new_cart)
+            .on_conflict((guest_carts::token, guest_carts::product_id))
+            .do_update()
+            .set((
+                guest_carts::quantity.eq(merged_quantity),
+                guest_carts::rate.eq(applied_price.unit_price),
+            ))
+            .returning(GuestCartModel::as_returning())
+            .get_result(conn)?;
+        touch(conn, &guest_token)?;
+        Ok(cart)
+    });
+
+    match result.and_then(|c| cart_service::validate_line(conn, c, product, today)) {
+        Ok(line) => HttpResponse::Ok()
+            .status(StatusCode::OK)
+            .json(serde_json::json!({"cart": line.as_response()})),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+#[patch("/{token}/{cart_id}/update-quantity")]
+pub async fn update_quantity(
+    path: web::Path<(String, String)>,
+    quantity_vm: web::Json<UpdateCartQuantity>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    use crate::schema::guest_carts;
+
+    if quantity_vm.new_quantity <= 0.25 {
+        return HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": "Quantity must be greater than 0.25 sku"}));
+    }
+
+    let (token, cart_id) = path.into_inner();
+    let guest_token = match validate_token(&token) {
+        Ok(t) => t,
+        Err(res) => return res,
+    };
+
+    let conn = &mut get_conn(&pool);
+
+    let (cart, product) = match find_guest_cart(conn, &guest_token, &cart_id) {
+        Ok(row) => row,
+        Err(res) => return res,
+    };
+
+    if quantity_vm.new_quantity > product.get_stock() {
+        return HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": "Requested quantity is greater than stock"}));
+    }
+
+    let applied_price =
+        match pricing_service::price_for_quantity(conn, &product, quantity_vm.new_quantity) {
+            Ok(p) => p,
+            Err(_) => {
+                return HttpResponse::InternalServerError()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .json(serde_json::json!({"message": "Ops! something went wrong"}))
+            }
+        };
+
+    let result = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        let updated = diesel::update(&cart)
+            .set((
+                guest_carts::quantity.eq(quantity_vm.new_quantity),
+                guest_carts::rate.eq(applied_price.unit_price),
+            ))
+            .returning(GuestCartModel::as_returning())
+            .get_result(conn)?;
+        touch(conn, &guest_token)?;
+        Ok(updated)
+    });
+
+    match result
+        .and_then(|c| cart_service::validate_line(conn, c, product, availability_service::today()))
+    {
+        Ok(line) => HttpResponse::Ok()
+            .status(StatusCode::OK)
+            .json(line.as_response()),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+#[delete("/{token}/{cart_id}")]
+pub async fn delete_cart(
+    path: web::Path<(String, String)>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    let (token, cart_id) = path.into_inner();
+    let guest_token = match validate_token(&token) {
+        Ok(t) => t,
+        Err(res) => return res,
+    };
+
+    let conn = &mut get_conn(&pool);
+
+    let (cart, _) = match find_guest_cart(conn, &guest_token, &cart_id) {
+        Ok(row) => row,
+        Err(res) => return res,
+    };
+
+    match diesel::delete(&cart).execute(conn) {
+        Ok(_) => HttpResponse::NoContent()
+            .status(StatusCode::NO_CONTENT)
+            .finish(),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
@@ -3,12 +3,14 @@ use diesel::prelude::*;
 use uuid::Uuid;
 
 use crate::{
+    base_types::permission::Permission,
     config::CompanyConfiguration,
     contracts::{
         invoice::{Invoice, InvoiceOnly, InvoiceQueryParams, NewInvoice},
         invoice_item::InvoiceItem,
     },
     db::connection::{get_conn, SqliteConnectionPool},
+    middlewares::user_info::UserInfo,
     models::{
         invoice::{Invoice as InvoiceModel, NewInvoice as NewInvoiceModel},
         invoice_item::NewInvoiceItem as NewInvoiceItemModel,
@@ -17,11 +19,19 @@ use crate::{
         product::Product as ProductModel,
         user::User as UserModel,
     },
-    services::invoice_service::{InvoiceItem as InvoiceItemService, InvoiceService},
+    policies::{
+        ownership::{self, Resource},
+        permission,
+    },
+    services::{
+        invoice_service::{InvoiceItem as InvoiceItemService, InvoiceService},
+        pricing_service,
+    },
 };
 
 #[post("")]
 pub async fn create(
+    user_info: UserInfo,
     inv_json: web::Json<NewInvoice>,
     pool: web::Data<SqliteConnectionPool>,
 ) -> impl Responder {
@@ -36,6 +46,10 @@ pub async fn create(
     //get a database connection for pool
     let conn = &mut get_conn(&pool);
 
+    if let Err(res) = ownership::authorize(conn, &user_info, Resource::Order(&inv_json.order_id)) {
+        return res;
+    }
+
     //first check if order exists or not
     let order: OrderModel = match orders
         .filter(orders::uuid.eq(&inv_json.order_id))
@@ -162,16 +176,28 @@ pub async fn create(
                             .json(serde_json::json!({"message": "Product quantity is more than stock"}));
                     }
 
+                    let applied_price =
+                        match pricing_service::price_for_quantity(conn, &prod, inv_item.quantity) {
+                            Ok(p) => p,
+                            Err(_) => {
+                                return HttpResponse::InternalServerError()
+                                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                                    .json(
+                                        serde_json::json!({"message": "Ops! something went wrong"}),
+                                    )
+                            }
+                        };
+
                     // Handle discount amount and discount percent
                     let mut dis_percent: f64 = 0.0;
                     let mut dis_amt: f64 = 0.0;
 
                     if let Some(d_a) = inv_item.discount_amount {
-                        dis_percent = d_a / prod.get_price() * 100.0;
+                        dis_percent = d_a / applied_price.unit_price * 100.0;
                     }
 
                     if let Some(d_p) = inv_item.discount_percent {
-                        dis_amt = prod.get_price() * d_p / 100.0;
+                        dis_amt = applied_price.unit_price * d_p / 100.0;
                     }
 
                     // Check if either discount percent or discount amount has been set
@@ -187,6 +213,7 @@ pub async fn create(
                         inv_item.quantity,
                         dis_percent,
                         dis_amt,
+                        &applied_price,
                     );
 
                     match diesel::insert_into(invoice_items)
@@ -215,6 +242,7 @@ pub async fn create(
 
 #[get("/{inv_id}")]
 pub async fn get(
+    user_info: UserInfo,
     inv_id: web::Path<(String,)>,
     pool: web::Data<SqliteConnectionPool>,
 ) -> impl Responder {
@@ -231,6 +259,12 @@ pub async fn get(
 
     let conn = &mut get_conn(&pool);
 
+    if let Err(res) =
+        ownership::authorize(conn, &user_info, Resource::Invoice(&inv_uuid.to_string()))
+    {
+        return res;
+    }
+
     use crate::schema::invoice_items::dsl::*;
     use crate::schema::invoices::dsl::*;
     use crate::schema::orders::dsl::*;
@@ -329,6 +363,7 @@ pub async fn get(
             invoice_items::discount_percent,
             invoice_items::discount_amount,
             invoice_items::total,
+            invoice_items::tier_min_quantity,
         ))
         .load::<InvoiceItem>(conn)
     {
@@ -358,8 +393,9 @@ pub async fn get(
     HttpResponse::Ok().status(StatusCode::OK).json(inv_vm)
 }
 
+// customers only see their own invoices, staff with invoices.view see all
 #[get("")]
-pub async fn get_all(pool: web::Data<SqliteConnectionPool>) -> impl Responder {
+pub async fn get_all(user_info: UserInfo, pool: web::Data<SqliteConnectionPool>) -> impl Responder {
     let conn = &mut get_conn(&pool);
     use crate::schema::invoices::dsl::*;
     use crate::schema::orders::dsl::*;
@@ -367,10 +403,22 @@ pub async fn get_all(pool: web::Data<SqliteConnectionPool>) -> impl Responder {
     use crate::schema::users::dsl::*;
     use crate::schema::{invoices, orders, payments, users};
 
-    match invoices
+    let mut query = invoices
         .inner_join(users)
         .inner_join(orders)
         .inner_join(payments)
+        .into_boxed();
+
+    let sees_all = match permission::allows(conn, &user_info, Permission::InvoicesView) {
+        Ok(a) => a,
+        Err(res) => return res,
+    };
+
+    if !sees_all {
+        query = query.filter(users::uuid.eq(&user_info.user_id));
+    }
+
+    match query
         .select((
             invoices::uuid,
             invoice_number,
@@ -458,6 +506,7 @@ pub async fn get_invoice_file(
             invoice_items::unit_price,
             products::unit,
             invoice_items::total,
+            invoice_items::tier_min_quantity,
         ))
         .load::<InvoiceItemService>(conn)
     {
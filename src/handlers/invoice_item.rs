@@ -5,14 +5,18 @@ use uuid::Uuid;
 use crate::{
     contracts::invoice_item::NewInvoiceItem,
     db::connection::{get_conn, SqliteConnectionPool},
+    middlewares::user_info::UserInfo,
     models::{
         invoice::Invoice as InvoiceModel, invoice_item::NewInvoiceItem as NewInvoiceItemModel,
         product::Product as ProductModel,
     },
+    policies::ownership::{self, Resource},
+    services::pricing_service,
 };
 
 #[post("")]
 pub async fn add(
+    user_info: UserInfo,
     inv_id: web::Path<(String,)>,
     inv_item: web::Json<NewInvoiceItem>,
     pool: web::Data<SqliteConnectionPool>,
@@ -32,6 +36,11 @@ pub async fn add(
     //get a connection from db pool
     let conn = &mut get_conn(&pool);
 
+    if let Err(res) = ownership::authorize(conn, &user_info, Resource::Invoice(&inv_id.to_string()))
+    {
+        return res;
+    }
+
     use crate::schema::invoice_items::dsl::*;
     use crate::schema::invoices::dsl::*;
     use crate::schema::products::dsl::*;
@@ -82,16 +91,26 @@ pub async fn add(
             .json(serde_json::json!({"message": "Product quantity is more than stock"}));
     }
 
+    let applied_price = match pricing_service::price_for_quantity(conn, &product, inv_item.quantity)
+    {
+        Ok(p) => p,
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! something went wrong"}))
+        }
+    };
+
     // Handle discount amount and discount percent
     let mut dis_percent: f64 = 0.0;
     let mut dis_amt: f64 = 0.0;
 
     if let Some(d_a) = inv_item.discount_amount {
-        dis_percent = d_a / product.get_price() * 100.0;
+        dis_percent = d_a / applied_price.unit_price * 100.0;
     }
 
     if let Some(d_p) = inv_item.discount_percent {
-        dis_amt = product.get_price() * d_p / 100.0;
+        dis_amt = applied_price.unit_price * d_p / 100.0;
     }
 
     // Check if either discount percent or discount amount has been set
@@ -101,8 +120,14 @@ pub async fn add(
                 .json(serde_json::json!({"message": "Either discount percent or discount amount has to be set"}));
     }
 
-    let inv_item_model: NewInvoiceItemModel =
-        NewInvoiceItemModel::new(&product, &invoice, inv_item.quantity, dis_percent, dis_amt);
+    let inv_item_model: NewInvoiceItemModel = NewInvoiceItemModel::new(
+        &product,
+        &invoice,
+        inv_item.quantity,
+        dis_percent,
+        dis_amt,
+        &applied_price,
+    );
 
     match diesel::insert_into(invoice_items)
         .values(&inv_item_model)
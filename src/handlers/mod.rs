@@ -1,13 +1,29 @@
 pub mod admin_device;
+pub mod audit_log;
 pub mod auth;
 pub mod base_type;
 pub mod cart;
 pub mod category;
+pub mod guest_cart;
 pub mod invoice;
 pub mod invoice_item;
+pub mod notification_preference;
 pub mod order;
 pub mod order_item;
 pub mod payment;
 pub mod product;
+pub mod product_attribute;
+pub mod product_availability_rule;
+pub mod product_pre_order;
+pub mod product_price_tier;
+pub mod product_rating;
+pub mod role;
+pub mod session;
+pub mod setting;
 pub mod shipment;
+pub mod subscription;
+pub mod tag;
+pub mod two_factor;
 pub mod user;
+pub mod user_device;
+pub mod wishlist;
@@ -0,0 +1,73 @@
+use actix_web::{get, http::StatusCode, put, web, HttpResponse, Responder};
+use diesel::prelude::*;
+
+use crate::{
+    contracts::notification_preference::NotificationPreferences,
+    db::connection::{get_conn, SqliteConnectionPool},
+    handlers::product_rating::find_user,
+    middlewares::user_info::UserInfo,
+    models::notification_preference::NewNotificationPreference,
+};
+
+/// Customers who never touched their preferences get every notification.
+#[get("")]
+pub async fn get(user_info: UserInfo, pool: web::Data<SqliteConnectionPool>) -> impl Responder {
+    use crate::schema::notification_preferences;
+
+    let conn = &mut get_conn(&pool);
+
+    let user = match find_user(conn, &user_info) {
+        Ok(u) => u,
+        Err(res) => return res,
+    };
+
+    match notification_preferences::table
+        .filter(notification_preferences::user_id.eq(user.get_id()))
+        .select(notification_preferences::cart_reminders)
+        .first::<bool>(conn)
+        .optional()
+    {
+        Ok(cart_reminders) => HttpResponse::Ok().status(StatusCode::OK).json(
+            serde_json::json!({"preferences": NotificationPreferences {
+                cart_reminders: cart_reminders.unwrap_or(true),
+            }}),
+        ),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+#[put("")]
+pub async fn update(
+    user_info: UserInfo,
+    preferences_json: web::Json<NotificationPreferences>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    use crate::schema::notification_preferences;
+
+    let conn = &mut get_conn(&pool);
+
+    let user = match find_user(conn, &user_info) {
+        Ok(u) => u,
+        Err(res) => return res,
+    };
+
+    let preferences = preferences_json.into_inner();
+    let new_preference = NewNotificationPreference::new(&user, preferences.cart_reminders);
+
+    match diesel::insert_into(notification_preferences::table)
+        .values(&new_preference)
+        .on_conflict(notification_preferences::user_id)
+        .do_update()
+        .set(notification_preferences::cart_reminders.eq(preferences.cart_reminders))
+        .execute(conn)
+    {
+        Ok(_) => HttpResponse::Ok()
+            .status(StatusCode::OK)
+            .json(serde_json::json!({"preferences": preferences})),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
@@ -7,33 +7,30 @@ use diesel::prelude::*;
 use crate::{
     base_types::{
         delivery_status::DeliveryStatus, order_status::OrderStatus, payment_method::PaymentMethod,
-        payment_status::PaymentStatus,
+        permission::Permission,
     },
     config::EmailConfiguration,
-    contracts::{
-        order::{
-            AllOrderResponse, CartCheckout, CategoryResponse, CheckOrderDuplicateRequest,
-            CheckOrderDuplicateResponse, DateFilterParams, Order, OrderCreate, OrderDeliveryStatus,
-            OrderEdit, OrderItemResponse, OrderResponse, OrderStatus as OrderStatusUpdate,
-            PaymentResponse, ProductResponse, ShipmentResponse, UserOrderResponse, UserResponse,
-        },
-        ResponseWrapper,
+    contracts::order::{
+        AllOrderResponse, CartCheckout, CategoryResponse, CheckOrderDuplicateRequest,
+        CheckOrderDuplicateResponse, DateFilterParams, Order, OrderCreate, OrderDeliveryStatus,
+        OrderEdit, OrderItemResponse, OrderResponse, OrderStatus as OrderStatusUpdate,
+        PaymentResponse, ProductResponse, ShipmentResponse, UserOrderResponse, UserResponse,
     },
     db::connection::{get_conn, SqliteConnectionPool},
+    middlewares::user_info::UserInfo,
     models::{
-        cart::Cart as CartModel,
-        invoice::{Invoice, NewInvoice},
-        invoice_item::NewInvoiceItem,
-        order::{NewOrder, Order as OrderModel},
-        order_item::NewOrderItem as NewOrderItemModel,
-        payment::{NewPayment, Payment as PaymentModel},
-        product::Product as ProductModel,
-        shipment::NewShipment,
+        cart::Cart as CartModel, order::Order as OrderModel, product::Product as ProductModel,
         user::User as UserModel,
     },
+    policies::{
+        ownership::{self, Resource},
+        permission,
+    },
     services::{
+        availability_service, cart_service,
         email_service::EmailServiceFactory,
         notification_service::{NewOrderPayload, NotificationEvent, NotificationService},
+        order_service::{self, OrderLine, OrderRequest, PlaceOrderError},
     },
     utils::uuid_validator,
 };
@@ -134,6 +131,7 @@ pub async fn get_orders_count(
 
 #[get("/{ord_id}")]
 pub async fn get_order(
+    user_info: UserInfo,
     ord_id: web::Path<(String,)>,
     pool: web::Data<SqliteConnectionPool>,
 ) -> impl Responder {
@@ -162,6 +160,10 @@ pub async fn get_order(
 
     let conn = &mut get_conn(&pool);
 
+    if let Err(res) = ownership::authorize(conn, &user_info, Resource::Order(&ord_id.to_string())) {
+        return res;
+    }
+
     type OrderTuple = (
         String,
         String,
@@ -180,6 +182,7 @@ pub async fn get_order(
             f64,
             f64,
             f64,
+            Option<f64>,
             (
                 String,
                 String,
@@ -229,6 +232,7 @@ pub async fn get_order(
                 order_items::price,
                 order_items::discount,
                 order_items::amount,
+                order_items::tier_min_quantity,
                 (
                     products::uuid,
                     products::name,
@@ -277,6 +281,7 @@ pub async fn get_order(
                     order_item_price,
                     order_item_discount,
                     order_item_amount,
+                    order_item_tier_min_quantity,
                     (
                         product_uuid,
                         product_name,
@@ -339,6 +344,7 @@ pub async fn get_order(
                     price: order_item_price,
                     discount: order_item_discount,
                     amount: order_item_amount,
+                    tier_min_quantity: order_item_tier_min_quantity,
                     product: ProductResponse {
                         uuid: product_uuid,
                         name: product_name,
@@ -369,6 +375,7 @@ pub async fn get_order(
 
 #[get("/user/{cust_id}")]
 pub async fn get_user_orders(
+    user_info: UserInfo,
     cust_id: web::Path<(String,)>,
     pool: web::Data<SqliteConnectionPool>,
 ) -> impl Responder {
@@ -391,6 +398,12 @@ pub async fn get_user_orders(
 
     let conn = &mut get_conn(&pool);
 
+    if let Err(res) =
+        ownership::authorize(conn, &user_info, Resource::User(&cust_id_uuid.to_string()))
+    {
+        return res;
+    }
+
     let cust: UserModel = match users
         .filter(users::uuid.eq(cust_id_uuid.to_string()))
         .select(UserModel::as_select())
@@ -427,6 +440,7 @@ pub async fn get_user_orders(
             f64,
             f64,
             f64,
+            Option<f64>,
             (
                 String,
                 String,
@@ -460,6 +474,7 @@ pub async fn get_user_orders(
                 order_items::price,
                 order_items::discount,
                 order_items::amount,
+                order_items::tier_min_quantity,
                 (
                     products::uuid,
                     products::name,
@@ -493,6 +508,7 @@ pub async fn get_user_orders(
                     order_item_price,
                     order_item_discount,
                     order_item_amount,
+                    order_item_tier_min_quantity,
                     (
                         product_uuid,
                         product_name,
@@ -511,6 +527,7 @@ pub async fn get_user_orders(
                     price: order_item_price,
                     discount: order_item_discount,
                     amount: order_item_amount,
+                    tier_min_quantity: order_item_tier_min_quantity,
                     product: ProductResponse {
                         uuid: product_uuid,
                         name: product_name,
@@ -570,6 +587,7 @@ pub async fn get_user_orders(
 
 #[get("/check-duplicate")]
 pub async fn check_duplicate_order(
+    user_info: UserInfo,
     data: web::Json<CheckOrderDuplicateRequest>,
     pool: web::Data<SqliteConnectionPool>,
 ) -> impl Responder {
@@ -581,6 +599,10 @@ pub async fn check_duplicate_order(
 
     let conn = &mut get_conn(&pool);
 
+    if let Err(res) = ownership::authorize(conn, &user_info, Resource::User(&data.user_id)) {
+        return res;
+    }
+
     let exists = match diesel::select(diesel::dsl::exists(
         orders
             .inner_join(users)
@@ -617,14 +639,13 @@ pub async fn check_duplicate_order(
 
 #[post("")]
 pub async fn create(
+    user_info: UserInfo,
     order_json: web::Json<OrderCreate>,
     pool: web::Data<SqliteConnectionPool>,
     notification_service: web::Data<Arc<dyn NotificationService>>,
     email_config: web::Data<EmailConfiguration>,
 ) -> impl Responder {
-    use crate::schema::{
-        invoice_items, invoices, order_items, orders, payments, products, shipments, users,
-    };
+    use crate::schema::users;
 
     let user_uuid = match uuid_validator::validate_uuid(&order_json.user_id) {
         Ok(uid) => uid,
@@ -648,6 +669,10 @@ pub async fn create(
 
     let conn = &mut get_conn(&pool);
 
+    if let Err(res) = ownership::authorize(conn, &user_info, Resource::User(&user_uuid)) {
+        return res;
+    }
+
     let (order_total, order_quantity, order_discount) =
         order_json
             .order_items
@@ -685,139 +710,42 @@ pub async fn create(
         }
     };
 
-    let mut created_order_id: String = String::new();
-
-    match conn.transaction::<ResponseWrapper, diesel::result::Error, _>(|con| {
-        let new_order = NewOrder::new(
-            &user,
-            &order_json.created_on,
-            order_json.delivery_charge,
-            DeliveryStatus::Pending,
-            &order_json.delivery_location,
-            order_total,
-            order_quantity,
-            OrderStatus::PaymentPending,
-            order_discount,
-        );
-
-        let order: OrderModel = diesel::insert_into(orders::table)
-            .values(&new_order)
-            .get_result(con)?;
-
-        created_order_id = order.get_uuid().to_string().clone();
-
-        let shipment = NewShipment::new(&order_json.delivery_location, &order);
-        diesel::insert_into(shipments::table)
-            .values(&shipment)
-            .execute(con)?;
-
-        let tran_id = if order_json.payment.payment_method == PaymentMethod::Cash.value() {
-            Uuid::new_v4().to_string().replace('-', "")
-        } else {
-            String::new()
-        };
-
-        let new_payment = NewPayment::new(
-            &pay_method,
-            &tran_id,
-            &user,
-            &order,
-            order.get_total_price(),
-            order.get_total_price(),
-            PaymentStatus::Pending.value().to_string(),
-        );
-
-        let payment = diesel::insert_into(payments::table)
-            .values(&new_payment)
-            .get_result::<PaymentModel>(con)?;
-
-        diesel::update(&order)
-            .set(orders::status.eq(OrderStatus::Processed.value()))
-            .execute(con)?;
-
-        let nepal_time = chrono::Utc::now()
-            .with_timezone(&chrono::FixedOffset::east_opt(5 * 3600 + 45 * 60).unwrap());
-        let inv_date = nepal_time.format("%Y-%m-%d %H:%M:%S").to_string();
+    let request = OrderRequest {
+        created_on: &order_json.created_on,
+        delivery_charge: order_json.delivery_charge,
+        delivery_location: &order_json.delivery_location,
+        payment_method: &pay_method,
+        expected_items_total: Some(order_total),
+        quantity: order_quantity,
+        discount: order_discount,
+        lines: order_json
+            .order_items
+            .iter()
+            .map(|od| OrderLine {
+                product_id: &od.product_id,
+                quantity: od.quantity,
+                discount: od.discount,
+            })
+            .collect(),
+    };
 
-        let new_inv = NewInvoice::new(
-            &inv_date,
-            order.get_total_price(),
-            0.0,
-            &order,
-            &user,
-            &payment,
-        );
-
-        let inv = diesel::insert_into(invoices::table)
-            .values(&new_inv)
-            .get_result::<Invoice>(con)?;
-
-        for order_item in &order_json.order_items {
-            let product: ProductModel = match products::table
-                .filter(products::uuid.eq(&order_item.product_id))
-                .select(ProductModel::as_select())
-                .first(con)
-                .optional()?
-            {
-                Some(p) => p,
-                None => {
-                    return Ok(ResponseWrapper {
-                        success: false,
-                        response: HttpResponse::BadRequest().json(serde_json::json!({
-                            "message": "Product not found for order"
-                        })),
-                    });
-                }
+    match order_service::place_order(conn, &user, &request) {
+        Ok(order) => {
+            let created_order_id = order.get_uuid().to_string();
+
+            let order_vm = Order {
+                user_id: format!("{};{}", user_uuid, user.get_fullname()),
+                created_on: order.get_created_on().to_owned(),
+                fulfilled_on: order.get_fulfilled_on().to_owned(),
+                total_price: order.get_total_price(),
+                uuid: order.get_uuid().to_string(),
+                delivery_charge: order.get_delivery_charge(),
+                delivery_location: order.get_delivery_location().to_owned(),
+                delivery_status: order.get_delivery_status().to_owned(),
             };
-
-            if product.get_stock() < order_item.quantity {
-                return Ok(ResponseWrapper {
-                    success: false,
-                    response: HttpResponse::BadRequest().json(serde_json::json!({
-                        "message": "Product out of stock"
-                    })),
-                });
-            }
-
-            let new_order_item =
-                NewOrderItemModel::new(order_item.quantity, order_item.discount, &product, &order);
-            diesel::insert_into(order_items::table)
-                .values(&new_order_item)
-                .execute(con)?;
-
-            diesel::update(&product)
-                .set(products::stock.eq(products::stock - order_item.quantity))
-                .execute(con)?;
-
-            let new_inv_item = NewInvoiceItem::new(&product, &inv, order_item.quantity, 0.0, 0.0);
-
-            diesel::insert_into(invoice_items::table)
-                .values(&new_inv_item)
-                .execute(con)?;
-        }
-
-        let order_vm = Order {
-            user_id: format!("{};{}", user_uuid, user.get_fullname()),
-            created_on: order.get_created_on().to_owned(),
-            fulfilled_on: order.get_fulfilled_on().to_owned(),
-            total_price: order.get_total_price(),
-            uuid: order.get_uuid().to_string(),
-            delivery_charge: order.get_delivery_charge(),
-            delivery_location: order.get_delivery_location().to_owned(),
-            delivery_status: order.get_delivery_status().to_owned(),
-        };
-
-        Ok(ResponseWrapper {
-            success: true,
-            response: HttpResponse::Ok()
+            let response = HttpResponse::Ok()
                 .status(StatusCode::OK)
-                .json(serde_json::json!({"order": order_vm})),
-        })
-    }) {
-        Ok(response) => {
-            if !response.success {
-                return response.response;
-            }
+                .json(serde_json::json!({"order": order_vm}));
 
             let email_service = EmailServiceFactory::create_gmail_service(email_config.get_ref());
 
@@ -847,7 +775,7 @@ pub async fn create(
             if PaymentMethod::from_str(&order_json.payment.payment_method).unwrap()
                 != PaymentMethod::Cash
             {
-                return response.response;
+                return response;
             }
 
             let order_created_payload = NewOrderPayload {
@@ -872,8 +800,11 @@ pub async fn create(
                 }
             });
 
-            response.response
+            response
         }
+        Err(PlaceOrderError::Rejected(message)) => HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": message})),
         Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
             "message": format!("Failed to process order transaction: {}", e)
         })),
@@ -882,6 +813,7 @@ pub async fn create(
 
 #[post("/cart/create-order")]
 pub async fn create_orders_from_cart(
+    user_info: UserInfo,
     carts_json: web::Json<CartCheckout>,
     pool: web::Data<SqliteConnectionPool>,
 ) -> impl Responder {
@@ -904,18 +836,22 @@ pub async fn create_orders_from_cart(
         };
     }
 
-    use crate::schema::carts::dsl::*;
-    use crate::schema::order_items::dsl::*;
-    use crate::schema::orders::dsl::*;
-    use crate::schema::products::dsl::*;
-    use crate::schema::users::dsl::*;
-    use crate::schema::{carts, invoice_items, invoices, payments, products, shipments, users};
+    use crate::schema::{carts, products, users};
 
     //get a pooled connection from db
     let conn = &mut get_conn(&pool);
 
+    if let Err(res) = ownership::authorize(conn, &user_info, Resource::User(&user_uuid)) {
+        return res;
+    }
+    for cart_uid in &carts_json.cart_ids {
+        if let Err(res) = ownership::authorize(conn, &user_info, Resource::Cart(cart_uid)) {
+            return res;
+        }
+    }
+
     //validate user exists
-    let user: UserModel = match users
+    let user: UserModel = match users::table
         .filter(users::uuid.eq(user_uuid.to_string()))
         .select(UserModel::as_select())
         .first(conn)
@@ -934,162 +870,98 @@ pub async fn create_orders_from_cart(
         }
     };
 
-    if user.get_location().is_none() {
-        return HttpResponse::BadRequest().json(serde_json::json!({
-            "message": "User's location is missing. Please update location"
-        }));
-    }
+    let delivery_location = match user.get_location() {
+        Some(location) => location.to_owned(),
+        None => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "message": "User's location is missing. Please update location"
+            }));
+        }
+    };
 
     // validate cart items exists
-    let result = conn.transaction::<ResponseWrapper, diesel::result::Error, _>(|con| {
-        let mut order_items_vec: Vec<(CartModel, ProductModel)> = vec![];
-        let mut order_total_price = 0.0;
-        let mut order_total_quantity = 0.0;
-        let mut order_total_discount = 0.0;
-
-        for cart_id in &carts_json.cart_ids {
-            let cart: CartModel = match carts
-                .filter(carts::uuid.eq(&cart_id))
-                .first::<CartModel>(con)
-                .optional()?
-            {
-                Some(c) => c,
-                None => {
-                    return Ok(ResponseWrapper {
-                        success: false,
-                        response: HttpResponse::BadRequest().json(serde_json::json!({
-                            "message": format!("Cart not found: {}", cart_id)
-                        })),
-                    });
-                }
-            };
-
-            let product: ProductModel = match products
-                .filter(products::uuid.eq(&cart.get_uuid()))
-                .first::<ProductModel>(con)
-                .optional()?
-            {
-                Some(p) => p,
-                None => {
-                    return Ok(ResponseWrapper {
-                        success: false,
-                        response: HttpResponse::BadRequest().json(serde_json::json!({
-                            "message": "Product not found for order"
-                        })),
-                    });
-                }
-            };
-
-            if product.get_stock() < cart.get_quantity() {
-                return Ok(ResponseWrapper {
-                    success: false,
-                    response: HttpResponse::BadRequest().json(serde_json::json!({
-                        "message": "Product out of stock"
-                    })),
-                });
+    let mut cart_items: Vec<(CartModel, ProductModel)> = vec![];
+    for cart_id in &carts_json.cart_ids {
+        let cart: CartModel = match carts::table
+            .filter(carts::uuid.eq(cart_id))
+            .select(CartModel::as_select())
+            .first(conn)
+            .optional()
+        {
+            Ok(Some(c)) => c,
+            Ok(None) => {
+                return HttpResponse::BadRequest().json(serde_json::json!({
+                    "message": format!("Cart not found: {}", cart_id)
+                }));
             }
+            Err(_) => {
+                return HttpResponse::InternalServerError()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .json(serde_json::json!({"message": "Ops! something went wrong"}));
+            }
+        };
 
-            order_total_price += cart.get_quantity() as f64 * product.get_price();
-            order_total_quantity += cart.get_quantity();
-            order_total_discount += cart.get_discount();
-
-            order_items_vec.push((cart, product));
-        }
-
-        let nepal_time = chrono::Utc::now()
-            .with_timezone(&chrono::FixedOffset::east_opt(5 * 3600 + 45 * 60).unwrap());
-        let formatted_time = nepal_time.format("%Y-%m-%d %H:%M:%S").to_string();
-
-        let order = NewOrder::new(
-            &user,
-            &formatted_time,
-            DELIVERY_CHARGE, //delivery charge
-            DeliveryStatus::Pending,
-            &user.get_location().unwrap().to_owned(),
-            order_total_price,
-            order_total_quantity,
-            OrderStatus::PaymentPending,
-            order_total_discount,
-        );
-
-        let inserted_order: OrderModel =
-            diesel::insert_into(orders).values(&order).get_result(con)?;
-
-        if pay_method == PaymentMethod::Cash {
-            let user_location = user.get_location().unwrap_or_default();
-            let shipment = NewShipment::new(user_location, &inserted_order);
-
-            diesel::insert_into(shipments::table)
-                .values(&shipment)
-                .execute(con)?;
-
-            let tran_id = Uuid::new_v4().to_string().replace('-', "");
-            let new_payment = NewPayment::new(
-                &pay_method,
-                &tran_id,
-                &user,
-                &inserted_order,
-                inserted_order.get_total_price(),
-                inserted_order.get_total_price(),
-                carts_json.payment_status.to_owned(),
-            );
-
-            let payment = diesel::insert_into(payments::table)
-                .values(&new_payment)
-                .get_result::<PaymentModel>(con)?;
-
-            let new_invoice: NewInvoice = NewInvoice::new(
-                &formatted_time,
-                inserted_order.get_total_price(),
-                0.0,
-                &inserted_order,
-                &user,
-                &payment,
-            );
-
-            let inserted_inv = diesel::insert_into(invoices::table)
-                .values(&new_invoice)
-                .get_result::<Invoice>(con)?;
-
-            // Now insert all order items
-            for (cart, product) in &order_items_vec {
-                let new_order_item = NewOrderItemModel::new(
-                    cart.get_quantity(),
-                    cart.get_discount(),
-                    product,
-                    &inserted_order,
-                );
-
-                diesel::insert_into(order_items)
-                    .values(&new_order_item)
-                    .execute(con)?;
+        let product: ProductModel = match products::table
+            .filter(products::id.eq(cart.get_product_id()))
+            .select(ProductModel::as_select())
+            .first(conn)
+            .optional()
+        {
+            Ok(Some(p)) => p,
+            Ok(None) => {
+                return HttpResponse::BadRequest().json(serde_json::json!({
+                    "message": "Product not found for order"
+                }));
+            }
+            Err(_) => {
+                return HttpResponse::InternalServerError()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .json(serde_json::json!({"message": "Ops! something went wrong"}));
+            }
+        };
 
-                //update the product stock if order creation successful
-                diesel::update(&product)
-                    .set(products::stock.eq(products::stock - cart.get_quantity()))
-                    .execute(con)?;
+        cart_items.push((cart, product));
+    }
 
-                let inv_item =
-                    NewInvoiceItem::new(product, &inserted_inv, cart.get_quantity(), 0.0, 0.0);
-                diesel::insert_into(invoice_items::table)
-                    .values(&inv_item)
-                    .execute(con)?;
+    let nepal_time = chrono::Utc::now()
+        .with_timezone(&chrono::FixedOffset::east_opt(5 * 3600 + 45 * 60).unwrap());
+    let formatted_time = nepal_time.format("%Y-%m-%d %H:%M:%S").to_string();
+
+    let request = OrderRequest {
+        created_on: &formatted_time,
+        delivery_charge: DELIVERY_CHARGE,
+        delivery_location: &delivery_location,
+        payment_method: &pay_method,
+        expected_items_total: None,
+        quantity: cart_items.iter().map(|(cart, _)| cart.get_quantity()).sum(),
+        discount: cart_items.iter().map(|(cart, _)| cart.get_discount()).sum(),
+        lines: cart_items
+            .iter()
+            .map(|(cart, product)| OrderLine {
+                product_id: product.get_uuid(),
+                quantity: cart.get_quantity(),
+                discount: cart.get_discount(),
+            })
+            .collect(),
+    };
 
-                //delete from cart
-                diesel::delete(&cart).execute(con)?;
-            }
+    // the carts are only emptied once the order is in
+    let result = conn.transaction::<_, PlaceOrderError, _>(|con| {
+        let order = order_service::place_order(con, &user, &request)?;
+        for (cart, _) in &cart_items {
+            diesel::delete(cart).execute(con)?;
         }
-        Ok(ResponseWrapper {
-            success: true,
-            response: HttpResponse::Ok().json(serde_json::json!({
-                "message": "Order created successfully",
-                "order_id": inserted_order.get_uuid()
-            })),
-        })
+        Ok(order)
     });
 
     match result {
-        Ok(response) => response.response,
+        Ok(order) => HttpResponse::Ok().json(serde_json::json!({
+            "message": "Order created successfully",
+            "order_id": order.get_uuid()
+        })),
+        Err(PlaceOrderError::Rejected(message)) => HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": message})),
         Err(_) => HttpResponse::InternalServerError().json(serde_json::json!({
             "message": "Failed to process order transaction"
         })),
@@ -1098,6 +970,7 @@ pub async fn create_orders_from_cart(
 
 #[put("/{order_id}")]
 pub async fn edit(
+    user_info: UserInfo,
     order_id: web::Path<(String,)>,
     order_json: web::Json<OrderEdit>,
     pool: web::Data<SqliteConnectionPool>,
@@ -1129,6 +1002,24 @@ pub async fn edit(
     //get a pooled connection from db
     let conn = &mut get_conn(&pool);
 
+    if let Err(res) =
+        ownership::authorize(conn, &user_info, Resource::Order(&order_uid.to_string()))
+    {
+        return res;
+    }
+
+    // the order can only be handed to someone the caller may act for
+    if let Err(res) = ownership::authorize(conn, &user_info, Resource::User(&user_uuid.to_string()))
+    {
+        return res;
+    }
+
+    // customers may move the delivery, the price and progress are up to staff
+    let is_staff = match permission::allows(conn, &user_info, Permission::OrdersUpdateStatus) {
+        Ok(allowed) => allowed,
+        Err(res) => return res,
+    };
+
     //find the order
     let order: OrderModel = match orders
         .filter(orders::uuid.eq(&order_uid.to_string()))
@@ -1169,13 +1060,27 @@ pub async fn edit(
         }
     };
 
+    let (new_fulfilled_on, new_delivery_status, new_total_price) = if is_staff {
+        (
+            order_json.fulfilled_on.to_owned(),
+            order_json.delivery_status.to_owned(),
+            order_json.total_price,
+        )
+    } else {
+        (
+            order.get_fulfilled_on().to_owned(),
+            order.get_delivery_status().to_owned(),
+            order.get_total_price(),
+        )
+    };
+
     match diesel::update(&order)
         .set((
             user_id.eq(user.get_id()),
-            fulfilled_on.eq(&order_json.fulfilled_on),
-            delivery_status.eq(&order_json.delivery_status),
+            fulfilled_on.eq(new_fulfilled_on),
+            delivery_status.eq(new_delivery_status),
             delivery_location.eq(&order_json.delivery_location),
-            total_price.eq(order_json.total_price),
+            total_price.eq(new_total_price),
         ))
         .get_result::<OrderModel>(conn)
     {
@@ -1200,6 +1105,7 @@ pub async fn edit(
 
 #[patch("/{order_id}/order-status/update")]
 pub async fn update_order_status(
+    user_info: UserInfo,
     order_id: web::Path<(String,)>,
     order_status: web::Query<OrderStatusUpdate>,
     pool: web::Data<SqliteConnectionPool>,
@@ -1231,6 +1137,11 @@ pub async fn update_order_status(
     //get a pooled connection from db
     let conn = &mut get_conn(&pool);
 
+    // the admin orders scope only asks for orders.view
+    if let Err(res) = permission::authorize(conn, &user_info, Permission::OrdersUpdateStatus) {
+        return res;
+    }
+
     let order: OrderModel = match orders
         .filter(uuid.eq(&order_uid.to_string()))
         .select(OrderModel::as_select())
@@ -1275,6 +1186,7 @@ pub async fn update_order_status(
 
 #[patch("/{order_id}/delivery-status/update")]
 pub async fn update_delivery_status(
+    user_info: UserInfo,
     order_id: web::Path<(String,)>,
     order_delivery_status: web::Query<OrderDeliveryStatus>,
     pool: web::Data<SqliteConnectionPool>,
@@ -1306,6 +1218,19 @@ pub async fn update_delivery_status(
     //get a pooled connection from db
     let conn = &mut get_conn(&pool);
 
+    // delivery staff may update any order, customers only their own
+    match permission::allows(conn, &user_info, Permission::OrdersUpdateDelivery) {
+        Ok(true) => {}
+        Ok(false) => {
+            if let Err(res) =
+                ownership::authorize(conn, &user_info, Resource::Order(&order_uid.to_string()))
+            {
+                return res;
+            }
+        }
+        Err(res) => return res,
+    }
+
     let order: OrderModel = match orders
         .filter(uuid.eq(&order_uid.to_string()))
         .select(OrderModel::as_select())
@@ -1347,3 +1272,71 @@ pub async fn update_delivery_status(
             .json(serde_json::json!({"message": "Ops! something went wrong"})),
     }
 }
+
+/// Puts everything from a past order back into the customer's cart at
+/// today's prices. The response lists each item with what was added and why
+/// it differs from the original order.
+#[post("/{order_id}/reorder")]
+pub async fn reorder(
+    user_info: UserInfo,
+    order_id: web::Path<(String,)>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    use crate::schema::{orders, users};
+
+    let order_uid = match uuid_validator::validate_uuid(&order_id.into_inner().0) {
+        Ok(u) => u,
+        Err(res) => return res,
+    };
+
+    let conn = &mut get_conn(&pool);
+
+    if let Err(res) = ownership::authorize(conn, &user_info, Resource::Order(&order_uid)) {
+        return res;
+    }
+
+    let (order, customer): (OrderModel, UserModel) = match orders::table
+        .inner_join(users::table)
+        .filter(orders::uuid.eq(&order_uid))
+        .select((OrderModel::as_select(), UserModel::as_select()))
+        .first(conn)
+        .optional()
+    {
+        Ok(Some(o)) => o,
+        Ok(None) => {
+            return HttpResponse::NotFound()
+                .status(StatusCode::NOT_FOUND)
+                .json(serde_json::json!({"message": "Order not found"}))
+        }
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! something went wrong"}))
+        }
+    };
+
+    let nepal_time = chrono::Utc::now()
+        .with_timezone(&chrono::FixedOffset::east_opt(5 * 3600 + 45 * 60).unwrap());
+    let created_on = nepal_time.format("%Y-%m-%d %H:%M:%S").to_string();
+
+    match cart_service::reorder(
+        conn,
+        &order,
+        &customer,
+        created_on,
+        availability_service::today(),
+    ) {
+        Ok(items) => {
+            let added = items.iter().filter(|i| i.added_quantity > 0.0).count();
+            HttpResponse::Ok()
+                .status(StatusCode::OK)
+                .json(serde_json::json!({
+                    "message": format!("{} of {} items added to cart", added, items.len()),
+                    "items": items,
+                }))
+        }
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
@@ -6,15 +6,19 @@ use diesel::prelude::*;
 use crate::{
     contracts::order_item::{NewOrderItem, OrderItems},
     db::connection::{get_conn, SqliteConnectionPool},
+    middlewares::user_info::UserInfo,
     models::{
         order::Order as OrderModel,
         order_item::{NewOrderItem as NewOrderItemModel, OrderItem as OrderItemsModel},
         product::Product as ProductModel,
     },
+    policies::ownership::{self, Resource},
+    services::pricing_service,
 };
 
 #[get("/{order_uid}")]
 pub async fn get(
+    user_info: UserInfo,
     order_uid: web::Path<(String,)>,
     pool: web::Data<SqliteConnectionPool>,
 ) -> impl Responder {
@@ -36,6 +40,12 @@ pub async fn get(
     //get a pooled connection from db
     let conn = &mut get_conn(&pool);
 
+    if let Err(res) =
+        ownership::authorize(conn, &user_info, Resource::Order(&order_uid.to_string()))
+    {
+        return res;
+    }
+
     let order: OrderModel = match orders
         .filter(orders::uuid.eq(&order_uid.to_string()))
         .select(OrderModel::as_select())
@@ -67,6 +77,7 @@ pub async fn get(
             order_items::price,
             order_items::amount,
             order_items::discount,
+            order_items::tier_min_quantity,
         ))
         .load::<OrderItems>(conn)
     {
@@ -81,6 +92,7 @@ pub async fn get(
 
 #[get("order-detail/{od_uuid}")]
 pub async fn get_order_detail(
+    user_info: UserInfo,
     od_uuid: web::Path<(String,)>,
     pool: web::Data<SqliteConnectionPool>,
 ) -> impl Responder {
@@ -102,6 +114,14 @@ pub async fn get_order_detail(
     //get a pooled connection from db
     let conn = &mut get_conn(&pool);
 
+    if let Err(res) = ownership::authorize(
+        conn,
+        &user_info,
+        Resource::OrderItem(&order_detail_uid.to_string()),
+    ) {
+        return res;
+    }
+
     match order_items
         .filter(order_items::uuid.eq(&order_detail_uid.to_string()))
         .select(OrderItemsModel::as_select())
@@ -130,6 +150,7 @@ pub async fn get_order_detail(
                 quantity: od.get_quantity(),
                 amount: od.get_amount(),
                 discount: od.get_discount(),
+                tier_min_quantity: od.get_tier_min_quantity(),
             };
             HttpResponse::Ok().status(StatusCode::OK).json(order_det)
         }
@@ -144,6 +165,7 @@ pub async fn get_order_detail(
 
 #[post("/{ord_id}/add")]
 pub async fn add_order_detail(
+    user_info: UserInfo,
     ord_id: web::Path<(String,)>,
     pool: web::Data<SqliteConnectionPool>,
     ord_det: web::Json<NewOrderItem>,
@@ -168,6 +190,10 @@ pub async fn add_order_detail(
     //get a pooled connection from db
     let conn = &mut get_conn(&pool);
 
+    if let Err(res) = ownership::authorize(conn, &user_info, Resource::Order(&ord_id.to_string())) {
+        return res;
+    }
+
     let order: OrderModel = match orders
         .filter(orders::uuid.eq(&ord_id.to_string()))
         .select(OrderModel::as_select())
@@ -207,9 +233,24 @@ pub async fn add_order_detail(
         }
     };
 
+    let applied_price =
+        match pricing_service::price_for_quantity(conn, &prod_bought, ord_det.quantity) {
+            Ok(p) => p,
+            Err(_) => {
+                return HttpResponse::InternalServerError()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .json(serde_json::json!({"message": "Ops! something went wrong"}))
+            }
+        };
+
     // if product and order both exists then insert the new record into order_items table
-    let od: NewOrderItemModel =
-        NewOrderItemModel::new(ord_det.quantity, ord_det.discount, &prod_bought, &order);
+    let od: NewOrderItemModel = NewOrderItemModel::new(
+        ord_det.quantity,
+        ord_det.discount,
+        &prod_bought,
+        &order,
+        &applied_price,
+    );
 
     match diesel::insert_into(order_items).values(&od).execute(conn) {
         Ok(_) => HttpResponse::Ok()
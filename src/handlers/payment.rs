@@ -16,7 +16,7 @@ use crate::{
     config::{ApplicationConfiguration, CompanyConfiguration},
     contracts::{
         khalti_payment::{
-            AmountBreakdown, KhaltiPaymentCallback, KhaltiPaymentPayload, KhaltiResponse,
+            KhaltiPaymentCallback, KhaltiPaymentPayload, KhaltiResponse,
             KhaltiResponseCamelCase, ProductDetail, UserInfo,
         },
         order::{
@@ -30,6 +30,7 @@ use crate::{
         ResponseWrapper,
     },
     db::connection::{get_conn, SqliteConnectionPool},
+    middlewares::user_info::UserInfo as AuthenticatedUser,
     models::{
         invoice::{Invoice, NewInvoice},
         invoice_item::NewInvoiceItem,
@@ -40,10 +41,12 @@ use crate::{
         shipment::NewShipment,
         user::User as UserModel,
     },
+    policies::ownership::{self, Resource},
     services::{
         email_service::EmailService,
         invoice_service::{InvoiceItem, InvoiceService},
         notification_service::{NotificationEvent, NotificationService, PaymentReceivedPayload},
+        pricing_service::AppliedPrice,
     },
     utils,
 };
@@ -101,6 +104,7 @@ pub async fn get_all(
 
 #[post("")]
 pub async fn create(
+    auth_user: AuthenticatedUser,
     payment_json: web::Json<NewPayment>,
     pool: web::Data<SqliteConnectionPool>,
     notification_service: web::Data<Arc<dyn NotificationService>>,
@@ -116,6 +120,12 @@ pub async fn create(
 
     let conn = &mut get_conn(&pool);
 
+    if let Err(res) =
+        ownership::authorize(conn, &auth_user, Resource::Order(&payment_json.order_id))
+    {
+        return res;
+    }
+
     // Parse UUIDs
     let o_uuid = match Uuid::parse_str(&payment_json.order_id) {
         Ok(u) => u,
@@ -279,7 +289,16 @@ pub async fn create(
                 .select(Product::as_select())
                 .first(con)?; //might get runtime here but hey who cares
 
-            let inv_item = NewInvoiceItem::new(&product, &invoice, item.get_quantity(), 0.0, 0.0);
+            let applied_price = AppliedPrice::from_order_item(&item);
+
+            let inv_item = NewInvoiceItem::new(
+                &product,
+                &invoice,
+                item.get_quantity(),
+                0.0,
+                0.0,
+                &applied_price,
+            );
 
             diesel::insert_into(invoice_items::table)
                 .values(&inv_item)
@@ -289,8 +308,9 @@ pub async fn create(
                 description: product.get_name().to_string(),
                 quantity: item.get_quantity(),
                 sku: product.get_unit().to_owned(),
-                unit_price: product.get_price(),
-                total: item.get_quantity() * product.get_price(),
+                unit_price: applied_price.unit_price,
+                total: applied_price.total_for(item.get_quantity()),
+                tier_min_quantity: applied_price.tier_min_quantity,
             });
         }
 
@@ -328,13 +348,15 @@ pub async fn create(
                 send_email_with_invoice(
                     email_service.get_ref().to_owned(),
                     company_config.get_ref().to_owned(),
-                    created_inv_id,
-                    created_inv_number,
-                    created_order_id_clone,
-                    user_fullname_for_invoice,
-                    user_email_for_invoice,
-                    order_delivery_location,
-                    pdf_inv_items,
+                    InvoiceEmail {
+                        invoice_id: created_inv_id,
+                        invoice_no: created_inv_number,
+                        created_order_id: created_order_id_clone,
+                        user_name: user_fullname_for_invoice,
+                        user_email: user_email_for_invoice,
+                        order_delivery_location,
+                        inv_items: pdf_inv_items,
+                    },
                 )
                 .await;
             });
@@ -409,6 +431,7 @@ pub async fn get_by_id(
 
 #[get("/{order_id}")]
 pub async fn get(
+    auth_user: AuthenticatedUser,
     ord_id: web::Path<(String,)>,
     pool: web::Data<SqliteConnectionPool>,
 ) -> impl Responder {
@@ -431,6 +454,10 @@ pub async fn get(
 
     let conn = &mut get_conn(&pool);
 
+    if let Err(res) = ownership::authorize(conn, &auth_user, Resource::Order(&ord_id.to_string())) {
+        return res;
+    }
+
     let order: OrderModel = match orders
         .filter(orders::uuid.eq(&ord_id.to_string()))
         .select(OrderModel::as_select())
@@ -546,6 +573,70 @@ pub async fn complete_payment(
     }
 }
 
+/// Marks a completed payment as refunded, the money itself is sent back
+/// outside the app.
+#[put("/{payment_id}/refund")]
+pub async fn refund_payment(
+    payment_id: web::Path<String>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    use crate::schema::payments;
+
+    let payment_id: Uuid = match Uuid::parse_str(&payment_id.into_inner()) {
+        Ok(p) => p,
+        Err(_) => {
+            return HttpResponse::BadRequest()
+                .status(StatusCode::BAD_REQUEST)
+                .json(serde_json::json!({"message": "Invalid payment id"}))
+        }
+    };
+
+    let conn = &mut get_conn(&pool);
+
+    let payment: PaymentModel = match payments::table
+        .filter(payments::uuid.eq(&payment_id.to_string()))
+        .select(PaymentModel::as_select())
+        .first(conn)
+        .optional()
+    {
+        Ok(Some(p)) => p,
+        Ok(None) => {
+            return HttpResponse::NotFound()
+                .status(StatusCode::NOT_FOUND)
+                .json(serde_json::json!({"message": "Invalid payment id"}))
+        }
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! something went wrong"}))
+        }
+    };
+
+    if payment.is_refunded() {
+        return HttpResponse::Conflict()
+            .status(StatusCode::CONFLICT)
+            .json(serde_json::json!({"message": "Payment is already refunded"}));
+    }
+
+    if payment.get_status() != PaymentStatus::Completed.value() {
+        return HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": "Only completed payments can be refunded"}));
+    }
+
+    match diesel::update(&payment)
+        .set(payments::refunded.eq(true))
+        .execute(conn)
+    {
+        Ok(_) => HttpResponse::Ok()
+            .status(StatusCode::OK)
+            .json(serde_json::json!({"message": "Payment refunded"})),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
 #[post("/esewa")]
 pub async fn esewa_payment_confirmation(
     req_body: web::Json<EsewaCallbackResponse>,
@@ -574,12 +665,14 @@ pub async fn esewa_payment_confirmation(
             let order_id = vr.product_id.clone(); // product_id is actually order id.
 
             match handle_notification_and_email(
-                &order_id,
-                &vr.transaction_details.reference_id,
-                vr.total_amount.parse::<f64>().unwrap_or(0.0),
-                0.0, // hopefully we could determine the fee later
-                &vr.transaction_details.status,
-                PaymentMethod::Esewa,
+                GatewayTransaction {
+                    purchase_order_id: &order_id,
+                    tran_id: &vr.transaction_details.reference_id,
+                    tran_amount: vr.total_amount.parse::<f64>().unwrap_or(0.0),
+                    tran_fee: 0.0, // hopefully we could determine the fee later
+                    tran_status: &vr.transaction_details.status,
+                    pay_method: PaymentMethod::Esewa,
+                },
                 notification_service.get_ref().to_owned(),
                 email_service.get_ref().to_owned(),
                 company_config.get_ref().to_owned(),
@@ -654,6 +747,7 @@ async fn verify_transaction(
 //khalti payment integration
 #[get("/khalti")]
 pub async fn khalti_payment_get_pidx(
+    auth_user: AuthenticatedUser,
     pidx_payload: web::Query<KhaltiPidxPayload>,
     pool: web::Data<SqliteConnectionPool>,
     client: web::Data<Client>,
@@ -666,6 +760,12 @@ pub async fn khalti_payment_get_pidx(
         Err(http_response) => return http_response,
     };
 
+    if let Err(res) =
+        ownership::authorize(&mut get_conn(&pool), &auth_user, Resource::Order(&order_id))
+    {
+        return res;
+    }
+
     let order_details: OrderResponse = match get_order_details(&order_id, &pool).await {
         Ok(o) => o,
         Err(http_response) => return http_response,
@@ -697,20 +797,11 @@ pub async fn khalti_payment_get_pidx(
     let khalti_payment_payload = KhaltiPaymentPayload::create(
         &app_config.khalti_payment_confirm_callback_url,
         &app_config.khalti_payment_confirm_callback_webiste_url,
-        order_details.total_price,
         order_details.uuid,
-        format!("{}'s Order", user_info.name),
         user_info,
-        Some(vec![
-            AmountBreakdown::new("Delivery Charge".into(), order_details.delivery_charge),
-            AmountBreakdown::new(
-                "Product Charge".into(),
-                order_details.total_price - order_details.delivery_charge,
-            ),
-        ]),
-        Some(product_details),
-        "Himal Poudel".into(), ////merchant username
-        String::from(""),      //merchant extra
+        order_details.total_price,
+        order_details.delivery_charge,
+        product_details,
     );
 
     println!("khalti_payment_payload: {khalti_payment_payload:?}");
@@ -808,12 +899,14 @@ pub async fn khalti_payment_confirmation(
                     println!("Confirmation response: {:?}", khalti_response);
 
                     match handle_notification_and_email(
-                        &params.purchase_order_id,
-                        &khalti_response.transaction_id.unwrap_or_default(),
-                        khalti_response.total_amount / 100.0,
-                        khalti_response.fee / 100.0,
-                        &khalti_response.status,
-                        PaymentMethod::Khalti,
+                        GatewayTransaction {
+                            purchase_order_id: &params.purchase_order_id,
+                            tran_id: &khalti_response.transaction_id.unwrap_or_default(),
+                            tran_amount: khalti_response.total_amount / 100.0,
+                            tran_fee: khalti_response.fee / 100.0,
+                            tran_status: &khalti_response.status,
+                            pay_method: PaymentMethod::Khalti,
+                        },
                         notification_service.get_ref().to_owned(),
                         email_service.get_ref().to_owned(),
                         company_config.get_ref().to_owned(),
@@ -895,6 +988,7 @@ async fn get_order_details(
             f64,
             f64,
             f64,
+            Option<f64>,
             (
                 String,
                 String,
@@ -944,6 +1038,7 @@ async fn get_order_details(
                 order_items::price,
                 order_items::discount,
                 order_items::amount,
+                order_items::tier_min_quantity,
                 (
                     products::uuid,
                     products::name,
@@ -992,6 +1087,7 @@ async fn get_order_details(
                     order_item_price,
                     order_item_discount,
                     order_item_amount,
+                    order_item_tier_min_quantity,
                     (
                         product_uuid,
                         product_name,
@@ -1054,6 +1150,7 @@ async fn get_order_details(
                     price: order_item_price,
                     discount: order_item_discount,
                     amount: order_item_amount,
+                    tier_min_quantity: order_item_tier_min_quantity,
                     product: ProductResponse {
                         uuid: product_uuid,
                         name: product_name,
@@ -1079,18 +1176,31 @@ async fn get_order_details(
     }
 }
 
-async fn handle_notification_and_email(
-    purchase_order_id: &String,
-    tran_id: &String,
+// a payment as the gateway reported it, amounts are in rupees
+struct GatewayTransaction<'a> {
+    purchase_order_id: &'a String,
+    tran_id: &'a String,
     tran_amount: f64,
     tran_fee: f64,
-    tran_status: &String,
+    tran_status: &'a String,
     pay_method: PaymentMethod,
+}
+
+async fn handle_notification_and_email(
+    transaction: GatewayTransaction<'_>,
     notification_service: Arc<dyn NotificationService>,
     email_service: Arc<dyn EmailService>,
     company_config: CompanyConfiguration,
     pool: web::Data<SqliteConnectionPool>,
 ) -> Result<(), HttpResponse> {
+    let GatewayTransaction {
+        purchase_order_id,
+        tran_id,
+        tran_amount,
+        tran_fee,
+        tran_status,
+        pay_method,
+    } = transaction;
     use crate::schema::invoice_items::dsl::*;
     use crate::schema::invoices::dsl::*;
     use crate::schema::orders::dsl::*;
@@ -1162,6 +1272,7 @@ async fn handle_notification_and_email(
             invoice_items::unit_price,
             products::unit,
             invoice_items::total,
+            invoice_items::tier_min_quantity,
         ))
         .load::<InvoiceItem>(conn)
     {
@@ -1223,13 +1334,15 @@ async fn handle_notification_and_email(
                 send_email_with_invoice(
                     email_service,
                     company_config,
-                    invoice.get_id(),
-                    invoice.invoice_number(),
-                    order_id_clone,
-                    format!("{} {}", order.user.first_name, order.user.last_name),
-                    order.user.email,
-                    order.delivery_location,
-                    inv_items,
+                    InvoiceEmail {
+                        invoice_id: invoice.get_id(),
+                        invoice_no: invoice.invoice_number(),
+                        created_order_id: order_id_clone,
+                        user_name: format!("{} {}", order.user.first_name, order.user.last_name),
+                        user_email: order.user.email,
+                        order_delivery_location: order.delivery_location,
+                        inv_items,
+                    },
                 )
                 .await;
             });
@@ -1275,9 +1388,8 @@ async fn send_order_notification(
     };
 }
 
-async fn send_email_with_invoice(
-    email_service: Arc<dyn EmailService>,
-    company_config: CompanyConfiguration,
+// everything the invoice pdf and its email are made from
+struct InvoiceEmail {
     invoice_id: i32,
     invoice_no: i32,
     created_order_id: String,
@@ -1285,7 +1397,22 @@ async fn send_email_with_invoice(
     user_email: String,
     order_delivery_location: String,
     inv_items: Vec<InvoiceItem>,
+}
+
+async fn send_email_with_invoice(
+    email_service: Arc<dyn EmailService>,
+    company_config: CompanyConfiguration,
+    invoice: InvoiceEmail,
 ) {
+    let InvoiceEmail {
+        invoice_id,
+        invoice_no,
+        created_order_id,
+        user_name,
+        user_email,
+        order_delivery_location,
+        inv_items,
+    } = invoice;
     let invoice_service = InvoiceService::new(company_config);
 
     // Generate PDF directly in memory
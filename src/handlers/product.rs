@@ -1,30 +1,199 @@
-use std::{env, path::Path};
+use std::{collections::HashMap, env, path::Path, sync::Arc};
 
 use crate::{
+    base_types::{moderation_status::ModerationStatus, order_status::OrderStatus},
     config::ApplicationConfiguration,
     contracts::{
         category::Category,
-        product::{CategoryFilterParams, Product, ProductCreate, ProductStockUpdate, UploadForm},
+        product::{
+            CategoryFilterParams, Product, ProductCreate, ProductFacets, ProductStockUpdate,
+            UploadForm,
+        },
+        product_attribute::{AttributeFacet, ProductAttribute},
         product_image::ProductImage,
-        product_rating::{NewProductRating, ProductRating},
+        product_rating::{NewProductRating, RatingListParams, RatingSummary},
+        tag::{Tag, TagFacet},
     },
     db::connection::{get_conn, SqliteConnectionPool},
+    handlers::product_rating::rating_responses,
     models::{
         category::Category as CategoryModel,
-        product::{NewProduct, Product as ProductModel},
+        product::{NewProduct, Product as ProductModel, ProductDetails},
+        product_attribute::ProductAttribute as ProductAttributeModel,
         product_image::{
             NewProductImage as NewProductImageModel, ProductImage as ProductImageModel,
         },
         product_rating::{
             NewProductRating as NewProductRatingModel, ProductRating as ProductRatingModel,
         },
+        product_rating_summary::ProductRatingSummary as ProductRatingSummaryModel,
+        tag::Tag as TagModel,
     },
+    services::{availability_service, notification_service::NotificationService, wishlist_service},
 };
 use ::uuid::Uuid;
 use actix_multipart::form::MultipartForm;
 use actix_web::{delete, get, http::StatusCode, patch, post, put, web, HttpResponse, Responder};
 use diesel::prelude::*;
 
+pub fn find_product(
+    conn: &mut SqliteConnection,
+    prod_id: &str,
+) -> Result<ProductModel, HttpResponse> {
+    use crate::schema::products;
+
+    //check if the product_id is valid uuid or not before trip to db
+    if Uuid::parse_str(prod_id).is_err() {
+        return Err(HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": "Invalid product id"})));
+    }
+
+    match products::table
+        .filter(products::uuid.eq(prod_id))
+        .select(ProductModel::as_select())
+        .first(conn)
+        .optional()
+    {
+        Ok(Some(p)) => Ok(p),
+        Ok(None) => Err(HttpResponse::NotFound()
+            .status(StatusCode::NOT_FOUND)
+            .json(serde_json::json!({"message": "Product not found"}))),
+        Err(_) => Err(HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"}))),
+    }
+}
+
+/// Builds the product responses for `rows` with their tags, attributes, rating
+/// summary and today's availability filled in.
+pub fn product_responses(
+    conn: &mut SqliteConnection,
+    rows: &[(ProductModel, CategoryModel)],
+) -> QueryResult<Vec<Product>> {
+    use crate::schema::{product_attributes, product_rating_summaries, product_tags, tags};
+
+    let ids: Vec<i32> = rows.iter().map(|(p, _)| p.get_id()).collect();
+
+    let product_tag_rows: Vec<(i32, TagModel)> = product_tags::table
+        .inner_join(tags::table)
+        .filter(product_tags::product_id.eq_any(&ids))
+        .order(tags::name.asc())
+        .select((product_tags::product_id, TagModel::as_select()))
+        .load(conn)?;
+
+    let attribute_rows: Vec<ProductAttributeModel> = product_attributes::table
+        .filter(product_attributes::product_id.eq_any(&ids))
+        .order(product_attributes::name.asc())
+        .select(ProductAttributeModel::as_select())
+        .load(conn)?;
+
+    let mut tags_by_product: HashMap<i32, Vec<Tag>> = HashMap::new();
+    for (prod_id, t) in product_tag_rows {
+        tags_by_product.entry(prod_id).or_default().push(Tag {
+            uuid: t.get_uuid().to_owned(),
+            name: t.get_name().to_owned(),
+            slug: t.get_slug().to_owned(),
+        });
+    }
+
+    let mut attributes_by_product: HashMap<i32, Vec<ProductAttribute>> = HashMap::new();
+    for a in attribute_rows {
+        attributes_by_product
+            .entry(a.get_product_id())
+            .or_default()
+            .push(ProductAttribute {
+                name: a.get_name().to_owned(),
+                value: a.get_value().to_owned(),
+            });
+    }
+
+    let mut summaries: HashMap<i32, ProductRatingSummaryModel> = product_rating_summaries::table
+        .filter(product_rating_summaries::product_id.eq_any(&ids))
+        .select(ProductRatingSummaryModel::as_select())
+        .load(conn)?
+        .into_iter()
+        .map(|r| (r.get_product_id(), r))
+        .collect();
+
+    let mut availability =
+        availability_service::availability_for_products(conn, &ids, availability_service::today())?;
+
+    Ok(rows
+        .iter()
+        .map(|(p, c)| {
+            let mut response = p.as_response(c);
+            if let Some(r) = summaries.remove(&p.get_id()) {
+                response.rating = RatingSummary {
+                    average: r.get_average_rating(),
+                    count: r.get_rating_count(),
+                    histogram: r.get_histogram(),
+                };
+            }
+            if let Some(a) = availability
+                .remove(&p.get_id())
+                .filter(|_| !p.is_archived())
+            {
+                response.available = a.available;
+                response.available_from =
+                    a.available_from.map(|d| d.format("%Y-%m-%d").to_string());
+            }
+            response.tags = tags_by_product.remove(&p.get_id()).unwrap_or_default();
+            response.attributes = attributes_by_product
+                .remove(&p.get_id())
+                .unwrap_or_default();
+            response
+        })
+        .collect())
+}
+
+// counts how many of the given products carry each tag and attribute value
+fn product_facets(conn: &mut SqliteConnection, ids: &[i32]) -> QueryResult<ProductFacets> {
+    use crate::schema::{product_attributes, product_tags, tags};
+    use diesel::dsl::count;
+
+    let tag_counts: Vec<(String, String, i64)> = product_tags::table
+        .inner_join(tags::table)
+        .filter(product_tags::product_id.eq_any(ids))
+        .group_by((tags::slug, tags::name))
+        .select((tags::slug, tags::name, count(product_tags::product_id)))
+        .order(tags::name.asc())
+        .load(conn)?;
+
+    let attribute_counts: Vec<(String, String, i64)> = product_attributes::table
+        .filter(product_attributes::product_id.eq_any(ids))
+        .group_by((product_attributes::name, product_attributes::value))
+        .select((
+            product_attributes::name,
+            product_attributes::value,
+            count(product_attributes::product_id),
+        ))
+        .order((
+            product_attributes::name.asc(),
+            product_attributes::value.asc(),
+        ))
+        .load(conn)?;
+
+    Ok(ProductFacets {
+        tags: tag_counts
+            .into_iter()
+            .map(|(tag_slug, tag_name, c)| TagFacet {
+                slug: tag_slug,
+                name: tag_name,
+                count: c,
+            })
+            .collect(),
+        attributes: attribute_counts
+            .into_iter()
+            .map(|(attribute_name, attribute_value, c)| AttributeFacet {
+                name: attribute_name,
+                value: attribute_value,
+                count: c,
+            })
+            .collect(),
+    })
+}
+
 #[get("")]
 pub async fn get(
     filters: web::Query<CategoryFilterParams>,
@@ -34,32 +203,130 @@ pub async fn get(
     use crate::schema::categories::dsl::*;
     use crate::schema::products;
     use crate::schema::products::dsl::*;
+    use crate::schema::{product_attributes, product_rating_summaries, product_tags, tags};
 
     let conn = &mut get_conn(&pool);
 
-    let mut query = products.inner_join(categories).into_boxed();
+    let mut query = products
+        .inner_join(categories)
+        .left_join(product_rating_summaries::table)
+        .filter(categories::is_active.eq(true))
+        .filter(products::is_archived.eq(false))
+        .into_boxed();
+
+    match filters.sort.as_deref() {
+        None => {}
+        // products nobody has rated yet have no summary row and sort last
+        Some("rating") => {
+            query = query.order((
+                product_rating_summaries::average_rating.desc(),
+                product_rating_summaries::rating_count.desc(),
+            ))
+        }
+        Some(_) => {
+            return HttpResponse::BadRequest()
+                .status(StatusCode::BAD_REQUEST)
+                .json(serde_json::json!({"message": "Invalid sort. Valid values are: 'rating'"}))
+        }
+    }
 
     if let Some(cid) = &filters.category_id {
-        query = query.filter(categories::uuid.eq(cid));
+        if filters.include_subcategories.unwrap_or(false) {
+            let root_id: Option<i32> = match categories
+                .filter(categories::uuid.eq(cid))
+                .select(categories::id)
+                .first(conn)
+                .optional()
+            {
+                Ok(r) => r,
+                Err(e) => {
+                    return HttpResponse::InternalServerError()
+                        .json(serde_json::json!({ "message": e.to_string() }))
+                }
+            };
+
+            match root_id.map(|r| CategoryModel::descendant_ids(conn, r)) {
+                Some(Ok(ids)) => query = query.filter(category_id.eq_any(ids)),
+                Some(Err(e)) => {
+                    return HttpResponse::InternalServerError()
+                        .json(serde_json::json!({ "message": e.to_string() }))
+                }
+                None => query = query.filter(categories::uuid.eq(cid)),
+            }
+        } else {
+            query = query.filter(categories::uuid.eq(cid));
+        }
     }
 
-    let result = query
-        .select((
-            products::uuid,
-            products::name,
-            description,
-            image,
-            price,
-            previous_price,
-            unit,
-            unit_change,
-            stock,
-            (categories::uuid, categories::name),
-        ))
-        .load::<Product>(conn);
+    //every requested tag has to be present on the product
+    if let Some(tag_filter) = &filters.tags {
+        for tag_slug in tag_filter
+            .split(',')
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+        {
+            query = query.filter(
+                products::id.eq_any(
+                    product_tags::table
+                        .inner_join(tags::table)
+                        .filter(tags::slug.eq(tag_slug.to_lowercase()))
+                        .select(product_tags::product_id),
+                ),
+            );
+        }
+    }
 
-    match result {
-        Ok(p) => HttpResponse::Ok().json(serde_json::json!({ "products": p })),
+    if let Some(attribute_filter) = &filters.attributes {
+        for pair in attribute_filter.split(',').filter(|a| !a.trim().is_empty()) {
+            let Some((attribute_name, attribute_value)) = pair.split_once(':') else {
+                return HttpResponse::BadRequest()
+                    .status(StatusCode::BAD_REQUEST)
+                    .json(serde_json::json!({"message": "Attribute filters must be in name:value format"}));
+            };
+
+            query = query.filter(
+                products::id.eq_any(
+                    product_attributes::table
+                        .filter(product_attributes::name.eq(attribute_name.trim().to_lowercase()))
+                        .filter(product_attributes::value.eq(attribute_value.trim().to_owned()))
+                        .select(product_attributes::product_id),
+                ),
+            );
+        }
+    }
+
+    let rows: Vec<(ProductModel, CategoryModel)> = match query
+        .select((ProductModel::as_select(), CategoryModel::as_select()))
+        .load(conn)
+    {
+        Ok(r) => r,
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .json(serde_json::json!({ "message": e.to_string() }))
+        }
+    };
+
+    let mut product_list = match product_responses(conn, &rows) {
+        Ok(p) => p,
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .json(serde_json::json!({ "message": e.to_string() }))
+        }
+    };
+    let mut ids: Vec<i32> = rows.iter().map(|(p, _)| p.get_id()).collect();
+
+    if filters.available_only.unwrap_or(false) {
+        (ids, product_list) = ids
+            .into_iter()
+            .zip(product_list)
+            .filter(|(_, p)| p.available)
+            .unzip();
+    }
+
+    match product_facets(conn, &ids) {
+        Ok(f) => {
+            HttpResponse::Ok().json(serde_json::json!({ "products": product_list, "facets": f }))
+        }
         Err(e) => HttpResponse::InternalServerError()
             .json(serde_json::json!({ "message": e.to_string() })),
     }
@@ -124,14 +391,16 @@ pub async fn create(
 
     // Now create the product, passing image_path (unwrap or default as needed)
     let product: NewProduct = NewProduct::new(
-        form.name.0.to_owned(),
-        form.description.0.to_owned(),
-        image_path.unwrap_or_default(),
-        form.price.0,
-        form.previous_price.0,
-        form.unit.0.to_owned(),
-        form.unit_change.0,
-        form.stock.0,
+        ProductDetails {
+            name: form.name.0.to_owned(),
+            description: form.description.0.to_owned(),
+            image: image_path.unwrap_or_default(),
+            price: form.price.0,
+            previous_price: form.previous_price.0,
+            unit: form.unit.0.to_owned(),
+            unit_change: form.unit_change.0,
+            stock: form.stock.0,
+        },
         &category,
     );
 
@@ -174,9 +443,14 @@ pub async fn get_product(
             Some(p) => {
                 let category: CategoryModel =
                     categories.find(p.get_category_id()).first(conn).unwrap();
-                HttpResponse::Ok()
-                    .status(StatusCode::OK)
-                    .json(serde_json::json!({"product": p.as_response(&category)}))
+                match product_responses(conn, &[(p, category)]) {
+                    Ok(mut r) => HttpResponse::Ok()
+                        .status(StatusCode::OK)
+                        .json(serde_json::json!({"product": r.remove(0)})),
+                    Err(_) => HttpResponse::InternalServerError()
+                        .status(StatusCode::INTERNAL_SERVER_ERROR)
+                        .json(serde_json::json!({"message": "Ops! something went wrong"})),
+                }
             }
             None => HttpResponse::NotFound()
                 .status(StatusCode::NOT_FOUND)
@@ -192,6 +466,7 @@ pub async fn get_product(
 pub async fn edit(
     product_id: web::Path<(String,)>,
     pool: web::Data<SqliteConnectionPool>,
+    notification_service: web::Data<Arc<dyn NotificationService>>,
     MultipartForm(form): MultipartForm<ProductCreate>,
 ) -> impl Responder {
     let prod_uuid: String = product_id.into_inner().0;
@@ -277,9 +552,18 @@ pub async fn edit(
         ))
         .get_result::<ProductModel>(conn)
     {
-        Ok(updated_product) => HttpResponse::Ok()
-            .status(StatusCode::OK)
-            .json(serde_json::json!({"product": updated_product.as_response(&category)})),
+        Ok(updated_product) => {
+            wishlist_service::alert_on_change(
+                &pool,
+                &notification_service,
+                &product,
+                &updated_product,
+            );
+
+            HttpResponse::Ok()
+                .status(StatusCode::OK)
+                .json(serde_json::json!({"product": updated_product.as_response(&category)}))
+        }
         Err(_) => HttpResponse::InternalServerError()
             .status(StatusCode::INTERNAL_SERVER_ERROR)
             .json(serde_json::json!({"message":"Ops! something went wrong!"})),
@@ -360,43 +644,67 @@ pub async fn update_product_stock(
     product_id: web::Path<(String,)>,
     new_stock: web::Query<ProductStockUpdate>,
     pool: web::Data<SqliteConnectionPool>,
+    notification_service: web::Data<Arc<dyn NotificationService>>,
 ) -> impl Responder {
-    let prod_uuid: String = product_id.into_inner().0;
-
-    //check if the product_id is valid uuid or not before trip to db
-    let prod_uuid: Uuid = match Uuid::parse_str(prod_uuid.as_str()) {
-        Ok(u) => u,
-        Err(_) => {
-            return HttpResponse::BadRequest()
-                .status(StatusCode::BAD_REQUEST)
-                .json(serde_json::json!({"message": "Invalid product id"}));
-        }
-    };
-
     use crate::schema::products::dsl::*;
 
     //get a pooled connection from db
     let conn = &mut get_conn(&pool);
 
+    let product: ProductModel = match find_product(conn, &product_id.into_inner().0) {
+        Ok(p) => p,
+        Err(res) => return res,
+    };
+
     //update the product's stock
-    match diesel::update(products)
-        .filter(uuid.eq(&prod_uuid.to_string()))
+    match diesel::update(&product)
         .set(stock.eq(new_stock.stock))
-        .execute(conn)
+        .returning(ProductModel::as_returning())
+        .get_result(conn)
     {
-        Ok(urc) if urc > 0 => HttpResponse::Ok().status(StatusCode::OK).finish(),
-        Ok(_) => HttpResponse::NotFound()
-            .status(StatusCode::NOT_FOUND)
-            .json(serde_json::json!({"message": "Product not found"})),
+        Ok(updated_product) => {
+            wishlist_service::alert_on_change(
+                &pool,
+                &notification_service,
+                &product,
+                &updated_product,
+            );
+            HttpResponse::Ok().status(StatusCode::OK).finish()
+        }
         Err(_) => HttpResponse::InternalServerError()
             .status(StatusCode::INTERNAL_SERVER_ERROR)
             .json(serde_json::json!({"message": "Ops! something went wrong."})),
     }
 }
 
+/// Archives the product instead of removing it, since past orders and
+/// invoices still point at it. Archived products drop out of the catalogue and
+/// can no longer be added to carts or ordered.
 #[delete("/{product_id}")]
-pub async fn delete(_product_id: web::Path<(String,)>) -> impl Responder {
-    HttpResponse::Ok().finish()
+pub async fn delete(
+    product_id: web::Path<(String,)>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    use crate::schema::products;
+
+    let conn = &mut get_conn(&pool);
+
+    let product = match find_product(conn, &product_id.into_inner().0) {
+        Ok(p) => p,
+        Err(res) => return res,
+    };
+
+    match diesel::update(&product)
+        .set(products::is_archived.eq(true))
+        .execute(conn)
+    {
+        Ok(_) => HttpResponse::NoContent()
+            .status(StatusCode::NO_CONTENT)
+            .finish(),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
 }
 
 #[post("/{prod_id}/images")]
@@ -590,7 +898,7 @@ pub async fn rate_product(
     };
     use crate::schema::product_ratings::dsl::*;
     use crate::schema::products::dsl::*;
-    use crate::schema::{products, users};
+    use crate::schema::{order_items, orders, products, users};
     let conn = &mut get_conn(&pool);
     if rating_json.rating < 1.0 || rating_json.rating > 5.0 {
         return HttpResponse::BadRequest()
@@ -636,11 +944,34 @@ pub async fn rate_product(
         }
     };
 
+    //only customers who have received the product may rate it
+    match orders::table
+        .inner_join(order_items::table)
+        .filter(orders::user_id.eq(user.get_id()))
+        .filter(order_items::product_id.eq(product.get_id()))
+        .filter(orders::status.eq(OrderStatus::Fulfilled.value()))
+        .count()
+        .get_result::<i64>(conn)
+    {
+        Ok(0) => {
+            return HttpResponse::Forbidden()
+                .status(StatusCode::FORBIDDEN)
+                .json(serde_json::json!({"message": "You can only rate products from your fulfilled orders"}));
+        }
+        Ok(_) => {}
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! something went wrong"}));
+        }
+    }
+
     let new_rating = NewProductRatingModel::new(
         &product,
         &user,
         rating_json.rating,
         rating_json.review.clone(),
+        true,
     );
 
     match diesel::insert_into(product_ratings)
@@ -651,12 +982,15 @@ pub async fn rate_product(
             rating.eq(rating_json.rating),
             review.eq(&rating_json.review),
             updated_at.eq(diesel::dsl::now),
+            verified_purchase.eq(true),
+            // an edited review goes back through moderation
+            moderation_status.eq(ModerationStatus::Pending.value()),
         ))
         .execute(conn)
     {
-        Ok(_) => {
-            HttpResponse::Ok().json(serde_json::json!({"message": "Rating saved successfully"}))
-        }
+        Ok(_) => HttpResponse::Ok().json(
+            serde_json::json!({"message": "Rating saved successfully and will be visible once approved"}),
+        ),
         Err(_) => HttpResponse::InternalServerError()
             .json(serde_json::json!({"message": "Ops! something went wrong while saving rating"})),
     }
@@ -665,6 +999,7 @@ pub async fn rate_product(
 #[get("/{prod_id}/ratings")]
 pub async fn get_product_ratings(
     prod_id: web::Path<String>,
+    params: web::Query<RatingListParams>,
     pool: web::Data<SqliteConnectionPool>,
 ) -> impl Responder {
     let prod_id: String = prod_id.into_inner();
@@ -679,6 +1014,9 @@ pub async fn get_product_ratings(
         }
     };
 
+    let page = params.page.unwrap_or(1).max(1);
+    let per_page = params.per_page.unwrap_or(10).clamp(1, 50);
+
     use crate::schema::products::dsl::*;
     use crate::schema::{product_ratings, products, users};
 
@@ -705,23 +1043,64 @@ pub async fn get_product_ratings(
         }
     };
 
-    match ProductRatingModel::belonging_to(&product)
+    let mut query = ProductRatingModel::belonging_to(&product)
         .inner_join(users::table)
+        .filter(product_ratings::moderation_status.eq(ModerationStatus::Approved.value()))
+        .into_boxed();
+
+    query = match params.sort.as_deref() {
+        None | Some("recent") => query.order(product_ratings::created_at.desc()),
+        Some("helpful") => query.order((
+            product_ratings::helpful_count.desc(),
+            product_ratings::created_at.desc(),
+        )),
+        Some(_) => {
+            return HttpResponse::BadRequest()
+                .status(StatusCode::BAD_REQUEST)
+                .json(serde_json::json!({"message": "Invalid sort. Valid values are: 'recent', 'helpful'"}))
+        }
+    };
+
+    let total: i64 = match ProductRatingModel::belonging_to(&product)
+        .filter(product_ratings::moderation_status.eq(ModerationStatus::Approved.value()))
+        .count()
+        .get_result(conn)
+    {
+        Ok(t) => t,
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! something went wrong"}));
+        }
+    };
+
+    let rows = match query
+        .limit(per_page)
+        .offset((page - 1) * per_page)
         .select((
-            product_ratings::uuid,
+            ProductRatingModel::as_select(),
             users::first_name,
             users::last_name,
-            product_ratings::rating,
-            product_ratings::review,
-            product_ratings::created_at,
-            product_ratings::updated_at,
         ))
-        .load::<ProductRating>(conn)
-        .optional()
+        .load::<(ProductRatingModel, String, String)>(conn)
     {
+        Ok(r) => r,
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! something went wrong"}));
+        }
+    };
+
+    match rating_responses(conn, rows) {
         Ok(r) => HttpResponse::Ok()
             .status(StatusCode::OK)
-            .json(serde_json::json!({"ratings": r})),
+            .json(serde_json::json!({
+                "ratings": r,
+                "page": page,
+                "perPage": per_page,
+                "total": total,
+            })),
         Err(_) => HttpResponse::InternalServerError()
             .status(StatusCode::INTERNAL_SERVER_ERROR)
             .json(serde_json::json!({"message": "Ops! something went wrong"})),
@@ -745,7 +1124,7 @@ pub async fn get_user_product_rating(
     }
 
     use crate::schema::product_ratings::dsl::*;
-    use crate::schema::{product_ratings, products, users};
+    use crate::schema::{products, users};
 
     let conn = &mut get_conn(&pool);
 
@@ -756,15 +1135,11 @@ pub async fn get_user_product_rating(
         .filter(products::uuid.eq(&prod_id))
         .filter(users::uuid.eq(&u_id))
         .select((
-            product_ratings::uuid,
+            ProductRatingModel::as_select(),
             users::first_name,
             users::last_name,
-            product_ratings::rating,
-            product_ratings::review,
-            product_ratings::created_at,
-            product_ratings::updated_at,
         ))
-        .first::<ProductRating>(conn)
+        .first::<(ProductRatingModel, String, String)>(conn)
         .optional()
     {
         Ok(Some(r)) => r,
@@ -778,5 +1153,9 @@ pub async fn get_user_product_rating(
         }
     };
 
-    HttpResponse::Ok().json(serde_json::json!({"rating": user_rating}))
+    match rating_responses(conn, vec![user_rating]) {
+        Ok(mut r) => HttpResponse::Ok().json(serde_json::json!({"rating": r.remove(0)})),
+        Err(_) => HttpResponse::InternalServerError()
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
 }
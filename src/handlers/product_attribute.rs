@@ -0,0 +1,85 @@
+use actix_web::{http::StatusCode, put, web, HttpResponse, Responder};
+use diesel::prelude::*;
+
+use crate::{
+    contracts::product_attribute::{ProductAttribute, ProductAttributesUpdate},
+    db::connection::{get_conn, SqliteConnectionPool},
+    handlers::product::find_product,
+    models::product_attribute::NewProductAttribute,
+};
+
+#[put("/{prod_id}/attributes")]
+pub async fn set(
+    prod_id: web::Path<String>,
+    attributes_json: web::Json<ProductAttributesUpdate>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    use crate::schema::product_attributes;
+
+    // names are matched case-insensitively by the product filter so they are
+    // stored lowercase, values are kept as entered since they are shown as is
+    let mut attributes: Vec<ProductAttribute> =
+        Vec::with_capacity(attributes_json.attributes.len());
+    for a in &attributes_json.attributes {
+        let attribute = ProductAttribute {
+            name: a.name.trim().to_lowercase(),
+            value: a.value.trim().to_owned(),
+        };
+
+        if attribute.name.is_empty() || attribute.value.is_empty() {
+            return HttpResponse::BadRequest()
+                .status(StatusCode::BAD_REQUEST)
+                .json(serde_json::json!({"message": "Attribute name and value are required"}));
+        }
+
+        // the filter syntax is name:value separated by commas
+        if attribute.name.contains([':', ',']) || attribute.value.contains(',') {
+            return HttpResponse::BadRequest()
+                .status(StatusCode::BAD_REQUEST)
+                .json(serde_json::json!({"message": "Attribute names cannot contain ':' or ',' and values cannot contain ','"}));
+        }
+
+        if attributes.iter().any(|x| x.name == attribute.name) {
+            return HttpResponse::BadRequest()
+                .status(StatusCode::BAD_REQUEST)
+                .json(serde_json::json!({"message": format!("Attribute {} is given more than once", attribute.name)}));
+        }
+
+        attributes.push(attribute);
+    }
+
+    let conn = &mut get_conn(&pool);
+
+    let product = match find_product(conn, &prod_id.into_inner()) {
+        Ok(p) => p,
+        Err(e) => return e,
+    };
+
+    let rows: Vec<NewProductAttribute> = attributes
+        .iter()
+        .map(|a| NewProductAttribute::new(&product, a.name.to_owned(), a.value.to_owned()))
+        .collect();
+
+    let result = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        diesel::delete(
+            product_attributes::table.filter(product_attributes::product_id.eq(product.get_id())),
+        )
+        .execute(conn)?;
+
+        diesel::insert_into(product_attributes::table)
+            .values(&rows)
+            .execute(conn)
+    });
+
+    match result {
+        Ok(_) => {
+            attributes.sort_by(|a, b| a.name.cmp(&b.name));
+            HttpResponse::Ok()
+                .status(StatusCode::OK)
+                .json(serde_json::json!({"attributes": attributes}))
+        }
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
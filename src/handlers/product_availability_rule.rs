@@ -0,0 +1,171 @@
+use actix_web::{delete, get, http::StatusCode, post, web, HttpResponse, Responder};
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    contracts::product_availability_rule::{
+        NewProductAvailabilityRule, ProductAvailability, ProductAvailabilityRule,
+    },
+    db::connection::{get_conn, SqliteConnectionPool},
+    handlers::product::find_product,
+    models::product_availability_rule::{
+        NewProductAvailabilityRule as NewProductAvailabilityRuleModel,
+        ProductAvailabilityRule as ProductAvailabilityRuleModel,
+    },
+    services::availability_service,
+};
+
+fn rule_response(rule: &ProductAvailabilityRuleModel) -> ProductAvailabilityRule {
+    ProductAvailabilityRule {
+        uuid: rule.get_uuid().to_owned(),
+        start_day: rule.get_start_day().map(str::to_owned),
+        end_day: rule.get_end_day().map(str::to_owned),
+        weekdays: availability_service::weekday_names(rule.get_weekday_mask()),
+    }
+}
+
+// MM-DD, checked against a leap year so 02-29 is accepted
+fn is_valid_day(day: &str) -> bool {
+    day.len() == 5 && NaiveDate::parse_from_str(&format!("2000-{}", day), "%Y-%m-%d").is_ok()
+}
+
+#[get("/{prod_id}/availability")]
+pub async fn get(
+    prod_id: web::Path<String>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    let conn = &mut get_conn(&pool);
+
+    let product = match find_product(conn, &prod_id.into_inner()) {
+        Ok(p) => p,
+        Err(e) => return e,
+    };
+
+    let rules: Vec<ProductAvailabilityRuleModel> =
+        match ProductAvailabilityRuleModel::belonging_to(&product)
+            .select(ProductAvailabilityRuleModel::as_select())
+            .load(conn)
+        {
+            Ok(r) => r,
+            Err(_) => {
+                return HttpResponse::InternalServerError()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .json(serde_json::json!({"message": "Ops! something went wrong"}))
+            }
+        };
+
+    let availability = availability_service::evaluate(
+        &rules.iter().collect::<Vec<_>>(),
+        availability_service::today(),
+    );
+
+    HttpResponse::Ok().status(StatusCode::OK).json(
+        serde_json::json!({"availability": ProductAvailability {
+            available: availability.available,
+            available_from: availability
+                .available_from
+                .map(|d| d.format("%Y-%m-%d").to_string()),
+            rules: rules.iter().map(rule_response).collect(),
+        }}),
+    )
+}
+
+#[post("/{prod_id}/availability-rules")]
+pub async fn create(
+    prod_id: web::Path<String>,
+    rule_json: web::Json<NewProductAvailabilityRule>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    use crate::schema::product_availability_rules;
+
+    let rule_json = rule_json.into_inner();
+
+    match (&rule_json.start_day, &rule_json.end_day) {
+        (Some(start), Some(end)) if is_valid_day(start) && is_valid_day(end) => {}
+        (None, None) => {}
+        _ => {
+            return HttpResponse::BadRequest()
+                .status(StatusCode::BAD_REQUEST)
+                .json(serde_json::json!({"message": "startDay and endDay must both be given in MM-DD format"}))
+        }
+    }
+
+    let weekday_mask = match &rule_json.weekdays {
+        None => 127,
+        Some(days) => match availability_service::weekday_mask(days) {
+            Some(m) if m > 0 => m,
+            _ => {
+                return HttpResponse::BadRequest()
+                    .status(StatusCode::BAD_REQUEST)
+                    .json(serde_json::json!({"message": "Weekdays must be one or more of mon, tue, wed, thu, fri, sat, sun"}))
+            }
+        },
+    };
+
+    let conn = &mut get_conn(&pool);
+
+    let product = match find_product(conn, &prod_id.into_inner()) {
+        Ok(p) => p,
+        Err(e) => return e,
+    };
+
+    let new_rule = NewProductAvailabilityRuleModel::new(
+        &product,
+        rule_json.start_day,
+        rule_json.end_day,
+        weekday_mask,
+    );
+
+    match diesel::insert_into(product_availability_rules::table)
+        .values(&new_rule)
+        .get_result::<ProductAvailabilityRuleModel>(conn)
+    {
+        Ok(r) => HttpResponse::Ok()
+            .status(StatusCode::OK)
+            .json(serde_json::json!({"availabilityRule": rule_response(&r)})),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+#[delete("/{prod_id}/availability-rules/{rule_id}")]
+pub async fn delete(
+    path: web::Path<(String, String)>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    let (prod_id, rule_id) = path.into_inner();
+
+    if Uuid::parse_str(&rule_id).is_err() {
+        return HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": "Invalid availability rule id"}));
+    }
+
+    use crate::schema::product_availability_rules;
+
+    let conn = &mut get_conn(&pool);
+
+    let product = match find_product(conn, &prod_id) {
+        Ok(p) => p,
+        Err(e) => return e,
+    };
+
+    match diesel::delete(
+        ProductAvailabilityRuleModel::belonging_to(&product)
+            .filter(product_availability_rules::uuid.eq(&rule_id)),
+    )
+    .execute(conn)
+    {
+        Ok(0) => HttpResponse::NotFound()
+            .status(StatusCode::NOT_FOUND)
+            .json(serde_json::json!({"message": "Availability rule not found"})),
+        Ok(_) => HttpResponse::NoContent()
+            .status(StatusCode::NO_CONTENT)
+            .finish(),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
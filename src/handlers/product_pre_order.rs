@@ -0,0 +1,173 @@
+use std::sync::Arc;
+
+use actix_web::{delete, http::StatusCode, post, put, web, HttpResponse, Responder};
+use chrono::NaiveDate;
+use diesel::prelude::*;
+
+use crate::{
+    contracts::product::{PreOrder, PreOrderSettings, StockReceipt},
+    db::connection::{get_conn, SqliteConnectionPool},
+    handlers::product::find_product,
+    models::product::Product,
+    services::{
+        availability_service, notification_service::NotificationService, pre_order_service,
+        wishlist_service,
+    },
+};
+
+/// Lists the product for pre-order until its stock is received. Changing the
+/// capacity never drops it below what customers already reserved.
+#[put("/{prod_id}/pre-order")]
+pub async fn set(
+    prod_id: web::Path<String>,
+    pre_order_json: web::Json<PreOrderSettings>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    use crate::schema::products;
+
+    let expected_on = match NaiveDate::parse_from_str(&pre_order_json.expected_on, "%Y-%m-%d") {
+        Ok(d) if d >= availability_service::today() => d,
+        Ok(_) => {
+            return HttpResponse::BadRequest()
+                .status(StatusCode::BAD_REQUEST)
+                .json(serde_json::json!({"message": "Expected date can not be in the past"}))
+        }
+        Err(_) => {
+            return HttpResponse::BadRequest()
+                .status(StatusCode::BAD_REQUEST)
+                .json(serde_json::json!({"message": "Invalid expected date. Expected format is YYYY-MM-DD"}))
+        }
+    };
+
+    let conn = &mut get_conn(&pool);
+
+    let product = match find_product(conn, &prod_id.into_inner()) {
+        Ok(p) => p,
+        Err(e) => return e,
+    };
+
+    if product.is_archived() {
+        return HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": "Archived products can not be pre-ordered"}));
+    }
+
+    if pre_order_json.capacity <= 0.0 || pre_order_json.capacity < product.get_preorder_reserved() {
+        return HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": format!(
+                "Capacity must be greater than 0 and at least the {} already pre-ordered",
+                product.get_preorder_reserved()
+            )}));
+    }
+
+    let expected_on = expected_on.format("%Y-%m-%d").to_string();
+
+    match diesel::update(&product)
+        .set((
+            products::is_preorder.eq(true),
+            products::preorder_available_on.eq(&expected_on),
+            products::preorder_capacity.eq(pre_order_json.capacity),
+        ))
+        .execute(conn)
+    {
+        Ok(_) => HttpResponse::Ok().status(StatusCode::OK).json(
+            serde_json::json!({"preOrder": PreOrder {
+                expected_on: Some(expected_on),
+                capacity: pre_order_json.capacity,
+                remaining: pre_order_json.capacity - product.get_preorder_reserved(),
+            }}),
+        ),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+#[delete("/{prod_id}/pre-order")]
+pub async fn delete(
+    prod_id: web::Path<String>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    use crate::schema::products;
+
+    let conn = &mut get_conn(&pool);
+
+    let product = match find_product(conn, &prod_id.into_inner()) {
+        Ok(p) => p,
+        Err(e) => return e,
+    };
+
+    if product.get_preorder_reserved() > 0.0 {
+        return HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": "Product has pre-orders waiting for stock. Receive the stock first"}));
+    }
+
+    match diesel::update(&product)
+        .set((
+            products::is_preorder.eq(false),
+            products::preorder_available_on.eq(None::<String>),
+            products::preorder_capacity.eq(0.0),
+        ))
+        .execute(conn)
+    {
+        Ok(_) => HttpResponse::NoContent()
+            .status(StatusCode::NO_CONTENT)
+            .finish(),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+/// Books harvested stock in. Waiting pre-orders are served first and the
+/// orders that are complete move from `Pre-Order` to `Processed`.
+#[post("/{prod_id}/receive-stock")]
+pub async fn receive_stock(
+    prod_id: web::Path<String>,
+    receipt_json: web::Json<StockReceipt>,
+    pool: web::Data<SqliteConnectionPool>,
+    notification_service: web::Data<Arc<dyn NotificationService>>,
+) -> impl Responder {
+    use crate::schema::products;
+
+    if receipt_json.quantity <= 0.0 {
+        return HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": "Quantity must be greater than 0"}));
+    }
+
+    let conn = &mut get_conn(&pool);
+
+    let product = match find_product(conn, &prod_id.into_inner()) {
+        Ok(p) => p,
+        Err(e) => return e,
+    };
+
+    match pre_order_service::receive_stock(conn, &product, receipt_json.quantity) {
+        Ok(released) => {
+            // whatever the waiting pre-orders did not take is back on sale
+            if let Ok(received) = products::table
+                .find(product.get_id())
+                .select(Product::as_select())
+                .first(conn)
+            {
+                wishlist_service::alert_on_change(
+                    &pool,
+                    &notification_service,
+                    &product,
+                    &received,
+                );
+            }
+
+            let released: Vec<&str> = released.iter().map(|o| o.get_uuid()).collect();
+            HttpResponse::Ok()
+                .status(StatusCode::OK)
+                .json(serde_json::json!({"releasedOrders": released}))
+        }
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
@@ -0,0 +1,145 @@
+use actix_web::{delete, get, http::StatusCode, post, web, HttpResponse, Responder};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    contracts::product_price_tier::{NewProductPriceTier, ProductPriceTier},
+    db::connection::{get_conn, SqliteConnectionPool},
+    handlers::product::find_product,
+    models::product_price_tier::{
+        NewProductPriceTier as NewProductPriceTierModel, ProductPriceTier as ProductPriceTierModel,
+    },
+};
+
+#[get("/{prod_id}/price-tiers")]
+pub async fn get(
+    prod_id: web::Path<String>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    use crate::schema::product_price_tiers;
+
+    let conn = &mut get_conn(&pool);
+
+    let product = match find_product(conn, &prod_id.into_inner()) {
+        Ok(p) => p,
+        Err(e) => return e,
+    };
+
+    match ProductPriceTierModel::belonging_to(&product)
+        .order(product_price_tiers::min_quantity.asc())
+        .select(ProductPriceTierModel::as_select())
+        .load(conn)
+    {
+        Ok(tiers) => {
+            let tiers: Vec<ProductPriceTier> = tiers
+                .iter()
+                .map(|t| ProductPriceTier {
+                    uuid: t.get_uuid().to_owned(),
+                    min_quantity: t.get_min_quantity(),
+                    price: t.get_price(),
+                })
+                .collect();
+
+            HttpResponse::Ok()
+                .status(StatusCode::OK)
+                .json(serde_json::json!({"priceTiers": tiers}))
+        }
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+#[post("/{prod_id}/price-tiers")]
+pub async fn create(
+    prod_id: web::Path<String>,
+    tier_json: web::Json<NewProductPriceTier>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    if tier_json.min_quantity <= 0.0 {
+        return HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": "Minimum quantity must be greater than 0"}));
+    }
+
+    if tier_json.price <= 0.0 {
+        return HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": "Tier price must be greater than 0"}));
+    }
+
+    use crate::schema::product_price_tiers;
+
+    let conn = &mut get_conn(&pool);
+
+    let product = match find_product(conn, &prod_id.into_inner()) {
+        Ok(p) => p,
+        Err(e) => return e,
+    };
+
+    let new_tier = NewProductPriceTierModel::new(&product, tier_json.min_quantity, tier_json.price);
+
+    // a product has at most one tier per minimum quantity, so posting the same
+    // minimum again just updates its price
+    match diesel::insert_into(product_price_tiers::table)
+        .values(&new_tier)
+        .on_conflict((
+            product_price_tiers::product_id,
+            product_price_tiers::min_quantity,
+        ))
+        .do_update()
+        .set(product_price_tiers::price.eq(tier_json.price))
+        .get_result::<ProductPriceTierModel>(conn)
+    {
+        Ok(t) => HttpResponse::Ok().status(StatusCode::OK).json(
+            serde_json::json!({"priceTier": ProductPriceTier {
+                uuid: t.get_uuid().to_owned(),
+                min_quantity: t.get_min_quantity(),
+                price: t.get_price(),
+            }}),
+        ),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+#[delete("/{prod_id}/price-tiers/{tier_id}")]
+pub async fn delete(
+    path: web::Path<(String, String)>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    let (prod_id, tier_id) = path.into_inner();
+
+    if Uuid::parse_str(&tier_id).is_err() {
+        return HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": "Invalid price tier id"}));
+    }
+
+    use crate::schema::product_price_tiers;
+
+    let conn = &mut get_conn(&pool);
+
+    let product = match find_product(conn, &prod_id) {
+        Ok(p) => p,
+        Err(e) => return e,
+    };
+
+    match diesel::delete(
+        ProductPriceTierModel::belonging_to(&product)
+            .filter(product_price_tiers::uuid.eq(&tier_id)),
+    )
+    .execute(conn)
+    {
+        Ok(0) => HttpResponse::NotFound()
+            .status(StatusCode::NOT_FOUND)
+            .json(serde_json::json!({"message": "Price tier not found"})),
+        Ok(_) => HttpResponse::NoContent()
+            .status(StatusCode::NO_CONTENT)
+            .finish(),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
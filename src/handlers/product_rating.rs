@@ -0,0 +1,593 @@
+use std::{collections::HashMap, str::FromStr};
+
+use actix_multipart::form::MultipartForm;
+use actix_web::{delete, get, http::StatusCode, patch, post, put, web, HttpResponse, Responder};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    base_types::{moderation_status::ModerationStatus, permission::Permission},
+    config::ApplicationConfiguration,
+    contracts::product_rating::{
+        AdminProductRating, ProductRating, RatingImage, RatingImageUpload, RatingModerationParams,
+        RatingModerationUpdate, RatingReply,
+    },
+    db::connection::{get_conn, SqliteConnectionPool},
+    middlewares::user_info::UserInfo,
+    models::{
+        product_rating::ProductRating as ProductRatingModel,
+        product_rating_image::{
+            NewProductRatingImage, ProductRatingImage as ProductRatingImageModel,
+        },
+        product_rating_vote::NewProductRatingVote,
+        user::User as UserModel,
+    },
+    policies::permission,
+};
+
+// keeps reviews light on storage and quick to load in the app
+const MAX_RATING_IMAGES: i64 = 3;
+
+/// Builds rating responses from `(rating, first_name, last_name)` rows,
+/// attaching the photos of each review.
+pub fn rating_responses(
+    conn: &mut SqliteConnection,
+    rows: Vec<(ProductRatingModel, String, String)>,
+) -> QueryResult<Vec<ProductRating>> {
+    use crate::schema::product_rating_images;
+
+    let ratings: Vec<&ProductRatingModel> = rows.iter().map(|(r, _, _)| r).collect();
+    let mut images: HashMap<i32, Vec<RatingImage>> = HashMap::new();
+    for img in ProductRatingImageModel::belonging_to(&ratings)
+        .order(product_rating_images::id.asc())
+        .select(ProductRatingImageModel::as_select())
+        .load(conn)?
+    {
+        images
+            .entry(img.get_rating_id())
+            .or_default()
+            .push(RatingImage {
+                uuid: img.get_uuid().to_owned(),
+                image_name: img.get_name().to_owned(),
+            });
+    }
+
+    Ok(rows
+        .into_iter()
+        .map(|(r, first_name, last_name)| ProductRating {
+            uuid: r.get_uuid().to_owned(),
+            first_name,
+            last_name,
+            rating: r.get_rating(),
+            review: r.get_review().map(str::to_owned),
+            created_at: r.get_created_at(),
+            updated_at: r.get_updated_at(),
+            verified_purchase: r.is_verified_purchase(),
+            moderation_status: r.get_moderation_status().to_owned(),
+            admin_reply: r.get_admin_reply().map(str::to_owned),
+            replied_at: r.get_replied_at(),
+            helpful_count: r.get_helpful_count(),
+            images: images.remove(&r.get_id()).unwrap_or_default(),
+        })
+        .collect())
+}
+
+fn find_rating(
+    conn: &mut SqliteConnection,
+    prod_id: &str,
+    rating_id: &str,
+) -> Result<ProductRatingModel, HttpResponse> {
+    use crate::schema::{product_ratings, products};
+
+    if Uuid::parse_str(prod_id).is_err() || Uuid::parse_str(rating_id).is_err() {
+        return Err(HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": "Invalid product or rating id"})));
+    }
+
+    match product_ratings::table
+        .inner_join(products::table)
+        .filter(products::uuid.eq(prod_id))
+        .filter(product_ratings::uuid.eq(rating_id))
+        .select(ProductRatingModel::as_select())
+        .first(conn)
+        .optional()
+    {
+        Ok(Some(r)) => Ok(r),
+        Ok(None) => Err(HttpResponse::NotFound()
+            .status(StatusCode::NOT_FOUND)
+            .json(serde_json::json!({"message": "Rating not found"}))),
+        Err(_) => Err(HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"}))),
+    }
+}
+
+pub fn find_user(
+    conn: &mut SqliteConnection,
+    user_info: &UserInfo,
+) -> Result<UserModel, HttpResponse> {
+    use crate::schema::users;
+
+    match users::table
+        .filter(users::uuid.eq(&user_info.user_id))
+        .select(UserModel::as_select())
+        .first(conn)
+        .optional()
+    {
+        Ok(Some(u)) => Ok(u),
+        Ok(None) => Err(HttpResponse::NotFound()
+            .status(StatusCode::NOT_FOUND)
+            .json(serde_json::json!({"message": "User not found"}))),
+        Err(_) => Err(HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"}))),
+    }
+}
+
+fn find_admin_rating(
+    conn: &mut SqliteConnection,
+    rating_id: &str,
+) -> QueryResult<Option<AdminProductRating>> {
+    use crate::schema::{product_ratings, products, users};
+
+    product_ratings::table
+        .inner_join(products::table)
+        .inner_join(users::table)
+        .filter(product_ratings::uuid.eq(rating_id))
+        .select((
+            product_ratings::uuid,
+            products::uuid,
+            products::name,
+            users::first_name,
+            users::last_name,
+            product_ratings::rating,
+            product_ratings::review,
+            product_ratings::created_at,
+            product_ratings::updated_at,
+            product_ratings::verified_purchase,
+            product_ratings::moderation_status,
+            product_ratings::admin_reply,
+            product_ratings::replied_at,
+        ))
+        .first::<AdminProductRating>(conn)
+        .optional()
+}
+
+#[get("")]
+pub async fn get_all(
+    params: web::Query<RatingModerationParams>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    use crate::schema::{product_ratings, products, users};
+
+    let mut query = product_ratings::table
+        .inner_join(products::table)
+        .inner_join(users::table)
+        .into_boxed();
+
+    if let Some(s) = &params.status {
+        match ModerationStatus::from_str(s) {
+            Ok(status) => {
+                query =
+                    query.filter(product_ratings::moderation_status.eq(status.value().to_owned()))
+            }
+            Err(e) => {
+                return HttpResponse::BadRequest()
+                    .status(StatusCode::BAD_REQUEST)
+                    .json(serde_json::json!({"message": e}))
+            }
+        }
+    }
+
+    let conn = &mut get_conn(&pool);
+
+    match query
+        .order(product_ratings::created_at.desc())
+        .select((
+            product_ratings::uuid,
+            products::uuid,
+            products::name,
+            users::first_name,
+            users::last_name,
+            product_ratings::rating,
+            product_ratings::review,
+            product_ratings::created_at,
+            product_ratings::updated_at,
+            product_ratings::verified_purchase,
+            product_ratings::moderation_status,
+            product_ratings::admin_reply,
+            product_ratings::replied_at,
+        ))
+        .load::<AdminProductRating>(conn)
+    {
+        Ok(r) => HttpResponse::Ok()
+            .status(StatusCode::OK)
+            .json(serde_json::json!({"ratings": r})),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+#[patch("/{rating_id}/moderation")]
+pub async fn moderate(
+    rating_id: web::Path<String>,
+    moderation_json: web::Json<RatingModerationUpdate>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    use crate::schema::product_ratings;
+
+    let rating_id = rating_id.into_inner();
+    if Uuid::parse_str(&rating_id).is_err() {
+        return HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": "Invalid rating id"}));
+    }
+
+    let status = match ModerationStatus::from_str(&moderation_json.status) {
+        Ok(s) => s,
+        Err(e) => {
+            return HttpResponse::BadRequest()
+                .status(StatusCode::BAD_REQUEST)
+                .json(serde_json::json!({"message": e}))
+        }
+    };
+
+    let conn = &mut get_conn(&pool);
+
+    match diesel::update(product_ratings::table.filter(product_ratings::uuid.eq(&rating_id)))
+        .set(product_ratings::moderation_status.eq(status.value()))
+        .execute(conn)
+    {
+        Ok(0) => {
+            return HttpResponse::NotFound()
+                .status(StatusCode::NOT_FOUND)
+                .json(serde_json::json!({"message": "Rating not found"}))
+        }
+        Ok(_) => {}
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! something went wrong"}))
+        }
+    }
+
+    match find_admin_rating(conn, &rating_id) {
+        Ok(Some(r)) => HttpResponse::Ok()
+            .status(StatusCode::OK)
+            .json(serde_json::json!({"rating": r})),
+        Ok(None) => HttpResponse::NotFound()
+            .status(StatusCode::NOT_FOUND)
+            .json(serde_json::json!({"message": "Rating not found"})),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+#[put("/{rating_id}/reply")]
+pub async fn reply(
+    rating_id: web::Path<String>,
+    reply_json: web::Json<RatingReply>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    use crate::schema::product_ratings;
+
+    let rating_id = rating_id.into_inner();
+    if Uuid::parse_str(&rating_id).is_err() {
+        return HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": "Invalid rating id"}));
+    }
+
+    // an empty reply removes the existing one
+    let reply_text = reply_json.reply.trim();
+    let (admin_reply, replied_at) = if reply_text.is_empty() {
+        (None, None)
+    } else {
+        (
+            Some(reply_text.to_owned()),
+            Some(chrono::Utc::now().naive_utc()),
+        )
+    };
+
+    let conn = &mut get_conn(&pool);
+
+    match diesel::update(product_ratings::table.filter(product_ratings::uuid.eq(&rating_id)))
+        .set((
+            product_ratings::admin_reply.eq(admin_reply),
+            product_ratings::replied_at.eq(replied_at),
+        ))
+        .execute(conn)
+    {
+        Ok(0) => {
+            return HttpResponse::NotFound()
+                .status(StatusCode::NOT_FOUND)
+                .json(serde_json::json!({"message": "Rating not found"}))
+        }
+        Ok(_) => {}
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! something went wrong"}))
+        }
+    }
+
+    match find_admin_rating(conn, &rating_id) {
+        Ok(Some(r)) => HttpResponse::Ok()
+            .status(StatusCode::OK)
+            .json(serde_json::json!({"rating": r})),
+        Ok(None) => HttpResponse::NotFound()
+            .status(StatusCode::NOT_FOUND)
+            .json(serde_json::json!({"message": "Rating not found"})),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+#[post("/{prod_id}/ratings/{rating_id}/images")]
+pub async fn upload_images(
+    path: web::Path<(String, String)>,
+    user_info: UserInfo,
+    pool: web::Data<SqliteConnectionPool>,
+    app_config: web::Data<ApplicationConfiguration>,
+    MultipartForm(form): MultipartForm<RatingImageUpload>,
+) -> impl Responder {
+    use crate::schema::{product_rating_images, product_ratings};
+
+    let (prod_id, rating_id) = path.into_inner();
+
+    if form.images.is_empty() {
+        return HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": "No images were uploaded"}));
+    }
+
+    let conn = &mut get_conn(&pool);
+
+    let rating = match find_rating(conn, &prod_id, &rating_id) {
+        Ok(r) => r,
+        Err(e) => return e,
+    };
+
+    let user = match find_user(conn, &user_info) {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
+
+    if rating.get_user_id() != user.get_id() {
+        return HttpResponse::Forbidden()
+            .status(StatusCode::FORBIDDEN)
+            .json(serde_json::json!({"message": "You can only add photos to your own review"}));
+    }
+
+    let existing: i64 = match ProductRatingImageModel::belonging_to(&rating)
+        .count()
+        .get_result(conn)
+    {
+        Ok(c) => c,
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! something went wrong"}))
+        }
+    };
+
+    if existing + form.images.len() as i64 > MAX_RATING_IMAGES {
+        return HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({
+                "message": format!("A review can have at most {} photos", MAX_RATING_IMAGES)
+            }));
+    }
+
+    // review photos live next to the product's extra images
+    let mut saved: Vec<String> = Vec::with_capacity(form.images.len());
+    for img in form.images {
+        let path = format!(
+            "{}review_{}.png",
+            app_config.product_extraimages_path,
+            Uuid::new_v4()
+        );
+
+        if std::fs::copy(img.file.path(), &path).is_err() {
+            for p in &saved {
+                let _ = std::fs::remove_file(p);
+            }
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(
+                    serde_json::json!({"message": "Ops! something went wrong while saving image"}),
+                );
+        }
+        saved.push(path);
+    }
+
+    // new photos have not been seen by a moderator yet
+    let result = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        for path in &saved {
+            diesel::insert_into(product_rating_images::table)
+                .values(&NewProductRatingImage::new(path, &rating))
+                .execute(conn)?;
+        }
+
+        diesel::update(&rating)
+            .set(product_ratings::moderation_status.eq(ModerationStatus::Pending.value()))
+            .execute(conn)?;
+
+        Ok(())
+    });
+
+    if result.is_err() {
+        for p in &saved {
+            let _ = std::fs::remove_file(p);
+        }
+        return HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong while saving image"}));
+    }
+
+    HttpResponse::Ok().json(serde_json::json!({
+        "message": "Photos uploaded and will be visible once the review is approved"
+    }))
+}
+
+#[delete("/{prod_id}/ratings/{rating_id}/images/{image_id}")]
+pub async fn delete_image(
+    path: web::Path<(String, String, String)>,
+    user_info: UserInfo,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    use crate::schema::product_rating_images;
+
+    let (prod_id, rating_id, image_id) = path.into_inner();
+
+    let conn = &mut get_conn(&pool);
+
+    let rating = match find_rating(conn, &prod_id, &rating_id) {
+        Ok(r) => r,
+        Err(e) => return e,
+    };
+
+    let user = match find_user(conn, &user_info) {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
+
+    let moderates = match permission::allows(conn, &user_info, Permission::RatingsModerate) {
+        Ok(m) => m,
+        Err(res) => return res,
+    };
+
+    if rating.get_user_id() != user.get_id() && !moderates {
+        return HttpResponse::Forbidden()
+            .status(StatusCode::FORBIDDEN)
+            .json(
+                serde_json::json!({"message": "You can only remove photos from your own review"}),
+            );
+    }
+
+    let image: ProductRatingImageModel = match ProductRatingImageModel::belonging_to(&rating)
+        .filter(product_rating_images::uuid.eq(&image_id))
+        .select(ProductRatingImageModel::as_select())
+        .first(conn)
+        .optional()
+    {
+        Ok(Some(i)) => i,
+        Ok(None) => {
+            return HttpResponse::NotFound()
+                .status(StatusCode::NOT_FOUND)
+                .json(serde_json::json!({"message": "Image not found"}))
+        }
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! something went wrong"}))
+        }
+    };
+
+    match diesel::delete(&image).execute(conn) {
+        Ok(_) => {
+            let _ = std::fs::remove_file(image.get_name());
+            HttpResponse::NoContent()
+                .status(StatusCode::NO_CONTENT)
+                .finish()
+        }
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+#[post("/{prod_id}/ratings/{rating_id}/helpful")]
+pub async fn mark_helpful(
+    path: web::Path<(String, String)>,
+    user_info: UserInfo,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    use crate::schema::product_rating_votes;
+
+    let (prod_id, rating_id) = path.into_inner();
+
+    let conn = &mut get_conn(&pool);
+
+    let rating = match find_rating(conn, &prod_id, &rating_id) {
+        Ok(r) => r,
+        Err(e) => return e,
+    };
+
+    if rating.get_moderation_status() != ModerationStatus::Approved.value() {
+        return HttpResponse::NotFound()
+            .status(StatusCode::NOT_FOUND)
+            .json(serde_json::json!({"message": "Rating not found"}));
+    }
+
+    let user = match find_user(conn, &user_info) {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
+
+    if rating.get_user_id() == user.get_id() {
+        return HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": "You cannot vote on your own review"}));
+    }
+
+    // voting twice is a no-op
+    match diesel::insert_into(product_rating_votes::table)
+        .values(&NewProductRatingVote::new(&rating, &user))
+        .on_conflict((
+            product_rating_votes::rating_id,
+            product_rating_votes::user_id,
+        ))
+        .do_nothing()
+        .execute(conn)
+    {
+        Ok(_) => HttpResponse::NoContent()
+            .status(StatusCode::NO_CONTENT)
+            .finish(),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+#[delete("/{prod_id}/ratings/{rating_id}/helpful")]
+pub async fn unmark_helpful(
+    path: web::Path<(String, String)>,
+    user_info: UserInfo,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    use crate::schema::product_rating_votes;
+
+    let (prod_id, rating_id) = path.into_inner();
+
+    let conn = &mut get_conn(&pool);
+
+    let rating = match find_rating(conn, &prod_id, &rating_id) {
+        Ok(r) => r,
+        Err(e) => return e,
+    };
+
+    let user = match find_user(conn, &user_info) {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
+
+    match diesel::delete(
+        product_rating_votes::table
+            .filter(product_rating_votes::rating_id.eq(rating.get_id()))
+            .filter(product_rating_votes::user_id.eq(user.get_id())),
+    )
+    .execute(conn)
+    {
+        Ok(_) => HttpResponse::NoContent()
+            .status(StatusCode::NO_CONTENT)
+            .finish(),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
@@ -0,0 +1,397 @@
+use actix_web::{delete, get, http::StatusCode, post, put, web, HttpResponse, Responder};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    base_types::permission::Permission as PermissionKind,
+    contracts::role::{Permission, Role, RoleCreate, RoleUpdate, UserRoleUpdate},
+    db::connection::{get_conn, SqliteConnectionPool},
+    handlers::product_rating::find_user,
+    middlewares::user_info::UserInfo,
+    models::{
+        role::{NewRole, Role as RoleModel},
+        user::User as UserModel,
+    },
+    policies::permission,
+    services::permission_service::{self, RoleError},
+};
+
+const ROLE_NAME_MAX_LENGTH: usize = 50;
+
+fn role_response(conn: &mut SqliteConnection, role: &RoleModel) -> QueryResult<Role> {
+    Ok(Role {
+        name: role.get_name().to_owned(),
+        description: role.get_description().map(str::to_owned),
+        is_system: role.is_system(),
+        permissions: permission_service::role_permissions(conn, role.get_id())?,
+    })
+}
+
+fn find_role(conn: &mut SqliteConnection, role_name: &str) -> Result<RoleModel, HttpResponse> {
+    use crate::schema::roles;
+
+    match roles::table
+        .filter(roles::name.eq(role_name))
+        .select(RoleModel::as_select())
+        .first(conn)
+        .optional()
+    {
+        Ok(Some(r)) => Ok(r),
+        Ok(None) => Err(HttpResponse::NotFound()
+            .status(StatusCode::NOT_FOUND)
+            .json(serde_json::json!({"message": "Role not found"}))),
+        Err(_) => Err(HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"}))),
+    }
+}
+
+// Non-admins may only hand out permissions their own role has, through a
+// role they edit or one they assign
+fn check_can_grant(
+    conn: &mut SqliteConnection,
+    caller: &UserModel,
+    names: &[String],
+) -> Result<(), HttpResponse> {
+    match permission_service::missing_permissions(conn, caller.get_user_type(), names) {
+        Ok(missing) if missing.is_empty() => Ok(()),
+        Ok(missing) => Err(HttpResponse::Forbidden()
+            .status(StatusCode::FORBIDDEN)
+            .json(serde_json::json!({
+                "message": format!(
+                    "You can't grant permissions you don't have: {}",
+                    missing.join(", ")
+                )
+            }))),
+        Err(_) => Err(HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"}))),
+    }
+}
+
+fn role_error_response(e: RoleError) -> HttpResponse {
+    match e {
+        RoleError::UnknownPermission(_) => HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": e.to_string()})),
+        RoleError::Database(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+#[get("")]
+pub async fn get_all(pool: web::Data<SqliteConnectionPool>) -> impl Responder {
+    use crate::schema::roles;
+
+    let conn = &mut get_conn(&pool);
+
+    let result = roles::table
+        .order(roles::name.asc())
+        .select(RoleModel::as_select())
+        .load(conn)
+        .and_then(|r| {
+            r.iter()
+                .map(|role| role_response(conn, role))
+                .collect::<QueryResult<Vec<Role>>>()
+        });
+
+    match result {
+        Ok(r) => HttpResponse::Ok()
+            .status(StatusCode::OK)
+            .json(serde_json::json!({"roles": r})),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+#[get("/permissions")]
+pub async fn get_permissions(pool: web::Data<SqliteConnectionPool>) -> impl Responder {
+    use crate::schema::permissions;
+
+    let conn = &mut get_conn(&pool);
+
+    match permissions::table
+        .order(permissions::name.asc())
+        .select((permissions::name, permissions::description))
+        .load::<Permission>(conn)
+    {
+        Ok(p) => HttpResponse::Ok()
+            .status(StatusCode::OK)
+            .json(serde_json::json!({"permissions": p})),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+#[post("")]
+pub async fn create(
+    user_info: UserInfo,
+    role_json: web::Json<RoleCreate>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    use crate::schema::roles;
+
+    let role_name = role_json.name.trim();
+    if role_name.is_empty() || role_name.chars().count() > ROLE_NAME_MAX_LENGTH {
+        return HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": format!("Role name is required and can be at most {} characters", ROLE_NAME_MAX_LENGTH)}));
+    }
+
+    let conn = &mut get_conn(&pool);
+
+    let caller = match find_user(conn, &user_info) {
+        Ok(u) => u,
+        Err(res) => return res,
+    };
+
+    if let Err(res) = check_can_grant(conn, &caller, &role_json.permissions) {
+        return res;
+    }
+
+    // names are compared case insensitively, see the roles migration
+    match roles::table
+        .filter(roles::name.eq(role_name))
+        .count()
+        .get_result::<i64>(conn)
+    {
+        Ok(0) => {}
+        Ok(_) => {
+            return HttpResponse::Conflict()
+                .status(StatusCode::CONFLICT)
+                .json(serde_json::json!({"message": "A role with this name already exists"}))
+        }
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! something went wrong"}))
+        }
+    }
+
+    let result = conn.transaction::<Role, RoleError, _>(|conn| {
+        let role = diesel::insert_into(roles::table)
+            .values(&NewRole::new(
+                role_name.to_owned(),
+                role_json.description.clone(),
+            ))
+            .returning(RoleModel::as_returning())
+            .get_result(conn)?;
+
+        permission_service::set_role_permissions(conn, role.get_id(), &role_json.permissions)?;
+
+        Ok(role_response(conn, &role)?)
+    });
+
+    match result {
+        Ok(r) => HttpResponse::Ok()
+            .status(StatusCode::OK)
+            .json(serde_json::json!({"role": r})),
+        Err(e) => role_error_response(e),
+    }
+}
+
+#[put("/{role_name}")]
+pub async fn edit(
+    user_info: UserInfo,
+    role_name: web::Path<String>,
+    role_json: web::Json<RoleUpdate>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    use crate::schema::roles;
+
+    let conn = &mut get_conn(&pool);
+
+    let role = match find_role(conn, &role_name.into_inner()) {
+        Ok(r) => r,
+        Err(res) => return res,
+    };
+
+    if role.is_system() {
+        return HttpResponse::Forbidden()
+            .status(StatusCode::FORBIDDEN)
+            .json(serde_json::json!({"message": "System roles can't be changed"}));
+    }
+
+    let caller = match find_user(conn, &user_info) {
+        Ok(u) => u,
+        Err(res) => return res,
+    };
+
+    if let Err(res) = check_can_grant(conn, &caller, &role_json.permissions) {
+        return res;
+    }
+
+    let result = conn.transaction::<Role, RoleError, _>(|conn| {
+        let role = diesel::update(&role)
+            .set(roles::description.eq(&role_json.description))
+            .returning(RoleModel::as_returning())
+            .get_result(conn)?;
+
+        permission_service::set_role_permissions(conn, role.get_id(), &role_json.permissions)?;
+
+        Ok(role_response(conn, &role)?)
+    });
+
+    match result {
+        Ok(r) => HttpResponse::Ok()
+            .status(StatusCode::OK)
+            .json(serde_json::json!({"role": r})),
+        Err(e) => role_error_response(e),
+    }
+}
+
+#[delete("/{role_name}")]
+pub async fn delete(
+    role_name: web::Path<String>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    use crate::schema::users;
+
+    let conn = &mut get_conn(&pool);
+
+    let role = match find_role(conn, &role_name.into_inner()) {
+        Ok(r) => r,
+        Err(res) => return res,
+    };
+
+    if role.is_system() {
+        return HttpResponse::Forbidden()
+            .status(StatusCode::FORBIDDEN)
+            .json(serde_json::json!({"message": "System roles can't be deleted"}));
+    }
+
+    // users.user_type holds the role name, a user must never be left with a
+    // role that no longer exists
+    match users::table
+        .filter(users::user_type.eq(role.get_name()))
+        .count()
+        .get_result::<i64>(conn)
+    {
+        Ok(0) => {}
+        Ok(n) => return HttpResponse::Conflict().status(StatusCode::CONFLICT).json(
+            serde_json::json!({"message": format!("The role is still assigned to {} user(s)", n)}),
+        ),
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! something went wrong"}))
+        }
+    }
+
+    // role_permissions rows go with it through ON DELETE CASCADE
+    match diesel::delete(&role).execute(conn) {
+        Ok(_) => HttpResponse::NoContent()
+            .status(StatusCode::NO_CONTENT)
+            .finish(),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+/// Takes effect on the user's next request, permissions are looked up from
+/// the role the user has now rather than the one in their access token. Only
+/// an admin may grant Admin or a role with permissions they don't hold, or
+/// change the role of another admin.
+#[put("/{user_id}/role")]
+pub async fn assign_user_role(
+    user_info: UserInfo,
+    user_id: web::Path<String>,
+    role_json: web::Json<UserRoleUpdate>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    use crate::schema::users;
+
+    let user_id = user_id.into_inner();
+    if Uuid::parse_str(&user_id).is_err() {
+        return HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": "Invalid user id"}));
+    }
+
+    // otherwise an admin could demote themselves and leave nobody to undo it
+    if user_id.eq_ignore_ascii_case(&user_info.user_id) {
+        return HttpResponse::Forbidden()
+            .status(StatusCode::FORBIDDEN)
+            .json(serde_json::json!({"message": "You can't change your own role"}));
+    }
+
+    let conn = &mut get_conn(&pool);
+
+    if let Err(res) = permission::authorize(conn, &user_info, PermissionKind::RolesManage) {
+        return res;
+    }
+
+    let caller = match find_user(conn, &user_info) {
+        Ok(u) => u,
+        Err(res) => return res,
+    };
+
+    let role = match find_role(conn, role_json.role.trim()) {
+        Ok(r) => r,
+        Err(res) => return res,
+    };
+
+    let user: UserModel = match users::table
+        .filter(users::uuid.eq(&user_id))
+        .select(UserModel::as_select())
+        .first(conn)
+        .optional()
+    {
+        Ok(Some(u)) => u,
+        Ok(None) => {
+            return HttpResponse::NotFound()
+                .status(StatusCode::NOT_FOUND)
+                .json(serde_json::json!({"message": "User not found"}))
+        }
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! something went wrong"}))
+        }
+    };
+
+    if !caller.is_admin() {
+        if user.is_admin() {
+            return HttpResponse::Forbidden()
+                .status(StatusCode::FORBIDDEN)
+                .json(
+                    serde_json::json!({"message": "Only an admin can change the role of an admin"}),
+                );
+        }
+
+        if role.get_name() == UserModel::USERTYPE_ADMIN {
+            return HttpResponse::Forbidden()
+                .status(StatusCode::FORBIDDEN)
+                .json(serde_json::json!({"message": "Only an admin can grant the Admin role"}));
+        }
+
+        let granted = match permission_service::role_permissions(conn, role.get_id()) {
+            Ok(p) => p,
+            Err(_) => {
+                return HttpResponse::InternalServerError()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .json(serde_json::json!({"message": "Ops! something went wrong"}))
+            }
+        };
+
+        if let Err(res) = check_can_grant(conn, &caller, &granted) {
+            return res;
+        }
+    }
+
+    match diesel::update(&user)
+        .set(users::user_type.eq(role.get_name()))
+        .execute(conn)
+    {
+        Ok(_) => HttpResponse::Ok()
+            .status(StatusCode::OK)
+            .json(serde_json::json!({"message": format!("Role changed to {}", role.get_name())})),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
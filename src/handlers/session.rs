@@ -0,0 +1,215 @@
+use actix_web::{delete, get, http::StatusCode, web, HttpResponse, Responder};
+use diesel::prelude::*;
+
+use crate::{
+    contracts::session::Session,
+    db::connection::{get_conn, SqliteConnectionPool},
+    handlers::product_rating::find_user,
+    middlewares::user_info::UserInfo,
+    models::{user::User as UserModel, user_session::UserSession},
+    services::{rate_limit_service::RateLimiter, refresh_token_service},
+    utils::uuid_validator::validate_uuid,
+};
+
+#[get("/sessions")]
+pub async fn get(user_info: UserInfo, pool: web::Data<SqliteConnectionPool>) -> impl Responder {
+    let conn = &mut get_conn(&pool);
+
+    let user = match find_user(conn, &user_info) {
+        Ok(u) => u,
+        Err(res) => return res,
+    };
+
+    sessions_response(conn, &user, Some(&user_info.session_id))
+}
+
+/// Signs out one of the caller's own devices, the current one included.
+#[delete("/sessions/{session_id}")]
+pub async fn revoke(
+    user_info: UserInfo,
+    path: web::Path<(String,)>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    let session_id = match validate_uuid(&path.into_inner().0) {
+        Ok(id) => id,
+        Err(res) => return res,
+    };
+
+    let conn = &mut get_conn(&pool);
+
+    let user = match find_user(conn, &user_info) {
+        Ok(u) => u,
+        Err(res) => return res,
+    };
+
+    revoke_session(conn, &user, &session_id)
+}
+
+#[get("/{user_id}/sessions")]
+pub async fn get_user_sessions(
+    path: web::Path<(String,)>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    let conn = &mut get_conn(&pool);
+
+    let user = match find_target_user(conn, &path.into_inner().0) {
+        Ok(u) => u,
+        Err(res) => return res,
+    };
+
+    sessions_response(conn, &user, None)
+}
+
+/// Signs the user out everywhere, e.g. when a staff member leaves.
+#[delete("/{user_id}/sessions")]
+pub async fn terminate_user_sessions(
+    path: web::Path<(String,)>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    let conn = &mut get_conn(&pool);
+
+    let user = match find_target_user(conn, &path.into_inner().0) {
+        Ok(u) => u,
+        Err(res) => return res,
+    };
+
+    match refresh_token_service::revoke_all(conn, &user) {
+        Ok(_) => HttpResponse::Ok()
+            .status(StatusCode::OK)
+            .json(serde_json::json!({"message": "All sessions terminated"})),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+#[delete("/{user_id}/sessions/{session_id}")]
+pub async fn terminate_user_session(
+    path: web::Path<(String, String)>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    let (user_id, session_id) = path.into_inner();
+
+    let session_id = match validate_uuid(&session_id) {
+        Ok(id) => id,
+        Err(res) => return res,
+    };
+
+    let conn = &mut get_conn(&pool);
+
+    let user = match find_target_user(conn, &user_id) {
+        Ok(u) => u,
+        Err(res) => return res,
+    };
+
+    revoke_session(conn, &user, &session_id)
+}
+
+/// Lifts a sign-in lockout before it runs out, e.g. after the owner called in.
+#[delete("/{user_id}/lockout")]
+pub async fn unlock_user(
+    path: web::Path<(String,)>,
+    pool: web::Data<SqliteConnectionPool>,
+    rate_limiter: web::Data<RateLimiter>,
+) -> impl Responder {
+    let conn = &mut get_conn(&pool);
+
+    let user = match find_target_user(conn, &path.into_inner().0) {
+        Ok(u) => u,
+        Err(res) => return res,
+    };
+
+    match rate_limiter.unlock(user.get_uuid()) {
+        Ok(_) => HttpResponse::Ok()
+            .status(StatusCode::OK)
+            .json(serde_json::json!({"message": "Account unlocked"})),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+fn find_target_user(conn: &mut SqliteConnection, user_id: &str) -> Result<UserModel, HttpResponse> {
+    use crate::schema::users;
+
+    let user_id = validate_uuid(user_id)?;
+
+    match users::table
+        .filter(users::uuid.eq(&user_id))
+        .select(UserModel::as_select())
+        .first(conn)
+        .optional()
+    {
+        Ok(Some(u)) => Ok(u),
+        Ok(None) => Err(HttpResponse::NotFound()
+            .status(StatusCode::NOT_FOUND)
+            .json(serde_json::json!({"message": "User not found"}))),
+        Err(_) => Err(HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"}))),
+    }
+}
+
+fn sessions_response(
+    conn: &mut SqliteConnection,
+    user: &UserModel,
+    current_session: Option<&str>,
+) -> HttpResponse {
+    match refresh_token_service::active_sessions(conn, user) {
+        Ok(rows) => {
+            let sessions: Vec<Session> = rows
+                .iter()
+                .map(|s| Session {
+                    uuid: s.get_uuid().to_owned(),
+                    device_name: s.get_device_name().map(|d| d.to_owned()),
+                    ip_address: s.get_ip_address().map(|ip| ip.to_owned()),
+                    user_agent: s.get_user_agent().map(|ua| ua.to_owned()),
+                    created_at: s.get_created_at(),
+                    last_used_at: s.get_last_used_at(),
+                    expires_on: s.get_expires_on().to_owned(),
+                    current: current_session == Some(s.get_uuid()),
+                })
+                .collect();
+
+            HttpResponse::Ok()
+                .status(StatusCode::OK)
+                .json(serde_json::json!({"sessions": sessions}))
+        }
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+fn revoke_session(conn: &mut SqliteConnection, user: &UserModel, session_id: &str) -> HttpResponse {
+    use crate::schema::user_sessions;
+
+    let session = match user_sessions::table
+        .filter(user_sessions::uuid.eq(session_id))
+        .filter(user_sessions::user_id.eq(user.get_id()))
+        .select(UserSession::as_select())
+        .first(conn)
+        .optional()
+    {
+        Ok(Some(s)) => s,
+        Ok(None) => {
+            return HttpResponse::NotFound()
+                .status(StatusCode::NOT_FOUND)
+                .json(serde_json::json!({"message": "Session not found"}))
+        }
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! something went wrong"}))
+        }
+    };
+
+    match refresh_token_service::revoke_family(conn, session.get_uuid()) {
+        Ok(_) => HttpResponse::Ok()
+            .status(StatusCode::OK)
+            .json(serde_json::json!({"message": "Session terminated"})),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
@@ -0,0 +1,73 @@
+use actix_web::{get, http::StatusCode, put, web, HttpResponse, Responder};
+
+use crate::{
+    contracts::setting::SecuritySettings,
+    db::connection::{get_conn, SqliteConnectionPool},
+    handlers::product_rating::find_user,
+    middlewares::user_info::UserInfo,
+    services::{setting_service, two_factor_service},
+};
+
+#[get("/security")]
+pub async fn get_security(pool: web::Data<SqliteConnectionPool>) -> impl Responder {
+    let conn = &mut get_conn(&pool);
+
+    match setting_service::get_bool(conn, setting_service::REQUIRE_ADMIN_TWO_FACTOR) {
+        Ok(required) => HttpResponse::Ok()
+            .status(StatusCode::OK)
+            .json(SecuritySettings {
+                require_admin_two_factor: required,
+            }),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+/// Making two-factor authentication mandatory keeps admins without it out of
+/// the admin endpoints from their next sign in until they set it up.
+#[put("/security")]
+pub async fn set_security(
+    user_info: UserInfo,
+    payload: web::Json<SecuritySettings>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    let conn = &mut get_conn(&pool);
+
+    let user = match find_user(conn, &user_info) {
+        Ok(u) => u,
+        Err(res) => return res,
+    };
+
+    // otherwise the admin turning it on locks themselves out
+    if payload.require_admin_two_factor {
+        match two_factor_service::is_enabled(conn, &user) {
+            Ok(true) => {}
+            Ok(false) => {
+                return HttpResponse::BadRequest()
+                    .status(StatusCode::BAD_REQUEST)
+                    .json(serde_json::json!({"message": "Set up two-factor authentication on your own account first"}));
+            }
+            Err(_) => {
+                return HttpResponse::InternalServerError()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .json(serde_json::json!({"message": "Ops! something went wrong"}));
+            }
+        }
+    }
+
+    match setting_service::set_bool(
+        conn,
+        setting_service::REQUIRE_ADMIN_TWO_FACTOR,
+        payload.require_admin_two_factor,
+    ) {
+        Ok(_) => HttpResponse::Ok()
+            .status(StatusCode::OK)
+            .json(SecuritySettings {
+                require_admin_two_factor: payload.require_admin_two_factor,
+            }),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
@@ -2,11 +2,12 @@ use actix_web::{get, http::StatusCode, patch, web, HttpResponse, Responder};
 use diesel::prelude::*;
 
 use crate::{
-    base_types::shipment_status::ShipmentStatus,
+    base_types::{permission::Permission, shipment_status::ShipmentStatus},
     contracts::shipment::{AssingShipment, Shipment},
     db::connection::{get_conn, SqliteConnectionPool},
     middlewares::user_info::UserInfo,
     models::{shipment::Shipment as ShipmentModel, user::User},
+    policies::permission,
     utils::uuid_validator,
 };
 
@@ -16,11 +17,16 @@ pub async fn get(pool: web::Data<SqliteConnectionPool>, user: UserInfo) -> impl
 
     println!("User id is {}", user.user_id);
 
-    assert!(user.roles.contains("Admin"));
     assert_eq!(ShipmentStatus::Pending.value(), "Pending");
 
     let conn = &mut get_conn(&pool);
 
+    // delivery staff only see pending shipments that are theirs or unassigned
+    let sees_all = match permission::allows(conn, &user, Permission::ShipmentsManage) {
+        Ok(a) => a,
+        Err(res) => return res,
+    };
+
     let mut shipments_query = shipments::table
         .inner_join(orders::table.on(shipments::order_id.eq(orders::id)))
         .left_join(users::table.on(shipments::assigned_to.eq(users::id.nullable())))
@@ -28,7 +34,7 @@ pub async fn get(pool: web::Data<SqliteConnectionPool>, user: UserInfo) -> impl
         .into_boxed(); //some runtime performance cost, if we want to remove that we have to create
                        //query in if and else condition.
 
-    if !user.roles.contains("Admin") {
+    if !sees_all {
         shipments_query = shipments_query
             .filter(
                 users::uuid
@@ -64,6 +70,7 @@ pub async fn get(pool: web::Data<SqliteConnectionPool>, user: UserInfo) -> impl
 
 #[patch("assign")]
 pub async fn assing_user_to_shipment(
+    user_info: UserInfo,
     payload: web::Data<AssingShipment>,
     pool: web::Data<SqliteConnectionPool>,
 ) -> impl Responder {
@@ -83,6 +90,10 @@ pub async fn assing_user_to_shipment(
 
     let conn = &mut get_conn(&pool);
 
+    if let Err(res) = permission::authorize(conn, &user_info, Permission::ShipmentsManage) {
+        return res;
+    }
+
     let user: User = match users
         .filter(users::uuid.eq(&u_id))
         .select(User::as_select())
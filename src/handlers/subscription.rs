@@ -0,0 +1,513 @@
+use std::str::FromStr;
+
+use actix_web::{delete, get, http::StatusCode, post, put, web, HttpResponse, Responder};
+use chrono::NaiveDate;
+use diesel::prelude::*;
+
+use crate::{
+    base_types::{
+        payment_method::PaymentMethod, subscription_frequency::SubscriptionFrequency,
+        subscription_status::SubscriptionStatus,
+    },
+    contracts::subscription::{SubscriptionCreate, SubscriptionItemCreate},
+    db::connection::{get_conn, SqliteConnectionPool},
+    handlers::{product::find_product, product_rating::find_user},
+    middlewares::user_info::UserInfo,
+    models::{
+        product::Product as ProductModel,
+        subscription::{NewSubscription, Subscription as SubscriptionModel},
+        user::User as UserModel,
+    },
+    policies::ownership::{self, Resource},
+    services::{availability_service, cart_service, subscription_service},
+    utils::uuid_validator,
+};
+
+// the validated parts of a create or edit request
+struct SubscriptionSettings {
+    frequency: SubscriptionFrequency,
+    delivery_slot: String,
+    delivery_location: String,
+    payment_method: PaymentMethod,
+    next_delivery_on: NaiveDate,
+    items: Vec<(ProductModel, f64)>,
+}
+
+fn validate_basket(
+    conn: &mut SqliteConnection,
+    items: &[SubscriptionItemCreate],
+) -> Result<Vec<(ProductModel, f64)>, HttpResponse> {
+    if items.is_empty() {
+        return Err(HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(
+                serde_json::json!({"message": "Subscription must contain at least one product"}),
+            ));
+    }
+
+    let mut basket: Vec<(ProductModel, f64)> = vec![];
+    for item in items {
+        let product = find_product(conn, &item.product_id)?;
+
+        if product.is_archived() {
+            return Err(HttpResponse::BadRequest()
+                .status(StatusCode::BAD_REQUEST)
+                .json(serde_json::json!({"message": format!(
+                    "{} is no longer sold",
+                    product.get_name()
+                )})));
+        }
+
+        if item.quantity <= 0.0
+            || !cart_service::is_quantity_step(item.quantity, product.get_unit_change())
+        {
+            return Err(HttpResponse::BadRequest()
+                .status(StatusCode::BAD_REQUEST)
+                .json(serde_json::json!({"message": format!(
+                    "{} is sold in steps of {} {}",
+                    product.get_name(),
+                    product.get_unit_change(),
+                    product.get_unit()
+                )})));
+        }
+
+        if basket.iter().any(|(p, _)| p.get_id() == product.get_id()) {
+            return Err(HttpResponse::BadRequest()
+                .status(StatusCode::BAD_REQUEST)
+                .json(serde_json::json!({"message": format!(
+                    "{} is in the subscription more than once",
+                    product.get_name()
+                )})));
+        }
+
+        basket.push((product, item.quantity));
+    }
+
+    Ok(basket)
+}
+
+fn validate_settings(
+    conn: &mut SqliteConnection,
+    user: &UserModel,
+    subscription_json: &SubscriptionCreate,
+) -> Result<SubscriptionSettings, HttpResponse> {
+    let frequency = SubscriptionFrequency::from_str(&subscription_json.frequency).map_err(|e| {
+        HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": e}))
+    })?;
+
+    if subscription_json.delivery_slot.trim().is_empty() {
+        return Err(HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": "Delivery slot is required"})));
+    }
+
+    let payment_method = match &subscription_json.payment_method {
+        Some(pm) => PaymentMethod::from_str(pm).map_err(|e| {
+            HttpResponse::BadRequest()
+                .status(StatusCode::BAD_REQUEST)
+                .json(serde_json::json!({"message": e}))
+        })?,
+        None => PaymentMethod::Cash,
+    };
+
+    let delivery_location = match (&subscription_json.delivery_location, user.get_location()) {
+        (Some(location), _) if !location.trim().is_empty() => location.trim().to_owned(),
+        (_, Some(location)) => location.to_owned(),
+        _ => {
+            return Err(HttpResponse::BadRequest().status(StatusCode::BAD_REQUEST).json(serde_json::json!({"message": "User's location is missing. Please update location"})))
+        }
+    };
+
+    let next_delivery_on = match subscription_service::parse_date(&subscription_json.start_on) {
+        Some(d) if d > availability_service::today() => d,
+        Some(_) => {
+            return Err(HttpResponse::BadRequest()
+                .status(StatusCode::BAD_REQUEST)
+                .json(serde_json::json!({"message": "First delivery must be after today"})))
+        }
+        None => return Err(HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(
+                serde_json::json!({"message": "Invalid start date. Expected format is YYYY-MM-DD"}),
+            )),
+    };
+
+    let items = validate_basket(conn, &subscription_json.items)?;
+
+    Ok(SubscriptionSettings {
+        frequency,
+        delivery_slot: subscription_json.delivery_slot.trim().to_owned(),
+        delivery_location,
+        payment_method,
+        next_delivery_on,
+        items,
+    })
+}
+
+fn find_subscription(
+    conn: &mut SqliteConnection,
+    user_info: &UserInfo,
+    subscription_id: &str,
+) -> Result<SubscriptionModel, HttpResponse> {
+    use crate::schema::subscriptions;
+
+    let subscription_id = uuid_validator::validate_uuid(subscription_id)?;
+
+    ownership::authorize(conn, user_info, Resource::Subscription(&subscription_id))?;
+
+    match subscriptions::table
+        .filter(subscriptions::uuid.eq(&subscription_id))
+        .select(SubscriptionModel::as_select())
+        .first(conn)
+        .optional()
+    {
+        Ok(Some(s)) => Ok(s),
+        Ok(None) => Err(HttpResponse::NotFound()
+            .status(StatusCode::NOT_FOUND)
+            .json(serde_json::json!({"message": "Subscription not found"}))),
+        Err(_) => Err(HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"}))),
+    }
+}
+
+// reloads the subscription so the response shows what was saved
+fn respond_with(conn: &mut SqliteConnection, subscription_id: &str) -> HttpResponse {
+    use crate::schema::subscriptions;
+
+    let subscription = subscriptions::table
+        .filter(subscriptions::uuid.eq(subscription_id))
+        .select(SubscriptionModel::as_select())
+        .first(conn)
+        .and_then(|s| subscription_service::subscription_response(conn, &s));
+
+    match subscription {
+        Ok(s) => HttpResponse::Ok()
+            .status(StatusCode::OK)
+            .json(serde_json::json!({"subscription": s})),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+#[get("")]
+pub async fn get(user_info: UserInfo, pool: web::Data<SqliteConnectionPool>) -> impl Responder {
+    use crate::schema::subscriptions;
+
+    let conn = &mut get_conn(&pool);
+
+    let user = match find_user(conn, &user_info) {
+        Ok(u) => u,
+        Err(res) => return res,
+    };
+
+    let subscriptions: Vec<SubscriptionModel> = match subscriptions::table
+        .filter(subscriptions::user_id.eq(user.get_id()))
+        .filter(subscriptions::status.ne(SubscriptionStatus::Cancelled.value()))
+        .order(subscriptions::created_at.desc())
+        .select(SubscriptionModel::as_select())
+        .load(conn)
+    {
+        Ok(s) => s,
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! something went wrong"}))
+        }
+    };
+
+    let mut response = vec![];
+    for subscription in &subscriptions {
+        match subscription_service::subscription_response(conn, subscription) {
+            Ok(s) => response.push(s),
+            Err(_) => {
+                return HttpResponse::InternalServerError()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .json(serde_json::json!({"message": "Ops! something went wrong"}))
+            }
+        }
+    }
+
+    HttpResponse::Ok()
+        .status(StatusCode::OK)
+        .json(serde_json::json!({"subscriptions": response}))
+}
+
+#[post("")]
+pub async fn create(
+    user_info: UserInfo,
+    subscription_json: web::Json<SubscriptionCreate>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    use crate::schema::subscriptions;
+
+    let conn = &mut get_conn(&pool);
+
+    let user = match find_user(conn, &user_info) {
+        Ok(u) => u,
+        Err(res) => return res,
+    };
+
+    let settings = match validate_settings(conn, &user, &subscription_json) {
+        Ok(s) => s,
+        Err(res) => return res,
+    };
+
+    let new_subscription = NewSubscription::new(
+        &user,
+        &settings.frequency,
+        &settings.delivery_slot,
+        &settings.delivery_location,
+        &settings.payment_method,
+        settings.next_delivery_on,
+    );
+
+    match conn.transaction::<_, diesel::result::Error, _>(|con| {
+        let subscription: SubscriptionModel = diesel::insert_into(subscriptions::table)
+            .values(&new_subscription)
+            .returning(SubscriptionModel::as_returning())
+            .get_result(con)?;
+
+        subscription_service::replace_items(con, &subscription, &settings.items)?;
+
+        Ok(subscription)
+    }) {
+        Ok(subscription) => respond_with(conn, subscription.get_uuid()),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+/// Replaces the basket and delivery details. `startOn` becomes the next
+/// delivery.
+#[put("/{subscription_id}")]
+pub async fn edit(
+    user_info: UserInfo,
+    subscription_id: web::Path<(String,)>,
+    subscription_json: web::Json<SubscriptionCreate>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    use crate::schema::{subscriptions, users};
+
+    let conn = &mut get_conn(&pool);
+
+    let subscription = match find_subscription(conn, &user_info, &subscription_id.into_inner().0) {
+        Ok(s) => s,
+        Err(res) => return res,
+    };
+
+    if subscription.get_status() == SubscriptionStatus::Cancelled.value() {
+        return HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": "Subscription is cancelled"}));
+    }
+
+    // settings fall back to the subscriber's details, not the caller's
+    let owner: UserModel = match users::table
+        .inner_join(subscriptions::table)
+        .filter(subscriptions::id.eq(subscription.get_id()))
+        .select(UserModel::as_select())
+        .first(conn)
+    {
+        Ok(u) => u,
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! something went wrong"}))
+        }
+    };
+
+    let settings = match validate_settings(conn, &owner, &subscription_json) {
+        Ok(s) => s,
+        Err(res) => return res,
+    };
+
+    match conn.transaction::<_, diesel::result::Error, _>(|con| {
+        diesel::update(&subscription)
+            .set((
+                subscriptions::frequency.eq(settings.frequency.value()),
+                subscriptions::delivery_slot.eq(&settings.delivery_slot),
+                subscriptions::delivery_location.eq(&settings.delivery_location),
+                subscriptions::payment_method.eq(settings.payment_method.value()),
+                subscriptions::next_delivery_on.eq(settings
+                    .next_delivery_on
+                    .format(subscription_service::DATE_FORMAT)
+                    .to_string()),
+            ))
+            .execute(con)?;
+
+        subscription_service::replace_items(con, &subscription, &settings.items)
+    }) {
+        Ok(_) => respond_with(conn, subscription.get_uuid()),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+/// Stops generating orders until the subscription is resumed.
+#[post("/{subscription_id}/pause")]
+pub async fn pause(
+    user_info: UserInfo,
+    subscription_id: web::Path<(String,)>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    use crate::schema::subscriptions;
+
+    let conn = &mut get_conn(&pool);
+
+    let subscription = match find_subscription(conn, &user_info, &subscription_id.into_inner().0) {
+        Ok(s) => s,
+        Err(res) => return res,
+    };
+
+    if subscription.get_status() != SubscriptionStatus::Active.value() {
+        return HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": "Only active subscriptions can be paused"}));
+    }
+
+    match diesel::update(&subscription)
+        .set(subscriptions::status.eq(SubscriptionStatus::Paused.value()))
+        .execute(conn)
+    {
+        Ok(_) => respond_with(conn, subscription.get_uuid()),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+/// Picks the subscription up again from the first delivery after today that
+/// fits its schedule.
+#[post("/{subscription_id}/resume")]
+pub async fn resume(
+    user_info: UserInfo,
+    subscription_id: web::Path<(String,)>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    use crate::schema::subscriptions;
+
+    let conn = &mut get_conn(&pool);
+
+    let subscription = match find_subscription(conn, &user_info, &subscription_id.into_inner().0) {
+        Ok(s) => s,
+        Err(res) => return res,
+    };
+
+    if subscription.get_status() != SubscriptionStatus::Paused.value() {
+        return HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": "Only paused subscriptions can be resumed"}));
+    }
+
+    let (frequency, next_delivery_on) = match (
+        SubscriptionFrequency::from_str(subscription.get_frequency()),
+        subscription_service::parse_date(subscription.get_next_delivery_on()),
+    ) {
+        (Ok(f), Some(d)) => (f, d),
+        _ => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! something went wrong"}))
+        }
+    };
+    let tomorrow = availability_service::today() + chrono::Duration::days(1);
+    let next_delivery_on =
+        subscription_service::roll_forward(&frequency, next_delivery_on, tomorrow);
+
+    match diesel::update(&subscription)
+        .set((
+            subscriptions::status.eq(SubscriptionStatus::Active.value()),
+            subscriptions::next_delivery_on.eq(next_delivery_on
+                .format(subscription_service::DATE_FORMAT)
+                .to_string()),
+        ))
+        .execute(conn)
+    {
+        Ok(_) => respond_with(conn, subscription.get_uuid()),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+/// Skips the next delivery. Orders already placed for it are left alone.
+#[post("/{subscription_id}/skip")]
+pub async fn skip(
+    user_info: UserInfo,
+    subscription_id: web::Path<(String,)>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    use crate::schema::subscriptions;
+
+    let conn = &mut get_conn(&pool);
+
+    let subscription = match find_subscription(conn, &user_info, &subscription_id.into_inner().0) {
+        Ok(s) => s,
+        Err(res) => return res,
+    };
+
+    if subscription.get_status() == SubscriptionStatus::Cancelled.value() {
+        return HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": "Subscription is cancelled"}));
+    }
+
+    let (frequency, next_delivery_on) = match (
+        SubscriptionFrequency::from_str(subscription.get_frequency()),
+        subscription_service::parse_date(subscription.get_next_delivery_on()),
+    ) {
+        (Ok(f), Some(d)) => (f, d),
+        _ => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! something went wrong"}))
+        }
+    };
+
+    match diesel::update(&subscription)
+        .set(
+            subscriptions::next_delivery_on.eq(frequency
+                .next_after(next_delivery_on)
+                .format(subscription_service::DATE_FORMAT)
+                .to_string()),
+        )
+        .execute(conn)
+    {
+        Ok(_) => respond_with(conn, subscription.get_uuid()),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+#[delete("/{subscription_id}")]
+pub async fn cancel(
+    user_info: UserInfo,
+    subscription_id: web::Path<(String,)>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    use crate::schema::subscriptions;
+
+    let conn = &mut get_conn(&pool);
+
+    let subscription = match find_subscription(conn, &user_info, &subscription_id.into_inner().0) {
+        Ok(s) => s,
+        Err(res) => return res,
+    };
+
+    match diesel::update(&subscription)
+        .set(subscriptions::status.eq(SubscriptionStatus::Cancelled.value()))
+        .execute(conn)
+    {
+        Ok(_) => HttpResponse::NoContent().finish(),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
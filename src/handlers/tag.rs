@@ -0,0 +1,196 @@
+use actix_web::{delete, get, http::StatusCode, post, put, web, HttpResponse, Responder};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    contracts::{
+        tag::{ProductTagsUpdate, Tag, TagCreate},
+        ResponseWrapper,
+    },
+    db::connection::{get_conn, SqliteConnectionPool},
+    handlers::product::find_product,
+    models::{
+        product_tag::NewProductTag,
+        tag::{NewTag, Tag as TagModel},
+    },
+    utils::slug,
+};
+
+fn tag_response(tag: &TagModel) -> Tag {
+    Tag {
+        uuid: tag.get_uuid().to_owned(),
+        name: tag.get_name().to_owned(),
+        slug: tag.get_slug().to_owned(),
+    }
+}
+
+#[get("")]
+pub async fn get(pool: web::Data<SqliteConnectionPool>) -> impl Responder {
+    use crate::schema::tags;
+
+    let conn = &mut get_conn(&pool);
+
+    match tags::table
+        .order(tags::name.asc())
+        .select(TagModel::as_select())
+        .load(conn)
+    {
+        Ok(t) => {
+            let t: Vec<Tag> = t.iter().map(tag_response).collect();
+            HttpResponse::Ok()
+                .status(StatusCode::OK)
+                .json(serde_json::json!({"tags": t}))
+        }
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+#[post("")]
+pub async fn create(
+    tag_json: web::Json<TagCreate>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    use crate::schema::tags;
+
+    let tag_name = tag_json.name.trim();
+    if tag_name.is_empty() {
+        return HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": "Tag name is required"}));
+    }
+
+    // the slug is what customers filter with, so unlike categories we never
+    // make one up when the name has no usable characters
+    let tag_slug = slug::slugify(tag_json.slug.as_deref().unwrap_or(tag_name));
+    if tag_slug.is_empty() {
+        return HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": "Please provide a slug for this tag"}));
+    }
+
+    let conn = &mut get_conn(&pool);
+
+    match tags::table
+        .filter(tags::slug.eq(&tag_slug))
+        .count()
+        .get_result::<i64>(conn)
+    {
+        Ok(0) => {}
+        Ok(_) => {
+            return HttpResponse::Conflict()
+                .status(StatusCode::CONFLICT)
+                .json(serde_json::json!({"message": "A tag with this slug already exists"}))
+        }
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! something went wrong"}))
+        }
+    }
+
+    match diesel::insert_into(tags::table)
+        .values(&NewTag::new(tag_name.to_owned(), tag_slug))
+        .get_result::<TagModel>(conn)
+    {
+        Ok(t) => HttpResponse::Ok()
+            .status(StatusCode::OK)
+            .json(serde_json::json!({"tag": tag_response(&t)})),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+#[delete("/{tag_id}")]
+pub async fn delete(
+    tag_id: web::Path<String>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    use crate::schema::tags;
+
+    let tag_id = tag_id.into_inner();
+    if Uuid::parse_str(&tag_id).is_err() {
+        return HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": "Invalid tag id"}));
+    }
+
+    let conn = &mut get_conn(&pool);
+
+    // product_tags rows go with it through ON DELETE CASCADE
+    match diesel::delete(tags::table.filter(tags::uuid.eq(&tag_id))).execute(conn) {
+        Ok(0) => HttpResponse::NotFound()
+            .status(StatusCode::NOT_FOUND)
+            .json(serde_json::json!({"message": "Tag not found"})),
+        Ok(_) => HttpResponse::NoContent()
+            .status(StatusCode::NO_CONTENT)
+            .finish(),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+#[put("/{prod_id}/tags")]
+pub async fn set_product_tags(
+    prod_id: web::Path<String>,
+    tags_json: web::Json<ProductTagsUpdate>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    use crate::schema::{product_tags, tags};
+
+    let conn = &mut get_conn(&pool);
+
+    let product = match find_product(conn, &prod_id.into_inner()) {
+        Ok(p) => p,
+        Err(e) => return e,
+    };
+
+    let result = conn.transaction::<ResponseWrapper, diesel::result::Error, _>(|conn| {
+        let new_tags: Vec<TagModel> = tags::table
+            .filter(tags::uuid.eq_any(&tags_json.tag_ids))
+            .order(tags::name.asc())
+            .select(TagModel::as_select())
+            .load(conn)?;
+
+        let mut requested = tags_json.tag_ids.clone();
+        requested.sort();
+        requested.dedup();
+        if new_tags.len() != requested.len() {
+            return Ok(ResponseWrapper {
+                success: false,
+                response: HttpResponse::BadRequest()
+                    .status(StatusCode::BAD_REQUEST)
+                    .json(serde_json::json!({"message": "One or more tags could not be found"})),
+            });
+        }
+
+        diesel::delete(product_tags::table.filter(product_tags::product_id.eq(product.get_id())))
+            .execute(conn)?;
+
+        let rows: Vec<NewProductTag> = new_tags
+            .iter()
+            .map(|t| NewProductTag::new(&product, t))
+            .collect();
+        diesel::insert_into(product_tags::table)
+            .values(&rows)
+            .execute(conn)?;
+
+        let t: Vec<Tag> = new_tags.iter().map(tag_response).collect();
+        Ok(ResponseWrapper {
+            success: true,
+            response: HttpResponse::Ok()
+                .status(StatusCode::OK)
+                .json(serde_json::json!({"tags": t})),
+        })
+    });
+
+    match result {
+        Ok(r) => r.response,
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
@@ -0,0 +1,267 @@
+use std::sync::Arc;
+
+use actix_web::{delete, get, http::StatusCode, post, web, HttpRequest, HttpResponse, Responder};
+use diesel::prelude::*;
+
+use crate::{
+    contracts::two_factor::{RecoveryCodes, TwoFactorCode, TwoFactorSetup, TwoFactorStatus},
+    db::connection::{get_conn, SqliteConnectionPool},
+    handlers::{
+        auth::{client_ip, count_failure, throttled_response},
+        product_rating::find_user,
+    },
+    middlewares::user_info::UserInfo,
+    models::user::User as UserModel,
+    services::{
+        email_service::EmailService,
+        rate_limit_service::{RateLimitAction, RateLimiter},
+        setting_service,
+        two_factor_service::{self, TwoFactorError},
+    },
+};
+
+#[get("/two-factor")]
+pub async fn get(user_info: UserInfo, pool: web::Data<SqliteConnectionPool>) -> impl Responder {
+    let conn = &mut get_conn(&pool);
+
+    let user = match find_user(conn, &user_info) {
+        Ok(u) => u,
+        Err(res) => return res,
+    };
+
+    let status = (|| {
+        Ok::<_, diesel::result::Error>(TwoFactorStatus {
+            enabled: two_factor_service::is_enabled(conn, &user)?,
+            required: user.is_admin()
+                && setting_service::get_bool(conn, setting_service::REQUIRE_ADMIN_TWO_FACTOR)?,
+            recovery_codes_remaining: two_factor_service::remaining_recovery_codes(conn, &user)?,
+        })
+    })();
+
+    match status {
+        Ok(s) => HttpResponse::Ok().status(StatusCode::OK).json(s),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+/// Starts setup, the secret has to be confirmed with a code from the app
+/// before sign in asks for it.
+#[post("/two-factor/setup")]
+pub async fn setup(user_info: UserInfo, pool: web::Data<SqliteConnectionPool>) -> impl Responder {
+    let conn = &mut get_conn(&pool);
+
+    let user = match find_user(conn, &user_info) {
+        Ok(u) => u,
+        Err(res) => return res,
+    };
+
+    if user.is_customer() {
+        return HttpResponse::Forbidden()
+            .status(StatusCode::FORBIDDEN)
+            .json(serde_json::json!({"message": "Two-factor authentication is only available to staff accounts"}));
+    }
+
+    match two_factor_service::begin_setup(conn, &user) {
+        Ok(enrolment) => HttpResponse::Ok()
+            .status(StatusCode::OK)
+            .json(TwoFactorSetup {
+                secret: enrolment.secret,
+                otpauth_uri: enrolment.otpauth_uri,
+                qr_code_svg: enrolment.qr_code_svg,
+            }),
+        Err(TwoFactorError::AlreadyEnabled) => HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": TwoFactorError::AlreadyEnabled.to_string()})),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+/// Turns two-factor authentication on. Tokens issued before still carry the
+/// pending setup, so an admin who had to set it up signs in again.
+#[post("/two-factor/enable")]
+pub async fn enable(
+    req: HttpRequest,
+    user_info: UserInfo,
+    payload: web::Json<TwoFactorCode>,
+    pool: web::Data<SqliteConnectionPool>,
+    rate_limiter: web::Data<RateLimiter>,
+    email_service: web::Data<Arc<dyn EmailService>>,
+) -> impl Responder {
+    let conn = &mut get_conn(&pool);
+
+    let user = match find_user(conn, &user_info) {
+        Ok(u) => u,
+        Err(res) => return res,
+    };
+
+    let ip = client_ip(&req);
+    if let Err(t) = rate_limiter.check(&RateLimitAction::OtpVerify, &ip, Some(user.get_uuid())) {
+        return throttled_response(&t);
+    }
+
+    match two_factor_service::confirm_setup(conn, &user, payload.code.trim()) {
+        Ok(codes) => {
+            rate_limiter.record_success(&RateLimitAction::OtpVerify, user.get_uuid());
+            HttpResponse::Ok()
+                .status(StatusCode::OK)
+                .json(RecoveryCodes {
+                    recovery_codes: codes,
+                })
+        }
+        Err(TwoFactorError::InvalidCode) => {
+            count_failure(
+                &rate_limiter,
+                &email_service,
+                RateLimitAction::OtpVerify,
+                &ip,
+                &user,
+            );
+            HttpResponse::BadRequest()
+                .status(StatusCode::BAD_REQUEST)
+                .json(serde_json::json!({"message": TwoFactorError::InvalidCode.to_string()}))
+        }
+        Err(e @ (TwoFactorError::NotSetUp | TwoFactorError::AlreadyEnabled)) => {
+            HttpResponse::BadRequest()
+                .status(StatusCode::BAD_REQUEST)
+                .json(serde_json::json!({"message": e.to_string()}))
+        }
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+#[post("/two-factor/recovery-codes")]
+pub async fn regenerate_recovery_codes(
+    req: HttpRequest,
+    user_info: UserInfo,
+    payload: web::Json<TwoFactorCode>,
+    pool: web::Data<SqliteConnectionPool>,
+    rate_limiter: web::Data<RateLimiter>,
+    email_service: web::Data<Arc<dyn EmailService>>,
+) -> impl Responder {
+    let conn = &mut get_conn(&pool);
+
+    let user = match find_user(conn, &user_info) {
+        Ok(u) => u,
+        Err(res) => return res,
+    };
+
+    if let Err(res) = check_code(
+        &req,
+        conn,
+        &rate_limiter,
+        &email_service,
+        &user,
+        &payload.code,
+    ) {
+        return res;
+    }
+
+    match two_factor_service::regenerate_recovery_codes(conn, &user) {
+        Ok(codes) => HttpResponse::Ok()
+            .status(StatusCode::OK)
+            .json(RecoveryCodes {
+                recovery_codes: codes,
+            }),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+#[delete("/two-factor")]
+pub async fn disable(
+    req: HttpRequest,
+    user_info: UserInfo,
+    payload: web::Json<TwoFactorCode>,
+    pool: web::Data<SqliteConnectionPool>,
+    rate_limiter: web::Data<RateLimiter>,
+    email_service: web::Data<Arc<dyn EmailService>>,
+) -> impl Responder {
+    let conn = &mut get_conn(&pool);
+
+    let user = match find_user(conn, &user_info) {
+        Ok(u) => u,
+        Err(res) => return res,
+    };
+
+    match setting_service::get_bool(conn, setting_service::REQUIRE_ADMIN_TWO_FACTOR) {
+        Ok(true) if user.is_admin() => {
+            return HttpResponse::Forbidden()
+                .status(StatusCode::FORBIDDEN)
+                .json(serde_json::json!({"message": "Two-factor authentication is mandatory for admins"}));
+        }
+        Ok(_) => {}
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! something went wrong"}));
+        }
+    }
+
+    if let Err(res) = check_code(
+        &req,
+        conn,
+        &rate_limiter,
+        &email_service,
+        &user,
+        &payload.code,
+    ) {
+        return res;
+    }
+
+    match two_factor_service::disable(conn, &user) {
+        Ok(_) => HttpResponse::Ok()
+            .status(StatusCode::OK)
+            .json(serde_json::json!({"message": "Two-factor authentication disabled"})),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+// changes to the second factor need a current code, a stolen access token
+// alone isn't enough to turn it off
+fn check_code(
+    req: &HttpRequest,
+    conn: &mut SqliteConnection,
+    rate_limiter: &RateLimiter,
+    email_service: &Arc<dyn EmailService>,
+    user: &UserModel,
+    code: &str,
+) -> Result<(), HttpResponse> {
+    let ip = client_ip(req);
+    rate_limiter
+        .check(&RateLimitAction::OtpVerify, &ip, Some(user.get_uuid()))
+        .map_err(|t| throttled_response(&t))?;
+
+    match two_factor_service::verify(conn, user, code) {
+        Ok(()) => {
+            rate_limiter.record_success(&RateLimitAction::OtpVerify, user.get_uuid());
+            Ok(())
+        }
+        Err(TwoFactorError::InvalidCode) => {
+            count_failure(
+                rate_limiter,
+                email_service,
+                RateLimitAction::OtpVerify,
+                &ip,
+                user,
+            );
+            Err(HttpResponse::BadRequest()
+                .status(StatusCode::BAD_REQUEST)
+                .json(serde_json::json!({"message": "Invalid authentication code"})))
+        }
+        Err(TwoFactorError::NotSetUp) => Err(HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": TwoFactorError::NotSetUp.to_string()}))),
+        Err(_) => Err(HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"}))),
+    }
+}
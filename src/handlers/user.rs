@@ -1,12 +1,34 @@
-use actix_web::{delete, get, http::StatusCode, post, put, web, HttpResponse, Responder};
+use std::sync::Arc;
+
+use actix_web::{
+    delete, get, http::StatusCode, post, put, web, HttpRequest, HttpResponse, Responder,
+};
 use diesel::prelude::*;
+use log::error;
 use uuid::Uuid;
 
 use crate::{
-    base_types::{email::Email, phone_number::PhoneNumber},
-    contracts::user::{User, UserCreate, UserPendingShipments},
+    base_types::{
+        email::Email, otp_channel::OtpChannel, otp_purpose::OtpPurpose, phone_number::PhoneNumber,
+    },
+    config::ApplicationConfiguration,
+    contracts::{
+        auth::PhoneOtpRequest,
+        user::{User, UserCreate, UserPendingShipments, UserUpdate},
+    },
     db::connection::{get_conn, SqliteConnectionPool},
+    handlers::auth::{client_ip, throttled_response},
+    middlewares::user_info::UserInfo,
     models::user::{NewUser, User as UserModel},
+    policies::ownership::{self, Resource},
+    services::{
+        cart_service,
+        email_service::EmailService,
+        email_verification_service,
+        opt_service::{OtpError, OtpService},
+        rate_limit_service::{RateLimitAction, RateLimiter},
+        sms_service::SmsService,
+    },
 };
 
 #[get("")]
@@ -28,10 +50,27 @@ pub async fn get(pool: web::Data<SqliteConnectionPool>) -> impl Responder {
     }
 }
 
+// Location validation
+//Location should in the below format address,city,state,country
+pub fn is_valid_location(location: &Option<String>) -> bool {
+    match location {
+        Some(loc) => {
+            if loc.matches(',').count() != 4 {
+                false
+            } else {
+                loc.split(',').all(|part| !part.trim().is_empty())
+            }
+        }
+        None => false,
+    }
+}
+
 #[post("")]
 pub async fn create(
     user: web::Json<UserCreate>,
     pool: web::Data<SqliteConnectionPool>,
+    app_config: web::Data<ApplicationConfiguration>,
+    email_service: web::Data<Arc<dyn EmailService>>,
 ) -> impl Responder {
     use crate::schema::users::dsl::*;
 
@@ -44,20 +83,7 @@ pub async fn create(
         }
     };
 
-    // Location validation
-    //Location should in the below format address,city,state,country
-    let is_valid_location = match &user.location {
-        Some(loc) => {
-            if loc.matches(',').count() != 4 {
-                false
-            } else {
-                loc.split(',').all(|part| !part.trim().is_empty())
-            }
-        }
-        None => false,
-    };
-
-    if !is_valid_location {
+    if !is_valid_location(&user.location) {
         return HttpResponse::BadRequest().json(serde_json::json!({
             "message": "Invalid location format. Expected: 'zip code, street, city, state, country'"
         }));
@@ -106,6 +132,16 @@ pub async fn create(
                 .get_result::<UserModel>(conn)
             {
                 Ok(c) => {
+                    if let Some(guest_token) = &user.guest_token {
+                        if let Err(e) = cart_service::merge_guest_cart(conn, guest_token, &c) {
+                            error!(
+                                "Failed to merge guest cart into user {}: {:?}",
+                                c.get_uuid(),
+                                e
+                            );
+                        }
+                    }
+
                     let user_created: User = User {
                         first_name: c.get_first_name().to_owned(),
                         last_name: c.get_last_name().to_owned(),
@@ -116,6 +152,13 @@ pub async fn create(
                         location: c.get_location().map(|s| s.to_owned()),
                         nearest_landmark: c.get_nearest_landmark().map(|s| s.to_owned()),
                     };
+
+                    email_verification_service::spawn_verification_email(
+                        email_service.get_ref().clone(),
+                        app_config.get_ref().clone(),
+                        c,
+                    );
+
                     HttpResponse::Ok().status(StatusCode::OK).json(user_created)
                 }
                 Err(e) => HttpResponse::InternalServerError()
@@ -132,6 +175,7 @@ pub async fn create(
 #[get("/{user_id}")]
 pub async fn get_user(
     user_id: web::Path<(String,)>,
+    user_info: UserInfo,
     pool: web::Data<SqliteConnectionPool>,
 ) -> impl Responder {
     let uid: String = user_id.into_inner().0;
@@ -150,6 +194,10 @@ pub async fn get_user(
     //get a pooled connection from db
     let conn = &mut get_conn(&pool);
 
+    if let Err(res) = ownership::authorize(conn, &user_info, Resource::User(&uid.to_string())) {
+        return res;
+    }
+
     match users
         .filter(uuid.eq(uid.to_string()))
         .select(User::as_select())
@@ -171,6 +219,7 @@ pub async fn get_user(
 #[get("/phone-number/{phone_num}")]
 pub async fn get_user_from_phone_number(
     phone_num: web::Path<(String,)>,
+    user_info: UserInfo,
     pool: web::Data<SqliteConnectionPool>,
 ) -> impl Responder {
     let phone_num_string: String = phone_num.into_inner().0;
@@ -196,7 +245,10 @@ pub async fn get_user_from_phone_number(
         .optional()
     {
         Ok(user) => match user {
-            Some(u) => HttpResponse::Ok().status(StatusCode::OK).json(u),
+            Some(u) => match ownership::authorize(conn, &user_info, Resource::User(&u.uuid)) {
+                Ok(()) => HttpResponse::Ok().status(StatusCode::OK).json(u),
+                Err(res) => res,
+            },
             None => HttpResponse::NotFound()
                 .status(StatusCode::NOT_FOUND)
                 .json(serde_json::json!({"message": "User not found"})),
@@ -210,6 +262,7 @@ pub async fn get_user_from_phone_number(
 #[get("/email/{email_str}")]
 pub async fn get_user_from_email(
     email_str: web::Path<(String,)>,
+    user_info: UserInfo,
     pool: web::Data<SqliteConnectionPool>,
 ) -> impl Responder {
     let email_str: String = email_str.into_inner().0;
@@ -233,7 +286,10 @@ pub async fn get_user_from_email(
         .optional()
     {
         Ok(user) => match user {
-            Some(u) => HttpResponse::Ok().status(StatusCode::OK).json(u),
+            Some(u) => match ownership::authorize(conn, &user_info, Resource::User(&u.uuid)) {
+                Ok(()) => HttpResponse::Ok().status(StatusCode::OK).json(u),
+                Err(res) => res,
+            },
             None => HttpResponse::NotFound()
                 .status(StatusCode::NOT_FOUND)
                 .json(serde_json::json!({"message": "User not found"})),
@@ -278,28 +334,108 @@ pub async fn get_staff_users(pool: web::Data<SqliteConnectionPool>) -> impl Resp
     }
 }
 
-#[put("/{user_id}")]
-pub async fn edit(
+/// Sends a code to the number the user wants to switch to. The number is only
+/// changed on edit once that code comes back.
+#[post("/{user_id}/phone-number/request-otp")]
+pub async fn request_phone_change_otp(
     user_id: web::Path<(String,)>,
-    user_update: web::Json<User>,
+    user_info: UserInfo,
+    payload: web::Json<PhoneOtpRequest>,
     pool: web::Data<SqliteConnectionPool>,
+    sms_service: web::Data<Arc<dyn SmsService>>,
 ) -> impl Responder {
-    let uid: String = user_id.into_inner().0;
-    //check if the user_id is valid uuid or not before trip to db
-    let uid: Uuid = match Uuid::parse_str(uid.as_str()) {
-        Ok(u) => u,
-        Err(_) => {
+    let phone = match PhoneNumber::from_str(payload.phone_number.to_owned()) {
+        Ok(p) => p,
+        Err(e) => {
             return HttpResponse::BadRequest()
                 .status(StatusCode::BAD_REQUEST)
-                .json(serde_json::json!({"message": "Invalid user id"}));
+                .json(serde_json::json!({"message": e}));
         }
     };
+    let local_number = phone.get_local_number();
 
-    //check if the phone number is valid or not
-    if let Err(e) = PhoneNumber::from_str(user_update.phone_number.to_owned()) {
+    let conn = &mut get_conn(&pool);
+
+    let user = match find_user_for_edit(conn, &user_info, &user_id.into_inner().0) {
+        Ok(u) => u,
+        Err(res) => return res,
+    };
+
+    if let Err(res) = check_phone_available(conn, &user, &phone) {
+        return res;
+    }
+
+    if phone
+        .get_variants()
+        .iter()
+        .any(|v| v == user.get_phone_number())
+    {
         return HttpResponse::BadRequest()
             .status(StatusCode::BAD_REQUEST)
-            .json(serde_json::json!({"message": e}));
+            .json(serde_json::json!({"message": "This is already your phone number"}));
+    }
+
+    let otp_service = OtpService::new(&pool);
+    match otp_service.has_valid_otp(&OtpPurpose::PhoneChange, &local_number) {
+        Ok(true) => {
+            return HttpResponse::TooManyRequests()
+                .json("An OTP has already been sent. Please wait before requesting a new one.");
+        }
+        Err(_) => {
+            return HttpResponse::InternalServerError().json("Database error");
+        }
+        _ => {} // Continue
+    }
+
+    let otp_expiry_minutes = OtpPurpose::PhoneChange.expiry_minutes();
+    let sms_service = sms_service.into_inner();
+    let owner_id = user.get_id();
+    tokio::spawn(async move {
+        let otp_record = match otp_service.create_otp(
+            &OtpPurpose::PhoneChange,
+            &OtpChannel::Sms,
+            &local_number,
+            Some(owner_id),
+        ) {
+            Ok(otp) => otp,
+            Err(e) => {
+                error!("Failed to create OTP for phone {}: {:?}", local_number, e);
+                return;
+            }
+        };
+
+        let message = format!(
+            "{} is your code to use this number on Haatbazar. It expires in {} minutes.",
+            otp_record.otp_code, otp_expiry_minutes
+        );
+        if let Err(e) = sms_service.send_sms(&local_number, &message).await {
+            error!("Failed to send OTP SMS to {}: {:?}", local_number, e);
+        }
+    });
+
+    HttpResponse::Ok().json(serde_json::json!({
+        "message": "OTP sent to the new phone number",
+        "expiresInMinutes": otp_expiry_minutes
+    }))
+}
+
+#[put("/{user_id}")]
+pub async fn edit(
+    req: HttpRequest,
+    user_id: web::Path<(String,)>,
+    user_info: UserInfo,
+    user_update: web::Json<UserUpdate>,
+    pool: web::Data<SqliteConnectionPool>,
+    rate_limiter: web::Data<RateLimiter>,
+) -> impl Responder {
+    //check if the phone number is valid or not
+    let phone = match PhoneNumber::from_str(user_update.phone_number.to_owned()) {
+        Ok(p) => p,
+        Err(e) => {
+            return HttpResponse::BadRequest()
+                .status(StatusCode::BAD_REQUEST)
+                .json(serde_json::json!({"message": e}));
+        }
     };
 
     use crate::schema::users::dsl::*;
@@ -307,49 +443,69 @@ pub async fn edit(
     //get a pooled connection from db
     let conn = &mut get_conn(&pool);
 
+    let user = match find_user_for_edit(conn, &user_info, &user_id.into_inner().0) {
+        Ok(u) => u,
+        Err(res) => return res,
+    };
+
     //check if the new phone number is already used or not
-    let user_from_phone = match users
-        .filter(phone_number.eq(user_update.phone_number.to_string()))
-        .select(UserModel::as_select())
-        .first(conn)
-        .optional()
+    if let Err(res) = check_phone_available(conn, &user, &phone) {
+        return res;
+    }
+
+    // a new number is only taken once the code sent to it comes back, otherwise
+    // anyone able to edit the profile could sign in as the user by phone
+    if !phone
+        .get_variants()
+        .iter()
+        .any(|v| v == user.get_phone_number())
     {
-        Ok(cu) => cu.unwrap_or_default(),
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .status(StatusCode::INTERNAL_SERVER_ERROR)
-                .json(serde_json::json!({"message": format!("Internal server error: {}", e)}));
+        let code = match &user_update.otp_code {
+            Some(code) if code.len() == 6 => code,
+            _ => {
+                return HttpResponse::BadRequest()
+                    .status(StatusCode::BAD_REQUEST)
+                    .json(serde_json::json!({"message": "The code sent to the new phone number is required"}));
+            }
+        };
+
+        let ip = client_ip(&req);
+        if let Err(t) = rate_limiter.check(&RateLimitAction::OtpVerify, &ip, Some(user.get_uuid()))
+        {
+            return throttled_response(&t);
         }
-    };
 
-    let user: UserModel = match users
-        .filter(uuid.eq(uid.to_string()))
-        .select(UserModel::as_select())
-        .first(conn)
-        .optional()
-    {
-        Ok(cu) => match cu {
-            Some(c) => {
-                if c == user_from_phone {
-                    c
-                } else {
-                    return HttpResponse::Conflict()
-                        .status(StatusCode::CONFLICT)
-                        .json(serde_json::json!({"message": "Phone number already used"}));
-                }
+        let otp_service = OtpService::new(pool.get_ref());
+        match otp_service.consume_otp(&OtpPurpose::PhoneChange, &phone.get_local_number(), code) {
+            Ok(true) => rate_limiter.record_success(&RateLimitAction::OtpVerify, user.get_uuid()),
+            Ok(false) => {
+                rate_limiter.record_failure(
+                    &RateLimitAction::OtpVerify,
+                    &ip,
+                    Some(user.get_uuid()),
+                );
+                return HttpResponse::BadRequest()
+                    .status(StatusCode::BAD_REQUEST)
+                    .json(serde_json::json!({"message": "Invalid otp provided"}));
             }
-            None => {
-                return HttpResponse::NotFound()
-                    .status(StatusCode::NOT_FOUND)
-                    .json(serde_json::json!({"message": "User not found"}));
+            Err(OtpError::AttemptsExceeded) => {
+                rate_limiter.record_failure(
+                    &RateLimitAction::OtpVerify,
+                    &ip,
+                    Some(user.get_uuid()),
+                );
+                return HttpResponse::BadRequest().json(
+                    serde_json::json!({"message": "Too many attempts. Please request a new OTP."}),
+                );
+            }
+            Err(e) => {
+                error!("Otp verification failed: {}", e);
+                return HttpResponse::InternalServerError()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .json(serde_json::json!({"message": "Otp verification failed"}));
             }
-        },
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .status(StatusCode::INTERNAL_SERVER_ERROR)
-                .json(serde_json::json!({"message": format!("Internal server error: {}", e)}));
         }
-    };
+    }
 
     let mut nearest_landmark_value = user.get_nearest_landmark();
 
@@ -365,15 +521,76 @@ pub async fn edit(
             location.eq(&user_update.location),
             nearest_landmark.eq(nearest_landmark_value),
         ))
-        .execute(conn)
+        .returning(User::as_returning())
+        .get_result(conn)
     {
-        Ok(_) => HttpResponse::Ok().json(user_update),
+        Ok(u) => HttpResponse::Ok().json(u),
         Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
             "message": format!("Internal server error: {}", e)
         })),
     }
 }
 
+// Loads the user being edited once the caller is allowed to edit them
+fn find_user_for_edit(
+    conn: &mut SqliteConnection,
+    user_info: &UserInfo,
+    uid: &str,
+) -> Result<UserModel, HttpResponse> {
+    use crate::schema::users;
+
+    //check if the user_id is valid uuid or not before trip to db
+    let uid: Uuid = match Uuid::parse_str(uid) {
+        Ok(u) => u,
+        Err(_) => {
+            return Err(HttpResponse::BadRequest()
+                .status(StatusCode::BAD_REQUEST)
+                .json(serde_json::json!({"message": "Invalid user id"})));
+        }
+    };
+
+    ownership::authorize(conn, user_info, Resource::User(&uid.to_string()))?;
+
+    match users::table
+        .filter(users::uuid.eq(uid.to_string()))
+        .select(UserModel::as_select())
+        .first(conn)
+        .optional()
+    {
+        Ok(Some(u)) => Ok(u),
+        Ok(None) => Err(HttpResponse::NotFound()
+            .status(StatusCode::NOT_FOUND)
+            .json(serde_json::json!({"message": "User not found"}))),
+        Err(e) => Err(HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": format!("Internal server error: {}", e)}))),
+    }
+}
+
+// A number can only belong to one account, whichever form it was saved in
+fn check_phone_available(
+    conn: &mut SqliteConnection,
+    user: &UserModel,
+    phone: &PhoneNumber,
+) -> Result<(), HttpResponse> {
+    use crate::schema::users;
+
+    match users::table
+        .filter(users::phone_number.eq_any(phone.get_variants()))
+        .filter(users::id.ne(user.get_id()))
+        .count()
+        .get_result::<i64>(conn)
+    {
+        Ok(0) => Ok(()),
+        Ok(_) => Err(HttpResponse::Conflict()
+            .status(StatusCode::CONFLICT)
+            .json(serde_json::json!({"message": "Phone number already used"}))),
+        Err(e) => Err(HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": format!("Internal server error: {}", e)}))),
+    }
+}
+
 #[delete("/{user_id}")]
 pub async fn delete(user_id: web::Path<(String,)>) -> impl Responder {
     let _id: String = user_id.into_inner().0;
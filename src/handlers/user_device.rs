@@ -0,0 +1,59 @@
+use actix_web::{http::StatusCode, post, web, HttpResponse, Responder};
+use diesel::prelude::*;
+
+use crate::{
+    contracts::user_device::UserDevice,
+    db::connection::{get_conn, SqliteConnectionPool},
+    handlers::product_rating::find_user,
+    middlewares::user_info::UserInfo,
+    models::user_device::NewUserDevice,
+};
+
+/// Registers the push token of the customer's device for wishlist alerts.
+/// A token only ever belongs to the last user who signed in on that device.
+#[post("/register-fcm-token")]
+pub async fn register_fcm_token(
+    user_info: UserInfo,
+    token: web::Json<UserDevice>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    use crate::schema::user_devices;
+
+    if token.fcm_token.trim().is_empty() {
+        return HttpResponse::BadRequest()
+            .status(StatusCode::BAD_REQUEST)
+            .json(serde_json::json!({"message": "FCM token is required"}));
+    }
+
+    let conn = &mut get_conn(&pool);
+
+    let user = match find_user(conn, &user_info) {
+        Ok(u) => u,
+        Err(res) => return res,
+    };
+
+    let user_dev = NewUserDevice::new(&user, token.fcm_token.clone());
+
+    let result = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        diesel::delete(
+            user_devices::table
+                .filter(user_devices::fcm_token.eq(&token.fcm_token))
+                .filter(user_devices::user_id.ne(user.get_id())),
+        )
+        .execute(conn)?;
+
+        diesel::insert_into(user_devices::table)
+            .values(&user_dev)
+            .on_conflict(user_devices::user_id)
+            .do_update()
+            .set(user_devices::fcm_token.eq(&token.fcm_token))
+            .execute(conn)
+    });
+
+    match result {
+        Ok(_) => HttpResponse::Ok().finish(),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
@@ -0,0 +1,151 @@
+use actix_web::{delete, get, http::StatusCode, post, web, HttpResponse, Responder};
+use diesel::prelude::*;
+
+use crate::{
+    contracts::wishlist::{Wishlist, WishlistCreate},
+    db::connection::{get_conn, SqliteConnectionPool},
+    handlers::{product::find_product, product::product_responses, product_rating::find_user},
+    middlewares::user_info::UserInfo,
+    models::{
+        category::Category as CategoryModel,
+        product::Product as ProductModel,
+        wishlist::{NewWishlist, Wishlist as WishlistModel},
+    },
+};
+
+#[get("")]
+pub async fn get(user_info: UserInfo, pool: web::Data<SqliteConnectionPool>) -> impl Responder {
+    use crate::schema::{categories, products, wishlists};
+
+    let conn = &mut get_conn(&pool);
+
+    let user = match find_user(conn, &user_info) {
+        Ok(u) => u,
+        Err(res) => return res,
+    };
+
+    let rows: Vec<(WishlistModel, ProductModel, CategoryModel)> = match wishlists::table
+        .inner_join(products::table.inner_join(categories::table))
+        .filter(wishlists::user_id.eq(user.get_id()))
+        .order(wishlists::created_at.desc())
+        .select((
+            WishlistModel::as_select(),
+            ProductModel::as_select(),
+            CategoryModel::as_select(),
+        ))
+        .load(conn)
+    {
+        Ok(r) => r,
+        Err(_) => {
+            return HttpResponse::InternalServerError()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .json(serde_json::json!({"message": "Ops! something went wrong"}))
+        }
+    };
+
+    let (items, product_rows): (Vec<WishlistModel>, Vec<(ProductModel, CategoryModel)>) =
+        rows.into_iter().map(|(w, p, c)| (w, (p, c))).unzip();
+
+    match product_responses(conn, &product_rows) {
+        Ok(product_list) => {
+            let wishlist: Vec<Wishlist> = items
+                .iter()
+                .zip(product_list)
+                .map(|(w, product)| Wishlist {
+                    uuid: w.get_uuid().to_owned(),
+                    product,
+                    notify: w.get_notify(),
+                    created_at: w.get_created_at(),
+                })
+                .collect();
+
+            HttpResponse::Ok()
+                .status(StatusCode::OK)
+                .json(serde_json::json!({"wishlist": wishlist}))
+        }
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+/// Adds a product to the wishlist. Adding one that is already there just
+/// updates whether the customer wants alerts for it.
+#[post("")]
+pub async fn add(
+    user_info: UserInfo,
+    wishlist_json: web::Json<WishlistCreate>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    use crate::schema::wishlists;
+
+    let conn = &mut get_conn(&pool);
+
+    let user = match find_user(conn, &user_info) {
+        Ok(u) => u,
+        Err(res) => return res,
+    };
+
+    let product = match find_product(conn, &wishlist_json.product_id) {
+        Ok(p) => p,
+        Err(res) => return res,
+    };
+
+    let notify = wishlist_json.notify.unwrap_or(false);
+    let new_item = NewWishlist::new(&user, &product, notify);
+
+    match diesel::insert_into(wishlists::table)
+        .values(&new_item)
+        .on_conflict((wishlists::user_id, wishlists::product_id))
+        .do_update()
+        .set(wishlists::notify.eq(notify))
+        .execute(conn)
+    {
+        Ok(_) => HttpResponse::Ok()
+            .status(StatusCode::OK)
+            .json(serde_json::json!({"message": "Product added to wishlist"})),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
+
+#[delete("/{product_id}")]
+pub async fn remove(
+    user_info: UserInfo,
+    path: web::Path<(String,)>,
+    pool: web::Data<SqliteConnectionPool>,
+) -> impl Responder {
+    use crate::schema::wishlists;
+
+    let product_id = path.into_inner().0;
+    let conn = &mut get_conn(&pool);
+
+    let user = match find_user(conn, &user_info) {
+        Ok(u) => u,
+        Err(res) => return res,
+    };
+
+    let product = match find_product(conn, &product_id) {
+        Ok(p) => p,
+        Err(res) => return res,
+    };
+
+    match diesel::delete(
+        wishlists::table
+            .filter(wishlists::user_id.eq(user.get_id()))
+            .filter(wishlists::product_id.eq(product.get_id())),
+    )
+    .execute(conn)
+    {
+        Ok(0) => HttpResponse::NotFound()
+            .status(StatusCode::NOT_FOUND)
+            .json(serde_json::json!({"message": "Product is not in the wishlist"})),
+        Ok(_) => HttpResponse::Ok()
+            .status(StatusCode::OK)
+            .json(serde_json::json!({"message": "Product removed from wishlist"})),
+        Err(_) => HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"})),
+    }
+}
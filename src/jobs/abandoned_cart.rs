@@ -0,0 +1,194 @@
+use std::{sync::Arc, time::Duration};
+
+use diesel::prelude::*;
+use tokio::time::{interval_at, Instant};
+
+use crate::{
+    config::ApplicationConfiguration,
+    db::connection::SqliteConnectionPool,
+    models::{cart_reminder::NewCartReminder, user::User},
+    services::{
+        email_service::EmailService,
+        notification_service::{CartReminderPayload, NotificationEvent, NotificationService},
+    },
+};
+
+/// A customer whose cart has gone idle, with the `(product name, quantity)`
+/// of every line still in it.
+struct AbandonedCart {
+    user: User,
+    items: Vec<(String, f64)>,
+}
+
+/// Starts the background job that reminds customers about carts they stopped
+/// touching. A cart counts as abandoned once none of its lines changed for
+/// `abandoned_cart_idle_hours`, and a customer is reminded at most once every
+/// `abandoned_cart_cooldown_hours` unless they opted out.
+pub fn start(
+    pool: SqliteConnectionPool,
+    email_service: Arc<dyn EmailService>,
+    notification_service: Arc<dyn NotificationService>,
+    config: &ApplicationConfiguration,
+) {
+    let idle_hours = config.abandoned_cart_idle_hours;
+    let cooldown_hours = config.abandoned_cart_cooldown_hours;
+    let period = Duration::from_secs(config.abandoned_cart_check_interval_minutes.max(1) * 60);
+
+    tokio::spawn(async move {
+        let mut ticker = interval_at(Instant::now() + period, period);
+
+        loop {
+            ticker.tick().await;
+
+            let carts = match pool.get() {
+                Ok(mut conn) => find_abandoned_carts(&mut conn, idle_hours, cooldown_hours),
+                Err(e) => {
+                    eprintln!("Abandoned cart job could not get a connection: {}", e);
+                    continue;
+                }
+            };
+
+            match carts {
+                Ok(carts) => {
+                    for cart in carts {
+                        remind(&pool, &email_service, &notification_service, cart).await;
+                    }
+                }
+                Err(e) => eprintln!("Failed to load abandoned carts: {}", e),
+            }
+        }
+    });
+}
+
+fn find_abandoned_carts(
+    conn: &mut SqliteConnection,
+    idle_hours: i64,
+    cooldown_hours: i64,
+) -> QueryResult<Vec<AbandonedCart>> {
+    use crate::schema::{cart_reminders, carts, notification_preferences, products, users};
+
+    let now = chrono::Utc::now().naive_utc();
+    let idle_cutoff = now - chrono::Duration::hours(idle_hours);
+    let cooldown_cutoff = now - chrono::Duration::hours(cooldown_hours);
+
+    // customers still working on their cart
+    let recent_carts = diesel::alias!(carts as recent_carts);
+    let active_users = recent_carts
+        .filter(recent_carts.field(carts::updated_at).gt(idle_cutoff))
+        .select(recent_carts.field(carts::user_id));
+
+    let opted_out_users = notification_preferences::table
+        .filter(notification_preferences::cart_reminders.eq(false))
+        .select(notification_preferences::user_id);
+
+    let recently_reminded_users = cart_reminders::table
+        .filter(cart_reminders::sent_at.gt(cooldown_cutoff))
+        .select(cart_reminders::user_id);
+
+    let rows: Vec<(User, String, f64)> = users::table
+        .inner_join(carts::table.inner_join(products::table))
+        .filter(carts::updated_at.le(idle_cutoff))
+        .filter(products::is_archived.eq(false))
+        .filter(users::id.ne_all(active_users))
+        .filter(users::id.ne_all(opted_out_users))
+        .filter(users::id.ne_all(recently_reminded_users))
+        .order((users::id.asc(), carts::updated_at.desc()))
+        .select((User::as_select(), products::name, carts::quantity))
+        .load(conn)?;
+
+    let mut abandoned: Vec<AbandonedCart> = vec![];
+    for (user, product_name, quantity) in rows {
+        match abandoned.last_mut() {
+            Some(cart) if cart.user.get_id() == user.get_id() => {
+                cart.items.push((product_name, quantity))
+            }
+            _ => abandoned.push(AbandonedCart {
+                user,
+                items: vec![(product_name, quantity)],
+            }),
+        }
+    }
+
+    Ok(abandoned)
+}
+
+async fn remind(
+    pool: &SqliteConnectionPool,
+    email_service: &Arc<dyn EmailService>,
+    notification_service: &Arc<dyn NotificationService>,
+    cart: AbandonedCart,
+) {
+    use crate::schema::cart_reminders;
+
+    let user = &cart.user;
+    let subject = "You left something in your cart";
+
+    let html_items: String = cart
+        .items
+        .iter()
+        .map(|(name, quantity)| format!("<li>{} &times; {}</li>", name, quantity))
+        .collect();
+    let html_body = format!(
+        "<p>Hi {},</p>\
+         <p>The following items are still waiting in your cart:</p>\
+         <ul>{}</ul>\
+         <p>Complete your order before they run out.</p>",
+        user.get_first_name(),
+        html_items
+    );
+
+    let text_items: Vec<String> = cart
+        .items
+        .iter()
+        .map(|(name, quantity)| format!("- {} x {}", name, quantity))
+        .collect();
+    let text_body = format!(
+        "Hi {},\n\nThe following items are still waiting in your cart:\n{}\n\nComplete your order before they run out.",
+        user.get_first_name(),
+        text_items.join("\n")
+    );
+
+    if let Err(e) = email_service
+        .send_html_email(user.get_email(), subject, &html_body, Some(&text_body))
+        .await
+    {
+        eprintln!(
+            "Failed to email cart reminder to user {}: {}",
+            user.get_id(),
+            e
+        );
+    }
+
+    if let Err(e) = notification_service
+        .send_to_users(
+            vec![user.get_id()],
+            NotificationEvent::CartReminder(CartReminderPayload {
+                item_count: cart.items.len(),
+            }),
+        )
+        .await
+    {
+        eprintln!(
+            "Failed to push cart reminder to user {}: {}",
+            user.get_id(),
+            e
+        );
+    }
+
+    // recorded even if a channel failed so a broken mail server does not make
+    // the job retry the same customer every tick
+    let reminder = NewCartReminder::new(user, cart.items.len() as i32);
+    let inserted = pool.get().map_err(|e| e.to_string()).and_then(|mut conn| {
+        diesel::insert_into(cart_reminders::table)
+            .values(&reminder)
+            .execute(&mut conn)
+            .map_err(|e| e.to_string())
+    });
+    if let Err(e) = inserted {
+        eprintln!(
+            "Failed to record cart reminder for user {}: {}",
+            user.get_id(),
+            e
+        );
+    }
+}
@@ -0,0 +1,3 @@
+pub mod abandoned_cart;
+pub mod otp_cleanup;
+pub mod subscription;
@@ -0,0 +1,27 @@
+use std::time::Duration;
+
+use tokio::time::{interval_at, Instant};
+
+use crate::{
+    config::ApplicationConfiguration, db::connection::SqliteConnectionPool,
+    services::opt_service::OtpService,
+};
+
+/// Starts the background job that deletes one-time codes once they have
+/// expired, every `otp_cleanup_interval_minutes`.
+pub fn start(pool: SqliteConnectionPool, config: &ApplicationConfiguration) {
+    let period = Duration::from_secs(config.otp_cleanup_interval_minutes.max(1) * 60);
+    let otp_service = OtpService::new(&pool);
+
+    tokio::spawn(async move {
+        let mut ticker = interval_at(Instant::now() + period, period);
+
+        loop {
+            ticker.tick().await;
+
+            if let Err(e) = otp_service.cleanup_expired_otps() {
+                eprintln!("Failed to clean up expired OTPs: {}", e);
+            }
+        }
+    });
+}
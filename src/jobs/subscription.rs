@@ -0,0 +1,184 @@
+use std::{str::FromStr, sync::Arc, time::Duration};
+
+use diesel::prelude::*;
+use tokio::time::{interval_at, Instant};
+
+use crate::{
+    base_types::{payment_method::PaymentMethod, subscription_frequency::SubscriptionFrequency},
+    config::ApplicationConfiguration,
+    db::connection::SqliteConnectionPool,
+    services::{
+        availability_service,
+        notification_service::{
+            NewOrderPayload, NotificationEvent, NotificationService, SubscriptionOrderPayload,
+        },
+        order_service::PlaceOrderError,
+        subscription_service,
+    },
+};
+
+/// Starts the background job that turns subscriptions into orders. Orders are
+/// placed `subscription_order_lead_days` ahead of the delivery so the shop can
+/// pack them, and the customer is told about each one.
+pub fn start(
+    pool: SqliteConnectionPool,
+    notification_service: Arc<dyn NotificationService>,
+    config: &ApplicationConfiguration,
+) {
+    let lead_days = config.subscription_order_lead_days;
+    let period = Duration::from_secs(config.subscription_check_interval_minutes.max(1) * 60);
+
+    tokio::spawn(async move {
+        let mut ticker = interval_at(Instant::now() + period, period);
+
+        loop {
+            ticker.tick().await;
+
+            let events = match pool.get() {
+                Ok(mut conn) => place_due_orders(&mut conn, lead_days),
+                Err(e) => {
+                    eprintln!("Subscription job could not get a connection: {}", e);
+                    continue;
+                }
+            };
+
+            for (user_id, event, admin_event) in events {
+                if let Err(e) = notification_service
+                    .send_to_users(vec![user_id], event)
+                    .await
+                {
+                    eprintln!(
+                        "Failed to notify user {} of a subscription order: {}",
+                        user_id, e
+                    );
+                }
+
+                if let Some(admin_event) = admin_event {
+                    if let Err(e) = notification_service.send_notification(admin_event).await {
+                        eprintln!("Failed to dispatch subscription order notification: {}", e);
+                    }
+                }
+            }
+        }
+    });
+}
+
+// places every order that is due and returns the notifications to send for
+// them, the customer's and, for cash orders, the one for the shop
+fn place_due_orders(
+    conn: &mut SqliteConnection,
+    lead_days: i64,
+) -> Vec<(i32, NotificationEvent, Option<NotificationEvent>)> {
+    use crate::schema::subscriptions;
+
+    let today = availability_service::today();
+    let due = match subscription_service::due_subscriptions(
+        conn,
+        today + chrono::Duration::days(lead_days),
+    ) {
+        Ok(d) => d,
+        Err(e) => {
+            eprintln!("Failed to load due subscriptions: {}", e);
+            return vec![];
+        }
+    };
+
+    let mut events = vec![];
+
+    for (subscription, user) in due {
+        let (frequency, delivery_on) = match (
+            SubscriptionFrequency::from_str(subscription.get_frequency()),
+            subscription_service::parse_date(subscription.get_next_delivery_on()),
+        ) {
+            (Ok(f), Some(d)) => (f, d),
+            _ => {
+                eprintln!(
+                    "Subscription {} has an invalid schedule",
+                    subscription.get_uuid()
+                );
+                continue;
+            }
+        };
+
+        // a delivery that already went by is not ordered late, the
+        // subscription just carries on with the next one
+        if delivery_on < today {
+            let next = subscription_service::roll_forward(&frequency, delivery_on, today);
+            if let Err(e) = diesel::update(&subscription)
+                .set(
+                    subscriptions::next_delivery_on
+                        .eq(next.format(subscription_service::DATE_FORMAT).to_string()),
+                )
+                .execute(conn)
+            {
+                eprintln!(
+                    "Failed to move subscription {} on: {}",
+                    subscription.get_uuid(),
+                    e
+                );
+            }
+            continue;
+        }
+
+        match subscription_service::place_subscription_order(
+            conn,
+            &subscription,
+            &user,
+            &frequency,
+            delivery_on,
+        ) {
+            Ok(order) => {
+                let event = NotificationEvent::SubscriptionOrder(SubscriptionOrderPayload {
+                    order_id: order.get_uuid().to_owned(),
+                    delivery_on: subscription.get_next_delivery_on().to_owned(),
+                    delivery_slot: subscription.get_delivery_slot().to_owned(),
+                    total_amount: order.get_total_price(),
+                });
+
+                let admin_event =
+                    (subscription.get_payment_method() == PaymentMethod::Cash.value()).then(|| {
+                        NotificationEvent::NewOrder(NewOrderPayload {
+                            order_id: order.get_uuid().to_owned(),
+                            customer_name: user.get_fullname(),
+                            total_amount: order.get_total_price(),
+                        })
+                    });
+
+                events.push((user.get_id(), event, admin_event));
+            }
+            Err(PlaceOrderError::Rejected(message)) => {
+                eprintln!(
+                    "Could not place the order for subscription {}: {}",
+                    subscription.get_uuid(),
+                    message
+                );
+
+                // retried on every run until the day of the delivery
+                if delivery_on <= today {
+                    if let Err(e) = diesel::update(&subscription)
+                        .set(
+                            subscriptions::next_delivery_on.eq(frequency
+                                .next_after(delivery_on)
+                                .format(subscription_service::DATE_FORMAT)
+                                .to_string()),
+                        )
+                        .execute(conn)
+                    {
+                        eprintln!(
+                            "Failed to move subscription {} on: {}",
+                            subscription.get_uuid(),
+                            e
+                        );
+                    }
+                }
+            }
+            Err(e) => eprintln!(
+                "Failed to place the order for subscription {}: {}",
+                subscription.get_uuid(),
+                e
+            ),
+        }
+    }
+
+    events
+}
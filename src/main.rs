@@ -10,7 +10,8 @@ use crate::{
     db::connection,
     services::{
         email_service::EmailServiceFactory, fcm_notification_service::FcmNotificationServiceImpl,
-        notification_service::NotificationService,
+        notification_service::NotificationService, rate_limit_service::RateLimiter,
+        rate_limit_store::RateLimitStoreFactory, sms_service::SmsServiceFactory,
     },
 };
 
@@ -19,8 +20,10 @@ mod config;
 mod contracts;
 mod db;
 mod handlers;
+mod jobs;
 mod middlewares;
 mod models;
+mod policies;
 mod routes;
 mod schema;
 mod services;
@@ -33,6 +36,8 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let app_config = config::ApplicationConfiguration::init();
     let email_config = config::EmailConfiguration::init();
     let company_config = config::CompanyConfiguration::init();
+    let sms_config = config::SmsConfiguration::init();
+    let rate_limit_config = config::RateLimitConfiguration::init();
 
     let db_pool: connection::SqliteConnectionPool =
         connection::establish_connection(&app_config.database_url);
@@ -49,8 +54,25 @@ async fn main() -> Result<(), Box<dyn Error>> {
     //Email service
     let email_service = EmailServiceFactory::create_gmail_service(&email_config)?;
 
+    //SMS service
+    let sms_service = SmsServiceFactory::create(&sms_config, &client)?;
+
+    //Brute-force protection
+    let rate_limit_store = RateLimitStoreFactory::create(&rate_limit_config, &db_pool)?;
+    let rate_limiter = RateLimiter::new(rate_limit_store, &rate_limit_config);
+
+    jobs::abandoned_cart::start(
+        db_pool.clone(),
+        email_service.clone(),
+        notification_service.clone(),
+        &app_config,
+    );
+    jobs::subscription::start(db_pool.clone(), notification_service.clone(), &app_config);
+    jobs::otp_cleanup::start(db_pool.clone(), &app_config);
+
     std::fs::create_dir_all(&app_config.product_extraimages_path)?;
     std::fs::create_dir_all(&app_config.product_thumbnail_path)?;
+    std::fs::create_dir_all(&app_config.category_image_path)?;
 
     let server_address = app_config.server_address.clone();
     let server_port = app_config.server_port;
@@ -66,6 +88,8 @@ async fn main() -> Result<(), Box<dyn Error>> {
             .app_data(Data::new(company_config.clone()))
             .app_data(Data::new(email_config.clone()))
             .app_data(Data::new(email_service.clone()))
+            .app_data(Data::new(sms_service.clone()))
+            .app_data(Data::new(rate_limiter.clone()))
             .app_data(Data::new(db_pool.clone()))
             .app_data(Data::new(client.clone()))
             .app_data(Data::new(notification_service.clone()))
@@ -0,0 +1,173 @@
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::Method,
+    web, Error as ActixWebError, HttpMessage,
+};
+use futures_util::future::{ok, FutureExt, LocalBoxFuture, Ready};
+use log::error;
+use std::rc::Rc;
+
+use super::user_info::UserInfo;
+use crate::{
+    db::connection::SqliteConnectionPool,
+    models::audit_log::NewAuditLog,
+    services::audit_service::{self, Snapshot},
+};
+
+#[derive(Clone)]
+struct AuditConfig {
+    // route patterns to record, every mutating route when `None`
+    routes: Option<&'static [&'static str]>,
+}
+
+/// Records who changed what in the `audit_logs` table. Only requests that
+/// can change something are recorded, refused ones included. The changes are
+/// diffed when the route carries the entity's id, a create has none to go by.
+#[derive(Clone)]
+pub struct AuditLog {
+    config: AuditConfig,
+}
+
+impl AuditLog {
+    pub fn mutations() -> Self {
+        AuditLog {
+            config: AuditConfig { routes: None },
+        }
+    }
+
+    /// For scopes where only some routes are staff actions, e.g. status
+    /// changes next to routes customers use.
+    pub fn routes(routes: &'static [&'static str]) -> Self {
+        AuditLog {
+            config: AuditConfig {
+                routes: Some(routes),
+            },
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for AuditLog
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = ActixWebError> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = ActixWebError;
+    type InitError = ();
+    type Transform = AuditLogMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(AuditLogMiddleware {
+            service: Rc::new(service),
+            config: self.config.clone(),
+        })
+    }
+}
+
+pub struct AuditLogMiddleware<S> {
+    service: Rc<S>,
+    config: AuditConfig,
+}
+
+impl<S, B> Service<ServiceRequest> for AuditLogMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = ActixWebError> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = ActixWebError;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+
+        let pattern = req.match_pattern().unwrap_or_else(|| req.path().to_owned());
+        let read_only = matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS);
+        let recorded = self
+            .config
+            .routes
+            .is_none_or(|routes| routes.contains(&pattern.as_str()));
+
+        if read_only || !recorded {
+            return async move { service.call(req).await }.boxed_local();
+        }
+
+        let action = format!("{} {}", req.method(), pattern);
+        let (entity_type, entity_id) = audit_service::entity_of(&pattern, req.path());
+        let actor = req
+            .extensions()
+            .get::<UserInfo>()
+            .map(|user_info| user_info.user_id.clone());
+        let ip = req
+            .connection_info()
+            .realip_remote_addr()
+            .map(str::to_owned);
+        let pool_opt = req.app_data::<web::Data<SqliteConnectionPool>>().cloned();
+
+        async move {
+            let Some(pool) = pool_opt else {
+                error!("SqliteConnectionPool not found in app_data");
+                return service.call(req).await;
+            };
+
+            let take_snapshot = || -> Option<Snapshot> {
+                let entity_id = entity_id.as_deref()?;
+                let mut conn = pool
+                    .get()
+                    .map_err(|e| error!("Failed to get database connection: {}", e))
+                    .ok()?;
+                audit_service::snapshot(&mut conn, &entity_type, entity_id)
+                    .map_err(|e| {
+                        error!(
+                            "Failed to snapshot {} for the audit log: {}",
+                            entity_type, e
+                        )
+                    })
+                    .ok()
+                    .flatten()
+            };
+
+            let before = take_snapshot();
+            let result = service.call(req).await;
+
+            let status = match &result {
+                Ok(res) => res.status(),
+                Err(e) => e.as_response_error().status_code(),
+            };
+
+            let changes = if status.is_success() {
+                audit_service::diff(before.as_ref(), take_snapshot().as_ref())
+            } else {
+                None
+            };
+
+            let entry = NewAuditLog::new(
+                actor,
+                action,
+                entity_type.clone(),
+                entity_id.clone(),
+                changes.map(|c| c.to_string()),
+                i32::from(status.as_u16()),
+                ip,
+            );
+
+            // the change has been made either way, a missing entry is only logged
+            match pool.get() {
+                Ok(mut conn) => {
+                    if let Err(e) = audit_service::record(&mut conn, &entry) {
+                        error!("Failed to write audit log: {}", e);
+                    }
+                }
+                Err(e) => error!("Failed to get database connection: {}", e),
+            }
+
+            result
+        }
+        .boxed_local()
+    }
+}
@@ -1,17 +1,21 @@
 use actix_web::{
     dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
-    error::{ErrorForbidden, ErrorUnauthorized},
-    Error as ActixWebError, HttpMessage,
+    error::{ErrorForbidden, ErrorInternalServerError, ErrorUnauthorized},
+    web, Error as ActixWebError, HttpMessage,
 };
 use futures_util::future::{ok, FutureExt, LocalBoxFuture, Ready};
-use std::collections::HashSet;
+use log::error;
 use std::rc::Rc;
 
 use super::user_info::UserInfo;
+use crate::{
+    base_types::permission::Permission, db::connection::SqliteConnectionPool,
+    services::permission_service,
+};
 
 #[derive(Clone)]
 struct AuthConfig {
-    required_roles: Option<HashSet<String>>,
+    required_permission: Option<Permission>,
 }
 
 // --- Authorization Middleware Factory ---
@@ -24,15 +28,17 @@ impl Auth {
     pub fn authenticated() -> Self {
         Auth {
             config: AuthConfig {
-                required_roles: None,
+                required_permission: None,
             },
         }
     }
 
-    pub fn require_roles(roles: &[&str]) -> Self {
+    /// Lets the request through when the user's role has been granted
+    /// `permission`, see the `roles` and `permissions` tables.
+    pub fn require_permission(permission: Permission) -> Self {
         Auth {
             config: AuthConfig {
-                required_roles: Some(roles.iter().map(|s| s.to_string()).collect()),
+                required_permission: Some(permission),
             },
         }
     }
@@ -79,6 +85,7 @@ where
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
         let user_opt = req.extensions().get::<UserInfo>().cloned();
+        let pool_opt = req.app_data::<web::Data<SqliteConnectionPool>>().cloned();
 
         let service = Rc::clone(&self.service);
         let config = self.config.clone();
@@ -86,16 +93,41 @@ where
         async move {
             match user_opt {
                 Some(user_info) => {
-                    // Check if specific roles are required
-                    if let Some(required_roles) = &config.required_roles {
-                        // Do this if one user has multiple roles
-                        // if required_roles.is_subset(&user_info.roles) {
-                        if user_info.roles.is_subset(&required_roles) {
-                            service.call(req).await
-                        } else {
-                            Err(ErrorForbidden(
+                    // Check if a specific permission is required
+                    if let Some(required_permission) = config.required_permission {
+                        if user_info.two_factor_setup_required {
+                            return Err(ErrorForbidden(serde_json::json!({
+                                "message": "Two-factor authentication must be set up first"
+                            })));
+                        }
+
+                        let allowed = match pool_opt.as_ref().map(|pool| pool.get()) {
+                            Some(Ok(mut conn)) => permission_service::user_has_permission(
+                                &mut conn,
+                                &user_info.user_id,
+                                required_permission,
+                            )
+                            .map_err(|e| {
+                                error!("Failed to check permission: {}", e);
+                            }),
+                            Some(Err(e)) => {
+                                error!("Failed to get database connection: {}", e);
+                                Err(())
+                            }
+                            None => {
+                                error!("SqliteConnectionPool not found in app_data");
+                                Err(())
+                            }
+                        };
+
+                        match allowed {
+                            Ok(true) => service.call(req).await,
+                            Ok(false) => Err(ErrorForbidden(
                                 serde_json::json!({"message":"Insufficient permissions"}),
-                            ))
+                            )),
+                            Err(()) => Err(ErrorInternalServerError(
+                                serde_json::json!({"message":"Ops! something went wrong"}),
+                            )),
                         }
                     } else {
                         service.call(req).await
@@ -4,9 +4,11 @@ use actix_web::{
     web, Error as ActixWebError, HttpMessage,
 };
 use futures_util::future::{ok, FutureExt, LocalBoxFuture, Ready};
-use log::error;
+use log::{error, warn};
 use std::rc::Rc;
 
+use crate::db::connection::SqliteConnectionPool;
+use crate::services::access_token_service;
 use crate::utils::jwt_helper;
 use crate::{config::ApplicationConfiguration, middlewares::user_info::UserInfo};
 
@@ -55,6 +57,8 @@ where
             .app_data::<web::Data<ApplicationConfiguration>>()
             .cloned(); // Clone Data<Arc<T>>
 
+        let pool_opt = req.app_data::<web::Data<SqliteConnectionPool>>().cloned();
+
         async move {
             let config = match config_opt {
                 Some(cfg) => cfg,
@@ -75,22 +79,45 @@ where
                 .map(|s| &s[7..]);
 
             if let Some(token_str) = token {
-                match jwt_helper::verify_jwt(token_str, &config.jwt_secret.as_bytes()).await {
+                match jwt_helper::verify_jwt(token_str, config.jwt_secret.as_bytes()).await {
                     Ok(claims) => {
-                        let user_info = UserInfo {
-                            user_id: claims.sub.clone(),
-                            roles: claims.roles.clone(),
+                        // signed out tokens are treated like no token at all
+                        let revoked = match pool_opt.as_ref().map(|pool| pool.get()) {
+                            Some(Ok(mut conn)) => access_token_service::is_revoked(
+                                &mut conn,
+                                &claims.jti,
+                                &claims.sid,
+                            )
+                            .unwrap_or_else(|e| {
+                                error!("Failed to check access token revocation: {}", e);
+                                true
+                            }),
+                            Some(Err(e)) => {
+                                error!("Failed to get database connection: {}", e);
+                                true
+                            }
+                            None => {
+                                error!("SqliteConnectionPool not found in app_data");
+                                true
+                            }
                         };
-                        req.extensions_mut().insert(user_info);
+
+                        if !revoked {
+                            let user_info = UserInfo {
+                                user_id: claims.sub.clone(),
+                                token_id: claims.jti.clone(),
+                                session_id: claims.sid.clone(),
+                                token_expires_at: claims.exp,
+                                two_factor_setup_required: claims.mfa_setup,
+                            };
+                            req.extensions_mut().insert(user_info);
+                        }
                     }
                     Err(e) => {
-                        println!("JWT verification failed: {}", e);
+                        warn!("JWT verification failed: {}", e);
                     }
                 }
             }
-            // else {
-            //     println!("No valid Bearer token found in Authorization header.");
-            // }
 
             service.call(req).await
         }
@@ -1,4 +1,4 @@
+pub mod audit_middleware;
 pub mod auth_middleware;
 pub mod jwt_middleware;
 pub mod user_info;
-pub mod internal_api;
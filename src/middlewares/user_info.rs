@@ -1,5 +1,3 @@
-use std::collections::HashSet;
-
 use actix_web::{dev::Payload, error::ErrorUnauthorized, Error, FromRequest, HttpMessage, HttpRequest};
 use futures_util::future::{ready, Ready};
 
@@ -7,7 +5,12 @@ use futures_util::future::{ready, Ready};
 #[derive(Clone, Debug)]
 pub struct UserInfo {
     pub user_id: String,
-    pub roles: HashSet<String>,
+    // jti, sid and exp of the access token, needed to sign it out
+    pub token_id: String,
+    pub session_id: String,
+    pub token_expires_at: usize,
+    // admins that still have to set up two-factor authentication
+    pub two_factor_setup_required: bool,
 }
 
 impl FromRequest for UserInfo {
@@ -21,4 +24,4 @@ impl FromRequest for UserInfo {
             ready(Err(ErrorUnauthorized("Not authenticated or user data unavailable")))
         }
     }
-}
\ No newline at end of file
+}
\ No newline at end of file
@@ -0,0 +1,84 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+
+#[derive(Queryable, Selectable)]
+#[diesel(table_name = crate::schema::audit_logs)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct AuditLog {
+    actor_user_id: Option<String>,
+    action: String,
+    entity_type: String,
+    entity_id: Option<String>,
+    changes: Option<String>,
+    status_code: i32,
+    ip_address: Option<String>,
+    created_at: NaiveDateTime,
+}
+
+impl AuditLog {
+    pub fn get_actor_user_id(&self) -> Option<&str> {
+        self.actor_user_id.as_deref()
+    }
+
+    pub fn get_action(&self) -> &str {
+        &self.action
+    }
+
+    pub fn get_entity_type(&self) -> &str {
+        &self.entity_type
+    }
+
+    pub fn get_entity_id(&self) -> Option<&str> {
+        self.entity_id.as_deref()
+    }
+
+    pub fn get_changes(&self) -> Option<&str> {
+        self.changes.as_deref()
+    }
+
+    pub fn get_status_code(&self) -> i32 {
+        self.status_code
+    }
+
+    pub fn get_ip_address(&self) -> Option<&str> {
+        self.ip_address.as_deref()
+    }
+
+    pub fn get_created_at(&self) -> NaiveDateTime {
+        self.created_at
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::audit_logs)]
+pub struct NewAuditLog {
+    actor_user_id: Option<String>,
+    action: String,
+    entity_type: String,
+    entity_id: Option<String>,
+    changes: Option<String>,
+    status_code: i32,
+    ip_address: Option<String>,
+}
+
+impl NewAuditLog {
+    pub fn new(
+        actor_user_id: Option<String>,
+        action: String,
+        entity_type: String,
+        entity_id: Option<String>,
+        changes: Option<String>,
+        status_code: i32,
+        ip_address: Option<String>,
+    ) -> Self {
+        Self {
+            actor_user_id,
+            action,
+            entity_type,
+            entity_id,
+            changes,
+            status_code,
+            ip_address,
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+
+#[derive(Queryable, QueryableByName, Selectable)]
+#[diesel(table_name = crate::schema::auth_rate_limits)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct AuthRateLimit {
+    failures: i32,
+    last_failure_at: NaiveDateTime,
+    locked_until: Option<NaiveDateTime>,
+}
+
+impl AuthRateLimit {
+    pub fn get_failures(&self) -> i32 {
+        self.failures
+    }
+
+    pub fn get_last_failure_at(&self) -> NaiveDateTime {
+        self.last_failure_at
+    }
+
+    pub fn get_locked_until(&self) -> Option<NaiveDateTime> {
+        self.locked_until
+    }
+}
@@ -17,6 +17,7 @@ pub struct Cart {
     sku: String,
     created_on: String,
     discount: f64,
+    rate: f64,
 }
 
 impl Cart {
@@ -46,6 +47,10 @@ impl Cart {
     pub fn get_discount(&self) -> f64 {
         self.discount
     }
+
+    pub fn get_rate(&self) -> f64 {
+        self.rate
+    }
 }
 
 #[derive(Insertable)]
@@ -57,10 +62,17 @@ pub struct NewCartItem {
     quantity: f64,
     sku: String,
     created_on: String,
+    rate: f64,
 }
 
 impl NewCartItem {
-    pub fn new(product: &Product, user: &User, quantity: f64, created_on: String) -> Self {
+    pub fn new(
+        product: &Product,
+        user: &User,
+        quantity: f64,
+        created_on: String,
+        rate: f64,
+    ) -> Self {
         Self {
             uuid: Uuid::new_v4().to_string(),
             user_id: user.get_id(),
@@ -68,6 +80,7 @@ impl NewCartItem {
             quantity,
             sku: product.get_unit().to_owned(),
             created_on,
+            rate,
         }
     }
 }
@@ -0,0 +1,19 @@
+use diesel::prelude::*;
+
+use super::user::User;
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::cart_reminders)]
+pub struct NewCartReminder {
+    user_id: i32,
+    item_count: i32,
+}
+
+impl NewCartReminder {
+    pub fn new(user: &User, item_count: i32) -> Self {
+        Self {
+            user_id: user.get_id(),
+            item_count,
+        }
+    }
+}
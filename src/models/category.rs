@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -9,6 +11,11 @@ pub struct Category {
     id: i32,
     uuid: String,
     name: String,
+    parent_id: Option<i32>,
+    slug: String,
+    display_order: i32,
+    image: Option<String>,
+    is_active: bool,
 }
 
 impl Category {
@@ -21,6 +28,53 @@ impl Category {
     pub fn get_name(&self) -> &str {
         &self.name
     }
+    pub fn get_parent_id(&self) -> Option<i32> {
+        self.parent_id
+    }
+    pub fn get_slug(&self) -> &str {
+        &self.slug
+    }
+    pub fn get_display_order(&self) -> i32 {
+        self.display_order
+    }
+    pub fn get_image(&self) -> Option<&str> {
+        self.image.as_deref()
+    }
+    pub fn is_active(&self) -> bool {
+        self.is_active
+    }
+
+    /// Returns the id of the category with `root_id` followed by the ids of all
+    /// categories below it in the tree.
+    pub fn descendant_ids(conn: &mut SqliteConnection, root_id: i32) -> QueryResult<Vec<i32>> {
+        use crate::schema::categories;
+
+        let links: Vec<(i32, Option<i32>)> = categories::table
+            .select((categories::id, categories::parent_id))
+            .load(conn)?;
+
+        let mut children: HashMap<i32, Vec<i32>> = HashMap::new();
+        for (cat_id, parent) in links {
+            if let Some(p) = parent {
+                children.entry(p).or_default().push(cat_id);
+            }
+        }
+
+        let mut ids = vec![root_id];
+        let mut index = 0;
+        while index < ids.len() {
+            if let Some(child_ids) = children.get(&ids[index]) {
+                for child_id in child_ids {
+                    if !ids.contains(child_id) {
+                        ids.push(*child_id);
+                    }
+                }
+            }
+            index += 1;
+        }
+
+        Ok(ids)
+    }
 }
 
 #[derive(Insertable)]
@@ -29,13 +83,27 @@ impl Category {
 pub struct NewCategory {
     uuid: String,
     name: String,
+    parent_id: Option<i32>,
+    slug: String,
+    display_order: i32,
+    is_active: bool,
 }
 
 impl NewCategory {
-    pub fn new(name: String) -> Self {
+    pub fn new(
+        name: String,
+        slug: String,
+        parent: Option<&Category>,
+        display_order: i32,
+        is_active: bool,
+    ) -> Self {
         Self {
             uuid: Uuid::new_v4().to_string(),
             name,
+            parent_id: parent.map(|p| p.get_id()),
+            slug,
+            display_order,
+            is_active,
         }
     }
 }
@@ -0,0 +1,75 @@
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use super::product::Product;
+
+#[derive(Queryable, Selectable, Associations, Identifiable)]
+#[diesel(table_name = crate::schema::guest_carts)]
+#[diesel(belongs_to(Product))]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct GuestCart {
+    id: i32,
+    uuid: String,
+    product_id: i32,
+    quantity: f64,
+    sku: String,
+    rate: f64,
+    created_on: String,
+}
+
+impl GuestCart {
+    pub fn get_uuid(&self) -> &str {
+        &self.uuid
+    }
+
+    pub fn get_quantity(&self) -> f64 {
+        self.quantity
+    }
+
+    pub fn get_sku(&self) -> &str {
+        &self.sku
+    }
+
+    pub fn get_rate(&self) -> f64 {
+        self.rate
+    }
+
+    pub fn get_created_on(&self) -> &str {
+        &self.created_on
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::guest_carts)]
+pub struct NewGuestCart {
+    uuid: String,
+    token: String,
+    product_id: i32,
+    quantity: f64,
+    sku: String,
+    rate: f64,
+    created_on: String,
+    expires_on: chrono::NaiveDateTime,
+}
+
+impl NewGuestCart {
+    pub fn new(
+        token: &str,
+        product: &Product,
+        quantity: f64,
+        created_on: String,
+        rate: f64,
+        expires_on: chrono::NaiveDateTime,
+    ) -> Self {
+        Self {
+            uuid: Uuid::new_v4().to_string(),
+            token: token.to_owned(),
+            product_id: product.get_id(),
+            quantity,
+            sku: product.get_unit().to_owned(),
+            rate,
+            created_on,
+            expires_on,
+        }
+    }
+}
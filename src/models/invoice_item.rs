@@ -2,23 +2,7 @@ use diesel::prelude::*;
 use uuid::Uuid;
 
 use super::{invoice::Invoice, product::Product};
-
-#[derive(Queryable, Selectable, Associations, Identifiable)]
-#[diesel(table_name = crate::schema::invoice_items)]
-#[diesel(belongs_to(Invoice))]
-#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
-pub struct InvoiceItem {
-    id: i32,
-    uuid: String,
-    quantity: f64,
-    unit_price: f64,
-    discount_percent: f64,
-    discount_amount: f64,
-    total: f64,
-
-    invoice_id: i32,
-    product_id: i32,
-}
+use crate::services::pricing_service::AppliedPrice;
 
 #[derive(Insertable)]
 #[diesel(table_name = crate::schema::invoice_items)]
@@ -31,6 +15,8 @@ pub struct NewInvoiceItem {
     discount_percent: f64,
     discount_amount: f64,
     total: f64,
+    price_tier_id: Option<i32>,
+    tier_min_quantity: Option<f64>,
 }
 
 impl NewInvoiceItem {
@@ -40,17 +26,20 @@ impl NewInvoiceItem {
         quantity: f64,
         discount_percent: f64,
         discount_amount: f64,
+        applied_price: &AppliedPrice,
     ) -> Self {
-        let total = product.get_price() * quantity - discount_amount;
+        let total = applied_price.total_for(quantity) - discount_amount;
         Self {
             uuid: Uuid::new_v4().to_string(),
             product_id: product.get_id(),
             invoice_id: invoice.get_id(),
             quantity,
-            unit_price: product.get_price(),
+            unit_price: applied_price.unit_price,
             discount_percent,
             discount_amount,
             total,
+            price_tier_id: applied_price.tier_id,
+            tier_min_quantity: applied_price.tier_min_quantity,
         }
     }
 }
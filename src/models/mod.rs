@@ -1,15 +1,39 @@
 pub mod admin_device;
+pub mod audit_log;
+pub mod auth_rate_limit;
 pub mod cart;
+pub mod cart_reminder;
 pub mod category;
+pub mod guest_cart;
 pub mod invoice;
 pub mod invoice_item;
+pub mod notification_preference;
 pub mod order;
 pub mod order_item;
-pub mod password_reset_otp;
+pub mod otp;
 pub mod payment;
 pub mod product;
 pub mod product_image;
 pub mod refresh_token;
+pub mod revoked_access_token;
+pub mod role;
+pub mod role_permission;
 pub mod shipment;
+pub mod subscription;
+pub mod subscription_item;
+pub mod subscription_order;
+pub mod two_factor_recovery_code;
 pub mod user;
+pub mod user_device;
+pub mod user_session;
+pub mod user_two_factor;
+pub mod wishlist;
 pub mod product_rating;
+pub mod product_rating_image;
+pub mod product_rating_summary;
+pub mod product_rating_vote;
+pub mod product_price_tier;
+pub mod product_attribute;
+pub mod product_availability_rule;
+pub mod product_tag;
+pub mod tag;
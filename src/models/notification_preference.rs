@@ -0,0 +1,19 @@
+use diesel::prelude::*;
+
+use super::user::User;
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::notification_preferences)]
+pub struct NewNotificationPreference {
+    user_id: i32,
+    cart_reminders: bool,
+}
+
+impl NewNotificationPreference {
+    pub fn new(user: &User, cart_reminders: bool) -> Self {
+        Self {
+            user_id: user.get_id(),
+            cart_reminders,
+        }
+    }
+}
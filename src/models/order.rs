@@ -96,36 +96,37 @@ pub struct NewOrder {
     amount: f64,
 }
 
+/// How a new order is placed, the totals are before delivery and discount.
+pub struct OrderDetails<'a> {
+    pub created_on: &'a str,
+    pub delivery_charge: f64,
+    pub delivery_status: DeliveryStatus,
+    pub delivery_location: &'a str,
+    pub order_total: f64,
+    pub quantity: f64,
+    pub status: OrderStatus,
+    pub discount: f64,
+}
+
 impl NewOrder {
-    pub fn new(
-        user: &User,
-        created_on: &String,
-        delivery_charge: f64,
-        delivery_status: DeliveryStatus,
-        delivery_location: &String,
-        order_total: f64,
-        quantity: f64,
-        status: OrderStatus,
-        discount: f64,
-    ) -> Self {
-        let created_on_clone = created_on.clone();
+    pub fn new(user: &User, details: OrderDetails) -> Self {
         Self {
             uuid: Uuid::new_v4().to_string(),
-            created_on: created_on.to_string(),
-            delivery_charge,
-            fulfilled_on: Self::get_delivery_duration(&created_on_clone),
-            delivery_location: delivery_location.to_string(),
-            delivery_status: delivery_status.value().to_owned(),
+            created_on: details.created_on.to_string(),
+            delivery_charge: details.delivery_charge,
+            fulfilled_on: Self::get_delivery_duration(details.created_on),
+            delivery_location: details.delivery_location.to_string(),
+            delivery_status: details.delivery_status.value().to_owned(),
             user_id: user.get_id(),
-            total_price: order_total + delivery_charge,
-            quantity,
-            status: status.value().to_owned(),
-            discount,
-            amount: order_total + delivery_charge - discount,
+            total_price: details.order_total + details.delivery_charge,
+            quantity: details.quantity,
+            status: details.status.value().to_owned(),
+            discount: details.discount,
+            amount: details.order_total + details.delivery_charge - details.discount,
         }
     }
 
-    pub fn get_delivery_duration(created_on: &String) -> String {
+    pub fn get_delivery_duration(created_on: &str) -> String {
         let created_on_dt: chrono::NaiveDateTime =
             chrono::NaiveDateTime::parse_from_str(created_on, "%Y-%m-%d %H:%M:%S")
                 .expect("Error parsing order date");
@@ -2,6 +2,7 @@ use diesel::prelude::*;
 use uuid::Uuid;
 
 use super::{order::Order, product::Product};
+use crate::services::pricing_service::AppliedPrice;
 
 #[derive(Queryable, Selectable, Associations, Identifiable)]
 #[diesel(table_name = crate::schema::order_items)]
@@ -16,6 +17,8 @@ pub struct OrderItem {
     price: f64,
     discount: f64,
     amount: f64,
+    price_tier_id: Option<i32>,
+    tier_min_quantity: Option<f64>,
 }
 
 impl OrderItem {
@@ -50,6 +53,14 @@ impl OrderItem {
     pub fn get_discount(&self) -> f64 {
         self.discount
     }
+
+    pub fn get_price_tier_id(&self) -> Option<i32> {
+        self.price_tier_id
+    }
+
+    pub fn get_tier_min_quantity(&self) -> Option<f64> {
+        self.tier_min_quantity
+    }
 }
 
 #[derive(Insertable)]
@@ -62,18 +73,36 @@ pub struct NewOrderItem {
     price: f64,
     discount: f64,
     amount: f64,
+    price_tier_id: Option<i32>,
+    tier_min_quantity: Option<f64>,
+    awaiting_stock: bool,
 }
 
 impl NewOrderItem {
-    pub fn new(quantity: f64, discount: f64, product: &Product, order: &Order) -> Self {
+    pub fn new(
+        quantity: f64,
+        discount: f64,
+        product: &Product,
+        order: &Order,
+        applied_price: &AppliedPrice,
+    ) -> Self {
         Self {
             uuid: Uuid::new_v4().to_string(),
             quantity,
             product_id: product.get_id(),
             order_id: order.get_id(),
-            price: quantity * product.get_price(),
+            price: applied_price.total_for(quantity),
             discount,
-            amount: quantity * product.get_price() + discount,
+            amount: applied_price.total_for(quantity) + discount,
+            price_tier_id: applied_price.tier_id,
+            tier_min_quantity: applied_price.tier_min_quantity,
+            awaiting_stock: false,
         }
     }
+
+    /// Marks the item as pre-ordered, it is reserved until the stock arrives.
+    pub fn reserved(mut self) -> Self {
+        self.awaiting_stock = true;
+        self
+    }
 }
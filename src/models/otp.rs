@@ -0,0 +1,56 @@
+use diesel::prelude::*;
+
+use crate::base_types::{otp_channel::OtpChannel, otp_purpose::OtpPurpose};
+
+#[derive(Queryable, Selectable, Identifiable, Debug)]
+#[diesel(table_name = crate::schema::otps)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct Otp {
+    pub id: i32,
+    pub purpose: String,
+    pub channel: String,
+    pub recipient: String,
+    pub user_id: Option<i32>,
+    pub otp_code: String,
+    pub expires_at: String,
+    pub is_used: bool,
+    pub attempts: i32,
+    pub created_at: String,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::otps)]
+pub struct NewOtp {
+    pub purpose: String,
+    pub channel: String,
+    pub recipient: String,
+    pub user_id: Option<i32>,
+    pub otp_code: String,
+    pub expires_at: String,
+    pub is_used: bool,
+    pub attempts: i32,
+}
+
+impl NewOtp {
+    pub fn new(
+        purpose: &OtpPurpose,
+        channel: &OtpChannel,
+        recipient: &str,
+        user_id: Option<i32>,
+        otp_code: String,
+    ) -> Self {
+        let expires_at =
+            chrono::Utc::now() + chrono::Duration::minutes(purpose.expiry_minutes() as i64);
+        let expires_at = expires_at.to_rfc3339();
+        Self {
+            purpose: purpose.value().to_owned(),
+            channel: channel.value().to_owned(),
+            recipient: recipient.to_owned(),
+            user_id,
+            otp_code,
+            expires_at,
+            is_used: false,
+            attempts: 0,
+        }
+    }
+}
@@ -19,6 +19,11 @@ pub struct Product {
     unit_change: f64,
     stock: f64,
     category_id: i32,
+    is_archived: bool,
+    is_preorder: bool,
+    preorder_available_on: Option<String>,
+    preorder_capacity: f64,
+    preorder_reserved: f64,
 }
 
 impl Product {
@@ -45,6 +50,10 @@ impl Product {
         self.price
     }
 
+    pub fn get_previous_price(&self) -> f64 {
+        self.previous_price
+    }
+
     pub fn get_stock(&self) -> f64 {
         self.stock
     }
@@ -57,6 +66,23 @@ impl Product {
         self.unit_change
     }
 
+    pub fn is_archived(&self) -> bool {
+        self.is_archived
+    }
+
+    pub fn is_preorder(&self) -> bool {
+        self.is_preorder
+    }
+
+    pub fn get_preorder_reserved(&self) -> f64 {
+        self.preorder_reserved
+    }
+
+    /// How much can still be pre-ordered before the capacity is used up.
+    pub fn get_preorder_remaining(&self) -> f64 {
+        (self.preorder_capacity - self.preorder_reserved).max(0.0)
+    }
+
     //I have created a dependency of this model to contracts which should be avoided
     pub fn as_response(&self, category: &Category) -> crate::contracts::product::Product {
         crate::contracts::product::Product {
@@ -70,6 +96,16 @@ impl Product {
             unit_change: self.unit_change,
             stock: self.stock,
             category: crate::contracts::category::Category::new(category.get_uuid(), category.get_name()),
+            tags: Vec::new(),
+            attributes: Vec::new(),
+            available: !self.is_archived,
+            available_from: None,
+            pre_order: self.is_preorder.then(|| crate::contracts::product::PreOrder {
+                expected_on: self.preorder_available_on.to_owned(),
+                capacity: self.preorder_capacity,
+                remaining: self.get_preorder_remaining(),
+            }),
+            rating: Default::default(),
         }
     }
 }
@@ -89,28 +125,30 @@ pub struct NewProduct {
     category_id: i32,
 }
 
+/// What is filled in for a new product, the product goes into a category.
+pub struct ProductDetails {
+    pub name: String,
+    pub description: String,
+    pub image: String,
+    pub price: f64,
+    pub previous_price: f64,
+    pub unit: String,
+    pub unit_change: f64,
+    pub stock: f64,
+}
+
 impl NewProduct {
-    pub fn new(
-        name: String,
-        description: String,
-        image: String,
-        price: f64,
-        previous_price: f64,
-        unit: String,
-        unit_change: f64,
-        stock: f64,
-        category: &Category,
-    ) -> Self {
+    pub fn new(details: ProductDetails, category: &Category) -> Self {
         Self {
             uuid: Uuid::new_v4().to_string(),
-            name,
-            description,
-            image,
-            price,
-            previous_price,
-            unit,
-            unit_change,
-            stock,
+            name: details.name,
+            description: details.description,
+            image: details.image,
+            price: details.price,
+            previous_price: details.previous_price,
+            unit: details.unit,
+            unit_change: details.unit_change,
+            stock: details.stock,
             category_id: category.get_id(),
         }
     }
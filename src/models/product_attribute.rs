@@ -0,0 +1,46 @@
+use diesel::prelude::*;
+
+use super::product::Product;
+
+#[derive(Queryable, Selectable, Identifiable, Associations)]
+#[diesel(table_name = crate::schema::product_attributes)]
+#[diesel(belongs_to(Product))]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct ProductAttribute {
+    id: i32,
+    product_id: i32,
+    name: String,
+    value: String,
+}
+
+impl ProductAttribute {
+    pub fn get_product_id(&self) -> i32 {
+        self.product_id
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_value(&self) -> &str {
+        &self.value
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::product_attributes)]
+pub struct NewProductAttribute {
+    product_id: i32,
+    name: String,
+    value: String,
+}
+
+impl NewProductAttribute {
+    pub fn new(product: &Product, name: String, value: String) -> Self {
+        Self {
+            product_id: product.get_id(),
+            name,
+            value,
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use chrono::{Datelike, NaiveDate};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use super::product::Product;
+
+#[derive(Queryable, Selectable, Identifiable, Associations)]
+#[diesel(table_name = crate::schema::product_availability_rules)]
+#[diesel(belongs_to(Product))]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct ProductAvailabilityRule {
+    id: i32,
+    uuid: String,
+    product_id: i32,
+    start_day: Option<String>,
+    end_day: Option<String>,
+    weekday_mask: i32,
+}
+
+impl ProductAvailabilityRule {
+    pub fn get_uuid(&self) -> &str {
+        &self.uuid
+    }
+
+    pub fn get_product_id(&self) -> i32 {
+        self.product_id
+    }
+
+    pub fn get_start_day(&self) -> Option<&str> {
+        self.start_day.as_deref()
+    }
+
+    pub fn get_end_day(&self) -> Option<&str> {
+        self.end_day.as_deref()
+    }
+
+    pub fn get_weekday_mask(&self) -> i32 {
+        self.weekday_mask
+    }
+
+    /// Whether the rule lets the product be sold on `date`. Days are stored as
+    /// MM-DD so they compare correctly as plain strings.
+    pub fn is_open_on(&self, date: NaiveDate) -> bool {
+        let weekday_bit = 1 << date.weekday().num_days_from_monday();
+        if self.weekday_mask & weekday_bit == 0 {
+            return false;
+        }
+
+        match (self.start_day.as_deref(), self.end_day.as_deref()) {
+            (Some(start), Some(end)) => {
+                let day = date.format("%m-%d").to_string();
+                if start <= end {
+                    start <= day.as_str() && day.as_str() <= end
+                } else {
+                    // the range wraps over the new year
+                    day.as_str() >= start || day.as_str() <= end
+                }
+            }
+            _ => true,
+        }
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::product_availability_rules)]
+pub struct NewProductAvailabilityRule {
+    uuid: String,
+    product_id: i32,
+    start_day: Option<String>,
+    end_day: Option<String>,
+    weekday_mask: i32,
+}
+
+impl NewProductAvailabilityRule {
+    pub fn new(
+        product: &Product,
+        start_day: Option<String>,
+        end_day: Option<String>,
+        weekday_mask: i32,
+    ) -> Self {
+        Self {
+            uuid: Uuid::new_v4().to_string(),
+            product_id: product.get_id(),
+            start_day,
+            end_day,
+            weekday_mask,
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use super::product::Product;
+
+#[derive(Queryable, Selectable, Identifiable, Associations)]
+#[diesel(table_name = crate::schema::product_price_tiers)]
+#[diesel(belongs_to(Product))]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct ProductPriceTier {
+    id: i32,
+    uuid: String,
+    product_id: i32,
+    min_quantity: f64,
+    price: f64,
+}
+
+impl ProductPriceTier {
+    pub fn get_id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn get_uuid(&self) -> &str {
+        &self.uuid
+    }
+
+    pub fn get_min_quantity(&self) -> f64 {
+        self.min_quantity
+    }
+
+    pub fn get_price(&self) -> f64 {
+        self.price
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::product_price_tiers)]
+pub struct NewProductPriceTier {
+    uuid: String,
+    product_id: i32,
+    min_quantity: f64,
+    price: f64,
+}
+
+impl NewProductPriceTier {
+    pub fn new(product: &Product, min_quantity: f64, price: f64) -> Self {
+        Self {
+            uuid: Uuid::new_v4().to_string(),
+            product_id: product.get_id(),
+            min_quantity,
+            price,
+        }
+    }
+}
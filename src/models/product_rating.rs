@@ -1,4 +1,5 @@
 use super::{product::Product, user::User};
+use crate::base_types::moderation_status::ModerationStatus;
 use diesel::prelude::*;
 
 #[derive(Queryable, Selectable, Identifiable, Associations)]
@@ -13,7 +14,12 @@ pub struct ProductRating {
     rating: f64,
     review: Option<String>,
     created_at: chrono::NaiveDateTime,
-    updated_at: chrono::NaiveDateTime,
+    updated_at: Option<chrono::NaiveDateTime>,
+    verified_purchase: bool,
+    moderation_status: String,
+    admin_reply: Option<String>,
+    replied_at: Option<chrono::NaiveDateTime>,
+    helpful_count: i32,
 }
 
 impl ProductRating {
@@ -21,6 +27,10 @@ impl ProductRating {
         self.id
     }
 
+    pub fn get_uuid(&self) -> &str {
+        &self.uuid
+    }
+
     pub fn get_product_id(&self) -> i32 {
         self.product_id
     }
@@ -41,9 +51,29 @@ impl ProductRating {
         self.created_at
     }
 
-    pub fn get_updated_at(&self) -> chrono::NaiveDateTime {
+    pub fn get_updated_at(&self) -> Option<chrono::NaiveDateTime> {
         self.updated_at
     }
+
+    pub fn is_verified_purchase(&self) -> bool {
+        self.verified_purchase
+    }
+
+    pub fn get_moderation_status(&self) -> &str {
+        &self.moderation_status
+    }
+
+    pub fn get_admin_reply(&self) -> Option<&str> {
+        self.admin_reply.as_deref()
+    }
+
+    pub fn get_replied_at(&self) -> Option<chrono::NaiveDateTime> {
+        self.replied_at
+    }
+
+    pub fn get_helpful_count(&self) -> i32 {
+        self.helpful_count
+    }
 }
 
 #[derive(Insertable)]
@@ -54,16 +84,26 @@ pub struct NewProductRating {
     user_id: i32,
     rating: f64,
     review: Option<String>,
+    verified_purchase: bool,
+    moderation_status: String,
 }
 
 impl NewProductRating {
-    pub fn new(product: &Product, user: &User, rating: f64, review: Option<String>) -> Self {
+    pub fn new(
+        product: &Product,
+        user: &User,
+        rating: f64,
+        review: Option<String>,
+        verified_purchase: bool,
+    ) -> Self {
         Self {
             uuid: uuid::Uuid::new_v4().to_string(),
             product_id: product.get_id(),
             user_id: user.get_id(),
             rating,
             review,
+            verified_purchase,
+            moderation_status: ModerationStatus::Pending.value().to_owned(),
         }
     }
 }
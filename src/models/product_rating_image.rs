@@ -0,0 +1,46 @@
+use diesel::prelude::*;
+
+use super::product_rating::ProductRating;
+
+#[derive(Queryable, Selectable, Associations, Identifiable)]
+#[diesel(table_name = crate::schema::product_rating_images)]
+#[diesel(belongs_to(ProductRating, foreign_key = rating_id))]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct ProductRatingImage {
+    id: i32,
+    uuid: String,
+    rating_id: i32,
+    image_name: String,
+}
+
+impl ProductRatingImage {
+    pub fn get_uuid(&self) -> &str {
+        &self.uuid
+    }
+
+    pub fn get_rating_id(&self) -> i32 {
+        self.rating_id
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.image_name
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::product_rating_images)]
+pub struct NewProductRatingImage {
+    uuid: String,
+    rating_id: i32,
+    image_name: String,
+}
+
+impl NewProductRatingImage {
+    pub fn new(image_name: &str, rating: &ProductRating) -> Self {
+        Self {
+            uuid: uuid::Uuid::new_v4().to_string(),
+            rating_id: rating.get_id(),
+            image_name: image_name.to_owned(),
+        }
+    }
+}
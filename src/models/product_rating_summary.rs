@@ -0,0 +1,46 @@
+use diesel::prelude::*;
+
+use super::product::Product;
+
+/// Aggregate of a product's approved ratings. Rows are written only by the
+/// database triggers on `product_ratings`, so there is no insertable.
+#[derive(Queryable, Selectable, Identifiable, Associations)]
+#[diesel(table_name = crate::schema::product_rating_summaries)]
+#[diesel(primary_key(product_id))]
+#[diesel(belongs_to(Product))]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct ProductRatingSummary {
+    product_id: i32,
+    rating_count: i32,
+    average_rating: f64,
+    one_star: i32,
+    two_star: i32,
+    three_star: i32,
+    four_star: i32,
+    five_star: i32,
+}
+
+impl ProductRatingSummary {
+    pub fn get_product_id(&self) -> i32 {
+        self.product_id
+    }
+
+    pub fn get_rating_count(&self) -> i32 {
+        self.rating_count
+    }
+
+    pub fn get_average_rating(&self) -> f64 {
+        self.average_rating
+    }
+
+    /// Number of ratings per star, one star first.
+    pub fn get_histogram(&self) -> [i32; 5] {
+        [
+            self.one_star,
+            self.two_star,
+            self.three_star,
+            self.four_star,
+            self.five_star,
+        ]
+    }
+}
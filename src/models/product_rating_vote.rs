@@ -0,0 +1,21 @@
+use diesel::prelude::*;
+
+use super::{product_rating::ProductRating, user::User};
+
+/// A user marking a review as helpful. The `helpful_count` on the rating is
+/// kept in step by database triggers.
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::product_rating_votes)]
+pub struct NewProductRatingVote {
+    rating_id: i32,
+    user_id: i32,
+}
+
+impl NewProductRatingVote {
+    pub fn new(rating: &ProductRating, user: &User) -> Self {
+        Self {
+            rating_id: rating.get_id(),
+            user_id: user.get_id(),
+        }
+    }
+}
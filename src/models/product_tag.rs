@@ -0,0 +1,30 @@
+use diesel::prelude::*;
+
+use super::{product::Product, tag::Tag};
+
+#[derive(Queryable, Selectable, Identifiable, Associations)]
+#[diesel(table_name = crate::schema::product_tags)]
+#[diesel(belongs_to(Product))]
+#[diesel(belongs_to(Tag))]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct ProductTag {
+    id: i32,
+    product_id: i32,
+    tag_id: i32,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::product_tags)]
+pub struct NewProductTag {
+    product_id: i32,
+    tag_id: i32,
+}
+
+impl NewProductTag {
+    pub fn new(product: &Product, tag: &Tag) -> Self {
+        Self {
+            product_id: product.get_id(),
+            tag_id: tag.get_id(),
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use chrono::NaiveDateTime;
 use diesel::prelude::*;
 use uuid::Uuid;
 
@@ -10,9 +11,13 @@ use crate::models::user::User;
 pub struct RefreshToken {
     id: i32,
     uuid: String,
-    token: String,
+    token_hash: String,
+    family_id: String,
     user_id: i32,
     expires_on: String,
+    created_at: NaiveDateTime,
+    rotated_at: Option<NaiveDateTime>,
+    revoked_at: Option<NaiveDateTime>,
 }
 
 impl RefreshToken {
@@ -20,37 +25,43 @@ impl RefreshToken {
         self.id
     }
 
-    pub fn get_uuid(&self) -> &str {
-        &self.uuid
+    pub fn get_family_id(&self) -> &str {
+        &self.family_id
     }
 
     pub fn get_user_id(&self) -> i32 {
         self.user_id
     }
 
-    pub fn get_token(&self) -> &str {
-        &self.token
-    }
-
     pub fn get_expires_on(&self) -> &str {
         &self.expires_on
     }
+
+    pub fn is_rotated(&self) -> bool {
+        self.rotated_at.is_some()
+    }
+
+    pub fn is_revoked(&self) -> bool {
+        self.revoked_at.is_some()
+    }
 }
 
 #[derive(Insertable)]
 #[diesel(table_name = crate::schema::refresh_tokens)]
 pub struct NewRefreshToken {
     uuid: String,
-    token: String,
+    token_hash: String,
+    family_id: String,
     user_id: i32,
     expires_on: String,
 }
 
 impl NewRefreshToken {
-    pub fn new(user: &User, token: String, expires_on: &String) -> Self {
+    pub fn new(user: &User, token_hash: String, family_id: &str, expires_on: &String) -> Self {
         Self {
             uuid: Uuid::new_v4().to_string(),
-            token,
+            token_hash,
+            family_id: family_id.to_owned(),
             user_id: user.get_id(),
             expires_on: expires_on.to_owned(),
         }
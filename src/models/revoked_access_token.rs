@@ -0,0 +1,22 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+
+use super::user::User;
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::revoked_access_tokens)]
+pub struct NewRevokedAccessToken {
+    jti: String,
+    user_id: i32,
+    expires_at: NaiveDateTime,
+}
+
+impl NewRevokedAccessToken {
+    pub fn new(user: &User, jti: &str, expires_at: NaiveDateTime) -> Self {
+        Self {
+            jti: jti.to_owned(),
+            user_id: user.get_id(),
+            expires_at,
+        }
+    }
+}
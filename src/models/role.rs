@@ -0,0 +1,42 @@
+use diesel::prelude::*;
+
+#[derive(Queryable, Selectable, Identifiable)]
+#[diesel(table_name = crate::schema::roles)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct Role {
+    id: i32,
+    name: String,
+    description: Option<String>,
+    is_system: bool,
+}
+
+impl Role {
+    pub fn get_id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn is_system(&self) -> bool {
+        self.is_system
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::roles)]
+pub struct NewRole {
+    name: String,
+    description: Option<String>,
+}
+
+impl NewRole {
+    pub fn new(name: String, description: Option<String>) -> Self {
+        Self { name, description }
+    }
+}
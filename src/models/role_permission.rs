@@ -0,0 +1,17 @@
+use diesel::prelude::*;
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::role_permissions)]
+pub struct NewRolePermission {
+    role_id: i32,
+    permission_id: i32,
+}
+
+impl NewRolePermission {
+    pub fn new(role_id: i32, permission_id: i32) -> Self {
+        Self {
+            role_id,
+            permission_id,
+        }
+    }
+}
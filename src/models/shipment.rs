@@ -86,7 +86,7 @@ impl NewShipment {
         let parts: Vec<&str> = address.split(',').map(|s| s.trim()).collect();
         //address should in the below format address,city,state,country
 
-        let address_part = parts.get(0).unwrap_or(&"").to_string();
+        let address_part = parts.first().unwrap_or(&"").to_string();
         let zip_code = parts.get(1).unwrap_or(&"").to_string();
         let city_part = parts.get(2).unwrap_or(&"").to_string();
         let state_part = parts.get(3).unwrap_or(&"").to_string();
@@ -99,7 +99,7 @@ impl NewShipment {
             city: city_part,
             state: state_part,
             country: country_part,
-            zip_code: zip_code,
+            zip_code,
             order_id: order.get_id(),
             status: order.get_delivery_status().to_string(),
             assigned_to: None,
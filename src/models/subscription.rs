@@ -0,0 +1,98 @@
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use super::user::User;
+use crate::base_types::{
+    payment_method::PaymentMethod, subscription_frequency::SubscriptionFrequency,
+    subscription_status::SubscriptionStatus,
+};
+
+#[derive(Queryable, Selectable, Identifiable, Associations)]
+#[diesel(table_name = crate::schema::subscriptions)]
+#[diesel(belongs_to(User))]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct Subscription {
+    id: i32,
+    uuid: String,
+    user_id: i32,
+    frequency: String,
+    delivery_slot: String,
+    delivery_location: String,
+    payment_method: String,
+    status: String,
+    next_delivery_on: String,
+    created_at: chrono::NaiveDateTime,
+}
+
+impl Subscription {
+    pub fn get_id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn get_uuid(&self) -> &str {
+        &self.uuid
+    }
+
+    pub fn get_frequency(&self) -> &str {
+        &self.frequency
+    }
+
+    pub fn get_delivery_slot(&self) -> &str {
+        &self.delivery_slot
+    }
+
+    pub fn get_delivery_location(&self) -> &str {
+        &self.delivery_location
+    }
+
+    pub fn get_payment_method(&self) -> &str {
+        &self.payment_method
+    }
+
+    pub fn get_status(&self) -> &str {
+        &self.status
+    }
+
+    pub fn get_next_delivery_on(&self) -> &str {
+        &self.next_delivery_on
+    }
+
+    pub fn get_created_at(&self) -> chrono::NaiveDateTime {
+        self.created_at
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::subscriptions)]
+pub struct NewSubscription {
+    uuid: String,
+    user_id: i32,
+    frequency: String,
+    delivery_slot: String,
+    delivery_location: String,
+    payment_method: String,
+    status: String,
+    next_delivery_on: String,
+}
+
+impl NewSubscription {
+    pub fn new(
+        user: &User,
+        frequency: &SubscriptionFrequency,
+        delivery_slot: &str,
+        delivery_location: &str,
+        payment_method: &PaymentMethod,
+        next_delivery_on: chrono::NaiveDate,
+    ) -> Self {
+        Self {
+            uuid: Uuid::new_v4().to_string(),
+            user_id: user.get_id(),
+            frequency: frequency.value().to_owned(),
+            delivery_slot: delivery_slot.to_owned(),
+            delivery_location: delivery_location.to_owned(),
+            payment_method: payment_method.value().to_owned(),
+            status: SubscriptionStatus::Active.value().to_owned(),
+            next_delivery_on: next_delivery_on.format("%Y-%m-%d").to_string(),
+        }
+    }
+}
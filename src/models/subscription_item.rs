@@ -0,0 +1,47 @@
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use super::{product::Product, subscription::Subscription};
+
+#[derive(Queryable, Selectable, Identifiable, Associations)]
+#[diesel(table_name = crate::schema::subscription_items)]
+#[diesel(belongs_to(Subscription))]
+#[diesel(belongs_to(Product))]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct SubscriptionItem {
+    id: i32,
+    uuid: String,
+    subscription_id: i32,
+    product_id: i32,
+    quantity: f64,
+}
+
+impl SubscriptionItem {
+    pub fn get_uuid(&self) -> &str {
+        &self.uuid
+    }
+
+    pub fn get_quantity(&self) -> f64 {
+        self.quantity
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::subscription_items)]
+pub struct NewSubscriptionItem {
+    uuid: String,
+    subscription_id: i32,
+    product_id: i32,
+    quantity: f64,
+}
+
+impl NewSubscriptionItem {
+    pub fn new(subscription: &Subscription, product: &Product, quantity: f64) -> Self {
+        Self {
+            uuid: Uuid::new_v4().to_string(),
+            subscription_id: subscription.get_id(),
+            product_id: product.get_id(),
+            quantity,
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use diesel::prelude::*;
+
+use super::{order::Order, subscription::Subscription};
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::subscription_orders)]
+pub struct NewSubscriptionOrder {
+    subscription_id: i32,
+    order_id: i32,
+    delivery_on: String,
+}
+
+impl NewSubscriptionOrder {
+    pub fn new(subscription: &Subscription, order: &Order) -> Self {
+        Self {
+            subscription_id: subscription.get_id(),
+            order_id: order.get_id(),
+            delivery_on: subscription.get_next_delivery_on().to_owned(),
+        }
+    }
+}
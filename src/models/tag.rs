@@ -0,0 +1,48 @@
+use diesel::prelude::*;
+use uuid::Uuid;
+
+#[derive(Queryable, Selectable, Identifiable)]
+#[diesel(table_name = crate::schema::tags)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct Tag {
+    id: i32,
+    uuid: String,
+    name: String,
+    slug: String,
+}
+
+impl Tag {
+    pub fn get_id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn get_uuid(&self) -> &str {
+        &self.uuid
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_slug(&self) -> &str {
+        &self.slug
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::tags)]
+pub struct NewTag {
+    uuid: String,
+    name: String,
+    slug: String,
+}
+
+impl NewTag {
+    pub fn new(name: String, slug: String) -> Self {
+        Self {
+            uuid: Uuid::new_v4().to_string(),
+            name,
+            slug,
+        }
+    }
+}
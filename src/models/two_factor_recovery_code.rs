@@ -0,0 +1,19 @@
+use diesel::prelude::*;
+
+use super::user::User;
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::two_factor_recovery_codes)]
+pub struct NewTwoFactorRecoveryCode {
+    user_id: i32,
+    code_hash: String,
+}
+
+impl NewTwoFactorRecoveryCode {
+    pub fn new(user: &User, code_hash: String) -> Self {
+        Self {
+            user_id: user.get_id(),
+            code_hash,
+        }
+    }
+}
@@ -21,6 +21,7 @@ pub struct User {
     user_type: String,
     location: Option<String>,
     nearest_landmark: Option<String>,
+    email_verified_at: Option<chrono::NaiveDateTime>,
 }
 
 impl User {
@@ -28,31 +29,6 @@ impl User {
     pub const USERTYPE_CUSTOMER: &'static str = "Customer";
     pub const USERTYPE_DELIVERY: &'static str = "Delivery";
 
-    pub fn new(
-        id: i32,
-        uuid: String,
-        first_name: String,
-        last_name: String,
-        phone_number: String,
-        email: String,
-        password: String,
-        location: Option<String>,
-        nearest_landmark: Option<String>,
-    ) -> Self {
-        Self {
-            id,
-            uuid,
-            first_name,
-            last_name,
-            phone_number,
-            email,
-            password,
-            user_type: Self::USERTYPE_CUSTOMER.to_string(),
-            location,
-            nearest_landmark,
-        }
-    }
-
     pub fn get_id(&self) -> i32 {
         self.id
     }
@@ -108,11 +84,14 @@ impl User {
     pub fn get_nearest_landmark(&self) -> Option<&str> {
         self.nearest_landmark.as_deref()
     }
+
+    pub fn is_email_verified(&self) -> bool {
+        self.email_verified_at.is_some()
+    }
 }
 
 #[derive(Insertable)]
 #[diesel(table_name = crate::schema::users)]
-
 pub struct NewUser {
     uuid: String,
     first_name: String,
@@ -0,0 +1,22 @@
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use super::user::User;
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::user_devices)]
+pub struct NewUserDevice {
+    uuid: String,
+    user_id: i32,
+    fcm_token: String,
+}
+
+impl NewUserDevice {
+    pub fn new(user: &User, fcm_token: String) -> Self {
+        Self {
+            uuid: Uuid::new_v4().to_string(),
+            user_id: user.get_id(),
+            fcm_token,
+        }
+    }
+}
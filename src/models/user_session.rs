@@ -0,0 +1,80 @@
+use diesel::prelude::*;
+
+use super::user::User;
+
+#[derive(Queryable, Selectable, Identifiable, Associations)]
+#[diesel(table_name = crate::schema::user_sessions)]
+#[diesel(belongs_to(User))]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct UserSession {
+    id: i32,
+    uuid: String,
+    user_id: i32,
+    device_name: Option<String>,
+    ip_address: Option<String>,
+    user_agent: Option<String>,
+    expires_on: String,
+    created_at: chrono::NaiveDateTime,
+    last_used_at: chrono::NaiveDateTime,
+}
+
+impl UserSession {
+    pub fn get_uuid(&self) -> &str {
+        &self.uuid
+    }
+
+    pub fn get_device_name(&self) -> Option<&str> {
+        self.device_name.as_deref()
+    }
+
+    pub fn get_ip_address(&self) -> Option<&str> {
+        self.ip_address.as_deref()
+    }
+
+    pub fn get_user_agent(&self) -> Option<&str> {
+        self.user_agent.as_deref()
+    }
+
+    pub fn get_expires_on(&self) -> &str {
+        &self.expires_on
+    }
+
+    pub fn get_created_at(&self) -> chrono::NaiveDateTime {
+        self.created_at
+    }
+
+    pub fn get_last_used_at(&self) -> chrono::NaiveDateTime {
+        self.last_used_at
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::user_sessions)]
+pub struct NewUserSession {
+    uuid: String,
+    user_id: i32,
+    device_name: Option<String>,
+    ip_address: Option<String>,
+    user_agent: Option<String>,
+    expires_on: String,
+}
+
+impl NewUserSession {
+    pub fn new(
+        user: &User,
+        session_id: &str,
+        device_name: Option<String>,
+        ip_address: Option<String>,
+        user_agent: Option<String>,
+        expires_on: &String,
+    ) -> Self {
+        Self {
+            uuid: session_id.to_owned(),
+            user_id: user.get_id(),
+            device_name,
+            ip_address,
+            user_agent,
+            expires_on: expires_on.to_owned(),
+        }
+    }
+}
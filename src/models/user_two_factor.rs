@@ -0,0 +1,45 @@
+use diesel::prelude::*;
+
+use super::user::User;
+
+#[derive(Queryable, Selectable, Identifiable, Associations)]
+#[diesel(table_name = crate::schema::user_two_factors)]
+#[diesel(belongs_to(User))]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct UserTwoFactor {
+    id: i32,
+    user_id: i32,
+    secret: String,
+    enabled_at: Option<chrono::NaiveDateTime>,
+    last_used_step: Option<i64>,
+}
+
+impl UserTwoFactor {
+    pub fn get_secret(&self) -> &str {
+        &self.secret
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled_at.is_some()
+    }
+
+    pub fn get_last_used_step(&self) -> Option<i64> {
+        self.last_used_step
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::user_two_factors)]
+pub struct NewUserTwoFactor {
+    user_id: i32,
+    secret: String,
+}
+
+impl NewUserTwoFactor {
+    pub fn new(user: &User, secret: String) -> Self {
+        Self {
+            user_id: user.get_id(),
+            secret,
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use super::{product::Product, user::User};
+
+#[derive(Queryable, Selectable, Identifiable, Associations)]
+#[diesel(table_name = crate::schema::wishlists)]
+#[diesel(belongs_to(User))]
+#[diesel(belongs_to(Product))]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct Wishlist {
+    id: i32,
+    uuid: String,
+    user_id: i32,
+    product_id: i32,
+    notify: bool,
+    created_at: chrono::NaiveDateTime,
+}
+
+impl Wishlist {
+    pub fn get_uuid(&self) -> &str {
+        &self.uuid
+    }
+
+    pub fn get_notify(&self) -> bool {
+        self.notify
+    }
+
+    pub fn get_created_at(&self) -> chrono::NaiveDateTime {
+        self.created_at
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::wishlists)]
+pub struct NewWishlist {
+    uuid: String,
+    user_id: i32,
+    product_id: i32,
+    notify: bool,
+}
+
+impl NewWishlist {
+    pub fn new(user: &User, product: &Product, notify: bool) -> Self {
+        Self {
+            uuid: Uuid::new_v4().to_string(),
+            user_id: user.get_id(),
+            product_id: product.get_id(),
+            notify,
+        }
+    }
+}
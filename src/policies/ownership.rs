@@ -0,0 +1,80 @@
+use actix_web::{http::StatusCode, HttpResponse};
+use diesel::prelude::*;
+
+use crate::{
+    base_types::permission::Permission, middlewares::user_info::UserInfo, policies::permission,
+};
+
+/// A customer owned resource, identified by the uuid the client sends.
+#[derive(Clone, Copy)]
+pub enum Resource<'a> {
+    User(&'a str),
+    Cart(&'a str),
+    Order(&'a str),
+    OrderItem(&'a str),
+    Invoice(&'a str),
+    Subscription(&'a str),
+}
+
+// uuid of the user the resource belongs to, `None` when it doesn't exist
+fn owner_of(conn: &mut SqliteConnection, resource: Resource) -> QueryResult<Option<String>> {
+    use crate::schema::{carts, invoices, order_items, orders, subscriptions, users};
+
+    match resource {
+        Resource::User(id) => Ok(Some(id.to_owned())),
+        Resource::Cart(id) => carts::table
+            .inner_join(users::table)
+            .filter(carts::uuid.eq(id))
+            .select(users::uuid)
+            .first(conn)
+            .optional(),
+        Resource::Order(id) => orders::table
+            .inner_join(users::table)
+            .filter(orders::uuid.eq(id))
+            .select(users::uuid)
+            .first(conn)
+            .optional(),
+        Resource::OrderItem(id) => order_items::table
+            .inner_join(orders::table.inner_join(users::table))
+            .filter(order_items::uuid.eq(id))
+            .select(users::uuid)
+            .first(conn)
+            .optional(),
+        Resource::Invoice(id) => invoices::table
+            .inner_join(users::table)
+            .filter(invoices::uuid.eq(id))
+            .select(users::uuid)
+            .first(conn)
+            .optional(),
+        Resource::Subscription(id) => subscriptions::table
+            .inner_join(users::table)
+            .filter(subscriptions::uuid.eq(id))
+            .select(users::uuid)
+            .first(conn)
+            .optional(),
+    }
+}
+
+/// Allows the request when the signed in user owns `resource`, or their role
+/// may assist customers. A resource that doesn't exist is let through so the
+/// handler answers with its usual not found response.
+pub fn authorize(
+    conn: &mut SqliteConnection,
+    user_info: &UserInfo,
+    resource: Resource,
+) -> Result<(), HttpResponse> {
+    if permission::allows(conn, user_info, Permission::CustomersAssist)? {
+        return Ok(());
+    }
+
+    match owner_of(conn, resource) {
+        Ok(None) => Ok(()),
+        Ok(Some(owner)) if owner.eq_ignore_ascii_case(&user_info.user_id) => Ok(()),
+        Ok(Some(_)) => Err(HttpResponse::Forbidden()
+            .status(StatusCode::FORBIDDEN)
+            .json(serde_json::json!({"message": "You are not allowed to access this resource"}))),
+        Err(_) => Err(HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"}))),
+    }
+}
@@ -0,0 +1,50 @@
+use actix_web::{http::StatusCode, HttpResponse};
+use diesel::prelude::*;
+
+use crate::{
+    base_types::permission::Permission, middlewares::user_info::UserInfo,
+    services::permission_service,
+};
+
+/// Whether the signed in user's role has `permission`, for handlers that show
+/// or allow more to staff than to customers. Nothing is allowed until a
+/// required two-factor setup is done, as in the permission middleware.
+pub fn allows(
+    conn: &mut SqliteConnection,
+    user_info: &UserInfo,
+    permission: Permission,
+) -> Result<bool, HttpResponse> {
+    if user_info.two_factor_setup_required {
+        return Ok(false);
+    }
+
+    permission_service::user_has_permission(conn, &user_info.user_id, permission).map_err(|_| {
+        HttpResponse::InternalServerError()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .json(serde_json::json!({"message": "Ops! something went wrong"}))
+    })
+}
+
+/// Allows the request when the signed in user's role has `permission`. For
+/// routes that share a scope with ones guarded by a different permission.
+pub fn authorize(
+    conn: &mut SqliteConnection,
+    user_info: &UserInfo,
+    permission: Permission,
+) -> Result<(), HttpResponse> {
+    if user_info.two_factor_setup_required {
+        return Err(HttpResponse::Forbidden()
+            .status(StatusCode::FORBIDDEN)
+            .json(
+                serde_json::json!({"message": "Two-factor authentication must be set up first"}),
+            ));
+    }
+
+    if allows(conn, user_info, permission)? {
+        Ok(())
+    } else {
+        Err(HttpResponse::Forbidden()
+            .status(StatusCode::FORBIDDEN)
+            .json(serde_json::json!({"message": "Insufficient permissions"})))
+    }
+}
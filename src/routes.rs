@@ -1,29 +1,55 @@
 use actix_web::web;
 
 use crate::{
+    base_types::permission::Permission,
     handlers::{
-        admin_device, auth, base_type, cart, category, invoice, invoice_item, order, order_item,
-        payment, product, shipment, user,
+        admin_device, audit_log, auth, base_type, cart, category, guest_cart, invoice,
+        invoice_item, notification_preference, order, order_item, payment, product,
+        product_attribute, product_availability_rule, product_pre_order, product_price_tier,
+        product_rating, role, session, setting, shipment, subscription, tag, two_factor, user,
+        user_device, wishlist,
     },
-    middlewares::auth_middleware::Auth,
+    middlewares::{audit_middleware::AuditLog, auth_middleware::Auth},
 };
 
 pub fn app_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/auth")
             .service(auth::login)
+            .service(auth::login_two_factor)
             .service(auth::refresh_token)
             .service(auth::reset_password_request)
             .service(auth::verify_otp)
-            .service(auth::reset_password),
+            .service(auth::reset_password)
+            .service(auth::request_phone_otp)
+            .service(auth::verify_phone_otp)
+            .service(auth::verify_email)
+            .service(auth::request_verification_email)
+            .service(
+                web::scope("")
+                    .wrap(Auth::authenticated())
+                    .service(auth::logout)
+                    .service(auth::logout_all)
+                    .service(auth::resend_verification_email)
+                    .service(session::get)
+                    .service(session::revoke)
+                    .service(two_factor::get)
+                    .service(two_factor::setup)
+                    .service(two_factor::enable)
+                    .service(two_factor::regenerate_recovery_codes)
+                    .service(two_factor::disable),
+            ),
     )
     .service(
-        web::scope("/users")
-            .service(user::create)
-            .service(user::get_user)
-            .service(user::get_user_from_phone_number)
-            .service(user::get_user_from_email)
-            .service(user::edit),
+        web::scope("/users").service(user::create).service(
+            web::scope("")
+                .wrap(Auth::authenticated())
+                .service(user::get_user)
+                .service(user::get_user_from_phone_number)
+                .service(user::get_user_from_email)
+                .service(user::request_phone_change_otp)
+                .service(user::edit),
+        ),
     )
     .service(
         web::scope("/products")
@@ -31,27 +57,41 @@ pub fn app_routes(cfg: &mut web::ServiceConfig) {
             .service(product::get_product)
             .service(product::get_product_images_list)
             .service(product::get_product_ratings)
+            .service(product_price_tier::get)
+            .service(product_availability_rule::get)
             .service(
                 web::scope("")
                     .wrap(Auth::authenticated())
                     .service(product::get_user_product_rating)
-                    .service(product::rate_product),
+                    .service(product::rate_product)
+                    .service(product_rating::upload_images)
+                    .service(product_rating::delete_image)
+                    .service(product_rating::mark_helpful)
+                    .service(product_rating::unmark_helpful),
             ),
     )
     .service(
         web::scope("/categories")
             .service(category::get)
+            .service(category::get_tree)
+            .service(category::get_category_by_slug)
             .service(category::get_category),
     )
+    .service(web::scope("/tags").service(tag::get))
     .service(
         web::scope("/orders")
             .wrap(Auth::authenticated())
+            .wrap(AuditLog::routes(&[
+                "/orders/{order_id}/delivery-status/update",
+            ]))
             .service(order::create)
             .service(order::edit)
             .service(order::get_order)
             .service(order::get_user_orders)
             .service(order::update_delivery_status)
-            .service(order::check_duplicate_order),
+            .service(order::check_duplicate_order)
+            .service(order::reorder)
+            .service(order::create_orders_from_cart),
     )
     .service(
         web::scope("/order-details")
@@ -67,6 +107,42 @@ pub fn app_routes(cfg: &mut web::ServiceConfig) {
             .service(cart::create)
             .service(cart::update_quantity),
     )
+    .service(
+        web::scope("/guest-carts")
+            .service(guest_cart::get)
+            .service(guest_cart::create)
+            .service(guest_cart::update_quantity)
+            .service(guest_cart::delete_cart),
+    )
+    .service(
+        web::scope("/wishlists")
+            .wrap(Auth::authenticated())
+            .service(wishlist::get)
+            .service(wishlist::add)
+            .service(wishlist::remove),
+    )
+    .service(
+        web::scope("/subscriptions")
+            .wrap(Auth::authenticated())
+            .service(subscription::get)
+            .service(subscription::create)
+            .service(subscription::edit)
+            .service(subscription::pause)
+            .service(subscription::resume)
+            .service(subscription::skip)
+            .service(subscription::cancel),
+    )
+    .service(
+        web::scope("/notification-preferences")
+            .wrap(Auth::authenticated())
+            .service(notification_preference::get)
+            .service(notification_preference::update),
+    )
+    .service(
+        web::scope("/devices")
+            .wrap(Auth::authenticated())
+            .service(user_device::register_fcm_token),
+    )
     .service(
         web::scope("/payments")
             .service(payment::esewa_payment_confirmation)
@@ -93,46 +169,121 @@ pub fn app_routes(cfg: &mut web::ServiceConfig) {
     )
     .service(
         web::scope("/base-types")
-            .wrap(Auth::require_roles(&["Admin", "Staff"]))
+            .wrap(Auth::require_permission(Permission::DashboardAccess))
             .service(base_type::get_delivery_status)
             .service(base_type::get_order_status)
             .service(base_type::get_shipment_status)
             .service(base_type::get_payment_methods)
-            .service(base_type::get_product_sku),
+            .service(base_type::get_product_sku)
+            .service(base_type::get_moderation_status)
+            .service(base_type::get_subscription_frequencies),
     )
     .service(
         web::scope("/admin")
-            .wrap(Auth::require_roles(&["Admin"]))
+            .wrap(AuditLog::mutations())
             .service(
                 web::scope("/categories")
+                    .wrap(Auth::require_permission(Permission::CategoriesManage))
+                    .service(category::get_all)
                     .service(category::create)
                     .service(category::edit)
+                    .service(category::upload_image)
                     .service(category::delete),
             )
             .service(
                 web::scope("/products")
+                    .wrap(Auth::require_permission(Permission::ProductsEdit))
                     .service(product::create)
                     .service(product::edit)
                     .service(product::upload_product_images)
-                    .service(product::delete),
+                    .service(product::delete)
+                    .service(product::update_product_stock)
+                    .service(product_price_tier::create)
+                    .service(product_price_tier::delete)
+                    .service(tag::set_product_tags)
+                    .service(product_attribute::set)
+                    .service(product_availability_rule::create)
+                    .service(product_availability_rule::delete)
+                    .service(product_pre_order::set)
+                    .service(product_pre_order::delete)
+                    .service(product_pre_order::receive_stock),
+            )
+            .service(
+                web::scope("/ratings")
+                    .wrap(Auth::require_permission(Permission::RatingsModerate))
+                    .service(product_rating::get_all)
+                    .service(product_rating::moderate)
+                    .service(product_rating::reply),
+            )
+            .service(
+                web::scope("/tags")
+                    .wrap(Auth::require_permission(Permission::TagsManage))
+                    .service(tag::create)
+                    .service(tag::delete),
             )
             .service(
                 web::scope("/users")
+                    .wrap(Auth::require_permission(Permission::UsersManage))
                     .service(user::get)
                     .service(user::get_staff_users)
+                    .service(session::get_user_sessions)
+                    .service(session::terminate_user_sessions)
+                    .service(session::terminate_user_session)
+                    .service(session::unlock_user)
+                    .service(role::assign_user_role)
                     .service(user::edit)
                     .service(user::delete),
             )
             .service(
                 web::scope("/shipments")
+                    .wrap(Auth::require_permission(Permission::ShipmentsView))
                     .service(shipment::get)
                     .service(shipment::assing_user_to_shipment),
             )
-            .service(web::scope("/payments").service(payment::get_all))
-            .service(web::scope("/device").service(admin_device::register_fcm_token))
-            .service(web::scope("/invoices").service(invoice::get_invoice_file))
+            .service(
+                web::scope("/payments")
+                    .wrap(Auth::require_permission(Permission::PaymentsView))
+                    .service(payment::get_all)
+                    .service(
+                        web::scope("")
+                            .wrap(Auth::require_permission(Permission::PaymentsRefund))
+                            .service(payment::complete_payment)
+                            .service(payment::refund_payment),
+                    ),
+            )
+            .service(
+                web::scope("/device")
+                    .wrap(Auth::require_permission(Permission::OrdersView))
+                    .service(admin_device::register_fcm_token),
+            )
+            .service(
+                web::scope("/invoices")
+                    .wrap(Auth::require_permission(Permission::InvoicesView))
+                    .service(invoice::get_invoice_file),
+            )
+            .service(
+                web::scope("/roles")
+                    .wrap(Auth::require_permission(Permission::RolesManage))
+                    .service(role::get_all)
+                    .service(role::get_permissions)
+                    .service(role::create)
+                    .service(role::edit)
+                    .service(role::delete),
+            )
+            .service(
+                web::scope("/audit-logs")
+                    .wrap(Auth::require_permission(Permission::AuditView))
+                    .service(audit_log::get_all),
+            )
+            .service(
+                web::scope("/settings")
+                    .wrap(Auth::require_permission(Permission::SettingsManage))
+                    .service(setting::get_security)
+                    .service(setting::set_security),
+            )
             .service(
                 web::scope("/orders")
+                    .wrap(Auth::require_permission(Permission::OrdersView))
                     .service(order::get_orders_count)
                     .service(order::update_order_status)
                     .service(order::update_delivery_status)
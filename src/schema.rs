@@ -11,6 +11,48 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    app_settings (id) {
+        id -> Integer,
+        setting_key -> Text,
+        setting_value -> Text,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    audit_logs (id) {
+        id -> Integer,
+        actor_user_id -> Nullable<Text>,
+        action -> Text,
+        entity_type -> Text,
+        entity_id -> Nullable<Text>,
+        changes -> Nullable<Text>,
+        status_code -> Integer,
+        ip_address -> Nullable<Text>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    auth_rate_limits (id) {
+        id -> Integer,
+        limit_key -> Text,
+        failures -> Integer,
+        last_failure_at -> Timestamp,
+        locked_until -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    cart_reminders (id) {
+        id -> Integer,
+        user_id -> Integer,
+        item_count -> Integer,
+        sent_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     carts (id) {
         id -> Integer,
@@ -21,6 +63,8 @@ diesel::table! {
         sku -> Text,
         created_on -> Text,
         discount -> Double,
+        rate -> Double,
+        updated_at -> Nullable<Timestamp>,
     }
 }
 
@@ -29,6 +73,25 @@ diesel::table! {
         id -> Integer,
         uuid -> Text,
         name -> Text,
+        parent_id -> Nullable<Integer>,
+        slug -> Text,
+        display_order -> Integer,
+        image -> Nullable<Text>,
+        is_active -> Bool,
+    }
+}
+
+diesel::table! {
+    guest_carts (id) {
+        id -> Integer,
+        uuid -> Text,
+        token -> Text,
+        product_id -> Integer,
+        quantity -> Double,
+        sku -> Text,
+        rate -> Double,
+        created_on -> Text,
+        expires_on -> Timestamp,
     }
 }
 
@@ -43,6 +106,8 @@ diesel::table! {
         total -> Double,
         product_id -> Integer,
         invoice_id -> Integer,
+        price_tier_id -> Nullable<Integer>,
+        tier_min_quantity -> Nullable<Double>,
     }
 }
 
@@ -62,6 +127,15 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    notification_preferences (id) {
+        id -> Integer,
+        user_id -> Integer,
+        cart_reminders -> Bool,
+        updated_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     order_items (id) {
         id -> Integer,
@@ -72,6 +146,9 @@ diesel::table! {
         price -> Double,
         discount -> Double,
         amount -> Double,
+        price_tier_id -> Nullable<Integer>,
+        tier_min_quantity -> Nullable<Double>,
+        awaiting_stock -> Bool,
     }
 }
 
@@ -94,9 +171,12 @@ diesel::table! {
 }
 
 diesel::table! {
-    password_reset_otps (id) {
+    otps (id) {
         id -> Integer,
-        user_id -> Integer,
+        purpose -> Text,
+        channel -> Text,
+        recipient -> Text,
+        user_id -> Nullable<Integer>,
         otp_code -> Text,
         expires_at -> Text,
         is_used -> Bool,
@@ -124,6 +204,26 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    product_attributes (id) {
+        id -> Integer,
+        product_id -> Integer,
+        name -> Text,
+        value -> Text,
+    }
+}
+
+diesel::table! {
+    product_availability_rules (id) {
+        id -> Integer,
+        uuid -> Text,
+        product_id -> Integer,
+        start_day -> Nullable<Text>,
+        end_day -> Nullable<Text>,
+        weekday_mask -> Integer,
+    }
+}
+
 diesel::table! {
     product_images (id) {
         id -> Integer,
@@ -133,6 +233,48 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    product_price_tiers (id) {
+        id -> Integer,
+        uuid -> Text,
+        product_id -> Integer,
+        min_quantity -> Double,
+        price -> Double,
+    }
+}
+
+diesel::table! {
+    product_rating_images (id) {
+        id -> Integer,
+        uuid -> Text,
+        rating_id -> Integer,
+        image_name -> Text,
+    }
+}
+
+diesel::table! {
+    product_rating_summaries (product_id) {
+        product_id -> Integer,
+        rating_count -> Integer,
+        rating_total -> Double,
+        average_rating -> Double,
+        one_star -> Integer,
+        two_star -> Integer,
+        three_star -> Integer,
+        four_star -> Integer,
+        five_star -> Integer,
+    }
+}
+
+diesel::table! {
+    product_rating_votes (id) {
+        id -> Integer,
+        rating_id -> Integer,
+        user_id -> Integer,
+        created_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     product_ratings (id) {
         id -> Integer,
@@ -143,6 +285,27 @@ diesel::table! {
         review -> Nullable<Text>,
         created_at -> Timestamp,
         updated_at -> Nullable<Timestamp>,
+        verified_purchase -> Bool,
+        moderation_status -> Text,
+        admin_reply -> Nullable<Text>,
+        replied_at -> Nullable<Timestamp>,
+        helpful_count -> Integer,
+    }
+}
+
+diesel::table! {
+    product_tags (id) {
+        id -> Integer,
+        product_id -> Integer,
+        tag_id -> Integer,
+    }
+}
+
+diesel::table! {
+    permissions (id) {
+        id -> Integer,
+        name -> Text,
+        description -> Text,
     }
 }
 
@@ -159,6 +322,11 @@ diesel::table! {
         unit_change -> Double,
         stock -> Double,
         category_id -> Integer,
+        is_archived -> Bool,
+        is_preorder -> Bool,
+        preorder_available_on -> Nullable<Text>,
+        preorder_capacity -> Double,
+        preorder_reserved -> Double,
     }
 }
 
@@ -166,9 +334,40 @@ diesel::table! {
     refresh_tokens (id) {
         id -> Integer,
         uuid -> Text,
-        token -> Text,
+        token_hash -> Text,
+        family_id -> Text,
         user_id -> Integer,
         expires_on -> Text,
+        created_at -> Timestamp,
+        rotated_at -> Nullable<Timestamp>,
+        revoked_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    revoked_access_tokens (id) {
+        id -> Integer,
+        jti -> Text,
+        user_id -> Integer,
+        expires_at -> Timestamp,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    role_permissions (role_id, permission_id) {
+        role_id -> Integer,
+        permission_id -> Integer,
+    }
+}
+
+diesel::table! {
+    roles (id) {
+        id -> Integer,
+        name -> Text,
+        description -> Nullable<Text>,
+        is_system -> Bool,
+        created_at -> Timestamp,
     }
 }
 
@@ -188,6 +387,98 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    subscription_items (id) {
+        id -> Integer,
+        uuid -> Text,
+        subscription_id -> Integer,
+        product_id -> Integer,
+        quantity -> Double,
+    }
+}
+
+diesel::table! {
+    subscription_orders (id) {
+        id -> Integer,
+        subscription_id -> Integer,
+        order_id -> Integer,
+        delivery_on -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    subscriptions (id) {
+        id -> Integer,
+        uuid -> Text,
+        user_id -> Integer,
+        frequency -> Text,
+        delivery_slot -> Text,
+        delivery_location -> Text,
+        payment_method -> Text,
+        status -> Text,
+        next_delivery_on -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    tags (id) {
+        id -> Integer,
+        uuid -> Text,
+        name -> Text,
+        slug -> Text,
+    }
+}
+
+diesel::table! {
+    two_factor_recovery_codes (id) {
+        id -> Integer,
+        user_id -> Integer,
+        code_hash -> Text,
+        used_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    user_devices (id) {
+        id -> Integer,
+        uuid -> Text,
+        user_id -> Integer,
+        fcm_token -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    user_sessions (id) {
+        id -> Integer,
+        uuid -> Text,
+        user_id -> Integer,
+        device_name -> Nullable<Text>,
+        ip_address -> Nullable<Text>,
+        user_agent -> Nullable<Text>,
+        expires_on -> Text,
+        created_at -> Timestamp,
+        last_used_at -> Timestamp,
+        revoked_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    user_two_factors (id) {
+        id -> Integer,
+        user_id -> Integer,
+        secret -> Text,
+        enabled_at -> Nullable<Timestamp>,
+        last_used_step -> Nullable<BigInt>,
+        created_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     users (id) {
         id -> Integer,
@@ -200,45 +491,109 @@ diesel::table! {
         user_type -> Text,
         location -> Nullable<Text>,
         nearest_landmark -> Nullable<Text>,
+        email_verified_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    wishlists (id) {
+        id -> Integer,
+        uuid -> Text,
+        user_id -> Integer,
+        product_id -> Integer,
+        notify -> Bool,
+        created_at -> Timestamp,
     }
 }
 
 diesel::joinable!(admin_devices -> users (user_id));
+diesel::joinable!(cart_reminders -> users (user_id));
 diesel::joinable!(carts -> products (product_id));
 diesel::joinable!(carts -> users (user_id));
+diesel::joinable!(guest_carts -> products (product_id));
 diesel::joinable!(invoice_items -> invoices (invoice_id));
 diesel::joinable!(invoice_items -> products (product_id));
 diesel::joinable!(invoices -> orders (order_id));
 diesel::joinable!(invoices -> payments (payment_id));
 diesel::joinable!(invoices -> users (user_id));
+diesel::joinable!(notification_preferences -> users (user_id));
 diesel::joinable!(order_items -> orders (order_id));
 diesel::joinable!(order_items -> products (product_id));
 diesel::joinable!(orders -> users (user_id));
-diesel::joinable!(password_reset_otps -> users (user_id));
+diesel::joinable!(otps -> users (user_id));
 diesel::joinable!(payments -> orders (order_id));
 diesel::joinable!(payments -> users (user_id));
+diesel::joinable!(product_attributes -> products (product_id));
+diesel::joinable!(product_availability_rules -> products (product_id));
 diesel::joinable!(product_images -> products (product_id));
+diesel::joinable!(product_price_tiers -> products (product_id));
+diesel::joinable!(product_rating_images -> product_ratings (rating_id));
+diesel::joinable!(product_rating_summaries -> products (product_id));
+diesel::joinable!(product_rating_votes -> product_ratings (rating_id));
+diesel::joinable!(product_rating_votes -> users (user_id));
 diesel::joinable!(product_ratings -> products (product_id));
 diesel::joinable!(product_ratings -> users (user_id));
+diesel::joinable!(product_tags -> products (product_id));
+diesel::joinable!(product_tags -> tags (tag_id));
 diesel::joinable!(products -> categories (category_id));
 diesel::joinable!(refresh_tokens -> users (user_id));
+diesel::joinable!(revoked_access_tokens -> users (user_id));
+diesel::joinable!(role_permissions -> permissions (permission_id));
+diesel::joinable!(role_permissions -> roles (role_id));
 diesel::joinable!(shipments -> orders (order_id));
 diesel::joinable!(shipments -> users (assigned_to));
+diesel::joinable!(subscription_items -> products (product_id));
+diesel::joinable!(subscription_items -> subscriptions (subscription_id));
+diesel::joinable!(subscription_orders -> orders (order_id));
+diesel::joinable!(subscription_orders -> subscriptions (subscription_id));
+diesel::joinable!(subscriptions -> users (user_id));
+diesel::joinable!(two_factor_recovery_codes -> users (user_id));
+diesel::joinable!(user_devices -> users (user_id));
+diesel::joinable!(user_sessions -> users (user_id));
+diesel::joinable!(user_two_factors -> users (user_id));
+diesel::joinable!(wishlists -> products (product_id));
+diesel::joinable!(wishlists -> users (user_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
     admin_devices,
+    app_settings,
+    audit_logs,
+    auth_rate_limits,
+    cart_reminders,
     carts,
     categories,
+    guest_carts,
     invoice_items,
     invoices,
+    notification_preferences,
     order_items,
     orders,
-    password_reset_otps,
+    otps,
     payments,
+    permissions,
+    product_attributes,
+    product_availability_rules,
     product_images,
+    product_price_tiers,
+    product_rating_images,
+    product_rating_summaries,
+    product_rating_votes,
     product_ratings,
+    product_tags,
     products,
     refresh_tokens,
+    revoked_access_tokens,
+    role_permissions,
+    roles,
     shipments,
+    subscription_items,
+    subscription_orders,
+    subscriptions,
+    tags,
+    two_factor_recovery_codes,
+    user_devices,
+    user_sessions,
+    user_two_factors,
     users,
+    wishlists,
 );
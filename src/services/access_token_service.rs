@@ -0,0 +1,48 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+
+use crate::models::{revoked_access_token::NewRevokedAccessToken, user::User};
+
+/// Deny-lists the access token `jti` until it expires on its own at `exp`.
+/// Entries whose tokens have already expired are cleared out on the way.
+pub fn revoke(conn: &mut SqliteConnection, user: &User, jti: &str, exp: usize) -> QueryResult<()> {
+    use crate::schema::revoked_access_tokens;
+
+    let now = Utc::now().naive_utc();
+    let expires_at = DateTime::from_timestamp(exp as i64, 0)
+        .map(|d| d.naive_utc())
+        .unwrap_or(now);
+
+    diesel::delete(revoked_access_tokens::table.filter(revoked_access_tokens::expires_at.lt(now)))
+        .execute(conn)?;
+
+    diesel::insert_into(revoked_access_tokens::table)
+        .values(&NewRevokedAccessToken::new(user, jti, expires_at))
+        .on_conflict_do_nothing()
+        .execute(conn)?;
+
+    Ok(())
+}
+
+/// An access token stops working once it is deny-listed or once the session
+/// it was issued for has been signed out.
+pub fn is_revoked(conn: &mut SqliteConnection, jti: &str, session_id: &str) -> QueryResult<bool> {
+    use crate::schema::{revoked_access_tokens, user_sessions};
+
+    let denied: i64 = revoked_access_tokens::table
+        .filter(revoked_access_tokens::jti.eq(jti))
+        .count()
+        .get_result(conn)?;
+
+    if denied > 0 {
+        return Ok(true);
+    }
+
+    let signed_out: i64 = user_sessions::table
+        .filter(user_sessions::uuid.eq(session_id))
+        .filter(user_sessions::revoked_at.is_not_null())
+        .count()
+        .get_result(conn)?;
+
+    Ok(signed_out > 0)
+}
@@ -0,0 +1,228 @@
+use diesel::{
+    prelude::*,
+    sql_types::{Nullable, Text},
+};
+use percent_encoding::percent_decode_str;
+use serde_json::{Map, Value};
+
+use crate::models::audit_log::NewAuditLog;
+
+// entity type (the first route segment after /admin), the table it lives in
+// and the column the id from the route is matched against
+const SNAPSHOT_TABLES: [(&str, &str, &str); 8] = [
+    ("categories", "categories", "uuid"),
+    ("orders", "orders", "uuid"),
+    ("payments", "payments", "uuid"),
+    ("products", "products", "uuid"),
+    ("ratings", "product_ratings", "uuid"),
+    ("roles", "roles", "name"),
+    ("tags", "tags", "uuid"),
+    ("users", "users", "uuid"),
+];
+
+// never copied into the log
+const REDACTED_COLUMNS: [&str; 1] = ["password"];
+
+pub type Snapshot = Map<String, Value>;
+
+#[derive(QueryableByName)]
+struct ColumnName {
+    #[diesel(sql_type = Text)]
+    name: String,
+}
+
+#[derive(QueryableByName)]
+struct JsonRow {
+    #[diesel(sql_type = Nullable<Text>)]
+    row: Option<String>,
+}
+
+/// Entity type and id of the request, read from the matched route pattern,
+/// e.g. `/admin/orders/{order_id}/order-status/update` gives the order's id.
+pub fn entity_of(pattern: &str, path: &str) -> (String, Option<String>) {
+    let pattern: Vec<&str> = pattern.split('/').filter(|s| !s.is_empty()).collect();
+    let path: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+    let skip = usize::from(pattern.first() == Some(&"admin"));
+    let entity_type = pattern.get(skip).copied().unwrap_or_default().to_owned();
+
+    let entity_id = pattern
+        .iter()
+        .position(|s| s.starts_with('{'))
+        .and_then(|i| path.get(i))
+        .map(|id| percent_decode_str(id).decode_utf8_lossy().into_owned());
+
+    (entity_type, entity_id)
+}
+
+/// The entity's row as JSON, `None` when the entity type isn't one we keep
+/// snapshots of or the row doesn't exist.
+pub fn snapshot(
+    conn: &mut SqliteConnection,
+    entity_type: &str,
+    entity_id: &str,
+) -> QueryResult<Option<Snapshot>> {
+    let Some((_, table, key)) = SNAPSHOT_TABLES.iter().find(|(t, _, _)| *t == entity_type) else {
+        return Ok(None);
+    };
+
+    let columns: Vec<String> = diesel::sql_query("SELECT name FROM pragma_table_info(?)")
+        .bind::<Text, _>(table)
+        .load::<ColumnName>(conn)?
+        .into_iter()
+        .map(|c| c.name)
+        .filter(|c| !REDACTED_COLUMNS.contains(&c.as_str()))
+        .collect();
+
+    // table and column names come from the list above and the schema itself
+    let fields: Vec<String> = columns
+        .iter()
+        .map(|c| format!("'{}', \"{}\"", c, c))
+        .collect();
+    let query = format!(
+        "SELECT json_object({}) AS row FROM \"{}\" WHERE \"{}\" = ?",
+        fields.join(", "),
+        table,
+        key
+    );
+
+    let row = diesel::sql_query(query)
+        .bind::<Text, _>(entity_id)
+        .get_result::<JsonRow>(conn)
+        .optional()?;
+
+    Ok(row
+        .and_then(|r| r.row)
+        .and_then(|r| serde_json::from_str::<Snapshot>(&r).ok()))
+}
+
+/// The columns that differ, `{"column": {"before": .., "after": ..}}`. A row
+/// that was created or deleted shows every column against null.
+pub fn diff(before: Option<&Snapshot>, after: Option<&Snapshot>) -> Option<Value> {
+    let empty = Snapshot::new();
+    let (before, after) = match (before, after) {
+        (None, None) => return None,
+        (b, a) => (b.unwrap_or(&empty), a.unwrap_or(&empty)),
+    };
+
+    let mut changes = Snapshot::new();
+    for key in before.keys().chain(after.keys()) {
+        let old = before.get(key).unwrap_or(&Value::Null);
+        let new = after.get(key).unwrap_or(&Value::Null);
+        if old != new && !changes.contains_key(key) {
+            changes.insert(
+                key.to_owned(),
+                serde_json::json!({"before": old, "after": new}),
+            );
+        }
+    }
+
+    (!changes.is_empty()).then_some(Value::Object(changes))
+}
+
+pub fn record(conn: &mut SqliteConnection, entry: &NewAuditLog) -> QueryResult<()> {
+    use crate::schema::audit_logs;
+
+    diesel::insert_into(audit_logs::table)
+        .values(entry)
+        .execute(conn)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::db::test_db;
+
+    fn snapshot_of(value: Value) -> Snapshot {
+        match value {
+            Value::Object(map) => map,
+            _ => panic!("not an object"),
+        }
+    }
+
+    #[test]
+    fn diff_keeps_only_the_changed_columns() {
+        let before = snapshot_of(json!({"name": "Carrot", "price": 100.0, "stock": 5.0}));
+        let after = snapshot_of(json!({"name": "Carrot", "price": 90.0, "stock": 5.0}));
+
+        assert_eq!(
+            diff(Some(&before), Some(&after)),
+            Some(json!({"price": {"before": 100.0, "after": 90.0}}))
+        );
+        assert_eq!(diff(Some(&before), Some(&before)), None);
+    }
+
+    #[test]
+    fn created_and_deleted_rows_diff_against_null() {
+        let row = snapshot_of(json!({"name": "Carrot", "price": 100.0}));
+
+        assert_eq!(
+            diff(None, Some(&row)),
+            Some(json!({
+                "name": {"before": null, "after": "Carrot"},
+                "price": {"before": null, "after": 100.0},
+            }))
+        );
+        assert_eq!(
+            diff(Some(&row), None),
+            Some(json!({
+                "name": {"before": "Carrot", "after": null},
+                "price": {"before": 100.0, "after": null},
+            }))
+        );
+        assert_eq!(diff(None, None), None);
+    }
+
+    #[test]
+    fn entity_comes_from_the_route_pattern() {
+        assert_eq!(
+            entity_of(
+                "/admin/orders/{order_id}/order-status/update",
+                "/admin/orders/abc-123/order-status/update"
+            ),
+            ("orders".to_string(), Some("abc-123".to_string()))
+        );
+        assert_eq!(
+            entity_of("/admin/roles/{name}", "/admin/roles/Store%20Manager"),
+            ("roles".to_string(), Some("Store Manager".to_string()))
+        );
+        assert_eq!(
+            entity_of("/admin/products", "/admin/products"),
+            ("products".to_string(), None)
+        );
+    }
+
+    #[test]
+    fn snapshots_leave_out_passwords_and_unknown_entities() {
+        use crate::schema::products;
+
+        let conn = &mut test_db::connection();
+        let user = test_db::user(conn, "Customer");
+        let product = test_db::product(conn, 100.0, 5.0, 0.5);
+
+        let user_row = snapshot(conn, "users", user.get_uuid()).unwrap().unwrap();
+        assert_eq!(user_row["uuid"], json!(user.get_uuid()));
+        assert!(!user_row.contains_key("password"));
+
+        assert!(snapshot(conn, "users", "missing").unwrap().is_none());
+        assert!(snapshot(conn, "sessions", user.get_uuid())
+            .unwrap()
+            .is_none());
+
+        let before = snapshot(conn, "products", product.get_uuid()).unwrap();
+        diesel::update(&product)
+            .set(products::price.eq(90.0))
+            .execute(conn)
+            .unwrap();
+        let after = snapshot(conn, "products", product.get_uuid()).unwrap();
+
+        assert_eq!(
+            diff(before.as_ref(), after.as_ref()),
+            Some(json!({"price": {"before": 100.0, "after": 90.0}}))
+        );
+    }
+}
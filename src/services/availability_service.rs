@@ -0,0 +1,252 @@
+use std::collections::HashMap;
+
+use chrono::{Days, NaiveDate};
+use diesel::prelude::*;
+
+use crate::models::{product::Product, product_availability_rule::ProductAvailabilityRule};
+
+pub const WEEKDAYS: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+const WEEKDAY_NAMES: [&str; 7] = [
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+];
+
+/// Whether a product can be sold on a given day and, when it can't, the next
+/// day it can.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Availability {
+    pub available: bool,
+    pub available_from: Option<NaiveDate>,
+}
+
+impl Availability {
+    pub fn always() -> Self {
+        Self {
+            available: true,
+            available_from: None,
+        }
+    }
+
+    pub fn unavailable_message(&self, product: &Product) -> String {
+        match self.available_from {
+            Some(d) => format!(
+                "{} is not available until {}",
+                product.get_name(),
+                d.format("%Y-%m-%d")
+            ),
+            None => format!("{} is currently not available", product.get_name()),
+        }
+    }
+}
+
+/// Today's date in Nepal time, which is what availability windows refer to.
+pub fn today() -> NaiveDate {
+    chrono::Utc::now()
+        .with_timezone(&chrono::FixedOffset::east_opt(5 * 3600 + 45 * 60).unwrap())
+        .date_naive()
+}
+
+/// A product without rules is always available, otherwise any one rule being
+/// open on `date` is enough.
+pub fn evaluate(rules: &[&ProductAvailabilityRule], date: NaiveDate) -> Availability {
+    if rules.is_empty() || rules.iter().any(|r| r.is_open_on(date)) {
+        return Availability::always();
+    }
+
+    // every rule repeats yearly so looking one year ahead is enough
+    let next = (1..=366)
+        .filter_map(|n| date.checked_add_days(Days::new(n)))
+        .find(|d| rules.iter().any(|r| r.is_open_on(*d)));
+
+    Availability {
+        available: false,
+        available_from: next,
+    }
+}
+
+pub fn availability_for_products(
+    conn: &mut SqliteConnection,
+    product_ids: &[i32],
+    date: NaiveDate,
+) -> QueryResult<HashMap<i32, Availability>> {
+    use crate::schema::product_availability_rules;
+
+    let rules: Vec<ProductAvailabilityRule> = product_availability_rules::table
+        .filter(product_availability_rules::product_id.eq_any(product_ids))
+        .select(ProductAvailabilityRule::as_select())
+        .load(conn)?;
+
+    let mut rules_by_product: HashMap<i32, Vec<&ProductAvailabilityRule>> = HashMap::new();
+    for r in &rules {
+        rules_by_product
+            .entry(r.get_product_id())
+            .or_default()
+            .push(r);
+    }
+
+    Ok(product_ids
+        .iter()
+        .map(|pid| {
+            let product_rules = rules_by_product.get(pid).map(Vec::as_slice).unwrap_or(&[]);
+            (*pid, evaluate(product_rules, date))
+        })
+        .collect())
+}
+
+/// Archived products are never available, whatever their rules say.
+pub fn availability_for_product(
+    conn: &mut SqliteConnection,
+    product: &Product,
+    date: NaiveDate,
+) -> QueryResult<Availability> {
+    if product.is_archived() {
+        return Ok(Availability {
+            available: false,
+            available_from: None,
+        });
+    }
+
+    Ok(availability_for_products(conn, &[product.get_id()], date)?
+        .remove(&product.get_id())
+        .unwrap_or_else(Availability::always))
+}
+
+/// Converts weekday names ("mon".."sun" or "monday".."sunday") into the
+/// stored bit mask, `None` when a name is not recognised.
+pub fn weekday_mask(days: &[String]) -> Option<i32> {
+    days.iter().try_fold(0, |mask, day| {
+        let day = day.trim().to_lowercase();
+        WEEKDAYS
+            .iter()
+            .zip(WEEKDAY_NAMES.iter())
+            .position(|(short, full)| day == *short || day == *full)
+            .map(|i| mask | (1 << i))
+    })
+}
+
+pub fn weekday_names(mask: i32) -> Vec<String> {
+    WEEKDAYS
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| mask & (1 << i) != 0)
+        .map(|(_, w)| w.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{db::test_db, models::product_availability_rule::NewProductAvailabilityRule};
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    fn days(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    fn add_rule(
+        conn: &mut SqliteConnection,
+        product: &Product,
+        start_day: Option<&str>,
+        end_day: Option<&str>,
+        weekday_mask: i32,
+    ) {
+        use crate::schema::product_availability_rules;
+
+        diesel::insert_into(product_availability_rules::table)
+            .values(&NewProductAvailabilityRule::new(
+                product,
+                start_day.map(str::to_string),
+                end_day.map(str::to_string),
+                weekday_mask,
+            ))
+            .execute(conn)
+            .unwrap();
+    }
+
+    #[test]
+    fn weekday_mask_accepts_short_and_full_names() {
+        assert_eq!(weekday_mask(&days(&["mon", "Sunday"])), Some(0b100_0001));
+        assert_eq!(weekday_mask(&days(&[" WED "])), Some(0b000_0100));
+        assert_eq!(weekday_mask(&days(&["mon", "someday"])), None);
+        assert_eq!(weekday_names(0b100_0001), days(&["mon", "sun"]));
+    }
+
+    #[test]
+    fn products_without_rules_are_always_available() {
+        let conn = &mut test_db::connection();
+        let product = test_db::product(conn, 100.0, 50.0, 0.5);
+
+        let availability = availability_for_product(conn, &product, date("2025-11-04")).unwrap();
+        assert_eq!(availability, Availability::always());
+    }
+
+    #[test]
+    fn weekday_rules_point_to_the_next_open_day() {
+        let conn = &mut test_db::connection();
+        let product = test_db::product(conn, 100.0, 50.0, 0.5);
+        let mask = weekday_mask(&days(&["mon", "wed"])).unwrap();
+        add_rule(conn, &product, None, None, mask);
+
+        // 2025-11-03 is a Monday
+        let monday = availability_for_product(conn, &product, date("2025-11-03")).unwrap();
+        assert!(monday.available);
+
+        let tuesday = availability_for_product(conn, &product, date("2025-11-04")).unwrap();
+        assert!(!tuesday.available);
+        assert_eq!(tuesday.available_from, Some(date("2025-11-05")));
+    }
+
+    #[test]
+    fn date_ranges_can_wrap_over_the_new_year() {
+        let conn = &mut test_db::connection();
+        let product = test_db::product(conn, 100.0, 50.0, 0.5);
+        add_rule(conn, &product, Some("11-15"), Some("02-28"), 127);
+
+        for open in ["2025-11-15", "2025-12-31", "2026-01-01", "2026-02-28"] {
+            let availability = availability_for_product(conn, &product, date(open)).unwrap();
+            assert!(availability.available, "{} should be open", open);
+        }
+
+        let closed = availability_for_product(conn, &product, date("2026-03-01")).unwrap();
+        assert!(!closed.available);
+        assert_eq!(closed.available_from, Some(date("2026-11-15")));
+    }
+
+    #[test]
+    fn any_open_rule_makes_the_product_available() {
+        let conn = &mut test_db::connection();
+        let product = test_db::product(conn, 100.0, 50.0, 0.5);
+        add_rule(conn, &product, Some("06-01"), Some("06-30"), 127);
+        let sunday_only = weekday_mask(&days(&["sun"])).unwrap();
+        add_rule(conn, &product, None, None, sunday_only);
+
+        // 2026-03-01 is a Sunday outside the June range
+        let sunday = availability_for_product(conn, &product, date("2026-03-01")).unwrap();
+        assert!(sunday.available);
+    }
+
+    #[test]
+    fn archived_products_are_never_available() {
+        use crate::schema::products;
+
+        let conn = &mut test_db::connection();
+        let product = test_db::product(conn, 100.0, 50.0, 0.5);
+        let product: Product = diesel::update(&product)
+            .set(products::is_archived.eq(true))
+            .returning(Product::as_returning())
+            .get_result(conn)
+            .unwrap();
+
+        let availability = availability_for_product(conn, &product, date("2025-11-04")).unwrap();
+        assert!(!availability.available);
+        assert_eq!(availability.available_from, None);
+    }
+}
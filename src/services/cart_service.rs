@@ -0,0 +1,491 @@
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    contracts::cart::{
+        Cart as CartResponse, CartSummary, CartWarning, CartWarningKind, ReorderItem,
+    },
+    models::{
+        cart::{Cart, NewCartItem},
+        guest_cart::GuestCart,
+        order::Order,
+        order_item::OrderItem,
+        product::Product,
+        user::User,
+    },
+    services::{
+        availability_service,
+        pricing_service::{self, AppliedPrice},
+    },
+};
+
+// prices are stored as doubles, anything closer than this is the same price
+const PRICE_EPSILON: f64 = 0.005;
+
+// carts of guests expire when they haven't been touched for this long
+pub const GUEST_CART_TTL_DAYS: i64 = 7;
+
+/// The parts of a cart row the checks need, shared by customer and guest
+/// carts.
+pub trait CartEntry {
+    fn uuid(&self) -> &str;
+    fn quantity(&self) -> f64;
+    fn sku(&self) -> &str;
+    fn created_on(&self) -> &str;
+    fn discount(&self) -> f64;
+    fn rate(&self) -> f64;
+}
+
+impl CartEntry for Cart {
+    fn uuid(&self) -> &str {
+        self.get_uuid()
+    }
+    fn quantity(&self) -> f64 {
+        self.get_quantity()
+    }
+    fn sku(&self) -> &str {
+        self.get_sku()
+    }
+    fn created_on(&self) -> &str {
+        self.get_created_on()
+    }
+    fn discount(&self) -> f64 {
+        self.get_discount()
+    }
+    fn rate(&self) -> f64 {
+        self.get_rate()
+    }
+}
+
+// guests never get a discount, those are handed out per customer
+impl CartEntry for GuestCart {
+    fn uuid(&self) -> &str {
+        self.get_uuid()
+    }
+    fn quantity(&self) -> f64 {
+        self.get_quantity()
+    }
+    fn sku(&self) -> &str {
+        self.get_sku()
+    }
+    fn created_on(&self) -> &str {
+        self.get_created_on()
+    }
+    fn discount(&self) -> f64 {
+        0.0
+    }
+    fn rate(&self) -> f64 {
+        self.get_rate()
+    }
+}
+
+/// A cart row checked against the product as it is now.
+pub struct CartLine<C: CartEntry> {
+    pub cart: C,
+    pub product: Product,
+    pub applied_price: AppliedPrice,
+    pub warnings: Vec<CartWarning>,
+}
+
+impl<C: CartEntry> CartLine<C> {
+    /// A price change is only something to tell the customer about, every
+    /// other warning keeps the line out of checkout until it is fixed.
+    pub fn is_orderable(&self) -> bool {
+        self.warnings
+            .iter()
+            .all(|w| w.kind == CartWarningKind::PriceChanged)
+    }
+
+    pub fn line_total(&self) -> f64 {
+        self.applied_price.total_for(self.cart.quantity())
+    }
+
+    pub fn as_response(&self) -> CartResponse {
+        CartResponse {
+            uuid: self.cart.uuid().to_owned(),
+            product_id: self.product.get_uuid().to_owned(),
+            quantity: self.cart.quantity(),
+            rate: self.applied_price.unit_price,
+            sku: self.cart.sku().to_owned(),
+            image: self.product.get_image().to_owned(),
+            created_on: self.cart.created_on().to_owned(),
+            product_name: self.product.get_name().to_owned(),
+            product_stock: self.product.get_stock(),
+            product_unit_change: self.product.get_unit_change(),
+            discount: self.cart.discount(),
+            line_total: self.line_total(),
+            warnings: self.warnings.clone(),
+        }
+    }
+}
+
+/// Whether `quantity` can be bought in steps of `step`, e.g. 1.5 kg of a
+/// product sold per 0.5 kg. A product without a step accepts any quantity.
+pub fn is_quantity_step(quantity: f64, step: f64) -> bool {
+    if step <= 0.0 {
+        return true;
+    }
+    let steps = quantity / step;
+    (steps - steps.round()).abs() < 1e-6
+}
+
+/// Prices the cart row for its current quantity and collects everything that
+/// changed since the customer added it.
+pub fn validate_line<C: CartEntry>(
+    conn: &mut SqliteConnection,
+    cart: C,
+    product: Product,
+    date: NaiveDate,
+) -> QueryResult<CartLine<C>> {
+    let applied_price = pricing_service::price_for_quantity(conn, &product, cart.quantity())?;
+    let mut warnings = vec![];
+
+    let availability = availability_service::availability_for_product(conn, &product, date)?;
+    if !availability.available {
+        warnings.push(CartWarning {
+            kind: CartWarningKind::Unavailable,
+            message: availability.unavailable_message(&product),
+        });
+    }
+
+    if product.get_stock() <= 0.0 {
+        warnings.push(CartWarning {
+            kind: CartWarningKind::InsufficientStock,
+            message: format!("{} is out of stock", product.get_name()),
+        });
+    } else if cart.quantity() > product.get_stock() {
+        warnings.push(CartWarning {
+            kind: CartWarningKind::InsufficientStock,
+            message: format!(
+                "Only {} {} of {} left in stock",
+                product.get_stock(),
+                product.get_unit(),
+                product.get_name()
+            ),
+        });
+    }
+
+    if !is_quantity_step(cart.quantity(), product.get_unit_change()) {
+        warnings.push(CartWarning {
+            kind: CartWarningKind::InvalidQuantity,
+            message: format!(
+                "{} is sold in steps of {} {}",
+                product.get_name(),
+                product.get_unit_change(),
+                product.get_unit()
+            ),
+        });
+    }
+
+    if (applied_price.unit_price - cart.rate()).abs() > PRICE_EPSILON {
+        warnings.push(CartWarning {
+            kind: CartWarningKind::PriceChanged,
+            message: format!(
+                "Price of {} changed from Rs. {:.2} to Rs. {:.2}",
+                product.get_name(),
+                cart.rate(),
+                applied_price.unit_price
+            ),
+        });
+    }
+
+    Ok(CartLine {
+        cart,
+        product,
+        applied_price,
+        warnings,
+    })
+}
+
+/// Server side totals for the cart. Delivery is only charged when there is
+/// something to deliver.
+pub fn summarize<C: CartEntry>(lines: &[CartLine<C>], delivery_charge: f64) -> CartSummary {
+    let (subtotal, discount) = lines
+        .iter()
+        .filter(|l| l.is_orderable())
+        .fold((0.0, 0.0), |(subtotal, discount), l| {
+            (subtotal + l.line_total(), discount + l.cart.discount())
+        });
+
+    let delivery_charge = if lines.iter().any(CartLine::is_orderable) {
+        delivery_charge
+    } else {
+        0.0
+    };
+
+    CartSummary {
+        subtotal,
+        discount,
+        delivery_charge,
+        total: subtotal - discount + delivery_charge,
+    }
+}
+
+/// When a guest cart touched now expires.
+pub fn guest_cart_expiry() -> chrono::NaiveDateTime {
+    chrono::Utc::now().naive_utc() + chrono::Duration::days(GUEST_CART_TTL_DAYS)
+}
+
+pub fn purge_expired_guest_carts(conn: &mut SqliteConnection) -> QueryResult<usize> {
+    use crate::schema::guest_carts;
+
+    diesel::delete(
+        guest_carts::table.filter(guest_carts::expires_on.le(chrono::Utc::now().naive_utc())),
+    )
+    .execute(conn)
+}
+
+/// Moves the guest cart of `token` into the customer's cart. Products already
+/// in the customer's cart get the guest quantity added, cut down to the stock
+/// that is left and priced for the merged quantity. The guest cart is removed
+/// afterwards, expired guest carts are simply dropped.
+pub fn merge_guest_cart(
+    conn: &mut SqliteConnection,
+    token: &str,
+    user: &User,
+) -> QueryResult<usize> {
+    use crate::schema::{carts, guest_carts, products};
+
+    // guest carts are stored under the hyphenated lowercase form of the token,
+    // anything that is not a uuid has no cart to merge
+    let token = match Uuid::parse_str(token) {
+        Ok(t) => t.to_string(),
+        Err(_) => return Ok(0),
+    };
+    let token = token.as_str();
+
+    conn.transaction(|conn| {
+        let guest_rows: Vec<(GuestCart, Product)> = guest_carts::table
+            .inner_join(products::table)
+            .filter(guest_carts::token.eq(token))
+            .filter(guest_carts::expires_on.gt(chrono::Utc::now().naive_utc()))
+            .select((GuestCart::as_select(), Product::as_select()))
+            .load(conn)?;
+
+        let mut merged = 0;
+
+        for (guest_cart, product) in &guest_rows {
+            let in_cart: f64 = carts::table
+                .filter(carts::user_id.eq(user.get_id()))
+                .filter(carts::product_id.eq(product.get_id()))
+                .select(carts::quantity)
+                .first(conn)
+                .optional()?
+                .unwrap_or(0.0);
+            let mut quantity = guest_cart.get_quantity().min(product.get_stock() - in_cart);
+            let step = product.get_unit_change();
+            if step > 0.0 && !is_quantity_step(quantity, step) {
+                quantity = (quantity / step).floor() * step;
+            }
+            if quantity <= 0.0 {
+                continue;
+            }
+
+            let merged_quantity = in_cart + quantity;
+            let applied_price =
+                pricing_service::price_for_quantity(conn, product, merged_quantity)?;
+
+            let cart = NewCartItem::new(
+                product,
+                user,
+                merged_quantity,
+                guest_cart.get_created_on().to_owned(),
+                applied_price.unit_price,
+            );
+            diesel::insert_into(carts::table)
+                .values(&cart)
+                .on_conflict((carts::user_id, carts::product_id))
+                .do_update()
+                .set((
+                    carts::quantity.eq(merged_quantity),
+                    carts::rate.eq(applied_price.unit_price),
+                ))
+                .execute(conn)?;
+
+            merged += 1;
+        }
+
+        diesel::delete(guest_carts::table.filter(guest_carts::token.eq(token))).execute(conn)?;
+
+        Ok(merged)
+    })
+}
+
+/// Copies the items of a past `order` into the customer's cart at today's
+/// prices, on top of whatever is already there. Items that can't be bought
+/// right now are skipped, quantities are cut down to the stock that is left,
+/// and every line reports what changed since the order was placed.
+pub fn reorder(
+    conn: &mut SqliteConnection,
+    order: &Order,
+    user: &User,
+    created_on: String,
+    date: NaiveDate,
+) -> QueryResult<Vec<ReorderItem>> {
+    use crate::schema::{carts, order_items, products};
+
+    conn.transaction(|conn| {
+        let items: Vec<(OrderItem, Product)> = order_items::table
+            .inner_join(products::table)
+            .filter(order_items::order_id.eq(order.get_id()))
+            .select((OrderItem::as_select(), Product::as_select()))
+            .load(conn)?;
+
+        let mut report = vec![];
+
+        for (item, product) in items {
+            let mut warnings = vec![];
+            let mut added_quantity = 0.0;
+
+            let availability =
+                availability_service::availability_for_product(conn, &product, date)?;
+
+            let in_cart: f64 = carts::table
+                .filter(carts::user_id.eq(user.get_id()))
+                .filter(carts::product_id.eq(product.get_id()))
+                .select(carts::quantity)
+                .first(conn)
+                .optional()?
+                .unwrap_or(0.0);
+            let mut quantity = item.get_quantity().min(product.get_stock() - in_cart);
+            let step = product.get_unit_change();
+            if step > 0.0 && !is_quantity_step(quantity, step) {
+                quantity = (quantity / step).floor() * step;
+            }
+
+            if !availability.available {
+                warnings.push(CartWarning {
+                    kind: CartWarningKind::Unavailable,
+                    message: availability.unavailable_message(&product),
+                });
+            } else if quantity <= 0.0 {
+                warnings.push(CartWarning {
+                    kind: CartWarningKind::InsufficientStock,
+                    message: format!("{} is out of stock", product.get_name()),
+                });
+            } else {
+                if quantity < item.get_quantity() {
+                    warnings.push(CartWarning {
+                        kind: CartWarningKind::InsufficientStock,
+                        message: format!(
+                            "Only {} {} of {} could be added",
+                            quantity,
+                            product.get_unit(),
+                            product.get_name()
+                        ),
+                    });
+                }
+
+                let merged_quantity = in_cart + quantity;
+                let applied_price =
+                    pricing_service::price_for_quantity(conn, &product, merged_quantity)?;
+
+                // order items keep the line total, the rate has to be worked out
+                let ordered_rate = AppliedPrice::from_order_item(&item).unit_price;
+                if (applied_price.unit_price - ordered_rate).abs() > PRICE_EPSILON {
+                    warnings.push(CartWarning {
+                        kind: CartWarningKind::PriceChanged,
+                        message: format!(
+                            "Price of {} changed from Rs. {:.2} to Rs. {:.2}",
+                            product.get_name(),
+                            ordered_rate,
+                            applied_price.unit_price
+                        ),
+                    });
+                }
+
+                let cart = NewCartItem::new(
+                    &product,
+                    user,
+                    merged_quantity,
+                    created_on.clone(),
+                    applied_price.unit_price,
+                );
+                diesel::insert_into(carts::table)
+                    .values(&cart)
+                    .on_conflict((carts::user_id, carts::product_id))
+                    .do_update()
+                    .set((
+                        carts::quantity.eq(merged_quantity),
+                        carts::rate.eq(applied_price.unit_price),
+                    ))
+                    .execute(conn)?;
+
+                added_quantity = quantity;
+            }
+
+            report.push(ReorderItem {
+                product_id: product.get_uuid().to_owned(),
+                product_name: product.get_name().to_owned(),
+                ordered_quantity: item.get_quantity(),
+                added_quantity,
+                warnings,
+            });
+        }
+
+        Ok(report)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_db;
+
+    #[test]
+    fn quantities_must_be_whole_steps() {
+        assert!(is_quantity_step(1.5, 0.5));
+        assert!(is_quantity_step(0.75, 0.25));
+        assert!(is_quantity_step(0.3, 0.1));
+        assert!(!is_quantity_step(1.2, 0.5));
+        assert!(!is_quantity_step(0.1, 0.25));
+    }
+
+    #[test]
+    fn products_without_a_step_take_any_quantity() {
+        assert!(is_quantity_step(1.234, 0.0));
+        assert!(is_quantity_step(7.0, -1.0));
+    }
+
+    #[test]
+    fn reorder_cuts_quantities_down_to_stock_in_whole_steps() {
+        use crate::{
+            models::order_item::NewOrderItem,
+            schema::{carts, order_items},
+        };
+
+        let conn = &mut test_db::connection();
+        let product = test_db::product(conn, 100.0, 3.3, 0.5);
+        let user = test_db::user(conn, "Customer");
+        let order = test_db::order(conn, &user);
+        diesel::insert_into(order_items::table)
+            .values(&NewOrderItem::new(
+                5.0,
+                0.0,
+                &product,
+                &order,
+                &AppliedPrice::base(&product),
+            ))
+            .execute(conn)
+            .unwrap();
+
+        let date = NaiveDate::from_ymd_opt(2025, 11, 4).unwrap();
+        let report = reorder(conn, &order, &user, "2025-11-04 10:00:00".to_string(), date).unwrap();
+
+        assert_eq!(report.len(), 1);
+        assert_eq!(report[0].added_quantity, 3.0);
+        assert!(report[0]
+            .warnings
+            .iter()
+            .any(|w| w.kind == CartWarningKind::InsufficientStock));
+
+        let (quantity, rate): (f64, f64) = carts::table
+            .filter(carts::user_id.eq(user.get_id()))
+            .select((carts::quantity, carts::rate))
+            .first(conn)
+            .unwrap();
+        assert_eq!(quantity, 3.0);
+        assert_eq!(rate, 100.0);
+    }
+}
@@ -0,0 +1,46 @@
+use std::io::Write;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+
+use super::sms_service::SmsService;
+use crate::config::SmsConfiguration;
+
+/// Stand-in gateway for development, messages are printed and appended to
+/// `SMS_LOG_PATH` instead of being sent.
+pub struct ConsoleSmsService {
+    log_path: String,
+}
+
+impl ConsoleSmsService {
+    pub fn new(config: &SmsConfiguration) -> Self {
+        Self {
+            log_path: config.log_path.to_owned(),
+        }
+    }
+}
+
+#[async_trait]
+impl SmsService for ConsoleSmsService {
+    async fn send_sms(&self, to_number: &str, message: &str) -> Result<()> {
+        let line = format!("[{}] SMS to {}: {}", chrono::Utc::now(), to_number, message);
+        println!("{}", line);
+
+        if self.log_path.is_empty() {
+            return Ok(());
+        }
+
+        if let Some(dir) = std::path::Path::new(&self.log_path).parent() {
+            std::fs::create_dir_all(dir).context("Failed to create SMS log directory")?;
+        }
+
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.log_path)
+            .context("Failed to open SMS log file")?;
+        writeln!(file, "{}", line).context("Failed to write SMS log")?;
+
+        Ok(())
+    }
+}
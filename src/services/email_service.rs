@@ -24,8 +24,6 @@ pub trait EmailService: Send + Sync {
         order_details: &str,
     ) -> Result<()>;
 
-    async fn send_simple_email(&self, to_email: &str, subject: &str, body: &str) -> Result<()>;
-
     async fn send_html_email(
         &self,
         to_email: &str,
@@ -33,8 +31,6 @@ pub trait EmailService: Send + Sync {
         html_body: &str,
         text_body: Option<&str>,
     ) -> Result<()>;
-
-    async fn test_connection(&self) -> Result<()>;
 }
 
 pub struct EmailServiceFactory;
@@ -50,10 +46,4 @@ impl EmailServiceFactory {
     ) -> Result<Arc<dyn EmailService>> {
         Self::create_lettre_service(gmail_config)
     }
-
-    pub fn create_outlook_service(
-        outlook_config: &EmailConfiguration,
-    ) -> Result<Arc<dyn EmailService>> {
-        Self::create_lettre_service(outlook_config)
-    }
 }
@@ -0,0 +1,67 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use super::email_service::EmailService;
+use crate::{
+    config::ApplicationConfiguration, models::user::User,
+    utils::jwt_helper::create_email_verification_token,
+};
+
+/// Emails `user` a link that confirms their address. The token is tied to the
+/// address, so it stops working if the email is changed before it's used.
+pub async fn send_verification_email(
+    email_service: &dyn EmailService,
+    app_config: &ApplicationConfiguration,
+    user: &User,
+) -> Result<()> {
+    let token = create_email_verification_token(
+        user.get_uuid().to_owned(),
+        user.get_email().to_owned(),
+        app_config.email_verification_expiry_hours * 60,
+        &app_config.jwt_secret,
+    )?;
+
+    let link = format!("{}?token={}", app_config.email_verification_url, token);
+    let email_content = format!(
+        r#"
+            <h2>Confirm your email address</h2>
+            <p>Hi {},</p>
+            <p>Please confirm your email address by opening the link below:</p>
+            <p><a href="{}">{}</a></p>
+            <p>This link will expire in {} hours.</p>
+            <p>If you didn't create an account, please ignore this email.</p>
+            "#,
+        user.get_fullname(),
+        link,
+        link,
+        app_config.email_verification_expiry_hours
+    );
+
+    email_service
+        .send_html_email(
+            user.get_email(),
+            "Confirm your email address",
+            &email_content,
+            None,
+        )
+        .await
+}
+
+/// Sends the verification email in the background, signing up doesn't wait
+/// on the mail server.
+pub fn spawn_verification_email(
+    email_service: Arc<dyn EmailService>,
+    app_config: ApplicationConfiguration,
+    user: User,
+) {
+    tokio::spawn(async move {
+        if let Err(e) = send_verification_email(email_service.as_ref(), &app_config, &user).await {
+            println!(
+                "Failed to send verification email to {}: {:?}",
+                user.get_email(),
+                e
+            );
+        }
+    });
+}
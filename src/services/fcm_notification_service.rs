@@ -31,19 +31,22 @@ impl FcmNotificationServiceImpl {
             .load::<AdminDevice>(conn)?;
         Ok(devices)
     }
-}
 
-#[async_trait]
-impl NotificationService for FcmNotificationServiceImpl {
-    async fn send_notification(&self, event: NotificationEvent) -> anyhow::Result<()> {
-        let admin_dev = self.get_admin_devices().await?;
+    async fn get_user_devices(&self, user_ids: Vec<i32>) -> anyhow::Result<Vec<(i32, String)>> {
+        use crate::schema::user_devices;
 
-        if admin_dev.is_empty() {
-            println!("No admin devices registered to send notification to.");
-            return Ok(());
-        }
+        let conn = &mut self.pool.get()?;
+        let devices = user_devices::table
+            .filter(user_devices::user_id.eq_any(user_ids))
+            .select((user_devices::user_id, user_devices::fcm_token))
+            .load::<(i32, String)>(conn)?;
+        Ok(devices)
+    }
 
-        let (notification, data_payload) = match event {
+    fn build_message(
+        event: NotificationEvent,
+    ) -> (FcmNotification, std::collections::HashMap<String, String>) {
+        match event {
             NotificationEvent::NewOrder(p) => {
                 let mut notif = FcmNotification::new();
                 notif.set_title(format!("New Order: {}", p.order_id));
@@ -75,77 +78,88 @@ impl NotificationService for FcmNotificationServiceImpl {
                 (notif, data)
             }
 
-            NotificationEvent::OrderCancelled(p) => {
+            NotificationEvent::BackInStock(p) => {
                 let mut notif = FcmNotification::new();
-                notif.set_title(format!("Order Cancelled: {}", p.order_id));
-
-                let na = String::from("N/A");
-                let body_reason = p.reason.as_ref().unwrap_or(&na);
-                notif.set_body(format!("Reason: {}", body_reason));
-
+                notif.set_title(format!("{} is back in stock", p.product_name));
+                notif.set_body(format!(
+                    "An item on your wishlist is available again at Rs. {:.2}",
+                    p.price
+                ));
                 let mut data = std::collections::HashMap::new();
-                data.insert("event_type".to_string(), "order_cancelled".to_string());
-                data.insert("order_id".to_string(), p.order_id);
-
-                if let Some(reason_val) = p.reason {
-                    data.insert("reason".to_string(), reason_val);
-                }
+                data.insert("event_type".to_string(), "back_in_stock".to_string());
+                data.insert("product_id".to_string(), p.product_id);
+                data.insert("price".to_string(), p.price.to_string());
                 (notif, data)
             }
 
-            NotificationEvent::OrderFulfilled(p) => {
+            NotificationEvent::PriceDrop(p) => {
                 let mut notif = FcmNotification::new();
-                notif.set_title(format!("Order Fulfilled: {}", p.order_id));
-
-                let details_for_body = p
-                    .details
-                    .as_ref()
-                    .map_or("Your order has been completed.", |s| s.as_str());
-                notif.set_body(format!("Details: {}", details_for_body));
-
+                notif.set_title(format!("Price drop on {}", p.product_name));
+                notif.set_body(format!(
+                    "Now Rs. {:.2}, down from Rs. {:.2}",
+                    p.price, p.previous_price
+                ));
                 let mut data = std::collections::HashMap::new();
-                data.insert("event_type".to_string(), "order_fulfilled".to_string());
-                data.insert("order_id".to_string(), p.order_id);
-
-                if let Some(details_val) = p.details {
-                    data.insert("details".to_string(), details_val);
-                }
+                data.insert("event_type".to_string(), "price_drop".to_string());
+                data.insert("product_id".to_string(), p.product_id);
+                data.insert("price".to_string(), p.price.to_string());
+                data.insert("previous_price".to_string(), p.previous_price.to_string());
                 (notif, data)
             }
 
-            NotificationEvent::Generic(p) => {
+            NotificationEvent::CartReminder(p) => {
                 let mut notif = FcmNotification::new();
-                notif.set_title(p.title);
-                notif.set_body(p.body);
-                let mut data = p.data;
+                notif.set_title("Your cart is waiting".to_string());
+                notif.set_body(format!(
+                    "You left {} item(s) in your cart. Complete your order before they run out.",
+                    p.item_count
+                ));
+                let mut data = std::collections::HashMap::new();
+                data.insert("event_type".to_string(), "cart_reminder".to_string());
+                data.insert("item_count".to_string(), p.item_count.to_string());
+                (notif, data)
+            }
 
-                if !data.contains_key("event_type") {
-                    data.insert("event_type".to_string(), "generic_notification".to_string());
-                }
+            NotificationEvent::SubscriptionOrder(p) => {
+                let mut notif = FcmNotification::new();
+                notif.set_title("Your subscription order is placed".to_string());
+                notif.set_body(format!(
+                    "Delivery on {} ({}), total Rs. {:.2}",
+                    p.delivery_on, p.delivery_slot, p.total_amount
+                ));
+                let mut data = std::collections::HashMap::new();
+                data.insert("event_type".to_string(), "subscription_order".to_string());
+                data.insert("order_id".to_string(), p.order_id);
+                data.insert("delivery_on".to_string(), p.delivery_on);
+                data.insert("delivery_slot".to_string(), p.delivery_slot);
+                data.insert("total_amount".to_string(), p.total_amount.to_string());
                 (notif, data)
             }
-        };
+        }
+    }
 
+    async fn dispatch(
+        &self,
+        targets: Vec<(i32, String)>,
+        notification: FcmNotification,
+        data_payload: std::collections::HashMap<String, String>,
+    ) {
         let mut tasks = vec![];
-        for device in admin_dev {
+        for (user_id, fcm_token) in targets {
             let mut fcm_message = FcmMessage::new();
             fcm_message.set_webpush(None);
-            fcm_message.set_target(Target::Token(device.fcm_token.clone()));
+            fcm_message.set_target(Target::Token(fcm_token));
             fcm_message.set_notification(Some(notification.clone()));
             fcm_message.set_data(Some(data_payload.clone()));
 
             let fcm_client_cloned = self.fcm_client.clone();
 
             let task = tokio::spawn(async move {
-                let admin_id = device.user_id;
                 match fcm_client_cloned.send_notification(fcm_message).await {
-                    Ok(_) => println!(
-                        "Successfully sent FCM notification to admin user: {}",
-                        admin_id
-                    ),
+                    Ok(_) => println!("Successfully sent FCM notification to user: {}", user_id),
                     Err(e) => eprintln!(
-                        "Failed to send FCM notification to admin user {}: {:?}",
-                        admin_id, e
+                        "Failed to send FCM notification to user {}: {:?}",
+                        user_id, e
                     ),
                 }
             });
@@ -155,6 +169,42 @@ impl NotificationService for FcmNotificationServiceImpl {
         for task in tasks {
             let _ = task.await;
         }
+    }
+}
+
+#[async_trait]
+impl NotificationService for FcmNotificationServiceImpl {
+    async fn send_notification(&self, event: NotificationEvent) -> anyhow::Result<()> {
+        let admin_dev = self.get_admin_devices().await?;
+
+        if admin_dev.is_empty() {
+            println!("No admin devices registered to send notification to.");
+            return Ok(());
+        }
+
+        let (notification, data_payload) = Self::build_message(event);
+        let targets = admin_dev
+            .into_iter()
+            .map(|device| (device.user_id, device.fcm_token))
+            .collect();
+        self.dispatch(targets, notification, data_payload).await;
+
+        Ok(())
+    }
+
+    async fn send_to_users(
+        &self,
+        user_ids: Vec<i32>,
+        event: NotificationEvent,
+    ) -> anyhow::Result<()> {
+        let devices = self.get_user_devices(user_ids).await?;
+
+        if devices.is_empty() {
+            return Ok(());
+        }
+
+        let (notification, data_payload) = Self::build_message(event);
+        self.dispatch(devices, notification, data_payload).await;
 
         Ok(())
     }
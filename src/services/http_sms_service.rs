@@ -0,0 +1,69 @@
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+
+use super::sms_service::SmsService;
+use crate::config::SmsConfiguration;
+
+#[derive(Clone, Copy)]
+pub enum SmsProvider {
+    Sparrow,
+    Aakash,
+}
+
+/// Sends through the HTTP APIs of the Nepali gateways. Both take a form post
+/// with the token, the recipient and the text, only the field names differ.
+pub struct HttpSmsService {
+    provider: SmsProvider,
+    client: Client,
+    api_url: String,
+    api_token: String,
+    sender_id: String,
+}
+
+impl HttpSmsService {
+    pub fn new(provider: SmsProvider, config: &SmsConfiguration, client: &Client) -> Self {
+        Self {
+            provider,
+            client: client.to_owned(),
+            api_url: config.api_url.to_owned(),
+            api_token: config.api_token.to_owned(),
+            sender_id: config.sender_id.to_owned(),
+        }
+    }
+}
+
+#[async_trait]
+impl SmsService for HttpSmsService {
+    async fn send_sms(&self, to_number: &str, message: &str) -> Result<()> {
+        let form: Vec<(&str, &str)> = match self.provider {
+            SmsProvider::Sparrow => vec![
+                ("token", &self.api_token),
+                ("from", &self.sender_id),
+                ("to", to_number),
+                ("text", message),
+            ],
+            SmsProvider::Aakash => vec![
+                ("auth_token", &self.api_token),
+                ("to", to_number),
+                ("text", message),
+            ],
+        };
+
+        let response = self
+            .client
+            .post(&self.api_url)
+            .form(&form)
+            .send()
+            .await
+            .context("Failed to reach SMS gateway")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            bail!("SMS gateway returned {}: {}", status, body);
+        }
+
+        Ok(())
+    }
+}
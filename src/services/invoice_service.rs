@@ -17,6 +17,7 @@ pub struct InvoiceItem {
     pub unit_price: f64,
     pub sku: String,
     pub total: f64,
+    pub tier_min_quantity: Option<f64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -135,7 +136,7 @@ impl InvoiceService {
         .context("Failed to load font family")?;
 
         let mut doc = Document::new(font_family);
-        doc.set_title(&format!("Invoice {}", invoice.invoice_number));
+        doc.set_title(format!("Invoice {}", invoice.invoice_number));
 
         let mut decorator = genpdf::SimplePageDecorator::new();
         decorator.set_margins(10);
@@ -197,8 +198,8 @@ impl InvoiceService {
 
         details.push(Paragraph::new(&invoice_title).styled(Style::new().bold()));
         details
-            .push(Paragraph::new(&format!("Order Id #: {}", order_id)).styled(Style::new().bold()));
-        details.push(Paragraph::new(&format!("Date: {}", invoice.date)).styled(Style::new()));
+            .push(Paragraph::new(format!("Order Id #: {}", order_id)).styled(Style::new().bold()));
+        details.push(Paragraph::new(format!("Date: {}", invoice.date)).styled(Style::new()));
 
         details
     }
@@ -231,7 +232,7 @@ impl InvoiceService {
             table
                 .row()
                 .element(Self::center_padded_paragraph(&format!("{}", index + 1)))
-                .element(Self::left_padded_paragraph(&item.description))
+                .element(Self::left_padded_paragraph(&Self::item_description(item)))
                 .element(Self::center_padded_paragraph(&format!(
                     "{:.2} {}",
                     item.quantity, item.sku
@@ -248,21 +249,31 @@ impl InvoiceService {
         table
     }
 
+    fn item_description(item: &InvoiceItem) -> String {
+        match item.tier_min_quantity {
+            Some(min_qty) => format!(
+                "{} (bulk rate {:.2}+ {})",
+                item.description, min_qty, item.sku
+            ),
+            None => item.description.to_owned(),
+        }
+    }
+
     fn create_totals_section(invoice: &Invoice) -> LinearLayout {
         let mut totals = LinearLayout::vertical();
         totals.push(Break::new(1));
         totals.push(
-            Paragraph::new(&format!("Subtotal: Rs. {:.2}", invoice.subtotal))
+            Paragraph::new(format!("Subtotal: Rs. {:.2}", invoice.subtotal))
                 .aligned(Alignment::Right)
                 .styled(Style::new().with_font_size(12)),
         );
         totals.push(
-            Paragraph::new(&format!("Discount: Rs. {:.2}", invoice.discount))
+            Paragraph::new(format!("Discount: Rs. {:.2}", invoice.discount))
                 .aligned(Alignment::Right)
                 .styled(Style::new().with_font_size(12)),
         );
         totals.push(
-            Paragraph::new(&format!(
+            Paragraph::new(format!(
                 "Tax ({:.1}%): Rs. {:.2}",
                 invoice.tax_rate * 100.0,
                 invoice.tax_amount
@@ -272,7 +283,7 @@ impl InvoiceService {
         );
         totals.push(Break::new(0.5));
         totals.push(
-            Paragraph::new(&format!("Total: Rs. {:.2}", invoice.total))
+            Paragraph::new(format!("Total: Rs. {:.2}", invoice.total))
                 .aligned(Alignment::Right)
                 .styled(Style::new().bold().with_font_size(14)),
         );
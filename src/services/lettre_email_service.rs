@@ -7,7 +7,7 @@ use lettre::{
 };
 use tokio::time::{timeout, Duration};
 
-use super::email_service::{EmailService, EmailServiceFactory};
+use super::email_service::EmailService;
 use crate::config::EmailConfiguration;
 use crate::services::invoice_service::{Invoice, InvoiceItem};
 
@@ -28,18 +28,20 @@ impl LettreEmailService {
     fn create_mailer(config: &EmailConfiguration) -> Result<AsyncSmtpTransport<Tokio1Executor>> {
         let creds = Credentials::new(config.username.clone(), config.password.clone());
 
-        let mailer_builder =
+        let mailer_builder = if config.use_tls {
             AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.smtp_server)
                 .context("Failed to create STARTTLS SMTP relay")?
-                .credentials(creds)
-                .timeout(Some(std::time::Duration::from_secs(config.timeout_seconds)));
-
-        let mailer = if config.use_tls {
-            mailer_builder.build()
         } else {
-            mailer_builder.build()
+            // no encryption at all, only meant for a local mail server
+            AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.smtp_server)
         };
 
+        let mailer = mailer_builder
+            .port(config.smtp_port)
+            .credentials(creds)
+            .timeout(Some(std::time::Duration::from_secs(config.timeout_seconds)))
+            .build();
+
         Ok(mailer)
     }
 
@@ -244,15 +246,6 @@ impl EmailService for LettreEmailService {
         Ok(())
     }
 
-    async fn send_simple_email(&self, to_email: &str, subject: &str, body: &str) -> Result<()> {
-        self.send_simple_text_email(to_email, subject, body)
-            .await
-            .context("Failed to send simple email")?;
-
-        println!("Simple email sent to {}: {}", to_email, subject);
-        Ok(())
-    }
-
     async fn send_html_email(
         &self,
         to_email: &str,
@@ -296,14 +289,4 @@ impl EmailService for LettreEmailService {
         println!("HTML email sent to {}: {}", to_email, subject);
         Ok(())
     }
-
-    async fn test_connection(&self) -> Result<()> {
-        timeout(Duration::from_secs(10), self.mailer.test_connection())
-            .await
-            .context("Connection test timed out")?
-            .context("SMTP connection test failed")?;
-
-        println!("SMTP connection test successful");
-        Ok(())
-    }
 }
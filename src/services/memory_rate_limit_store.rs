@@ -0,0 +1,42 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use anyhow::{anyhow, Result};
+use chrono::NaiveDateTime;
+
+use super::rate_limit_store::{FailureCount, FailureRecord, RateLimitStore};
+
+/// Keeps the counts in the process, they are lost on restart and not shared
+/// with other processes.
+#[derive(Default)]
+pub struct MemoryRateLimitStore {
+    records: Mutex<HashMap<String, FailureRecord>>,
+}
+
+impl RateLimitStore for MemoryRateLimitStore {
+    fn get(&self, key: &str) -> Result<Option<FailureRecord>> {
+        let records = self.records.lock().map_err(|e| anyhow!("{}", e))?;
+        Ok(records.get(key).cloned())
+    }
+
+    fn increment(&self, key: &str, count: &FailureCount) -> Result<FailureRecord> {
+        // held across the read and the write so no failure is lost
+        let mut records = self.records.lock().map_err(|e| anyhow!("{}", e))?;
+        let record = FailureRecord::incremented(records.get(key), count);
+        records.insert(key.to_owned(), record.clone());
+        Ok(record)
+    }
+
+    fn remove(&self, keys: &[String]) -> Result<()> {
+        let mut records = self.records.lock().map_err(|e| anyhow!("{}", e))?;
+        for key in keys {
+            records.remove(key);
+        }
+        Ok(())
+    }
+
+    fn purge(&self, before: NaiveDateTime) -> Result<()> {
+        let mut records = self.records.lock().map_err(|e| anyhow!("{}", e))?;
+        records.retain(|_, r| r.last_failure_at >= before);
+        Ok(())
+    }
+}
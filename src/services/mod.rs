@@ -1,6 +1,27 @@
+pub mod access_token_service;
+pub mod audit_service;
+pub mod availability_service;
+pub mod cart_service;
+pub mod console_sms_service;
 pub mod email_service;
+pub mod email_verification_service;
 pub mod fcm_notification_service;
+pub mod http_sms_service;
 pub mod invoice_service;
 pub mod lettre_email_service;
+pub mod memory_rate_limit_store;
 pub mod notification_service;
 pub mod opt_service;
+pub mod order_service;
+pub mod permission_service;
+pub mod pre_order_service;
+pub mod pricing_service;
+pub mod rate_limit_service;
+pub mod rate_limit_store;
+pub mod refresh_token_service;
+pub mod setting_service;
+pub mod sms_service;
+pub mod sqlite_rate_limit_store;
+pub mod subscription_service;
+pub mod two_factor_service;
+pub mod wishlist_service;
@@ -3,9 +3,10 @@ use async_trait::async_trait;
 pub enum NotificationEvent {
     NewOrder(NewOrderPayload),
     PaymentReceived(PaymentReceivedPayload),
-    OrderCancelled(OrderCancelledPayload),
-    OrderFulfilled(OrderFulfilledPayload),
-    Generic(GenericNotificationPayload),
+    BackInStock(ProductAlertPayload),
+    PriceDrop(ProductAlertPayload),
+    CartReminder(CartReminderPayload),
+    SubscriptionOrder(SubscriptionOrderPayload),
 }
 
 pub struct NewOrderPayload {
@@ -21,23 +22,33 @@ pub struct PaymentReceivedPayload {
     pub payment_method: String,
 }
 
-pub struct OrderCancelledPayload {
-    pub order_id: String,
-    pub reason: Option<String>,
+pub struct ProductAlertPayload {
+    pub product_id: String,
+    pub product_name: String,
+    pub price: f64,
+    pub previous_price: f64,
 }
 
-pub struct OrderFulfilledPayload {
-    pub order_id: String,
-    pub details: Option<String>,
+pub struct CartReminderPayload {
+    pub item_count: usize,
 }
 
-pub struct GenericNotificationPayload {
-    pub title: String,
-    pub body: String,
-    pub data: std::collections::HashMap<String, String>,
+pub struct SubscriptionOrderPayload {
+    pub order_id: String,
+    pub delivery_on: String,
+    pub delivery_slot: String,
+    pub total_amount: f64,
 }
 
 #[async_trait]
 pub trait NotificationService: Send + Sync + 'static {
     async fn send_notification(&self, event: NotificationEvent) -> Result<(), anyhow::Error>;
+
+    /// Sends the event to the devices customers registered, unlike
+    /// `send_notification` which only reaches admins.
+    async fn send_to_users(
+        &self,
+        user_ids: Vec<i32>,
+        event: NotificationEvent,
+    ) -> Result<(), anyhow::Error>;
 }
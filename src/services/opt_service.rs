@@ -1,11 +1,14 @@
+use std::fmt;
+
 use chrono::Utc;
 use diesel::prelude::*;
 use rand::Rng;
 
 use crate::{
+    base_types::{otp_channel::OtpChannel, otp_purpose::OtpPurpose},
     db::connection::{get_conn, SqliteConnectionPool},
     models::{
-        password_reset_otp::{NewPasswordResetOtp, PasswordResetOtp},
+        otp::{NewOtp, Otp},
         user::User,
     },
     utils::password_helper,
@@ -14,12 +17,20 @@ use crate::{
 #[derive(Debug)]
 pub enum OtpError {
     DatabaseError(diesel::result::Error),
-    UserNotFound,
-    InvalidOtp,
     AttemptsExceeded,
     OtpExpired,
 }
 
+impl fmt::Display for OtpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OtpError::DatabaseError(e) => write!(f, "{}", e),
+            OtpError::AttemptsExceeded => write!(f, "too many attempts"),
+            OtpError::OtpExpired => write!(f, "otp has expired"),
+        }
+    }
+}
+
 impl From<diesel::result::Error> for OtpError {
     fn from(err: diesel::result::Error) -> Self {
         OtpError::DatabaseError(err)
@@ -45,6 +56,24 @@ impl OtpService {
         (otp, otp_hash)
     }
 
+    fn check_code(
+        purpose: &OtpPurpose,
+        attempts: i32,
+        expires_at: &str,
+        otp_hash: &str,
+        otp_code_str: &str,
+    ) -> Result<bool, OtpError> {
+        if attempts >= purpose.max_attempts() {
+            return Err(OtpError::AttemptsExceeded);
+        }
+
+        if Utc::now().to_rfc3339().as_str() > expires_at {
+            return Err(OtpError::OtpExpired);
+        }
+
+        Ok(password_helper::verify_otp_hash(otp_hash, otp_code_str))
+    }
+
     pub fn find_user_by_email(&self, email: &str) -> Result<Option<User>, diesel::result::Error> {
         use crate::schema::users;
 
@@ -57,120 +86,141 @@ impl OtpService {
             .optional()
     }
 
-    pub fn create_password_reset_otp(
+    /// Issues a new code for `purpose` to `recipient`, the email address or
+    /// phone number it is sent to. Codes issued earlier for the same purpose
+    /// and recipient stop working. The plain code is only on the returned
+    /// record, the table keeps its hash.
+    pub fn create_otp(
         &self,
-        user_id: i32,
-        expiry_seconds: i32,
-    ) -> Result<PasswordResetOtp, OtpError> {
-        use crate::schema::password_reset_otps;
+        purpose: &OtpPurpose,
+        channel: &OtpChannel,
+        recipient: &str,
+        user_id: Option<i32>,
+    ) -> Result<Otp, OtpError> {
+        use crate::schema::otps;
 
         let conn = &mut get_conn(&self.pool);
 
         conn.transaction::<_, OtpError, _>(|con| {
-            diesel::update(password_reset_otps::table)
-                .filter(password_reset_otps::user_id.eq(user_id))
-                .filter(password_reset_otps::is_used.eq(false))
-                .set(password_reset_otps::is_used.eq(true))
+            diesel::update(otps::table)
+                .filter(otps::purpose.eq(purpose.value()))
+                .filter(otps::recipient.eq(recipient))
+                .filter(otps::is_used.eq(false))
+                .set(otps::is_used.eq(true))
                 .execute(con)?;
 
             let otp_code_str = Self::generate_otp();
 
-            let new_otp = NewPasswordResetOtp::new(user_id, otp_code_str.1, expiry_seconds);
+            let new_otp = NewOtp::new(purpose, channel, recipient, user_id, otp_code_str.1);
 
-            let mut otp = diesel::insert_into(password_reset_otps::table)
+            let mut otp = diesel::insert_into(otps::table)
                 .values(&new_otp)
-                .get_result::<PasswordResetOtp>(con)?;
+                .returning(Otp::as_returning())
+                .get_result(con)?;
 
             otp.otp_code = otp_code_str.0;
             Ok(otp)
         })
     }
 
-    pub fn verify_otp(&self, uid: i32, otp_code_str: &str) -> Result<bool, OtpError> {
-        use crate::schema::password_reset_otps;
+    /// Checks a code without using it up, for flows that confirm the code
+    /// first and act on it in a later request.
+    pub fn verify_otp(
+        &self,
+        purpose: &OtpPurpose,
+        recipient: &str,
+        otp_code_str: &str,
+    ) -> Result<bool, OtpError> {
+        self.check_otp(purpose, recipient, otp_code_str, false)
+    }
+
+    /// Checks a code and uses it up when it matches.
+    pub fn consume_otp(
+        &self,
+        purpose: &OtpPurpose,
+        recipient: &str,
+        otp_code_str: &str,
+    ) -> Result<bool, OtpError> {
+        self.check_otp(purpose, recipient, otp_code_str, true)
+    }
+
+    fn check_otp(
+        &self,
+        purpose: &OtpPurpose,
+        recipient: &str,
+        otp_code_str: &str,
+        consume: bool,
+    ) -> Result<bool, OtpError> {
+        use crate::schema::otps;
 
         let conn = &mut get_conn(&self.pool);
 
+        // a wrong code is recorded even though the check itself fails
         conn.transaction::<_, OtpError, _>(|con| {
-            let otp_record = password_reset_otps::table
-                .filter(password_reset_otps::user_id.eq(uid))
-                .filter(password_reset_otps::is_used.eq(false))
-                .filter(password_reset_otps::expires_at.gt(Utc::now().to_rfc3339()))
-                .order(password_reset_otps::created_at.desc())
-                .first::<PasswordResetOtp>(con)
+            let otp_record = otps::table
+                .filter(otps::purpose.eq(purpose.value()))
+                .filter(otps::recipient.eq(recipient))
+                .filter(otps::is_used.eq(false))
+                .filter(otps::expires_at.gt(Utc::now().to_rfc3339()))
+                .order(otps::created_at.desc())
+                .select(Otp::as_select())
+                .first(con)
                 .optional()?;
 
             let otp_record = match otp_record {
                 Some(record) => record,
-                None => return Ok(false),
+                None => return Ok(Ok(false)),
             };
 
-            if otp_record.attempts >= 2 {
-                diesel::update(password_reset_otps::table)
-                    .filter(password_reset_otps::id.eq(otp_record.id))
-                    .set(password_reset_otps::is_used.eq(true))
-                    .execute(con)?;
-                return Err(OtpError::AttemptsExceeded);
-            }
-
-            diesel::update(password_reset_otps::table)
-                .filter(password_reset_otps::id.eq(otp_record.id))
-                .set(password_reset_otps::attempts.eq(otp_record.attempts + 1))
-                .execute(con)?;
-
-            if Utc::now().to_rfc3339() > otp_record.expires_at {
-                return Err(OtpError::OtpExpired);
-            }
-
-            if password_helper::verify_otp_hash(&otp_record.otp_code, otp_code_str) {
-                return Ok(true);
-            }
-
-            Ok(false)
-        })
-    }
-
-    pub fn mark_otp_as_used(
-        &self,
-        uid: i32,
-        otp_code_str: &str,
-    ) -> Result<(), diesel::result::Error> {
-        use crate::schema::password_reset_otps;
-
-        let conn = &mut get_conn(&self.pool);
-
-        diesel::update(password_reset_otps::table)
-            .filter(password_reset_otps::user_id.eq(uid))
-            .filter(password_reset_otps::expires_at.gt(Utc::now().to_rfc3339()))
-            .filter(password_reset_otps::otp_code.eq(otp_code_str))
-            .filter(password_reset_otps::is_used.eq(false))
-            .set(password_reset_otps::is_used.eq(true))
-            .execute(conn)?;
+            let result = Self::check_code(
+                purpose,
+                otp_record.attempts,
+                &otp_record.expires_at,
+                &otp_record.otp_code,
+                otp_code_str,
+            );
+
+            let update = diesel::update(otps::table).filter(otps::id.eq(otp_record.id));
+            match result {
+                Ok(true) if !consume => 0,
+                Ok(true) | Err(OtpError::AttemptsExceeded) => {
+                    update.set(otps::is_used.eq(true)).execute(con)?
+                }
+                _ => update
+                    .set(otps::attempts.eq(otp_record.attempts + 1))
+                    .execute(con)?,
+            };
 
-        Ok(())
+            Ok(result)
+        })?
     }
 
     pub fn cleanup_expired_otps(&self) -> Result<usize, diesel::result::Error> {
-        use crate::schema::password_reset_otps;
+        use crate::schema::otps;
 
         let conn = &mut get_conn(&self.pool);
 
-        let deleted_count = diesel::delete(password_reset_otps::table)
-            .filter(password_reset_otps::expires_at.lt(Utc::now().to_rfc3339()))
+        let deleted_count = diesel::delete(otps::table)
+            .filter(otps::expires_at.lt(Utc::now().to_rfc3339()))
             .execute(conn)?;
 
         Ok(deleted_count)
     }
 
-    pub fn has_valid_otp(&self, user_id: i32) -> Result<bool, diesel::result::Error> {
-        use crate::schema::password_reset_otps;
+    pub fn has_valid_otp(
+        &self,
+        purpose: &OtpPurpose,
+        recipient: &str,
+    ) -> Result<bool, diesel::result::Error> {
+        use crate::schema::otps;
 
         let conn = &mut get_conn(&self.pool);
 
-        let count = password_reset_otps::table
-            .filter(password_reset_otps::user_id.eq(user_id))
-            .filter(password_reset_otps::is_used.eq(false))
-            .filter(password_reset_otps::expires_at.gt(Utc::now().to_rfc3339()))
+        let count = otps::table
+            .filter(otps::purpose.eq(purpose.value()))
+            .filter(otps::recipient.eq(recipient))
+            .filter(otps::is_used.eq(false))
+            .filter(otps::expires_at.gt(Utc::now().to_rfc3339()))
             .count()
             .get_result::<i64>(conn)?;
 
@@ -182,7 +232,7 @@ impl OtpService {
         user_id: i32,
         new_password_hash: &str,
     ) -> Result<(), diesel::result::Error> {
-        use crate::schema::{password_reset_otps, users};
+        use crate::schema::{otps, users};
 
         let conn = &mut get_conn(&self.pool);
 
@@ -192,9 +242,10 @@ impl OtpService {
                 .set(users::password.eq(new_password_hash))
                 .execute(conn)?;
 
-            diesel::update(password_reset_otps::table)
-                .filter(password_reset_otps::user_id.eq(user_id))
-                .set(password_reset_otps::is_used.eq(true))
+            diesel::update(otps::table)
+                .filter(otps::user_id.eq(user_id))
+                .filter(otps::purpose.eq(OtpPurpose::PasswordReset.value()))
+                .set(otps::is_used.eq(true))
                 .execute(conn)?;
 
             Ok(())
@@ -0,0 +1,255 @@
+use std::fmt;
+
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    base_types::{
+        delivery_status::DeliveryStatus, order_status::OrderStatus, payment_method::PaymentMethod,
+        payment_status::PaymentStatus,
+    },
+    models::{
+        invoice::{Invoice, NewInvoice},
+        invoice_item::NewInvoiceItem,
+        order::{NewOrder, Order, OrderDetails},
+        order_item::NewOrderItem,
+        payment::{NewPayment, Payment},
+        product::Product,
+        shipment::NewShipment,
+        user::User,
+    },
+    services::{availability_service, cart_service, pricing_service},
+};
+
+pub struct OrderLine<'a> {
+    pub product_id: &'a str,
+    pub quantity: f64,
+    pub discount: f64,
+}
+
+/// Everything needed to place an order for a customer. The items are priced
+/// here, delivery is added on top by the order itself.
+pub struct OrderRequest<'a> {
+    pub created_on: &'a String,
+    pub delivery_charge: f64,
+    pub delivery_location: &'a String,
+    pub payment_method: &'a PaymentMethod,
+    // items total the customer was shown, the order is refused when the
+    // current prices come to something else
+    pub expected_items_total: Option<f64>,
+    pub quantity: f64,
+    pub discount: f64,
+    pub lines: Vec<OrderLine<'a>>,
+}
+
+pub enum PlaceOrderError {
+    // the order can't be placed as asked, the message is meant for the customer
+    Rejected(String),
+    Database(diesel::result::Error),
+}
+
+impl From<diesel::result::Error> for PlaceOrderError {
+    fn from(e: diesel::result::Error) -> Self {
+        PlaceOrderError::Database(e)
+    }
+}
+
+impl fmt::Display for PlaceOrderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlaceOrderError::Rejected(message) => write!(f, "{}", message),
+            PlaceOrderError::Database(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// Places an order with its shipment, payment and invoice, and takes the
+/// ordered quantities out of stock, or reserves them for pre-order products.
+/// Nothing is written unless every line can be ordered.
+pub fn place_order(
+    conn: &mut SqliteConnection,
+    user: &User,
+    request: &OrderRequest,
+) -> Result<Order, PlaceOrderError> {
+    use crate::schema::{
+        invoice_items, invoices, order_items, orders, payments, products, shipments,
+    };
+
+    let today = availability_service::today();
+
+    conn.transaction::<_, PlaceOrderError, _>(|con| {
+        let mut items_total = 0.0;
+        let mut priced_lines = Vec::with_capacity(request.lines.len());
+
+        for line in &request.lines {
+            let product: Product = products::table
+                .filter(products::uuid.eq(line.product_id))
+                .select(Product::as_select())
+                .first(con)
+                .optional()?
+                .ok_or_else(|| {
+                    PlaceOrderError::Rejected("Product not found for order".to_string())
+                })?;
+
+            if line.quantity <= 0.0
+                || !cart_service::is_quantity_step(line.quantity, product.get_unit_change())
+            {
+                return Err(PlaceOrderError::Rejected(format!(
+                    "{} is sold in steps of {} {}",
+                    product.get_name(),
+                    product.get_unit_change(),
+                    product.get_unit()
+                )));
+            }
+
+            // pre-orders reserve against stock that has not come in yet
+            if product.is_preorder() {
+                if product.get_preorder_remaining() < line.quantity {
+                    return Err(PlaceOrderError::Rejected(format!(
+                        "Only {} {} of {} left to pre-order",
+                        product.get_preorder_remaining(),
+                        product.get_unit(),
+                        product.get_name()
+                    )));
+                }
+            } else {
+                if product.get_stock() < line.quantity {
+                    return Err(PlaceOrderError::Rejected(
+                        "Product out of stock".to_string(),
+                    ));
+                }
+
+                let availability =
+                    availability_service::availability_for_product(con, &product, today)?;
+                if !availability.available {
+                    return Err(PlaceOrderError::Rejected(
+                        availability.unavailable_message(&product),
+                    ));
+                }
+            }
+
+            let applied_price = pricing_service::price_for_quantity(con, &product, line.quantity)?;
+            items_total += applied_price.total_for(line.quantity);
+            priced_lines.push((line, product, applied_price));
+        }
+
+        if let Some(expected) = request.expected_items_total {
+            // allow for rounding in the client's sum
+            if (expected - items_total).abs() > 0.01 {
+                return Err(PlaceOrderError::Rejected(format!(
+                    "Prices have changed, the items now come to {:.2}",
+                    items_total
+                )));
+            }
+        }
+
+        let new_order = NewOrder::new(
+            user,
+            OrderDetails {
+                created_on: request.created_on,
+                delivery_charge: request.delivery_charge,
+                delivery_status: DeliveryStatus::Pending,
+                delivery_location: request.delivery_location,
+                order_total: items_total,
+                quantity: request.quantity,
+                status: OrderStatus::PaymentPending,
+                discount: request.discount,
+            },
+        );
+
+        let order: Order = diesel::insert_into(orders::table)
+            .values(&new_order)
+            .get_result(con)?;
+
+        let shipment = NewShipment::new(request.delivery_location, &order);
+        diesel::insert_into(shipments::table)
+            .values(&shipment)
+            .execute(con)?;
+
+        let tran_id = if *request.payment_method == PaymentMethod::Cash {
+            Uuid::new_v4().to_string().replace('-', "")
+        } else {
+            String::new()
+        };
+
+        let new_payment = NewPayment::new(
+            request.payment_method,
+            &tran_id,
+            user,
+            &order,
+            order.get_total_price(),
+            order.get_total_price(),
+            PaymentStatus::Pending.value().to_string(),
+        );
+
+        let payment = diesel::insert_into(payments::table)
+            .values(&new_payment)
+            .get_result::<Payment>(con)?;
+
+        let nepal_time = chrono::Utc::now()
+            .with_timezone(&chrono::FixedOffset::east_opt(5 * 3600 + 45 * 60).unwrap());
+        let inv_date = nepal_time.format("%Y-%m-%d %H:%M:%S").to_string();
+
+        let new_inv = NewInvoice::new(
+            &inv_date,
+            order.get_total_price(),
+            0.0,
+            &order,
+            user,
+            &payment,
+        );
+
+        let inv = diesel::insert_into(invoices::table)
+            .values(&new_inv)
+            .get_result::<Invoice>(con)?;
+
+        let mut awaiting_stock = false;
+
+        for (line, product, applied_price) in &priced_lines {
+            let new_order_item =
+                NewOrderItem::new(line.quantity, line.discount, product, &order, applied_price);
+
+            if product.is_preorder() {
+                diesel::insert_into(order_items::table)
+                    .values(&new_order_item.reserved())
+                    .execute(con)?;
+
+                diesel::update(product)
+                    .set(
+                        products::preorder_reserved.eq(products::preorder_reserved + line.quantity),
+                    )
+                    .execute(con)?;
+
+                awaiting_stock = true;
+            } else {
+                diesel::insert_into(order_items::table)
+                    .values(&new_order_item)
+                    .execute(con)?;
+
+                diesel::update(product)
+                    .set(products::stock.eq(products::stock - line.quantity))
+                    .execute(con)?;
+            }
+
+            let new_inv_item =
+                NewInvoiceItem::new(product, &inv, line.quantity, 0.0, 0.0, applied_price);
+
+            diesel::insert_into(invoice_items::table)
+                .values(&new_inv_item)
+                .execute(con)?;
+        }
+
+        // orders with pre-ordered items wait in their own queue until the
+        // stock is received
+        let status = if awaiting_stock {
+            OrderStatus::PreOrder
+        } else {
+            OrderStatus::Processed
+        };
+        diesel::update(&order)
+            .set(orders::status.eq(status.value()))
+            .execute(con)?;
+
+        Ok(order)
+    })
+}
@@ -0,0 +1,157 @@
+use std::fmt;
+
+use diesel::prelude::*;
+
+use crate::{
+    base_types::permission::Permission,
+    models::{role_permission::NewRolePermission, user::User},
+};
+
+#[derive(Debug)]
+pub enum RoleError {
+    UnknownPermission(String),
+    Database(diesel::result::Error),
+}
+
+impl From<diesel::result::Error> for RoleError {
+    fn from(e: diesel::result::Error) -> Self {
+        RoleError::Database(e)
+    }
+}
+
+impl fmt::Display for RoleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RoleError::UnknownPermission(name) => write!(f, "Unknown permission: {}", name),
+            RoleError::Database(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+/// Admin always has every permission, so permissions added later don't have
+/// to be granted to it and it can't be locked out of role management.
+pub fn role_has_permission(
+    conn: &mut SqliteConnection,
+    role: &str,
+    permission: Permission,
+) -> QueryResult<bool> {
+    use crate::schema::{permissions, role_permissions, roles};
+
+    if role == User::USERTYPE_ADMIN {
+        return Ok(true);
+    }
+
+    diesel::select(diesel::dsl::exists(
+        role_permissions::table
+            .inner_join(roles::table)
+            .inner_join(permissions::table)
+            .filter(roles::name.eq(role))
+            .filter(permissions::name.eq(permission.value())),
+    ))
+    .get_result(conn)
+}
+
+/// Looks the role up instead of trusting the one in the access token, a role
+/// change applies from the next request.
+pub fn user_has_permission(
+    conn: &mut SqliteConnection,
+    user_uuid: &str,
+    permission: Permission,
+) -> QueryResult<bool> {
+    use crate::schema::users;
+
+    let role: Option<String> = users::table
+        .filter(users::uuid.eq(user_uuid))
+        .select(users::user_type)
+        .first(conn)
+        .optional()?;
+
+    match role {
+        Some(role) => role_has_permission(conn, &role, permission),
+        None => Ok(false),
+    }
+}
+
+pub fn role_permissions(conn: &mut SqliteConnection, role_id: i32) -> QueryResult<Vec<String>> {
+    use crate::schema::{permissions, role_permissions};
+
+    role_permissions::table
+        .inner_join(permissions::table)
+        .filter(role_permissions::role_id.eq(role_id))
+        .select(permissions::name)
+        .order(permissions::name.asc())
+        .load(conn)
+}
+
+/// The permissions in `names` that the role named `holder` doesn't have, for
+/// checking someone only hands out what they hold themselves. Names that are
+/// not permissions at all are left to `set_role_permissions` to reject.
+pub fn missing_permissions(
+    conn: &mut SqliteConnection,
+    holder: &str,
+    names: &[String],
+) -> QueryResult<Vec<String>> {
+    use crate::schema::{permissions, role_permissions, roles};
+
+    if holder == User::USERTYPE_ADMIN {
+        return Ok(vec![]);
+    }
+
+    let held: Vec<String> = role_permissions::table
+        .inner_join(roles::table)
+        .inner_join(permissions::table)
+        .filter(roles::name.eq(holder))
+        .select(permissions::name)
+        .load(conn)?;
+
+    let names: Vec<&str> = names.iter().map(|n| n.trim()).collect();
+    let known: Vec<String> = permissions::table
+        .filter(permissions::name.eq_any(&names))
+        .select(permissions::name)
+        .order(permissions::name.asc())
+        .load(conn)?;
+
+    Ok(known
+        .into_iter()
+        .filter(|name| !held.contains(name))
+        .collect())
+}
+
+/// Replaces the role's permissions, run it inside the transaction that saves
+/// the role.
+pub fn set_role_permissions(
+    conn: &mut SqliteConnection,
+    role_id: i32,
+    names: &[String],
+) -> Result<(), RoleError> {
+    use crate::schema::{permissions, role_permissions};
+
+    let mut permission_ids = Vec::with_capacity(names.len());
+    for name in names {
+        let id: Option<i32> = permissions::table
+            .filter(permissions::name.eq(name.trim()))
+            .select(permissions::id)
+            .first(conn)
+            .optional()?;
+
+        match id {
+            Some(id) if !permission_ids.contains(&id) => permission_ids.push(id),
+            Some(_) => {}
+            None => return Err(RoleError::UnknownPermission(name.to_owned())),
+        }
+    }
+
+    diesel::delete(role_permissions::table.filter(role_permissions::role_id.eq(role_id)))
+        .execute(conn)?;
+
+    let rows: Vec<NewRolePermission> = permission_ids
+        .into_iter()
+        .map(|permission_id| NewRolePermission::new(role_id, permission_id))
+        .collect();
+
+    diesel::insert_into(role_permissions::table)
+        .values(&rows)
+        .execute(conn)?;
+
+    Ok(())
+}
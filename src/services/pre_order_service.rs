@@ -0,0 +1,79 @@
+use diesel::prelude::*;
+
+use crate::{
+    base_types::order_status::OrderStatus,
+    models::{order::Order, order_item::OrderItem, product::Product},
+};
+
+/// Adds `quantity` to the stock of `product` and hands it to waiting
+/// pre-orders, oldest order first. An order moves on to normal fulfilment once
+/// none of its items are waiting any more. When every reservation is covered
+/// the product goes back to regular sale. Returns the orders that moved on.
+pub fn receive_stock(
+    conn: &mut SqliteConnection,
+    product: &Product,
+    quantity: f64,
+) -> QueryResult<Vec<Order>> {
+    use crate::schema::{order_items, orders, products};
+
+    conn.transaction(|conn| {
+        let waiting: Vec<(OrderItem, Order)> = order_items::table
+            .inner_join(orders::table)
+            .filter(order_items::product_id.eq(product.get_id()))
+            .filter(order_items::awaiting_stock.eq(true))
+            .order(orders::id.asc())
+            .select((OrderItem::as_select(), Order::as_select()))
+            .load(conn)?;
+
+        let mut stock = product.get_stock() + quantity;
+        let mut reserved = product.get_preorder_reserved();
+        let mut released = vec![];
+
+        for (item, order) in waiting {
+            // later orders don't jump the queue with a smaller quantity
+            if item.get_quantity() > stock {
+                break;
+            }
+            stock -= item.get_quantity();
+            reserved -= item.get_quantity();
+
+            diesel::update(&item)
+                .set(order_items::awaiting_stock.eq(false))
+                .execute(conn)?;
+
+            let still_waiting: i64 = order_items::table
+                .filter(order_items::order_id.eq(order.get_id()))
+                .filter(order_items::awaiting_stock.eq(true))
+                .count()
+                .get_result(conn)?;
+
+            if still_waiting == 0 {
+                diesel::update(&order)
+                    .set(orders::status.eq(OrderStatus::Processed.value()))
+                    .execute(conn)?;
+                released.push(order);
+            }
+        }
+
+        if reserved <= 0.0 {
+            diesel::update(product)
+                .set((
+                    products::stock.eq(stock),
+                    products::is_preorder.eq(false),
+                    products::preorder_available_on.eq(None::<String>),
+                    products::preorder_capacity.eq(0.0),
+                    products::preorder_reserved.eq(0.0),
+                ))
+                .execute(conn)?;
+        } else {
+            diesel::update(product)
+                .set((
+                    products::stock.eq(stock),
+                    products::preorder_reserved.eq(reserved),
+                ))
+                .execute(conn)?;
+        }
+
+        Ok(released)
+    })
+}
@@ -0,0 +1,173 @@
+use diesel::prelude::*;
+
+use crate::models::{
+    order_item::OrderItem, product::Product, product_price_tier::ProductPriceTier,
+};
+
+/// Unit price resolved for a product at a given quantity, together with the
+/// tier (if any) that produced it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AppliedPrice {
+    pub unit_price: f64,
+    pub tier_id: Option<i32>,
+    pub tier_min_quantity: Option<f64>,
+}
+
+impl AppliedPrice {
+    pub fn base(product: &Product) -> Self {
+        Self {
+            unit_price: product.get_price(),
+            tier_id: None,
+            tier_min_quantity: None,
+        }
+    }
+
+    pub fn from_tier(tier: &ProductPriceTier) -> Self {
+        Self {
+            unit_price: tier.get_price(),
+            tier_id: Some(tier.get_id()),
+            tier_min_quantity: Some(tier.get_min_quantity()),
+        }
+    }
+
+    /// Rebuilds the price that was applied when the order item was created so
+    /// invoices raised later bill the same rate the customer ordered at. An
+    /// item without quantity has no rate to rebuild and bills nothing.
+    pub fn from_order_item(item: &OrderItem) -> Self {
+        let unit_price = if item.get_quantity() > 0.0 {
+            item.get_price() / item.get_quantity()
+        } else {
+            0.0
+        };
+
+        Self {
+            unit_price,
+            tier_id: item.get_price_tier_id(),
+            tier_min_quantity: item.get_tier_min_quantity(),
+        }
+    }
+
+    pub fn total_for(&self, quantity: f64) -> f64 {
+        self.unit_price * quantity
+    }
+}
+
+/// Picks the tier with the largest minimum quantity not exceeding `quantity`,
+/// falling back to the product's list price when no tier applies.
+pub fn price_for_quantity(
+    conn: &mut SqliteConnection,
+    product: &Product,
+    quantity: f64,
+) -> QueryResult<AppliedPrice> {
+    use crate::schema::product_price_tiers;
+
+    let tier = ProductPriceTier::belonging_to(product)
+        .filter(product_price_tiers::min_quantity.le(quantity))
+        .order(product_price_tiers::min_quantity.desc())
+        .select(ProductPriceTier::as_select())
+        .first(conn)
+        .optional()?;
+
+    Ok(match tier {
+        Some(t) => AppliedPrice::from_tier(&t),
+        None => AppliedPrice::base(product),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::test_db,
+        models::{order_item::NewOrderItem, product_price_tier::NewProductPriceTier},
+    };
+
+    #[test]
+    fn price_for_quantity_uses_the_largest_tier_reached() {
+        use crate::schema::product_price_tiers;
+
+        let conn = &mut test_db::connection();
+        let product = test_db::product(conn, 100.0, 50.0, 0.5);
+        diesel::insert_into(product_price_tiers::table)
+            .values(&vec![
+                NewProductPriceTier::new(&product, 5.0, 90.0),
+                NewProductPriceTier::new(&product, 10.0, 80.0),
+            ])
+            .execute(conn)
+            .unwrap();
+
+        let below = price_for_quantity(conn, &product, 4.5).unwrap();
+        assert_eq!(below, AppliedPrice::base(&product));
+
+        let at_first = price_for_quantity(conn, &product, 5.0).unwrap();
+        assert_eq!(at_first.unit_price, 90.0);
+        assert_eq!(at_first.tier_min_quantity, Some(5.0));
+
+        let below_second = price_for_quantity(conn, &product, 9.5).unwrap();
+        assert_eq!(below_second.unit_price, 90.0);
+
+        let at_second = price_for_quantity(conn, &product, 10.0).unwrap();
+        assert_eq!(at_second.unit_price, 80.0);
+        assert_eq!(at_second.tier_min_quantity, Some(10.0));
+        assert_eq!(at_second.total_for(10.0), 800.0);
+    }
+
+    #[test]
+    fn price_for_quantity_without_tiers_is_the_list_price() {
+        let conn = &mut test_db::connection();
+        let product = test_db::product(conn, 100.0, 50.0, 0.5);
+
+        let price = price_for_quantity(conn, &product, 40.0).unwrap();
+        assert_eq!(price.unit_price, 100.0);
+        assert_eq!(price.tier_id, None);
+    }
+
+    #[test]
+    fn from_order_item_rebuilds_the_ordered_rate() {
+        use crate::schema::order_items;
+
+        let conn = &mut test_db::connection();
+        let product = test_db::product(conn, 100.0, 50.0, 0.5);
+        let user = test_db::user(conn, "Customer");
+        let order = test_db::order(conn, &user);
+        let ordered = AppliedPrice {
+            unit_price: 90.0,
+            tier_id: None,
+            tier_min_quantity: Some(5.0),
+        };
+
+        let item: OrderItem = diesel::insert_into(order_items::table)
+            .values(&NewOrderItem::new(6.0, 0.0, &product, &order, &ordered))
+            .returning(OrderItem::as_returning())
+            .get_result(conn)
+            .unwrap();
+
+        assert_eq!(AppliedPrice::from_order_item(&item), ordered);
+    }
+
+    #[test]
+    fn from_order_item_without_quantity_bills_nothing() {
+        use crate::schema::order_items;
+
+        let conn = &mut test_db::connection();
+        let product = test_db::product(conn, 100.0, 50.0, 0.5);
+        let user = test_db::user(conn, "Customer");
+        let order = test_db::order(conn, &user);
+
+        let item: OrderItem = diesel::insert_into(order_items::table)
+            .values(&NewOrderItem::new(
+                0.0,
+                0.0,
+                &product,
+                &order,
+                &AppliedPrice::base(&product),
+            ))
+            .returning(OrderItem::as_returning())
+            .get_result(conn)
+            .unwrap();
+
+        let price = AppliedPrice::from_order_item(&item);
+        assert_eq!(price.unit_price, 0.0);
+        assert_eq!(price.total_for(item.get_quantity()), 0.0);
+    }
+}
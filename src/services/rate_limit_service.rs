@@ -0,0 +1,353 @@
+use std::sync::Arc;
+
+use chrono::{Duration, NaiveDateTime, Utc};
+use log::error;
+
+use super::{
+    email_service::EmailService,
+    rate_limit_store::{FailureCount, FailureRecord, RateLimitStore},
+};
+use crate::config::RateLimitConfiguration;
+
+/// The endpoints that are throttled, each one is counted separately.
+pub enum RateLimitAction {
+    Login,
+    PasswordResetRequest,
+    OtpVerify,
+    VerificationEmailRequest,
+    PhoneOtpRequest,
+}
+
+impl RateLimitAction {
+    pub fn value(&self) -> &str {
+        match *self {
+            RateLimitAction::Login => "login",
+            RateLimitAction::PasswordResetRequest => "password-reset-request",
+            RateLimitAction::OtpVerify => "otp-verify",
+            RateLimitAction::VerificationEmailRequest => "verification-email-request",
+            RateLimitAction::PhoneOtpRequest => "phone-otp-request",
+        }
+    }
+
+    fn description(&self) -> &str {
+        match *self {
+            RateLimitAction::Login => "failed sign-in attempts",
+            RateLimitAction::PasswordResetRequest => "password reset requests",
+            RateLimitAction::OtpVerify => "wrong one-time codes",
+            RateLimitAction::VerificationEmailRequest => "verification email requests",
+            RateLimitAction::PhoneOtpRequest => "sign-in code requests",
+        }
+    }
+
+    fn all() -> Vec<Self> {
+        vec![
+            RateLimitAction::Login,
+            RateLimitAction::PasswordResetRequest,
+            RateLimitAction::OtpVerify,
+            RateLimitAction::VerificationEmailRequest,
+            RateLimitAction::PhoneOtpRequest,
+        ]
+    }
+}
+
+/// Why a request was turned away and when it may be tried again.
+pub struct Throttled {
+    pub retry_after_seconds: i64,
+    pub locked: bool,
+}
+
+/// Counts failures per IP address and per account. Every failure makes the
+/// caller wait a little longer before the next try, and too many within
+/// `window_minutes` lock the address or account for `lockout_minutes`.
+#[derive(Clone)]
+pub struct RateLimiter {
+    store: Arc<dyn RateLimitStore>,
+    config: RateLimitConfiguration,
+}
+
+impl RateLimiter {
+    pub fn new(store: Arc<dyn RateLimitStore>, config: &RateLimitConfiguration) -> Self {
+        Self {
+            store,
+            config: config.to_owned(),
+        }
+    }
+
+    pub fn get_lockout_minutes(&self) -> i64 {
+        self.config.lockout_minutes
+    }
+
+    fn ip_key(action: &RateLimitAction, ip: &str) -> String {
+        format!("{}:ip:{}", action.value(), ip)
+    }
+
+    fn account_key(action: &RateLimitAction, account: &str) -> String {
+        format!("{}:account:{}", action.value(), account.to_lowercase())
+    }
+
+    fn delay_seconds(&self, failures: i32) -> i64 {
+        let doubled = self
+            .config
+            .base_delay_seconds
+            .saturating_mul(1i64 << (failures - 1).clamp(0, 30));
+        doubled.min(self.config.max_delay_seconds)
+    }
+
+    // a record older than the window no longer counts
+    fn current(&self, key: &str, now: NaiveDateTime) -> Option<FailureRecord> {
+        let record = match self.store.get(key) {
+            Ok(r) => r?,
+            Err(e) => {
+                // failing open, a broken store shouldn't stop everyone signing in
+                error!("Failed to read rate limit {}: {}", key, e);
+                return None;
+            }
+        };
+
+        let window_start = now - Duration::minutes(self.config.window_minutes);
+        let locked = record.locked_until.is_some_and(|until| until > now);
+        (locked || record.last_failure_at >= window_start).then_some(record)
+    }
+
+    fn wait_for(&self, key: &str, now: NaiveDateTime) -> Option<Throttled> {
+        let record = self.current(key, now)?;
+
+        if let Some(until) = record.locked_until.filter(|until| *until > now) {
+            return Some(Throttled {
+                retry_after_seconds: (until - now).num_seconds().max(1),
+                locked: true,
+            });
+        }
+
+        let retry_at =
+            record.last_failure_at + Duration::seconds(self.delay_seconds(record.failures));
+        (retry_at > now).then(|| Throttled {
+            retry_after_seconds: (retry_at - now).num_seconds().max(1),
+            locked: false,
+        })
+    }
+
+    /// Turns the request away while the address or the account still has to
+    /// wait, the longer wait of the two is reported.
+    pub fn check(
+        &self,
+        action: &RateLimitAction,
+        ip: &str,
+        account: Option<&str>,
+    ) -> Result<(), Throttled> {
+        let now = Utc::now().naive_utc();
+
+        let mut keys = vec![Self::ip_key(action, ip)];
+        if let Some(account) = account {
+            keys.push(Self::account_key(action, account));
+        }
+
+        match keys
+            .iter()
+            .filter_map(|key| self.wait_for(key, now))
+            .max_by_key(|t| (t.locked, t.retry_after_seconds))
+        {
+            Some(throttled) => Err(throttled),
+            None => Ok(()),
+        }
+    }
+
+    fn add_failure(&self, key: &str, max_failures: i32, now: NaiveDateTime) -> bool {
+        let lock_until = now + Duration::minutes(self.config.lockout_minutes);
+        let count = FailureCount {
+            now,
+            window_start: now - Duration::minutes(self.config.window_minutes),
+            max_failures,
+            lock_until,
+        };
+
+        match self.store.increment(key, &count) {
+            // a lock that was already running keeps its own end time
+            Ok(record) => record.locked_until == Some(lock_until),
+            Err(e) => {
+                error!("Failed to save rate limit {}: {}", key, e);
+                false
+            }
+        }
+    }
+
+    /// Counts a failure against the address and the account. Returns true
+    /// when this failure locked the account.
+    pub fn record_failure(
+        &self,
+        action: &RateLimitAction,
+        ip: &str,
+        account: Option<&str>,
+    ) -> bool {
+        let now = Utc::now().naive_utc();
+
+        let keep_for = self.config.window_minutes.max(self.config.lockout_minutes);
+        if let Err(e) = self.store.purge(now - Duration::minutes(keep_for)) {
+            error!("Failed to purge rate limits: {}", e);
+        }
+
+        self.add_failure(&Self::ip_key(action, ip), self.config.ip_max_failures, now);
+
+        match account {
+            Some(account) => self.add_failure(
+                &Self::account_key(action, account),
+                self.config.account_max_failures,
+                now,
+            ),
+            None => false,
+        }
+    }
+
+    /// Clears the account's failures once it gets through. The address keeps
+    /// its count, one good account shouldn't cover for guessing at others.
+    pub fn record_success(&self, action: &RateLimitAction, account: &str) {
+        if let Err(e) = self.store.remove(&[Self::account_key(action, account)]) {
+            error!("Failed to clear rate limit for {}: {}", account, e);
+        }
+    }
+
+    /// Lifts every lockout and delay on the account, used by admins.
+    pub fn unlock(&self, account: &str) -> anyhow::Result<()> {
+        let keys: Vec<String> = RateLimitAction::all()
+            .iter()
+            .map(|action| Self::account_key(action, account))
+            .collect();
+
+        self.store.remove(&keys)
+    }
+}
+
+/// Lets the account owner know it was locked, in the background so the
+/// request isn't held up by the mail server.
+pub fn spawn_lockout_alert(
+    email_service: Arc<dyn EmailService>,
+    action: &RateLimitAction,
+    to_email: String,
+    fullname: String,
+    lockout_minutes: i64,
+) {
+    let email_content = format!(
+        r#"
+            <h2>Your account has been locked</h2>
+            <p>Hi {},</p>
+            <p>We noticed too many {} on your account, so it has been locked for {} minutes.</p>
+            <p>If this was you, please wait and try again. If it wasn't, we recommend changing your password once the lock is lifted.</p>
+            "#,
+        fullname,
+        action.description(),
+        lockout_minutes
+    );
+
+    tokio::spawn(async move {
+        if let Err(e) = email_service
+            .send_html_email(
+                &to_email,
+                "Your account has been locked",
+                &email_content,
+                None,
+            )
+            .await
+        {
+            error!("Failed to send lockout alert to {}: {:?}", to_email, e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::memory_rate_limit_store::MemoryRateLimitStore;
+
+    fn limiter(base_delay_seconds: i64) -> RateLimiter {
+        let config = RateLimitConfiguration {
+            backend: "memory".to_string(),
+            window_minutes: 15,
+            account_max_failures: 3,
+            ip_max_failures: 10,
+            base_delay_seconds,
+            max_delay_seconds: 60,
+            lockout_minutes: 30,
+        };
+        RateLimiter::new(Arc::new(MemoryRateLimitStore::default()), &config)
+    }
+
+    #[test]
+    fn delays_double_up_to_the_maximum() {
+        let limiter = limiter(2);
+        assert_eq!(limiter.delay_seconds(1), 2);
+        assert_eq!(limiter.delay_seconds(2), 4);
+        assert_eq!(limiter.delay_seconds(4), 16);
+        assert_eq!(limiter.delay_seconds(10), 60);
+    }
+
+    #[test]
+    fn an_account_is_locked_after_too_many_failures() {
+        let limiter = limiter(0);
+        let action = RateLimitAction::Login;
+
+        assert!(!limiter.record_failure(&action, "10.0.0.1", Some("cat@example.com")));
+        assert!(!limiter.record_failure(&action, "10.0.0.2", Some("cat@example.com")));
+        assert!(limiter
+            .check(&action, "10.0.0.3", Some("cat@example.com"))
+            .is_ok());
+
+        assert!(limiter.record_failure(&action, "10.0.0.3", Some("CAT@example.com")));
+
+        let throttled = limiter
+            .check(&action, "10.0.0.4", Some("cat@example.com"))
+            .unwrap_err();
+        assert!(throttled.locked);
+        assert!(throttled.retry_after_seconds > 29 * 60);
+
+        // other actions and accounts are counted on their own
+        assert!(limiter
+            .check(
+                &RateLimitAction::OtpVerify,
+                "10.0.0.4",
+                Some("cat@example.com")
+            )
+            .is_ok());
+        assert!(limiter
+            .check(&action, "10.0.0.4", Some("dog@example.com"))
+            .is_ok());
+    }
+
+    #[test]
+    fn success_clears_the_account_but_not_the_address() {
+        let limiter = limiter(0);
+        let action = RateLimitAction::Login;
+
+        for _ in 0..10 {
+            limiter.record_failure(&action, "10.0.0.1", Some("cat@example.com"));
+        }
+        limiter.record_success(&action, "cat@example.com");
+
+        assert!(limiter
+            .check(&action, "10.0.0.2", Some("cat@example.com"))
+            .is_ok());
+        assert!(
+            limiter
+                .check(&action, "10.0.0.1", Some("dog@example.com"))
+                .unwrap_err()
+                .locked
+        );
+    }
+
+    #[test]
+    fn unlock_lifts_every_action_on_the_account() {
+        let limiter = limiter(0);
+
+        for action in [RateLimitAction::Login, RateLimitAction::OtpVerify] {
+            for ip in ["10.0.0.1", "10.0.0.2", "10.0.0.3"] {
+                limiter.record_failure(&action, ip, Some("cat@example.com"));
+            }
+        }
+        limiter.unlock("cat@example.com").unwrap();
+
+        for action in [RateLimitAction::Login, RateLimitAction::OtpVerify] {
+            assert!(limiter
+                .check(&action, "10.0.0.4", Some("cat@example.com"))
+                .is_ok());
+        }
+    }
+}
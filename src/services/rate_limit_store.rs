@@ -0,0 +1,148 @@
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use chrono::NaiveDateTime;
+
+use super::{
+    memory_rate_limit_store::MemoryRateLimitStore, sqlite_rate_limit_store::SqliteRateLimitStore,
+};
+use crate::{config::RateLimitConfiguration, db::connection::SqliteConnectionPool};
+
+/// Failed attempts counted against one key, times are UTC.
+#[derive(Clone)]
+pub struct FailureRecord {
+    pub failures: i32,
+    pub last_failure_at: NaiveDateTime,
+    pub locked_until: Option<NaiveDateTime>,
+}
+
+/// How `RateLimitStore::increment` counts a failure made at `now`.
+pub struct FailureCount {
+    pub now: NaiveDateTime,
+    // failures before this no longer count unless the key is still locked
+    pub window_start: NaiveDateTime,
+    pub max_failures: i32,
+    // lock set when the count reaches `max_failures` and no lock is running
+    pub lock_until: NaiveDateTime,
+}
+
+impl FailureRecord {
+    /// The record after one more failure, `None` being no earlier failures.
+    pub fn incremented(previous: Option<&FailureRecord>, count: &FailureCount) -> Self {
+        let running_lock = previous
+            .and_then(|r| r.locked_until)
+            .filter(|until| *until > count.now);
+        let failures = match previous {
+            Some(r) if running_lock.is_some() || r.last_failure_at >= count.window_start => {
+                r.failures + 1
+            }
+            _ => 1,
+        };
+
+        Self {
+            failures,
+            last_failure_at: count.now,
+            locked_until: running_lock
+                .or((failures >= count.max_failures).then_some(count.lock_until)),
+        }
+    }
+}
+
+pub trait RateLimitStore: Send + Sync {
+    fn get(&self, key: &str) -> Result<Option<FailureRecord>>;
+
+    /// Adds a failure to `key` as one atomic step, so concurrent failures are
+    /// all counted. Returns the record as saved.
+    fn increment(&self, key: &str, count: &FailureCount) -> Result<FailureRecord>;
+
+    fn remove(&self, keys: &[String]) -> Result<()>;
+
+    /// Drops records whose last failure is older than `before`.
+    fn purge(&self, before: NaiveDateTime) -> Result<()>;
+}
+
+pub struct RateLimitStoreFactory;
+
+impl RateLimitStoreFactory {
+    /// Picks the store named by `RATE_LIMIT_BACKEND`: `memory` for a single
+    /// process, `sqlite` when several processes share the database.
+    pub fn create(
+        config: &RateLimitConfiguration,
+        pool: &SqliteConnectionPool,
+    ) -> Result<Arc<dyn RateLimitStore>> {
+        match config.backend.to_lowercase().as_str() {
+            "memory" => Ok(Arc::new(MemoryRateLimitStore::default())),
+            "sqlite" => Ok(Arc::new(SqliteRateLimitStore::new(pool))),
+            other => bail!("Unknown rate limit backend: {}", other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, NaiveDate};
+
+    use super::*;
+
+    fn at(minutes: i64) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 11, 17)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap()
+            + Duration::minutes(minutes)
+    }
+
+    // a 15 minute window, locked for 30 minutes after 3 failures
+    fn count_at(minutes: i64) -> FailureCount {
+        FailureCount {
+            now: at(minutes),
+            window_start: at(minutes - 15),
+            max_failures: 3,
+            lock_until: at(minutes + 30),
+        }
+    }
+
+    #[test]
+    fn failures_within_the_window_add_up_to_a_lock() {
+        let first = FailureRecord::incremented(None, &count_at(0));
+        assert_eq!(first.failures, 1);
+        assert_eq!(first.locked_until, None);
+
+        let second = FailureRecord::incremented(Some(&first), &count_at(5));
+        assert_eq!(second.failures, 2);
+        assert_eq!(second.locked_until, None);
+
+        let third = FailureRecord::incremented(Some(&second), &count_at(10));
+        assert_eq!(third.failures, 3);
+        assert_eq!(third.last_failure_at, at(10));
+        assert_eq!(third.locked_until, Some(at(40)));
+    }
+
+    #[test]
+    fn failures_older_than_the_window_start_over() {
+        let first = FailureRecord::incremented(None, &count_at(0));
+        let second = FailureRecord::incremented(Some(&first), &count_at(5));
+
+        let later = FailureRecord::incremented(Some(&second), &count_at(21));
+        assert_eq!(later.failures, 1);
+        assert_eq!(later.locked_until, None);
+    }
+
+    #[test]
+    fn a_running_lock_keeps_counting_and_its_end_time() {
+        let locked = FailureRecord {
+            failures: 3,
+            last_failure_at: at(0),
+            locked_until: Some(at(30)),
+        };
+
+        // outside the window but still locked
+        let during = FailureRecord::incremented(Some(&locked), &count_at(20));
+        assert_eq!(during.failures, 4);
+        assert_eq!(during.locked_until, Some(at(30)));
+
+        let after = FailureRecord::incremented(Some(&during), &count_at(60));
+        assert_eq!(after.failures, 1);
+        assert_eq!(after.locked_until, None);
+    }
+}
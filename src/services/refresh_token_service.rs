@@ -0,0 +1,228 @@
+use std::fmt;
+
+use chrono::{NaiveDateTime, Utc};
+use diesel::prelude::*;
+use sha2::{Digest, Sha256};
+
+use crate::models::{
+    refresh_token::{NewRefreshToken, RefreshToken},
+    user::User,
+    user_session::{NewUserSession, UserSession},
+};
+
+const EXPIRES_ON_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+#[derive(Debug)]
+pub enum RefreshError {
+    NotFound,
+    Expired,
+    // the session was signed out or closed after a reuse
+    Revoked,
+    // an already rotated token came back, the session is revoked
+    Reused,
+    Database(diesel::result::Error),
+}
+
+impl From<diesel::result::Error> for RefreshError {
+    fn from(e: diesel::result::Error) -> Self {
+        RefreshError::Database(e)
+    }
+}
+
+impl fmt::Display for RefreshError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RefreshError::NotFound => write!(f, "Invalid refresh token provided"),
+            RefreshError::Expired => write!(f, "Refresh token expired, please sign in again"),
+            RefreshError::Revoked => write!(f, "Session has ended, please sign in again"),
+            RefreshError::Reused => write!(
+                f,
+                "Refresh token was already used, the session has been signed out"
+            ),
+            RefreshError::Database(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// Only the SHA-256 of a refresh token is stored, a leaked table can't be
+/// replayed against the refresh endpoint.
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// Stores the first refresh token of a new sign-in along with the device it
+/// came from. Every device gets its own family, so signing in on one doesn't
+/// sign out the others.
+pub fn start_session(
+    conn: &mut SqliteConnection,
+    user: &User,
+    session: &NewUserSession,
+    session_id: &str,
+    token: &str,
+    expires_on: &String,
+) -> QueryResult<()> {
+    use crate::schema::{refresh_tokens, user_sessions};
+
+    let new_token = NewRefreshToken::new(user, hash_token(token), session_id, expires_on);
+
+    conn.transaction(|conn| {
+        diesel::insert_into(user_sessions::table)
+            .values(session)
+            .execute(conn)?;
+
+        diesel::insert_into(refresh_tokens::table)
+            .values(&new_token)
+            .execute(conn)?;
+
+        Ok(())
+    })
+}
+
+/// Swaps `presented` for `new_token` within the same session. A token that
+/// was already rotated means it has been copied, so the whole family is
+/// revoked and both holders have to sign in again.
+pub fn rotate(
+    conn: &mut SqliteConnection,
+    user: &User,
+    presented: &str,
+    new_token: &str,
+    expires_on: &String,
+) -> Result<(), RefreshError> {
+    use crate::schema::{refresh_tokens, user_sessions};
+
+    let current = refresh_tokens::table
+        .filter(refresh_tokens::token_hash.eq(hash_token(presented)))
+        .filter(refresh_tokens::user_id.eq(user.get_id()))
+        .select(RefreshToken::as_select())
+        .first::<RefreshToken>(conn)
+        .optional()?
+        .ok_or(RefreshError::NotFound)?;
+
+    if current.is_revoked() {
+        return Err(RefreshError::Revoked);
+    }
+
+    if current.is_rotated() {
+        revoke_family(conn, current.get_family_id())?;
+        return Err(RefreshError::Reused);
+    }
+
+    match NaiveDateTime::parse_from_str(current.get_expires_on(), EXPIRES_ON_FORMAT) {
+        Ok(expiry) if expiry >= nepal_now() => {}
+        _ => return Err(RefreshError::Expired),
+    }
+
+    let rotated = conn.transaction::<_, RefreshError, _>(|conn| {
+        // only one of two concurrent refreshes with the same token wins
+        let updated = diesel::update(
+            refresh_tokens::table
+                .filter(refresh_tokens::id.eq(current.get_id()))
+                .filter(refresh_tokens::rotated_at.is_null())
+                .filter(refresh_tokens::revoked_at.is_null()),
+        )
+        .set(refresh_tokens::rotated_at.eq(Utc::now().naive_utc()))
+        .execute(conn)?;
+
+        if updated == 0 {
+            return Ok(false);
+        }
+
+        let next = NewRefreshToken::new(
+            user,
+            hash_token(new_token),
+            current.get_family_id(),
+            expires_on,
+        );
+        diesel::insert_into(refresh_tokens::table)
+            .values(&next)
+            .execute(conn)?;
+
+        diesel::update(
+            user_sessions::table.filter(user_sessions::uuid.eq(current.get_family_id())),
+        )
+        .set((
+            user_sessions::expires_on.eq(expires_on),
+            user_sessions::last_used_at.eq(Utc::now().naive_utc()),
+        ))
+        .execute(conn)?;
+
+        Ok(true)
+    })?;
+
+    if !rotated {
+        revoke_family(conn, current.get_family_id())?;
+        return Err(RefreshError::Reused);
+    }
+
+    Ok(())
+}
+
+/// Signs out one session, its refresh tokens stop rotating and its access
+/// tokens are rejected from the next request on.
+pub fn revoke_family(conn: &mut SqliteConnection, family_id: &str) -> QueryResult<usize> {
+    use crate::schema::{refresh_tokens, user_sessions};
+
+    let now = Utc::now().naive_utc();
+
+    conn.transaction(|conn| {
+        diesel::update(
+            user_sessions::table
+                .filter(user_sessions::uuid.eq(family_id))
+                .filter(user_sessions::revoked_at.is_null()),
+        )
+        .set(user_sessions::revoked_at.eq(now))
+        .execute(conn)?;
+
+        diesel::update(
+            refresh_tokens::table
+                .filter(refresh_tokens::family_id.eq(family_id))
+                .filter(refresh_tokens::revoked_at.is_null()),
+        )
+        .set(refresh_tokens::revoked_at.eq(now))
+        .execute(conn)
+    })
+}
+
+/// Revokes every session of `user`, used when signing out of all devices.
+pub fn revoke_all(conn: &mut SqliteConnection, user: &User) -> QueryResult<usize> {
+    use crate::schema::{refresh_tokens, user_sessions};
+
+    let now = Utc::now().naive_utc();
+
+    conn.transaction(|conn| {
+        diesel::update(
+            user_sessions::table
+                .filter(user_sessions::user_id.eq(user.get_id()))
+                .filter(user_sessions::revoked_at.is_null()),
+        )
+        .set(user_sessions::revoked_at.eq(now))
+        .execute(conn)?;
+
+        diesel::update(
+            refresh_tokens::table
+                .filter(refresh_tokens::user_id.eq(user.get_id()))
+                .filter(refresh_tokens::revoked_at.is_null()),
+        )
+        .set(refresh_tokens::revoked_at.eq(now))
+        .execute(conn)
+    })
+}
+
+/// Sessions of `user` that can still be refreshed, most recently used first.
+pub fn active_sessions(conn: &mut SqliteConnection, user: &User) -> QueryResult<Vec<UserSession>> {
+    use crate::schema::user_sessions;
+
+    user_sessions::table
+        .filter(user_sessions::user_id.eq(user.get_id()))
+        .filter(user_sessions::revoked_at.is_null())
+        .filter(user_sessions::expires_on.gt(nepal_now().format(EXPIRES_ON_FORMAT).to_string()))
+        .order(user_sessions::last_used_at.desc())
+        .select(UserSession::as_select())
+        .load(conn)
+}
+
+fn nepal_now() -> NaiveDateTime {
+    Utc::now()
+        .with_timezone(&chrono::FixedOffset::east_opt(5 * 3600 + 45 * 60).unwrap())
+        .naive_local()
+}
@@ -0,0 +1,41 @@
+use chrono::Utc;
+use diesel::prelude::*;
+
+/// Makes TOTP two-factor authentication mandatory for the Admin role.
+pub const REQUIRE_ADMIN_TWO_FACTOR: &str = "require_admin_two_factor";
+
+/// Reads an on/off setting, a setting that was never saved is off.
+pub fn get_bool(conn: &mut SqliteConnection, key: &str) -> QueryResult<bool> {
+    use crate::schema::app_settings;
+
+    let value = app_settings::table
+        .filter(app_settings::setting_key.eq(key))
+        .select(app_settings::setting_value)
+        .first::<String>(conn)
+        .optional()?;
+
+    Ok(value.is_some_and(|v| v == "true"))
+}
+
+pub fn set_bool(conn: &mut SqliteConnection, key: &str, value: bool) -> QueryResult<()> {
+    use crate::schema::app_settings;
+
+    let value = if value { "true" } else { "false" };
+    let now = Utc::now().naive_utc();
+
+    diesel::insert_into(app_settings::table)
+        .values((
+            app_settings::setting_key.eq(key),
+            app_settings::setting_value.eq(value),
+            app_settings::updated_at.eq(now),
+        ))
+        .on_conflict(app_settings::setting_key)
+        .do_update()
+        .set((
+            app_settings::setting_value.eq(value),
+            app_settings::updated_at.eq(now),
+        ))
+        .execute(conn)?;
+
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+
+use super::{
+    console_sms_service::ConsoleSmsService,
+    http_sms_service::{HttpSmsService, SmsProvider},
+};
+use crate::config::SmsConfiguration;
+
+#[async_trait]
+pub trait SmsService: Send + Sync {
+    async fn send_sms(&self, to_number: &str, message: &str) -> Result<()>;
+}
+
+pub struct SmsServiceFactory;
+
+impl SmsServiceFactory {
+    /// Picks the gateway named by `SMS_PROVIDER`: `console` during
+    /// development, `sparrow` or `aakash` in production.
+    pub fn create(config: &SmsConfiguration, client: &Client) -> Result<Arc<dyn SmsService>> {
+        match config.provider.to_lowercase().as_str() {
+            "console" => Ok(Arc::new(ConsoleSmsService::new(config))),
+            "sparrow" => Ok(Arc::new(HttpSmsService::new(
+                SmsProvider::Sparrow,
+                config,
+                client,
+            ))),
+            "aakash" => Ok(Arc::new(HttpSmsService::new(
+                SmsProvider::Aakash,
+                config,
+                client,
+            ))),
+            other => bail!("Unknown SMS provider: {}", other),
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+
+use super::rate_limit_store::{FailureCount, FailureRecord, RateLimitStore};
+use crate::{
+    db::connection::{get_conn, SqliteConnectionPool},
+    models::auth_rate_limit::AuthRateLimit,
+};
+
+/// Keeps the counts in `auth_rate_limits`, so every process using the same
+/// database throttles the same addresses and accounts.
+pub struct SqliteRateLimitStore {
+    pool: SqliteConnectionPool,
+}
+
+impl SqliteRateLimitStore {
+    pub fn new(pool: &SqliteConnectionPool) -> Self {
+        Self {
+            pool: pool.to_owned(),
+        }
+    }
+}
+
+impl RateLimitStore for SqliteRateLimitStore {
+    fn get(&self, key: &str) -> Result<Option<FailureRecord>> {
+        use crate::schema::auth_rate_limits;
+
+        let conn = &mut get_conn(&self.pool);
+
+        let record = auth_rate_limits::table
+            .filter(auth_rate_limits::limit_key.eq(key))
+            .select(AuthRateLimit::as_select())
+            .first(conn)
+            .optional()?;
+
+        Ok(record.map(|r| FailureRecord {
+            failures: r.get_failures(),
+            last_failure_at: r.get_last_failure_at(),
+            locked_until: r.get_locked_until(),
+        }))
+    }
+
+    fn increment(&self, key: &str, count: &FailureCount) -> Result<FailureRecord> {
+        use diesel::sql_types::{Integer, Text, Timestamp};
+
+        let conn = &mut get_conn(&self.pool);
+
+        // one statement, so failures from other processes can't be lost in
+        // between; the SET expressions all see the row as it was before
+        let row: AuthRateLimit = diesel::sql_query(
+            r#"
+    INSERT INTO auth_rate_limits (limit_key, failures, last_failure_at, locked_until)
+    VALUES (?1, 1, ?2, CASE WHEN 1 >= ?4 THEN ?5 END)
+    ON CONFLICT (limit_key) DO UPDATE SET
+        failures = CASE
+            WHEN locked_until > ?2 OR last_failure_at >= ?3 THEN failures + 1
+            ELSE 1
+        END,
+        locked_until = CASE
+            WHEN locked_until > ?2 THEN locked_until
+            WHEN (CASE WHEN last_failure_at >= ?3 THEN failures + 1 ELSE 1 END) >= ?4 THEN ?5
+        END,
+        last_failure_at = ?2
+    RETURNING failures, last_failure_at, locked_until;
+    "#,
+        )
+        .bind::<Text, _>(key)
+        .bind::<Timestamp, _>(count.now)
+        .bind::<Timestamp, _>(count.window_start)
+        .bind::<Integer, _>(count.max_failures)
+        .bind::<Timestamp, _>(count.lock_until)
+        .get_result(conn)?;
+
+        Ok(FailureRecord {
+            failures: row.get_failures(),
+            last_failure_at: row.get_last_failure_at(),
+            locked_until: row.get_locked_until(),
+        })
+    }
+
+    fn remove(&self, keys: &[String]) -> Result<()> {
+        use crate::schema::auth_rate_limits;
+
+        let conn = &mut get_conn(&self.pool);
+
+        diesel::delete(auth_rate_limits::table.filter(auth_rate_limits::limit_key.eq_any(keys)))
+            .execute(conn)?;
+
+        Ok(())
+    }
+
+    fn purge(&self, before: NaiveDateTime) -> Result<()> {
+        use crate::schema::auth_rate_limits;
+
+        let conn = &mut get_conn(&self.pool);
+
+        diesel::delete(
+            auth_rate_limits::table.filter(auth_rate_limits::last_failure_at.lt(before)),
+        )
+        .execute(conn)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,197 @@
+use chrono::NaiveDate;
+use diesel::prelude::*;
+
+use crate::{
+    base_types::{
+        payment_method::PaymentMethod, subscription_frequency::SubscriptionFrequency,
+        subscription_status::SubscriptionStatus,
+    },
+    contracts::subscription::{
+        Subscription as SubscriptionResponse, SubscriptionItem as SubscriptionItemResponse,
+    },
+    handlers::order::DELIVERY_CHARGE,
+    models::{
+        order::Order,
+        product::Product,
+        subscription::Subscription,
+        subscription_item::{NewSubscriptionItem, SubscriptionItem},
+        subscription_order::NewSubscriptionOrder,
+        user::User,
+    },
+    services::{
+        order_service::{self, OrderLine, OrderRequest, PlaceOrderError},
+        pricing_service,
+    },
+};
+
+pub const DATE_FORMAT: &str = "%Y-%m-%d";
+
+pub fn parse_date(date: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date, DATE_FORMAT).ok()
+}
+
+/// Moves a delivery date forward one cycle at a time until it is no earlier
+/// than `today`, for subscriptions that were paused or missed a run.
+pub fn roll_forward(
+    frequency: &SubscriptionFrequency,
+    mut date: NaiveDate,
+    today: NaiveDate,
+) -> NaiveDate {
+    while date < today {
+        date = frequency.next_after(date);
+    }
+    date
+}
+
+pub fn subscription_response(
+    conn: &mut SqliteConnection,
+    subscription: &Subscription,
+) -> QueryResult<SubscriptionResponse> {
+    use crate::schema::{products, subscription_items};
+
+    let rows: Vec<(SubscriptionItem, Product)> = subscription_items::table
+        .inner_join(products::table)
+        .filter(subscription_items::subscription_id.eq(subscription.get_id()))
+        .select((SubscriptionItem::as_select(), Product::as_select()))
+        .load(conn)?;
+
+    let mut items = vec![];
+    for (item, product) in rows {
+        let applied_price =
+            pricing_service::price_for_quantity(conn, &product, item.get_quantity())?;
+        items.push(SubscriptionItemResponse {
+            uuid: item.get_uuid().to_owned(),
+            product_id: product.get_uuid().to_owned(),
+            product_name: product.get_name().to_owned(),
+            quantity: item.get_quantity(),
+            rate: applied_price.unit_price,
+        });
+    }
+
+    Ok(SubscriptionResponse {
+        uuid: subscription.get_uuid().to_owned(),
+        frequency: subscription.get_frequency().to_owned(),
+        delivery_slot: subscription.get_delivery_slot().to_owned(),
+        delivery_location: subscription.get_delivery_location().to_owned(),
+        payment_method: subscription.get_payment_method().to_owned(),
+        status: subscription.get_status().to_owned(),
+        next_delivery_on: subscription.get_next_delivery_on().to_owned(),
+        items,
+        created_at: subscription.get_created_at(),
+    })
+}
+
+/// Swaps the basket of `subscription` for `items`.
+pub fn replace_items(
+    conn: &mut SqliteConnection,
+    subscription: &Subscription,
+    items: &[(Product, f64)],
+) -> QueryResult<()> {
+    use crate::schema::subscription_items;
+
+    diesel::delete(
+        subscription_items::table
+            .filter(subscription_items::subscription_id.eq(subscription.get_id())),
+    )
+    .execute(conn)?;
+
+    let new_items: Vec<NewSubscriptionItem> = items
+        .iter()
+        .map(|(product, quantity)| NewSubscriptionItem::new(subscription, product, *quantity))
+        .collect();
+
+    diesel::insert_into(subscription_items::table)
+        .values(&new_items)
+        .execute(conn)?;
+
+    Ok(())
+}
+
+/// Active subscriptions with a delivery on or before `until` that has no
+/// order yet.
+pub fn due_subscriptions(
+    conn: &mut SqliteConnection,
+    until: NaiveDate,
+) -> QueryResult<Vec<(Subscription, User)>> {
+    use crate::schema::{subscriptions, users};
+
+    subscriptions::table
+        .inner_join(users::table)
+        .filter(subscriptions::status.eq(SubscriptionStatus::Active.value()))
+        .filter(subscriptions::next_delivery_on.le(until.format(DATE_FORMAT).to_string()))
+        .order(subscriptions::next_delivery_on.asc())
+        .select((Subscription::as_select(), User::as_select()))
+        .load(conn)
+}
+
+/// Places the order for the next delivery of `subscription` through the same
+/// path as a customer order, priced as of today, and moves the subscription on
+/// to the following delivery.
+pub fn place_subscription_order(
+    conn: &mut SqliteConnection,
+    subscription: &Subscription,
+    user: &User,
+    frequency: &SubscriptionFrequency,
+    delivery_on: NaiveDate,
+) -> Result<Order, PlaceOrderError> {
+    use crate::schema::{products, subscription_items, subscription_orders, subscriptions};
+
+    let payment_method = PaymentMethod::from_str(subscription.get_payment_method())
+        .map_err(|e| PlaceOrderError::Rejected(e.to_string()))?;
+
+    conn.transaction::<_, PlaceOrderError, _>(|con| {
+        let items: Vec<(SubscriptionItem, Product)> = subscription_items::table
+            .inner_join(products::table)
+            .filter(subscription_items::subscription_id.eq(subscription.get_id()))
+            .select((SubscriptionItem::as_select(), Product::as_select()))
+            .load(con)?;
+
+        if items.is_empty() {
+            return Err(PlaceOrderError::Rejected(
+                "Subscription has no items".to_string(),
+            ));
+        }
+
+        let quantity: f64 = items.iter().map(|(item, _)| item.get_quantity()).sum();
+
+        let nepal_time = chrono::Utc::now()
+            .with_timezone(&chrono::FixedOffset::east_opt(5 * 3600 + 45 * 60).unwrap());
+        let created_on = nepal_time.format("%Y-%m-%d %H:%M:%S").to_string();
+        let delivery_location = subscription.get_delivery_location().to_owned();
+
+        let request = OrderRequest {
+            created_on: &created_on,
+            delivery_charge: DELIVERY_CHARGE,
+            delivery_location: &delivery_location,
+            payment_method: &payment_method,
+            expected_items_total: None,
+            quantity,
+            discount: 0.0,
+            lines: items
+                .iter()
+                .map(|(item, product)| OrderLine {
+                    product_id: product.get_uuid(),
+                    quantity: item.get_quantity(),
+                    discount: 0.0,
+                })
+                .collect(),
+        };
+
+        let order = order_service::place_order(con, user, &request)?;
+
+        diesel::insert_into(subscription_orders::table)
+            .values(&NewSubscriptionOrder::new(subscription, &order))
+            .execute(con)?;
+
+        diesel::update(subscriptions::table.find(subscription.get_id()))
+            .set(
+                subscriptions::next_delivery_on.eq(frequency
+                    .next_after(delivery_on)
+                    .format(DATE_FORMAT)
+                    .to_string()),
+            )
+            .execute(con)?;
+
+        Ok(order)
+    })
+}
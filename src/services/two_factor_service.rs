@@ -0,0 +1,420 @@
+use std::fmt;
+
+use chrono::Utc;
+use diesel::prelude::*;
+use qrcode::{render::svg, QrCode};
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use totp_rs::{Algorithm, Secret, TOTP};
+
+use super::setting_service;
+use crate::models::{
+    two_factor_recovery_code::NewTwoFactorRecoveryCode,
+    user::User,
+    user_two_factor::{NewUserTwoFactor, UserTwoFactor},
+};
+
+const ISSUER: &str = "Haatbazar";
+const STEP_SECONDS: i64 = 30;
+const RECOVERY_CODE_COUNT: usize = 10;
+// no 0/o or 1/l/i, the codes are read off paper
+const RECOVERY_CODE_ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+
+#[derive(Debug)]
+pub enum TwoFactorError {
+    NotSetUp,
+    AlreadyEnabled,
+    InvalidCode,
+    Totp(String),
+    Database(diesel::result::Error),
+}
+
+impl From<diesel::result::Error> for TwoFactorError {
+    fn from(e: diesel::result::Error) -> Self {
+        TwoFactorError::Database(e)
+    }
+}
+
+impl fmt::Display for TwoFactorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TwoFactorError::NotSetUp => write!(f, "Two-factor authentication is not set up"),
+            TwoFactorError::AlreadyEnabled => {
+                write!(f, "Two-factor authentication is already enabled")
+            }
+            TwoFactorError::InvalidCode => write!(f, "Invalid authentication code"),
+            TwoFactorError::Totp(e) => write!(f, "{}", e),
+            TwoFactorError::Database(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// What the user needs to add the account to an authenticator app.
+pub struct Enrolment {
+    pub secret: String,
+    pub otpauth_uri: String,
+    pub qr_code_svg: String,
+}
+
+fn totp(secret: &str, user: &User) -> Result<TOTP, TwoFactorError> {
+    let secret = Secret::Encoded(secret.to_owned())
+        .to_bytes()
+        .map_err(|e| TwoFactorError::Totp(format!("{:?}", e)))?;
+
+    // steps are checked one by one below, so no skew here
+    TOTP::new(
+        Algorithm::SHA1,
+        6,
+        0,
+        STEP_SECONDS as u64,
+        secret,
+        Some(ISSUER.to_owned()),
+        user.get_email().to_owned(),
+    )
+    .map_err(|e| TwoFactorError::Totp(e.to_string()))
+}
+
+fn find(conn: &mut SqliteConnection, user: &User) -> QueryResult<Option<UserTwoFactor>> {
+    UserTwoFactor::belonging_to(user)
+        .select(UserTwoFactor::as_select())
+        .first(conn)
+        .optional()
+}
+
+pub fn is_enabled(conn: &mut SqliteConnection, user: &User) -> QueryResult<bool> {
+    Ok(find(conn, user)?.is_some_and(|t| t.is_enabled()))
+}
+
+/// An admin who has to set up two-factor authentication before the admin
+/// endpoints let them in.
+pub fn setup_required(conn: &mut SqliteConnection, user: &User) -> QueryResult<bool> {
+    if !user.is_admin()
+        || !setting_service::get_bool(conn, setting_service::REQUIRE_ADMIN_TWO_FACTOR)?
+    {
+        return Ok(false);
+    }
+
+    Ok(!is_enabled(conn, user)?)
+}
+
+/// Starts setup with a new secret, replacing one from an unfinished setup.
+/// Nothing changes at sign in until a code from the app is confirmed.
+pub fn begin_setup(conn: &mut SqliteConnection, user: &User) -> Result<Enrolment, TwoFactorError> {
+    use crate::schema::user_two_factors;
+
+    if is_enabled(conn, user)? {
+        return Err(TwoFactorError::AlreadyEnabled);
+    }
+
+    let secret = match Secret::generate_secret().to_encoded() {
+        Secret::Encoded(s) => s,
+        Secret::Raw(_) => unreachable!("to_encoded always returns an encoded secret"),
+    };
+    let otpauth_uri = totp(&secret, user)?.get_url();
+    let qr_code_svg = QrCode::new(otpauth_uri.as_bytes())
+        .map_err(|e| TwoFactorError::Totp(e.to_string()))?
+        .render::<svg::Color>()
+        .min_dimensions(200, 200)
+        .build();
+
+    conn.transaction(|conn| {
+        diesel::delete(UserTwoFactor::belonging_to(user)).execute(conn)?;
+        diesel::insert_into(user_two_factors::table)
+            .values(&NewUserTwoFactor::new(user, secret.clone()))
+            .execute(conn)
+    })?;
+
+    Ok(Enrolment {
+        secret,
+        otpauth_uri,
+        qr_code_svg,
+    })
+}
+
+/// Finishes setup with a code from the app and hands out the recovery codes,
+/// the only time they can be seen.
+pub fn confirm_setup(
+    conn: &mut SqliteConnection,
+    user: &User,
+    code: &str,
+) -> Result<Vec<String>, TwoFactorError> {
+    use crate::schema::user_two_factors;
+
+    let two_factor = find(conn, user)?.ok_or(TwoFactorError::NotSetUp)?;
+    if two_factor.is_enabled() {
+        return Err(TwoFactorError::AlreadyEnabled);
+    }
+
+    let step = matching_step(&two_factor, user, code)?.ok_or(TwoFactorError::InvalidCode)?;
+
+    conn.transaction(|conn| {
+        diesel::update(&two_factor)
+            .set((
+                user_two_factors::enabled_at.eq(Utc::now().naive_utc()),
+                user_two_factors::last_used_step.eq(step),
+            ))
+            .execute(conn)?;
+
+        replace_recovery_codes(conn, user)
+    })
+    .map_err(TwoFactorError::from)
+}
+
+/// Checks the second factor at sign in, either a code from the app or one of
+/// the recovery codes. Both can only be used once.
+pub fn verify(conn: &mut SqliteConnection, user: &User, code: &str) -> Result<(), TwoFactorError> {
+    use crate::schema::{two_factor_recovery_codes, user_two_factors};
+
+    let two_factor = find(conn, user)?
+        .filter(|t| t.is_enabled())
+        .ok_or(TwoFactorError::NotSetUp)?;
+
+    let code = code.trim();
+    if code.len() == 6 && code.chars().all(|c| c.is_ascii_digit()) {
+        let step = matching_step(&two_factor, user, code)?.ok_or(TwoFactorError::InvalidCode)?;
+
+        // the step moves forward only once, a second request with the
+        // same code loses
+        let updated = diesel::update(&two_factor)
+            .filter(
+                user_two_factors::last_used_step
+                    .is_null()
+                    .or(user_two_factors::last_used_step.lt(step)),
+            )
+            .set(user_two_factors::last_used_step.eq(step))
+            .execute(conn)?;
+
+        return if updated > 0 {
+            Ok(())
+        } else {
+            Err(TwoFactorError::InvalidCode)
+        };
+    }
+
+    let used = diesel::update(
+        two_factor_recovery_codes::table
+            .filter(two_factor_recovery_codes::user_id.eq(user.get_id()))
+            .filter(two_factor_recovery_codes::code_hash.eq(hash_recovery_code(code)))
+            .filter(two_factor_recovery_codes::used_at.is_null()),
+    )
+    .set(two_factor_recovery_codes::used_at.eq(Utc::now().naive_utc()))
+    .execute(conn)?;
+
+    if used > 0 {
+        Ok(())
+    } else {
+        Err(TwoFactorError::InvalidCode)
+    }
+}
+
+/// Throws away the old recovery codes, used or not, and returns new ones.
+pub fn regenerate_recovery_codes(
+    conn: &mut SqliteConnection,
+    user: &User,
+) -> Result<Vec<String>, TwoFactorError> {
+    if !is_enabled(conn, user)? {
+        return Err(TwoFactorError::NotSetUp);
+    }
+
+    conn.transaction(|conn| replace_recovery_codes(conn, user))
+        .map_err(TwoFactorError::from)
+}
+
+pub fn remaining_recovery_codes(conn: &mut SqliteConnection, user: &User) -> QueryResult<i64> {
+    use crate::schema::two_factor_recovery_codes;
+
+    two_factor_recovery_codes::table
+        .filter(two_factor_recovery_codes::user_id.eq(user.get_id()))
+        .filter(two_factor_recovery_codes::used_at.is_null())
+        .count()
+        .get_result(conn)
+}
+
+pub fn disable(conn: &mut SqliteConnection, user: &User) -> QueryResult<()> {
+    use crate::schema::two_factor_recovery_codes;
+
+    conn.transaction(|conn| {
+        diesel::delete(
+            two_factor_recovery_codes::table
+                .filter(two_factor_recovery_codes::user_id.eq(user.get_id())),
+        )
+        .execute(conn)?;
+        diesel::delete(UserTwoFactor::belonging_to(user)).execute(conn)?;
+
+        Ok(())
+    })
+}
+
+// the time step the code was generated for, within one step either way of
+// now to allow for clock drift, and only if it's newer than the last one used
+fn matching_step(
+    two_factor: &UserTwoFactor,
+    user: &User,
+    code: &str,
+) -> Result<Option<i64>, TwoFactorError> {
+    let totp = totp(two_factor.get_secret(), user)?;
+    let current = Utc::now().timestamp() / STEP_SECONDS;
+
+    Ok((current - 1..=current + 1)
+        .filter(|step| two_factor.get_last_used_step() < Some(*step))
+        .find(|step| totp.check(code, (*step * STEP_SECONDS) as u64)))
+}
+
+fn replace_recovery_codes(
+    conn: &mut SqliteConnection,
+    user: &User,
+) -> Result<Vec<String>, diesel::result::Error> {
+    use crate::schema::two_factor_recovery_codes;
+
+    diesel::delete(
+        two_factor_recovery_codes::table
+            .filter(two_factor_recovery_codes::user_id.eq(user.get_id())),
+    )
+    .execute(conn)?;
+
+    let mut rng = rand::rng();
+    let codes: Vec<String> = (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let chars: String = (0..10)
+                .map(|_| {
+                    RECOVERY_CODE_ALPHABET[rng.random_range(0..RECOVERY_CODE_ALPHABET.len())]
+                        as char
+                })
+                .collect();
+            format!("{}-{}", &chars[..5], &chars[5..])
+        })
+        .collect();
+
+    let rows: Vec<NewTwoFactorRecoveryCode> = codes
+        .iter()
+        .map(|c| NewTwoFactorRecoveryCode::new(user, hash_recovery_code(c)))
+        .collect();
+    diesel::insert_into(two_factor_recovery_codes::table)
+        .values(&rows)
+        .execute(conn)?;
+
+    Ok(codes)
+}
+
+// codes are accepted with or without the dash and in any case
+fn hash_recovery_code(code: &str) -> String {
+    let normalized: String = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+
+    hex::encode(Sha256::digest(normalized.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_db;
+
+    // the code the authenticator app shows `steps` steps from now
+    fn app_code(secret: &str, user: &User, steps: i64) -> String {
+        let step = Utc::now().timestamp() / STEP_SECONDS + steps;
+        totp(secret, user)
+            .unwrap()
+            .generate((step * STEP_SECONDS) as u64)
+    }
+
+    // a user with two-factor enabled, the secret, the code that confirmed
+    // setup and the recovery codes
+    fn enabled_user(conn: &mut SqliteConnection) -> (User, String, String, Vec<String>) {
+        let user = test_db::user(conn, "Admin");
+        let enrolment = begin_setup(conn, &user).unwrap();
+        let confirmed_with = app_code(&enrolment.secret, &user, 0);
+        let recovery_codes = confirm_setup(conn, &user, &confirmed_with).unwrap();
+        (user, enrolment.secret, confirmed_with, recovery_codes)
+    }
+
+    #[test]
+    fn setup_is_confirmed_with_a_code_from_the_app() {
+        let conn = &mut test_db::connection();
+        let user = test_db::user(conn, "Admin");
+
+        assert!(matches!(
+            confirm_setup(conn, &user, "123456"),
+            Err(TwoFactorError::NotSetUp)
+        ));
+
+        let enrolment = begin_setup(conn, &user).unwrap();
+        assert!(!is_enabled(conn, &user).unwrap());
+        assert!(matches!(
+            confirm_setup(conn, &user, "abcdef"),
+            Err(TwoFactorError::InvalidCode)
+        ));
+
+        let codes = confirm_setup(conn, &user, &app_code(&enrolment.secret, &user, 0)).unwrap();
+        assert_eq!(codes.len(), RECOVERY_CODE_COUNT);
+        assert!(is_enabled(conn, &user).unwrap());
+        assert!(matches!(
+            begin_setup(conn, &user),
+            Err(TwoFactorError::AlreadyEnabled)
+        ));
+    }
+
+    #[test]
+    fn an_app_code_only_works_once() {
+        let conn = &mut test_db::connection();
+        let (user, secret, confirmed_with, _) = enabled_user(conn);
+
+        // the code used to confirm setup can't be replayed at sign in
+        assert!(matches!(
+            verify(conn, &user, &confirmed_with),
+            Err(TwoFactorError::InvalidCode)
+        ));
+
+        let next = app_code(&secret, &user, 1);
+        assert!(verify(conn, &user, &next).is_ok());
+        assert!(matches!(
+            verify(conn, &user, &next),
+            Err(TwoFactorError::InvalidCode)
+        ));
+
+        // nor can an older step once a newer one was used
+        assert!(matches!(
+            verify(conn, &user, &app_code(&secret, &user, -1)),
+            Err(TwoFactorError::InvalidCode)
+        ));
+    }
+
+    #[test]
+    fn a_recovery_code_is_used_up() {
+        let conn = &mut test_db::connection();
+        let (user, _, _, codes) = enabled_user(conn);
+
+        // accepted without the dash and in upper case
+        let typed = codes[0].replace('-', "").to_uppercase();
+        assert!(verify(conn, &user, &typed).is_ok());
+        assert_eq!(
+            remaining_recovery_codes(conn, &user).unwrap(),
+            RECOVERY_CODE_COUNT as i64 - 1
+        );
+
+        assert!(matches!(
+            verify(conn, &user, &codes[0]),
+            Err(TwoFactorError::InvalidCode)
+        ));
+        assert!(verify(conn, &user, &codes[1]).is_ok());
+    }
+
+    #[test]
+    fn regenerated_recovery_codes_replace_the_old_ones() {
+        let conn = &mut test_db::connection();
+        let (user, _, _, old_codes) = enabled_user(conn);
+
+        let new_codes = regenerate_recovery_codes(conn, &user).unwrap();
+        assert_eq!(
+            remaining_recovery_codes(conn, &user).unwrap(),
+            RECOVERY_CODE_COUNT as i64
+        );
+        assert!(matches!(
+            verify(conn, &user, &old_codes[0]),
+            Err(TwoFactorError::InvalidCode)
+        ));
+        assert!(verify(conn, &user, &new_codes[0]).is_ok());
+    }
+}
@@ -0,0 +1,105 @@
+use std::sync::Arc;
+
+use diesel::prelude::*;
+
+use crate::{
+    db::connection::SqliteConnectionPool,
+    models::product::Product,
+    services::notification_service::{NotificationEvent, NotificationService, ProductAlertPayload},
+};
+
+fn alert_payload(product: &Product) -> ProductAlertPayload {
+    ProductAlertPayload {
+        product_id: product.get_uuid().to_owned(),
+        product_name: product.get_name().to_owned(),
+        price: product.get_price(),
+        previous_price: product.get_previous_price(),
+    }
+}
+
+/// Works out which wishlist alerts an edit from `before` to `after` should
+/// raise. A price drop needs the new price to be lower than both the old one
+/// and the advertised `previous_price`, so moving a price back up after a sale
+/// and down again to the normal rate is not announced as a deal.
+pub fn product_alerts(before: &Product, after: &Product) -> Vec<NotificationEvent> {
+    let mut alerts = vec![];
+
+    if before.get_stock() <= 0.0 && after.get_stock() > 0.0 {
+        alerts.push(NotificationEvent::BackInStock(alert_payload(after)));
+    }
+
+    if after.get_price() < before.get_price() && after.get_price() < after.get_previous_price() {
+        alerts.push(NotificationEvent::PriceDrop(alert_payload(after)));
+    }
+
+    alerts
+}
+
+/// Raises the wishlist alerts for a change from `before` to `after` in the
+/// background so the request does not wait on notifications.
+pub fn alert_on_change(
+    pool: &SqliteConnectionPool,
+    notification_service: &Arc<dyn NotificationService>,
+    before: &Product,
+    after: &Product,
+) {
+    let alerts = product_alerts(before, after);
+    if alerts.is_empty() {
+        return;
+    }
+
+    let pool = pool.to_owned();
+    let notification_service = notification_service.to_owned();
+    let product_id = after.get_id();
+    tokio::spawn(async move {
+        notify_watchers(pool, notification_service, product_id, alerts).await;
+    });
+}
+
+/// Sends `alerts` to every customer who wishlisted the product with
+/// notifications turned on.
+pub async fn notify_watchers(
+    pool: SqliteConnectionPool,
+    notification_service: Arc<dyn NotificationService>,
+    product_id: i32,
+    alerts: Vec<NotificationEvent>,
+) {
+    use crate::schema::wishlists;
+
+    let user_ids: Vec<i32> = match pool
+        .get()
+        .map_err(anyhow::Error::from)
+        .and_then(|mut conn| {
+            wishlists::table
+                .filter(wishlists::product_id.eq(product_id))
+                .filter(wishlists::notify.eq(true))
+                .select(wishlists::user_id)
+                .load(&mut conn)
+                .map_err(anyhow::Error::from)
+        }) {
+        Ok(ids) => ids,
+        Err(e) => {
+            eprintln!(
+                "Failed to load wishlist owners of product {}: {:?}",
+                product_id, e
+            );
+            return;
+        }
+    };
+
+    if user_ids.is_empty() {
+        return;
+    }
+
+    for alert in alerts {
+        if let Err(e) = notification_service
+            .send_to_users(user_ids.clone(), alert)
+            .await
+        {
+            eprintln!(
+                "Failed to send wishlist alert for product {}: {:?}",
+                product_id, e
+            );
+        }
+    }
+}
@@ -3,13 +3,23 @@ use std::collections::HashSet;
 use chrono::{Duration, Utc};
 use jsonwebtoken::{decode, encode, errors::Error, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String,
-    exp: usize,
+    pub exp: usize,
     iat: usize,
     pub roles: HashSet<String>,
+    // unique per token so two tokens issued in the same second never collide
+    #[serde(default)]
+    pub jti: String,
+    // sign-in session the token belongs to, shared by its access and refresh tokens
+    #[serde(default)]
+    pub sid: String,
+    // an admin who still has to set up two-factor authentication
+    #[serde(default)]
+    pub mfa_setup: bool,
 }
 
 pub async fn create_jwt_token(
@@ -17,6 +27,8 @@ pub async fn create_jwt_token(
     role: String,
     max_age: i32,
     jwt_secret: String,
+    session_id: &str,
+    two_factor_setup_required: bool,
 ) -> Result<String, Error> {
     let claims = Claims {
         sub: user_id,
@@ -27,6 +39,9 @@ pub async fn create_jwt_token(
             roles_set.insert(role);
             roles_set
         },
+        jti: Uuid::new_v4().to_string(),
+        sid: session_id.to_owned(),
+        mfa_setup: two_factor_setup_required,
     };
 
     let token = encode(
@@ -42,12 +57,16 @@ pub async fn create_refresh_token(
     user_id: String,
     refresh_secret: &String,
     refresh_expiry: usize,
+    session_id: &str,
 ) -> Result<String, Error> {
     let claims = Claims {
         sub: user_id,
         exp: refresh_expiry,
         iat: get_current_datetime(),
         roles: HashSet::new(),
+        jti: Uuid::new_v4().to_string(),
+        sid: session_id.to_owned(),
+        mfa_setup: false,
     };
 
     let token = encode(
@@ -77,6 +96,106 @@ pub async fn verify_jwt(token: &str, jwt_secret: &[u8]) -> Result<Claims, Error>
     verify_jwt_with_validation(token, jwt_secret, true).await
 }
 
+// Emailed on sign up, it has no roles so it can't pass as an access token
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EmailVerificationClaims {
+    pub sub: String,
+    pub email: String,
+    exp: usize,
+    iat: usize,
+}
+
+pub fn create_email_verification_token(
+    user_id: String,
+    email: String,
+    expiry_minutes: i64,
+    jwt_secret: &String,
+) -> Result<String, Error> {
+    let claims = EmailVerificationClaims {
+        sub: user_id,
+        email,
+        exp: get_expiration(expiry_minutes).0,
+        iat: get_current_datetime(),
+    };
+
+    encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(jwt_secret.as_bytes()),
+    )
+}
+
+pub fn verify_email_verification_token(
+    token: &str,
+    jwt_secret: &[u8],
+) -> Result<EmailVerificationClaims, Error> {
+    let validation = Validation::new(jsonwebtoken::Algorithm::HS256);
+
+    let token_data = decode::<EmailVerificationClaims>(
+        token,
+        &DecodingKey::from_secret(jwt_secret),
+        &validation,
+    )?;
+
+    Ok(token_data.claims)
+}
+
+// Handed out after the password when the account has two-factor
+// authentication, it carries the sign in over to the second step
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TwoFactorChallengeClaims {
+    pub sub: String,
+    purpose: String,
+    pub device_name: Option<String>,
+    pub guest_token: Option<String>,
+    exp: usize,
+    iat: usize,
+}
+
+const TWO_FACTOR_PURPOSE: &str = "two-factor";
+
+pub fn create_two_factor_challenge_token(
+    user_id: String,
+    device_name: Option<String>,
+    guest_token: Option<String>,
+    expiry_minutes: i64,
+    jwt_secret: &String,
+) -> Result<String, Error> {
+    let claims = TwoFactorChallengeClaims {
+        sub: user_id,
+        purpose: TWO_FACTOR_PURPOSE.to_owned(),
+        device_name,
+        guest_token,
+        exp: get_expiration(expiry_minutes).0,
+        iat: get_current_datetime(),
+    };
+
+    encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(jwt_secret.as_bytes()),
+    )
+}
+
+pub fn verify_two_factor_challenge_token(
+    token: &str,
+    jwt_secret: &[u8],
+) -> Result<TwoFactorChallengeClaims, Error> {
+    let validation = Validation::new(jsonwebtoken::Algorithm::HS256);
+
+    let token_data = decode::<TwoFactorChallengeClaims>(
+        token,
+        &DecodingKey::from_secret(jwt_secret),
+        &validation,
+    )?;
+
+    if token_data.claims.purpose != TWO_FACTOR_PURPOSE {
+        return Err(jsonwebtoken::errors::ErrorKind::InvalidToken.into());
+    }
+
+    Ok(token_data.claims)
+}
+
 /// Returns both expiration timestamp (UTC) and formatted datetime in +05:45.
 pub fn get_expiration(exp_in_minutes: i64) -> (usize, String) {
     let timezone = get_timezone();
@@ -2,4 +2,5 @@ pub mod fcm_client;
 pub mod jwt_helper;
 pub mod number_to_words;
 pub mod password_helper;
+pub mod slug;
 pub mod uuid_validator;
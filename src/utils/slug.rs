@@ -0,0 +1,45 @@
+/// Turns a display name into a lowercase, hyphen separated URL slug.
+/// Characters outside ASCII letters and digits act as separators, so names
+/// written only in Devanagari produce an empty slug and callers need a fallback.
+pub fn slugify(value: &str) -> String {
+    let mut slug = String::with_capacity(value.len());
+    let mut pending_separator = false;
+
+    for ch in value.trim().chars() {
+        if ch.is_ascii_alphanumeric() {
+            if pending_separator && !slug.is_empty() {
+                slug.push('-');
+            }
+            slug.push(ch.to_ascii_lowercase());
+            pending_separator = false;
+        } else {
+            pending_separator = true;
+        }
+    }
+
+    slug
+}
+
+#[cfg(test)]
+mod tests {
+    use super::slugify;
+
+    #[test]
+    fn lowercases_and_joins_words_with_hyphens() {
+        assert_eq!(slugify("Fresh Vegetables"), "fresh-vegetables");
+        assert_eq!(slugify("Rice 25 KG"), "rice-25-kg");
+    }
+
+    #[test]
+    fn collapses_separators_and_trims_the_ends() {
+        assert_eq!(slugify("  Fruits & Nuts  "), "fruits-nuts");
+        assert_eq!(slugify("--Dairy--/--Eggs--"), "dairy-eggs");
+    }
+
+    #[test]
+    fn names_without_ascii_letters_give_an_empty_slug() {
+        assert_eq!(slugify("तरकारी"), "");
+        assert_eq!(slugify("Tarkari तरकारी"), "tarkari");
+        assert_eq!(slugify(""), "");
+    }
+}